@@ -54,6 +54,15 @@ interface SearchResult {
   metadata: ChunkMetadata;
 }
 
+interface ParserLanguageMetrics {
+  language: string;
+  filesParsed: number;
+  symbols: number;
+  fallbacks: number;
+  p50ParseMs: number;
+  p95ParseMs: number;
+}
+
 function parseFiles(files: { path: string; content: string; hash: string }[]): ParsedFile[] {
   return native.parseFiles(files);
 }
@@ -376,6 +385,7 @@ async function runBenchmarks(): Promise<void> {
       }
 
       let totalChunks = 0;
+      native.resetParserMetrics();
       benchmark(
         `Parse ${fileCount} files`,
         () => {
@@ -387,6 +397,13 @@ async function runBenchmarks(): Promise<void> {
       );
 
       console.log(`    -> Total chunks: ${totalChunks}`);
+      for (const metrics of native.getParserMetrics() as ParserLanguageMetrics[]) {
+        console.log(
+          `    -> ${metrics.language}: ${metrics.filesParsed} files, ${metrics.symbols} symbols, ` +
+            `p50 ${formatNumber(metrics.p50ParseMs)}ms, p95 ${formatNumber(metrics.p95ParseMs)}ms, ` +
+            `${metrics.fallbacks} fallbacks`
+        );
+      }
     }
 
     // ============================================
@@ -0,0 +1,90 @@
+; =============================================================
+; Tree-sitter query for extracting calls from C#
+; =============================================================
+
+; Direct calls: Helper(), Process<int>(x)
+(invocation_expression
+  function: (identifier) @callee.name) @call
+
+(invocation_expression
+  function: (generic_name
+    (identifier) @callee.name)) @call
+
+; Method calls: obj.Run(), this.Save(), Console.WriteLine(), list.Select<T>()
+(invocation_expression
+  function: (member_access_expression
+    name: (identifier) @callee.name)) @call @method.call
+
+(invocation_expression
+  function: (member_access_expression
+    name: (generic_name
+      (identifier) @callee.name))) @call @method.call
+
+; Null-conditional calls: obj?.Run()
+(invocation_expression
+  function: (conditional_access_expression
+    (member_binding_expression
+      name: (identifier) @callee.name))) @call @method.call
+
+; Constructor calls: new Foo(), new List<int>(), new System.Text.StringBuilder()
+(object_creation_expression
+  type: (identifier) @callee.name) @constructor
+
+(object_creation_expression
+  type: (generic_name
+    (identifier) @callee.name)) @constructor
+
+(object_creation_expression
+  type: (qualified_name
+    name: (identifier) @callee.name)) @constructor
+
+(object_creation_expression
+  type: (qualified_name
+    name: (generic_name
+      (identifier) @callee.name))) @constructor
+
+; Usings: using System; using System.Collections.Generic; using static System.Math;
+; Alias usings (using Json = Newtonsoft.Json;) import the aliased type.
+(using_directive
+  !name
+  (identifier) @import.name) @import
+
+(using_directive
+  (qualified_name
+    name: (identifier) @import.name)) @import
+
+; Base types: class Foo : Base, IDisposable, IComparable<Foo>
+; C# does not separate base classes from interfaces syntactically, so the
+; .NET naming convention (I + uppercase letter) decides between the two.
+(base_list
+  (identifier) @inherits.name
+  (#not-match? @inherits.name "^I[A-Z]")) @inherits
+
+(base_list
+  (identifier) @implements.name
+  (#match? @implements.name "^I[A-Z]")) @implements
+
+(base_list
+  (generic_name
+    (identifier) @inherits.name)
+  (#not-match? @inherits.name "^I[A-Z]")) @inherits
+
+(base_list
+  (generic_name
+    (identifier) @implements.name)
+  (#match? @implements.name "^I[A-Z]")) @implements
+
+(base_list
+  (qualified_name
+    name: (identifier) @inherits.name)
+  (#not-match? @inherits.name "^I[A-Z]")) @inherits
+
+(base_list
+  (qualified_name
+    name: (identifier) @implements.name)
+  (#match? @implements.name "^I[A-Z]")) @implements
+
+; Primary constructor base: record Point3(int X, int Y, int Z) : Point(X, Y)
+(base_list
+  (primary_constructor_base_type
+    type: (identifier) @inherits.name)) @inherits
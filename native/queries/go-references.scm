@@ -0,0 +1,45 @@
+; =============================================================
+; Tree-sitter query for extracting non-call references from Go
+; Call, import and embedding sites come from go-calls.scm;
+; references at the same position are dropped in their favour.
+; =============================================================
+
+; Type uses: user UserDto, []UserDto, models.UserDto
+; Declared names (type UserDto struct) are skipped by the extractor
+(type_identifier) @reference.type
+
+; Field accesses: user.Email, s.cache
+(selector_expression
+  field: (field_identifier) @reference.field)
+
+; Writes: count = 1, total += n, retries++, s.state = next
+(assignment_statement
+  left: (expression_list (identifier) @reference.write))
+(assignment_statement
+  left: (expression_list
+    (selector_expression
+      field: (field_identifier) @reference.write)))
+(inc_statement (identifier) @reference.write)
+(dec_statement (identifier) @reference.write)
+
+; Reads: identifiers used as values
+(argument_list (identifier) @reference.read)
+(assignment_statement right: (expression_list (identifier) @reference.read))
+(short_var_declaration right: (expression_list (identifier) @reference.read))
+(var_spec value: (expression_list (identifier) @reference.read))
+(return_statement (expression_list (identifier) @reference.read))
+(binary_expression left: (identifier) @reference.read)
+(binary_expression right: (identifier) @reference.read)
+(unary_expression operand: (identifier) @reference.read)
+(selector_expression operand: (identifier) @reference.read)
+(index_expression operand: (identifier) @reference.read)
+(index_expression index: (identifier) @reference.read)
+(literal_element (identifier) @reference.read)
+
+; Bindings; inside a function they shadow package-level names
+(parameter_declaration name: (identifier) @local.definition)
+(short_var_declaration left: (expression_list (identifier) @local.definition))
+(var_spec name: (identifier) @local.definition)
+(const_spec name: (identifier) @local.definition)
+(range_clause left: (expression_list (identifier) @local.definition))
+(type_parameter_declaration name: (identifier) @local.definition)
@@ -0,0 +1,99 @@
+; =============================================================
+; Tree-sitter query for extracting calls from Java
+;
+; Java uses a single `method_invocation` node for both bare calls
+; (helper()) and calls on a receiver (obj.run(), Util.parse()). As in
+; the Apex query, the `.` anchor separates the two: invocations whose
+; first named child is `name` have no `object`.
+; =============================================================
+
+; Direct calls: helper(), process(1, 2)
+(method_invocation
+  .
+  name: (identifier) @callee.name) @call
+
+; Method calls on a receiver: obj.run(), this.save(), Util.parse(),
+; super.close()
+(method_invocation
+  object: (_)
+  name: (identifier) @callee.name) @call @method.call
+
+; Reflective calls: cls.getMethod("save").invoke(target), and
+; method.invoke(target) on a variable bound to such a lookup. The method
+; name is a best guess, so the edge is inferred.
+(method_invocation
+  object: (method_invocation
+    name: (identifier) @_lookup
+    arguments: (argument_list
+      .
+      (string_literal) @callee.name))
+  name: (identifier) @_invoke
+  (#any-of? @_lookup "getMethod" "getDeclaredMethod")
+  (#eq? @_invoke "invoke")) @call @method.call @inferred
+
+(method_invocation
+  object: (identifier) @callee.name
+  name: (identifier) @_invoke
+  (#eq? @_invoke "invoke")) @call @method.call @inferred
+
+; Constructor calls: new Foo(), new java.util.ArrayList<>(), new Box<T>()
+(object_creation_expression
+  type: (type_identifier) @callee.name) @constructor
+
+(object_creation_expression
+  type: (scoped_type_identifier
+    (type_identifier) @callee.name .)) @constructor
+
+(object_creation_expression
+  type: (generic_type
+    (type_identifier) @callee.name)) @constructor
+
+(object_creation_expression
+  type: (generic_type
+    (scoped_type_identifier
+      (type_identifier) @callee.name .))) @constructor
+
+; Imports: import java.util.List; import static org.junit.Assert.assertEquals;
+; Wildcard imports (java.util.*) name a package, not a symbol, and are skipped
+; by requiring the scoped identifier to be the last named child.
+(import_declaration
+  (scoped_identifier
+    scope: (_) @import.source
+    name: (identifier) @import.name) .) @import
+
+; Class inheritance: class Foo extends Bar, extends Base<T>, extends pkg.Base
+(superclass
+  (type_identifier) @inherits.name) @inherits
+
+(superclass
+  (generic_type
+    (type_identifier) @inherits.name)) @inherits
+
+(superclass
+  (scoped_type_identifier
+    (type_identifier) @inherits.name .)) @inherits
+
+; Interface implementation: class Foo implements Runnable, Comparable<Foo>
+(super_interfaces
+  (type_list
+    (type_identifier) @implements.name)) @implements
+
+(super_interfaces
+  (type_list
+    (generic_type
+      (type_identifier) @implements.name))) @implements
+
+(super_interfaces
+  (type_list
+    (scoped_type_identifier
+      (type_identifier) @implements.name .))) @implements
+
+; Interface inheritance: interface Foo extends Bar, Baz<T>
+(extends_interfaces
+  (type_list
+    (type_identifier) @inherits.name)) @inherits
+
+(extends_interfaces
+  (type_list
+    (generic_type
+      (type_identifier) @inherits.name))) @inherits
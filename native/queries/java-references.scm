@@ -0,0 +1,49 @@
+; =============================================================
+; Tree-sitter query for extracting non-call references from Java
+; Call, import and heritage sites come from java-calls.scm;
+; references at the same position are dropped in their favour.
+; =============================================================
+
+; Type uses: UserDto user, List<UserDto>, (UserDto) value
+(type_identifier) @reference.type
+
+; Field accesses: user.email, this.cache
+(field_access
+  field: (identifier) @reference.field)
+
+; Writes: count = 1, total += n, retries++, this.state = next
+(assignment_expression
+  left: (identifier) @reference.write)
+(assignment_expression
+  left: (field_access
+    field: (identifier) @reference.write))
+(update_expression (identifier) @reference.write)
+
+; Reads: identifiers used as values
+(argument_list (identifier) @reference.read)
+(variable_declarator value: (identifier) @reference.read)
+(assignment_expression right: (identifier) @reference.read)
+(return_statement (identifier) @reference.read)
+(binary_expression left: (identifier) @reference.read)
+(binary_expression right: (identifier) @reference.read)
+(unary_expression operand: (identifier) @reference.read)
+(field_access object: (identifier) @reference.read)
+(method_invocation object: (identifier) @reference.read)
+(array_access array: (identifier) @reference.read)
+(array_access index: (identifier) @reference.read)
+(ternary_expression condition: (identifier) @reference.read)
+(ternary_expression consequence: (identifier) @reference.read)
+(ternary_expression alternative: (identifier) @reference.read)
+
+; Annotations: @Override, @Entity, @Table(name = "users")
+(marker_annotation name: (identifier) @reference.decorator)
+(annotation name: (identifier) @reference.decorator)
+
+; Bindings; inside a method they shadow fields and static imports
+(formal_parameter name: (identifier) @local.definition)
+(catch_formal_parameter name: (identifier) @local.definition)
+(variable_declarator name: (identifier) @local.definition)
+(lambda_expression parameters: (identifier) @local.definition)
+(inferred_parameters (identifier) @local.definition)
+(enhanced_for_statement name: (identifier) @local.definition)
+(type_parameter (type_identifier) @local.definition)
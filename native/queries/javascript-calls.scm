@@ -18,6 +18,14 @@
   function: (member_expression
     property: (property_identifier) @callee.name)) @call
 
+; -------------------------------------------------------------
+; Computed method calls: obj[methodName](), obj["save"]()
+; The method name is a best guess, so the edge is inferred
+; -------------------------------------------------------------
+(call_expression
+  function: (subscript_expression
+    index: [(string) (identifier)] @callee.name)) @call @inferred
+
 ; -------------------------------------------------------------
 ; Constructor calls: new Foo(), new Bar(args)
 ; Captures the class/constructor name
@@ -54,3 +62,15 @@
     (namespace_import
       (identifier) @import.namespace))
   source: (string) @import.source) @import
+
+; -------------------------------------------------------------
+; Dynamic requires: require(modulePath)
+; The module is a best guess, so the edge is inferred
+; -------------------------------------------------------------
+(call_expression
+  function: (identifier) @_require
+  arguments: (arguments
+    .
+    (identifier) @import.name
+    .)
+  (#eq? @_require "require")) @import @inferred
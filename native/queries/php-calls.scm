@@ -29,6 +29,14 @@
 (nullsafe_member_call_expression
   name: (name) @callee.name) @method.call
 
+; Variable functions and methods: $fn(), $obj->$method()
+; The function name is a best guess, so the edge is inferred
+(function_call_expression
+  function: (variable_name) @callee.name) @call @inferred
+
+(member_call_expression
+  name: (variable_name) @callee.name) @method.call @inferred
+
 ; Constructor calls: new Foo()
 (object_creation_expression
   (name) @callee.name) @constructor
@@ -11,6 +11,18 @@
   function: (attribute
     attribute: (identifier) @callee.name)) @call
 
+; Dynamic attribute calls: getattr(obj, "save")(), getattr(obj, name)()
+; The attribute name is a best guess, so the edge is inferred.
+(call
+  function: (call
+    function: (identifier) @_getattr
+    arguments: (argument_list
+      .
+      (_)
+      .
+      [(string) (identifier)] @callee.name))
+  (#eq? @_getattr "getattr")) @call @inferred
+
 ; Constructor calls (same as function calls in Python, but capitalized by convention)
 ; Handled by the Call type — caller can check capitalization
 
@@ -21,6 +33,7 @@
 
 ; From import: from module import foo, bar
 (import_from_statement
+  module_name: (_) @import.source
   name: (dotted_name
     (identifier) @import.name)) @import
 
@@ -0,0 +1,71 @@
+; =============================================================
+; Tree-sitter query for extracting non-call references from Python
+; Call, import and base class sites come from python-calls.scm;
+; references at the same position are dropped in their favour.
+; =============================================================
+
+; Type annotations: user: UserDto, -> list[UserDto], models.UserDto
+(type (identifier) @reference.type)
+(generic_type (identifier) @reference.type)
+(type
+  (attribute
+    attribute: (identifier) @reference.type))
+
+; Field accesses: user.email, self.cache
+(attribute
+  attribute: (identifier) @reference.field)
+
+; Writes: count = 1, total += n, self.state = next
+(assignment
+  left: (identifier) @reference.write)
+(augmented_assignment
+  left: (identifier) @reference.write)
+(assignment
+  left: (attribute
+    attribute: (identifier) @reference.write))
+(augmented_assignment
+  left: (attribute
+    attribute: (identifier) @reference.write))
+
+; Reads: identifiers used as values
+(argument_list (identifier) @reference.read)
+(keyword_argument value: (identifier) @reference.read)
+(assignment right: (identifier) @reference.read)
+(augmented_assignment right: (identifier) @reference.read)
+(return_statement (identifier) @reference.read)
+(binary_operator left: (identifier) @reference.read)
+(binary_operator right: (identifier) @reference.read)
+(boolean_operator left: (identifier) @reference.read)
+(boolean_operator right: (identifier) @reference.read)
+(comparison_operator (identifier) @reference.read)
+(not_operator argument: (identifier) @reference.read)
+(unary_operator argument: (identifier) @reference.read)
+(attribute object: (identifier) @reference.read)
+(subscript value: (identifier) @reference.read)
+(subscript subscript: (identifier) @reference.read)
+(list (identifier) @reference.read)
+(tuple (identifier) @reference.read)
+(set (identifier) @reference.read)
+(pair value: (identifier) @reference.read)
+(interpolation expression: (identifier) @reference.read)
+(if_statement condition: (identifier) @reference.read)
+(while_statement condition: (identifier) @reference.read)
+
+; Decorators without arguments: @dataclass, @functools.cache
+; Decorator calls such as @app.route("/") are calls
+(decorator (identifier) @reference.decorator)
+(decorator
+  (attribute
+    attribute: (identifier) @reference.decorator))
+
+; Bindings; inside a function they shadow module-level names
+(parameters (identifier) @local.definition)
+(lambda_parameters (identifier) @local.definition)
+(typed_parameter (identifier) @local.definition)
+(default_parameter name: (identifier) @local.definition)
+(typed_default_parameter name: (identifier) @local.definition)
+(assignment left: (identifier) @local.definition)
+(for_statement left: (identifier) @local.definition)
+(pattern_list (identifier) @local.definition)
+(as_pattern_target (identifier) @local.definition)
+(function_definition name: (identifier) @local.definition)
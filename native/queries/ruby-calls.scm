@@ -0,0 +1,67 @@
+; =============================================================
+; Tree-sitter query for extracting calls from Ruby
+;
+; A bare identifier (`foo`) is indistinguishable from a local variable
+; read, so only `call` nodes (with arguments, parentheses or a receiver)
+; are reported. `require`, `include` and friends are captured by the
+; dedicated import/mixin patterns below instead of as plain calls.
+; =============================================================
+
+; Direct calls: helper(1), puts "hi", validate!
+(call
+  !receiver
+  method: (identifier) @callee.name
+  (#not-any-of? @callee.name
+    "require" "require_relative" "load" "include" "extend" "prepend")) @call
+
+; Method calls: obj.run, self.save, Util.parse(x), list&.first
+(call
+  receiver: (_)
+  method: (identifier) @callee.name
+  (#not-eq? @callee.name "new")) @call @method.call
+
+; Constructor calls: Foo.new, Admin::User.new(name)
+(call
+  receiver: (constant) @callee.name
+  method: (identifier) @_method
+  (#eq? @_method "new")) @constructor
+
+(call
+  receiver: (scope_resolution
+    name: (constant) @callee.name)
+  method: (identifier) @_method
+  (#eq? @_method "new")) @constructor
+
+; Requires: require "json", require_relative "lib/helper"
+(call
+  !receiver
+  method: (identifier) @_require
+  arguments: (argument_list
+    .
+    (string
+      (string_content) @import.name))
+  (#any-of? @_require "require" "require_relative" "load")) @import
+
+; Class inheritance: class Admin < User, class Job < ActiveJob::Base
+(superclass
+  (constant) @inherits.name) @inherits
+
+(superclass
+  (scope_resolution
+    name: (constant) @inherits.name)) @inherits
+
+; Mixins: include Comparable, extend Forwardable, prepend Concerns::Audit
+(call
+  !receiver
+  method: (identifier) @_mixin
+  arguments: (argument_list
+    (constant) @implements.name)
+  (#any-of? @_mixin "include" "extend" "prepend")) @implements
+
+(call
+  !receiver
+  method: (identifier) @_mixin
+  arguments: (argument_list
+    (scope_resolution
+      name: (constant) @implements.name))
+  (#any-of? @_mixin "include" "extend" "prepend")) @implements
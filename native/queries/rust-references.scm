@@ -0,0 +1,56 @@
+; =============================================================
+; Tree-sitter query for extracting non-call references from Rust
+; Call and `use` sites come from rust-calls.scm; references at
+; the same position are dropped in their favour.
+; =============================================================
+
+; Type uses: user: UserDto, Vec<UserDto>, models::UserDto
+; Declared names (struct UserDto) are skipped by the extractor
+(type_identifier) @reference.type
+
+; Field accesses: user.email, self.cache
+(field_expression
+  field: (field_identifier) @reference.field)
+
+; Writes: count = 1, total += n, self.state = next
+(assignment_expression
+  left: (identifier) @reference.write)
+(compound_assignment_expr
+  left: (identifier) @reference.write)
+(assignment_expression
+  left: (field_expression
+    field: (field_identifier) @reference.write))
+(compound_assignment_expr
+  left: (field_expression
+    field: (field_identifier) @reference.write))
+
+; Reads: identifiers used as values
+(arguments (identifier) @reference.read)
+(let_declaration value: (identifier) @reference.read)
+(assignment_expression right: (identifier) @reference.read)
+(compound_assignment_expr right: (identifier) @reference.read)
+(return_expression (identifier) @reference.read)
+(binary_expression left: (identifier) @reference.read)
+(binary_expression right: (identifier) @reference.read)
+(unary_expression (identifier) @reference.read)
+(reference_expression value: (identifier) @reference.read)
+(field_expression value: (identifier) @reference.read)
+(index_expression (identifier) @reference.read)
+(array_expression (identifier) @reference.read)
+(tuple_expression (identifier) @reference.read)
+(field_initializer value: (identifier) @reference.read)
+(shorthand_field_initializer (identifier) @reference.read)
+(block (identifier) @reference.read .)
+
+; Attributes: #[derive(...)], #[test], #[serde(...)]
+(attribute_item
+  (attribute
+    (identifier) @reference.decorator))
+
+; Bindings; inside a function they shadow module-level names
+(parameter pattern: (identifier) @local.definition)
+(closure_parameters (identifier) @local.definition)
+(let_declaration pattern: (identifier) @local.definition)
+(tuple_pattern (identifier) @local.definition)
+(for_expression pattern: (identifier) @local.definition)
+(type_parameter name: (type_identifier) @local.definition)
@@ -0,0 +1,79 @@
+; =============================================================
+; Tree-sitter query for extracting non-call references from TS
+; Call, import and heritage sites come from typescript-calls.scm;
+; references at the same position are dropped in their favour.
+; =============================================================
+
+; -------------------------------------------------------------
+; Type uses: let user: UserDto, Promise<UserDto>, Models.UserDto
+; Declared names (interface UserDto) are skipped by the extractor
+; -------------------------------------------------------------
+(type_identifier) @reference.type
+
+; -------------------------------------------------------------
+; Field accesses: user.email, this.cache
+; -------------------------------------------------------------
+(member_expression
+  property: (property_identifier) @reference.field)
+
+; -------------------------------------------------------------
+; Writes: count = 1, total += n, retries++, this.state = next
+; -------------------------------------------------------------
+(assignment_expression
+  left: (identifier) @reference.write)
+(augmented_assignment_expression
+  left: (identifier) @reference.write)
+(update_expression
+  argument: (identifier) @reference.write)
+(assignment_expression
+  left: (member_expression
+    property: (property_identifier) @reference.write))
+(augmented_assignment_expression
+  left: (member_expression
+    property: (property_identifier) @reference.write))
+
+; -------------------------------------------------------------
+; Reads: identifiers used as values
+; -------------------------------------------------------------
+(arguments (identifier) @reference.read)
+(variable_declarator value: (identifier) @reference.read)
+(assignment_expression right: (identifier) @reference.read)
+(augmented_assignment_expression right: (identifier) @reference.read)
+(return_statement (identifier) @reference.read)
+(binary_expression left: (identifier) @reference.read)
+(binary_expression right: (identifier) @reference.read)
+(unary_expression argument: (identifier) @reference.read)
+(ternary_expression condition: (identifier) @reference.read)
+(ternary_expression consequence: (identifier) @reference.read)
+(ternary_expression alternative: (identifier) @reference.read)
+(member_expression object: (identifier) @reference.read)
+(subscript_expression object: (identifier) @reference.read)
+(subscript_expression index: (identifier) @reference.read)
+(spread_element (identifier) @reference.read)
+(array (identifier) @reference.read)
+(pair value: (identifier) @reference.read)
+(shorthand_property_identifier) @reference.read
+(template_substitution (identifier) @reference.read)
+(await_expression (identifier) @reference.read)
+(parenthesized_expression (identifier) @reference.read)
+
+; -------------------------------------------------------------
+; Decorators without arguments: @Injectable, @core.Input
+; Decorator calls such as @Component({...}) are calls
+; -------------------------------------------------------------
+(decorator (identifier) @reference.decorator)
+(decorator
+  (member_expression
+    property: (property_identifier) @reference.decorator))
+
+; -------------------------------------------------------------
+; Bindings; inside a function they shadow module-level names
+; -------------------------------------------------------------
+(required_parameter pattern: (identifier) @local.definition)
+(optional_parameter pattern: (identifier) @local.definition)
+(arrow_function parameter: (identifier) @local.definition)
+(variable_declarator name: (identifier) @local.definition)
+(shorthand_property_identifier_pattern) @local.definition
+(catch_clause parameter: (identifier) @local.definition)
+(for_in_statement left: (identifier) @local.definition)
+(type_parameter name: (type_identifier) @local.definition)
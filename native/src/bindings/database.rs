@@ -1,10 +1,17 @@
+use crate::graph_export::{self, ExportFilter, ExportFormat};
 use crate::{
-    community, db, CallEdgeData, CentralityData, CommunityCouplingData, CommunityData,
-    CommunityRelationshipData, PathHopData, ReachabilityData, SymbolData,
+    community, db, CallEdgeData, CallGraphFilter, CallPathData, CallPathsData,
+    CallResolutionStatsData, CentralityData, CommunityCouplingData, CommunityData,
+    CommunityRelationshipData, EdgeWeightsData, PathHopData, ReachabilityData, SymbolCandidateData,
+    SymbolData, WeightedReachabilityData,
 };
 use napi::bindgen_prelude::{Buffer, Error, Result};
 use napi_derive::napi;
 
+/// Upper bound on `k` in `find_call_paths`; each extra path costs another
+/// search.
+const MAX_CALL_PATHS: u32 = 50;
+
 #[napi]
 pub struct Database {
     conn: std::sync::Mutex<Option<rusqlite::Connection>>,
@@ -20,6 +27,12 @@ pub struct ChunkData {
     pub node_type: Option<String>,
     pub name: Option<String>,
     pub language: String,
+    pub qualified_name: Option<String>,
+    pub parent_symbol: Option<String>,
+    pub depth: Option<u32>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
     pub blame_sha: Option<String>,
     pub blame_author: Option<String>,
     pub blame_author_email: Option<String>,
@@ -181,23 +194,8 @@ impl Database {
     #[napi]
     pub fn upsert_chunk(&self, chunk: ChunkData) -> Result<()> {
         self.with_conn(|conn| {
-            db::upsert_chunk_with_blame(
-                conn,
-                &chunk.chunk_id,
-                &chunk.content_hash,
-                &chunk.file_path,
-                chunk.start_line,
-                chunk.end_line,
-                chunk.node_type.as_deref(),
-                chunk.name.as_deref(),
-                &chunk.language,
-                chunk.blame_sha.as_deref(),
-                chunk.blame_author.as_deref(),
-                chunk.blame_author_email.as_deref(),
-                chunk.blame_committed_at,
-                chunk.blame_summary.as_deref(),
-            )
-            .map_err(|e| Error::from_reason(e.to_string()))
+            db::upsert_chunk_row(conn, &chunk_row(chunk))
+                .map_err(|e| Error::from_reason(e.to_string()))
         })
     }
 
@@ -215,6 +213,12 @@ impl Database {
                 node_type: row.node_type,
                 name: row.name,
                 language: row.language,
+                qualified_name: row.qualified_name,
+                parent_symbol: row.parent_symbol,
+                depth: row.depth,
+                signature: row.signature,
+                doc_comment: row.doc_comment,
+                visibility: row.visibility,
                 blame_sha: row.blame_sha,
                 blame_author: row.blame_author,
                 blame_author_email: row.blame_author_email,
@@ -240,6 +244,12 @@ impl Database {
                     node_type: row.node_type,
                     name: row.name,
                     language: row.language,
+                    qualified_name: row.qualified_name,
+                    parent_symbol: row.parent_symbol,
+                    depth: row.depth,
+                    signature: row.signature,
+                    doc_comment: row.doc_comment,
+                    visibility: row.visibility,
                     blame_sha: row.blame_sha,
                     blame_author: row.blame_author,
                     blame_author_email: row.blame_author_email,
@@ -266,6 +276,12 @@ impl Database {
                     node_type: row.node_type,
                     name: row.name,
                     language: row.language,
+                    qualified_name: row.qualified_name,
+                    parent_symbol: row.parent_symbol,
+                    depth: row.depth,
+                    signature: row.signature,
+                    doc_comment: row.doc_comment,
+                    visibility: row.visibility,
                     blame_sha: row.blame_sha,
                     blame_author: row.blame_author,
                     blame_author_email: row.blame_author_email,
@@ -292,6 +308,12 @@ impl Database {
                     node_type: row.node_type,
                     name: row.name,
                     language: row.language,
+                    qualified_name: row.qualified_name,
+                    parent_symbol: row.parent_symbol,
+                    depth: row.depth,
+                    signature: row.signature,
+                    doc_comment: row.doc_comment,
+                    visibility: row.visibility,
                     blame_sha: row.blame_sha,
                     blame_author: row.blame_author,
                     blame_author_email: row.blame_author_email,
@@ -348,24 +370,7 @@ impl Database {
 
     #[napi]
     pub fn upsert_chunks_batch(&self, chunks: Vec<ChunkData>) -> Result<()> {
-        let batch: Vec<db::ChunkRow> = chunks
-            .into_iter()
-            .map(|c| db::ChunkRow {
-                chunk_id: c.chunk_id,
-                content_hash: c.content_hash,
-                file_path: c.file_path,
-                start_line: c.start_line,
-                end_line: c.end_line,
-                node_type: c.node_type,
-                name: c.name,
-                language: c.language,
-                blame_sha: c.blame_sha,
-                blame_author: c.blame_author,
-                blame_author_email: c.blame_author_email,
-                blame_committed_at: c.blame_committed_at,
-                blame_summary: c.blame_summary,
-            })
-            .collect();
+        let batch: Vec<db::ChunkRow> = chunks.into_iter().map(chunk_row).collect();
         self.with_conn_mut(|conn| {
             db::upsert_chunks_batch(conn, &batch).map_err(|e| Error::from_reason(e.to_string()))
         })
@@ -536,6 +541,12 @@ impl Database {
             end_line: symbol.end_line,
             end_col: symbol.end_col,
             language: symbol.language,
+            qualified_name: symbol.qualified_name,
+            parent_symbol: symbol.parent_symbol,
+            depth: symbol.depth,
+            signature: symbol.signature,
+            doc_comment: symbol.doc_comment,
+            visibility: symbol.visibility,
         };
         self.with_conn(|conn| {
             db::upsert_symbol(conn, &row).map_err(|e| Error::from_reason(e.to_string()))
@@ -556,6 +567,12 @@ impl Database {
                 end_line: s.end_line,
                 end_col: s.end_col,
                 language: s.language,
+                qualified_name: s.qualified_name,
+                parent_symbol: s.parent_symbol,
+                depth: s.depth,
+                signature: s.signature,
+                doc_comment: s.doc_comment,
+                visibility: s.visibility,
             })
             .collect();
         self.with_conn_mut(|conn| {
@@ -580,6 +597,12 @@ impl Database {
                     end_line: r.end_line,
                     end_col: r.end_col,
                     language: r.language,
+                    qualified_name: r.qualified_name,
+                    parent_symbol: r.parent_symbol,
+                    depth: r.depth,
+                    signature: r.signature,
+                    doc_comment: r.doc_comment,
+                    visibility: r.visibility,
                 })
                 .collect())
         })
@@ -604,6 +627,12 @@ impl Database {
                 end_line: r.end_line,
                 end_col: r.end_col,
                 language: r.language,
+                qualified_name: r.qualified_name,
+                parent_symbol: r.parent_symbol,
+                depth: r.depth,
+                signature: r.signature,
+                doc_comment: r.doc_comment,
+                visibility: r.visibility,
             }))
         })
     }
@@ -625,6 +654,12 @@ impl Database {
                     end_line: r.end_line,
                     end_col: r.end_col,
                     language: r.language,
+                    qualified_name: r.qualified_name,
+                    parent_symbol: r.parent_symbol,
+                    depth: r.depth,
+                    signature: r.signature,
+                    doc_comment: r.doc_comment,
+                    visibility: r.visibility,
                 })
                 .collect())
         })
@@ -647,15 +682,31 @@ impl Database {
                     end_line: r.end_line,
                     end_col: r.end_col,
                     language: r.language,
+                    qualified_name: r.qualified_name,
+                    parent_symbol: r.parent_symbol,
+                    depth: r.depth,
+                    signature: r.signature,
+                    doc_comment: r.doc_comment,
+                    visibility: r.visibility,
                 })
                 .collect())
         })
     }
+    /// The branch's symbols; the `<module>` symbols owning top-level call
+    /// sites only with `include_file_scopes`.
     #[napi]
-    pub fn get_symbols_for_branch(&self, branch: String) -> Result<Vec<SymbolData>> {
+    pub fn get_symbols_for_branch(
+        &self,
+        branch: String,
+        include_file_scopes: Option<bool>,
+    ) -> Result<Vec<SymbolData>> {
         self.with_conn(|conn| {
-            let rows = db::get_symbols_for_branch(conn, &branch)
-                .map_err(|e| Error::from_reason(e.to_string()))?;
+            let rows = if include_file_scopes.unwrap_or(false) {
+                db::get_symbols_for_branch_with_file_scopes(conn, &branch)
+            } else {
+                db::get_symbols_for_branch(conn, &branch)
+            }
+            .map_err(|e| Error::from_reason(e.to_string()))?;
             Ok(rows
                 .into_iter()
                 .map(|r| SymbolData {
@@ -668,20 +719,33 @@ impl Database {
                     end_line: r.end_line,
                     end_col: r.end_col,
                     language: r.language,
+                    qualified_name: r.qualified_name,
+                    parent_symbol: r.parent_symbol,
+                    depth: r.depth,
+                    signature: r.signature,
+                    doc_comment: r.doc_comment,
+                    visibility: r.visibility,
                 })
                 .collect())
         })
     }
 
+    /// Symbols of `file_paths` on the branch; their `<module>` symbols only
+    /// with `include_file_scopes`.
     #[napi]
     pub fn get_symbols_for_files(
         &self,
         file_paths: Vec<String>,
         branch: String,
+        include_file_scopes: Option<bool>,
     ) -> Result<Vec<SymbolData>> {
         self.with_conn(|conn| {
-            let rows = db::get_symbols_for_files(conn, &file_paths, &branch)
-                .map_err(|e| Error::from_reason(e.to_string()))?;
+            let rows = if include_file_scopes.unwrap_or(false) {
+                db::get_symbols_for_files_with_file_scopes(conn, &file_paths, &branch)
+            } else {
+                db::get_symbols_for_files(conn, &file_paths, &branch)
+            }
+            .map_err(|e| Error::from_reason(e.to_string()))?;
             Ok(rows
                 .into_iter()
                 .map(|r| SymbolData {
@@ -694,6 +758,12 @@ impl Database {
                     end_line: r.end_line,
                     end_col: r.end_col,
                     language: r.language,
+                    qualified_name: r.qualified_name,
+                    parent_symbol: r.parent_symbol,
+                    depth: r.depth,
+                    signature: r.signature,
+                    doc_comment: r.doc_comment,
+                    visibility: r.visibility,
                 })
                 .collect())
         })
@@ -722,6 +792,9 @@ impl Database {
             line: edge.line,
             col: edge.col,
             is_resolved: edge.is_resolved,
+            qualifier: edge.qualifier,
+            resolution_strategy: edge.resolution_strategy,
+            resolution_confidence: edge.resolution_confidence,
         };
         self.with_conn(|conn| {
             db::upsert_call_edge(conn, &row).map_err(|e| Error::from_reason(e.to_string()))
@@ -742,6 +815,9 @@ impl Database {
                 line: e.line,
                 col: e.col,
                 is_resolved: e.is_resolved,
+                qualifier: e.qualifier,
+                resolution_strategy: e.resolution_strategy,
+                resolution_confidence: e.resolution_confidence,
             })
             .collect();
         self.with_conn_mut(|conn| {
@@ -773,6 +849,9 @@ impl Database {
                     line: r.line,
                     col: r.col,
                     is_resolved: r.is_resolved,
+                    qualifier: r.qualifier,
+                    resolution_strategy: r.resolution_strategy,
+                    resolution_confidence: r.resolution_confidence,
                 })
                 .collect())
         })
@@ -802,6 +881,9 @@ impl Database {
                     line: r.line,
                     col: r.col,
                     is_resolved: r.is_resolved,
+                    qualifier: r.qualifier,
+                    resolution_strategy: r.resolution_strategy,
+                    resolution_confidence: r.resolution_confidence,
                 })
                 .collect())
         })
@@ -836,6 +918,9 @@ impl Database {
                     line: r.line,
                     col: r.col,
                     is_resolved: r.is_resolved,
+                    qualifier: r.qualifier,
+                    resolution_strategy: r.resolution_strategy,
+                    resolution_confidence: r.resolution_confidence,
                 })
                 .collect())
         })
@@ -858,6 +943,33 @@ impl Database {
         })
     }
 
+    /// Picks targets for the call edges of symbols on `branch` from receiver
+    /// types, same-file definitions and imports, recording for that branch
+    /// how each was chosen. Run after the branch's symbols and edges are
+    /// written. `changed_symbol_ids`, the symbols added to or removed from
+    /// the branch and not yet collected, limits the run to the edges they can
+    /// affect; without it every edge is resolved.
+    #[napi]
+    pub fn resolve_call_edges(
+        &self,
+        branch: String,
+        changed_symbol_ids: Option<Vec<String>>,
+    ) -> Result<CallResolutionStatsData> {
+        self.with_conn_mut(|conn| {
+            let stats = db::resolve_call_edges(conn, &branch, changed_symbol_ids.as_deref())
+                .map_err(|e| Error::from_reason(e.to_string()))?;
+            Ok(CallResolutionStatsData {
+                edges: stats.edges as u32,
+                receiver_type: stats.receiver_type as u32,
+                same_file: stats.same_file as u32,
+                import: stats.import as u32,
+                unique_name: stats.unique_name as u32,
+                unresolved: stats.unresolved as u32,
+                updated: stats.updated as u32,
+            })
+        })
+    }
+
     #[napi]
     pub fn find_shortest_path(
         &self,
@@ -870,16 +982,56 @@ impl Database {
             let depth = max_depth.unwrap_or(10);
             let hops = db::find_shortest_path(conn, &from_name, &to_name, &branch, depth)
                 .map_err(|e| Error::from_reason(e.to_string()))?;
-            Ok(hops
-                .into_iter()
-                .map(|h| PathHopData {
-                    symbol_id: h.symbol_id,
-                    symbol_name: h.symbol_name,
-                    file_path: h.file_path,
-                    line: h.line,
-                    call_type: h.call_type,
-                })
-                .collect())
+            Ok(hops.into_iter().map(path_hop_data).collect())
+        })
+    }
+
+    /// Up to `k` (default 3, at most 50) distinct paths from `from` to `to`, each given
+    /// as a symbol id or name. An ambiguous name yields its candidates
+    /// instead of paths. With `weights`, paths are ranked by edge cost
+    /// rather than hop count.
+    #[napi]
+    pub fn find_call_paths(
+        &self,
+        from: String,
+        to: String,
+        branch: String,
+        k: Option<u32>,
+        max_depth: Option<u32>,
+        weights: Option<EdgeWeightsData>,
+    ) -> Result<CallPathsData> {
+        let weights = weights.map(edge_weights).transpose()?;
+        self.with_conn(|conn| {
+            let found = db::find_k_shortest_paths(
+                conn,
+                &from,
+                &to,
+                &branch,
+                k.unwrap_or(3).min(MAX_CALL_PATHS),
+                max_depth.unwrap_or(10),
+                weights.as_ref(),
+            )
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+            Ok(CallPathsData {
+                paths: found
+                    .paths
+                    .into_iter()
+                    .map(|path| CallPathData {
+                        hops: path.hops.into_iter().map(path_hop_data).collect(),
+                        cost: path.cost,
+                    })
+                    .collect(),
+                from_candidates: found
+                    .from_candidates
+                    .into_iter()
+                    .map(symbol_candidate_data)
+                    .collect(),
+                to_candidates: found
+                    .to_candidates
+                    .into_iter()
+                    .map(symbol_candidate_data)
+                    .collect(),
+            })
         })
     }
 
@@ -1001,6 +1153,44 @@ impl Database {
         })
     }
 
+    /// Like `get_transitive_reachability`, but each symbol carries the cost
+    /// of its cheapest chain under `weights` (the defaults when omitted),
+    /// and symbols costing more than `max_cost` are left out.
+    #[napi]
+    pub fn get_weighted_reachability(
+        &self,
+        root_symbol_ids: Vec<String>,
+        branch: String,
+        direction: String,
+        weights: Option<EdgeWeightsData>,
+        max_depth: Option<u32>,
+        max_cost: Option<f64>,
+    ) -> Result<Vec<WeightedReachabilityData>> {
+        let weights = weights.map(edge_weights).transpose()?.unwrap_or_default();
+        self.with_conn(|conn| {
+            let rows = db::get_weighted_reachability(
+                conn,
+                &root_symbol_ids,
+                &branch,
+                &direction,
+                &weights,
+                max_depth.unwrap_or(10),
+                max_cost,
+            )
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+            Ok(rows
+                .into_iter()
+                .map(|r| WeightedReachabilityData {
+                    symbol_id: r.symbol_id,
+                    symbol_name: r.symbol_name,
+                    file_path: r.file_path,
+                    depth: r.depth,
+                    cost: r.cost,
+                })
+                .collect())
+        })
+    }
+
     #[napi]
     pub fn detect_communities(
         &self,
@@ -1052,6 +1242,37 @@ impl Database {
         })
     }
 
+    /// The branch's call graph as Graphviz DOT (`dot`), GraphML (`graphml`)
+    /// or JSON Graph Format (`json`), optionally restricted by `filter`.
+    #[napi]
+    pub fn export_call_graph(
+        &self,
+        branch: String,
+        format: String,
+        filter: Option<CallGraphFilter>,
+    ) -> Result<String> {
+        let format = ExportFormat::from_string(&format).ok_or_else(|| {
+            Error::from_reason(format!(
+                "unknown call graph format `{}`; expected dot, graphml or json",
+                format
+            ))
+        })?;
+        let filter = filter
+            .map(|filter| ExportFilter {
+                file_prefix: filter.file_prefix,
+                community_id: filter.community_id,
+                root_symbol_ids: filter.root_symbol_ids,
+                direction: filter.direction.unwrap_or_else(|| "both".to_string()),
+                max_depth: filter.max_depth.unwrap_or(10),
+                call_types: filter.call_types,
+            })
+            .unwrap_or_default();
+        self.with_conn(|conn| {
+            graph_export::export_call_graph(conn, &branch, format, &filter)
+                .map_err(|e| Error::from_reason(e.to_string()))
+        })
+    }
+
     #[napi]
     pub fn compute_centrality(&self, branch: String) -> Result<Vec<CentralityData>> {
         self.with_conn(|conn| {
@@ -1071,3 +1292,93 @@ impl Database {
         })
     }
 }
+
+fn path_hop_data(hop: db::PathHopRow) -> PathHopData {
+    PathHopData {
+        symbol_id: hop.symbol_id,
+        symbol_name: hop.symbol_name,
+        file_path: hop.file_path,
+        line: hop.line,
+        call_type: hop.call_type,
+    }
+}
+
+fn symbol_candidate_data(candidate: db::SymbolCandidateRow) -> SymbolCandidateData {
+    SymbolCandidateData {
+        symbol_id: candidate.symbol_id,
+        symbol_name: candidate.symbol_name,
+        kind: candidate.kind,
+        file_path: candidate.file_path,
+        line: candidate.line,
+    }
+}
+
+/// Layers `weights` over `db::EdgeWeights::default()`. Costs must be finite;
+/// call types cost more than zero so every path has a positive length.
+fn edge_weights(weights: EdgeWeightsData) -> Result<db::EdgeWeights> {
+    let mut edge_weights = db::EdgeWeights::default();
+    for (call_type, cost) in weights.call_types.unwrap_or_default() {
+        if !(cost.is_finite() && cost > 0.0) {
+            return Err(Error::from_reason(format!(
+                "call type cost for {} must be a positive number, got {}",
+                call_type, cost
+            )));
+        }
+        edge_weights.call_types.insert(call_type, cost);
+    }
+    for call_type in weights.exclude_call_types.unwrap_or_default() {
+        edge_weights.call_types.remove(&call_type);
+    }
+    for (name, penalty, exclude, slot) in [
+        (
+            "inferred",
+            weights.inferred,
+            weights.exclude_inferred,
+            &mut edge_weights.inferred,
+        ),
+        (
+            "unresolved",
+            weights.unresolved,
+            weights.exclude_unresolved,
+            &mut edge_weights.unresolved,
+        ),
+    ] {
+        if let Some(penalty) = penalty {
+            if !(penalty.is_finite() && penalty >= 0.0) {
+                return Err(Error::from_reason(format!(
+                    "{} penalty must be a non-negative number, got {}",
+                    name, penalty
+                )));
+            }
+            *slot = Some(penalty);
+        }
+        if exclude == Some(true) {
+            *slot = None;
+        }
+    }
+    Ok(edge_weights)
+}
+
+fn chunk_row(chunk: ChunkData) -> db::ChunkRow {
+    db::ChunkRow {
+        chunk_id: chunk.chunk_id,
+        content_hash: chunk.content_hash,
+        file_path: chunk.file_path,
+        start_line: chunk.start_line,
+        end_line: chunk.end_line,
+        node_type: chunk.node_type,
+        name: chunk.name,
+        language: chunk.language,
+        qualified_name: chunk.qualified_name,
+        parent_symbol: chunk.parent_symbol,
+        depth: chunk.depth,
+        signature: chunk.signature,
+        doc_comment: chunk.doc_comment,
+        visibility: chunk.visibility,
+        blame_sha: chunk.blame_sha,
+        blame_author: chunk.blame_author,
+        blame_author_email: chunk.blame_author_email,
+        blame_committed_at: chunk.blame_committed_at,
+        blame_summary: chunk.blame_summary,
+    }
+}
@@ -1,8 +1,15 @@
+use crate::parser;
+use crate::parser_pool;
+use crate::receiver::ReceiverTypes;
+use crate::reference_extractor::FUNCTION_SCOPES;
+use crate::registry;
 use crate::types::Language;
-use anyhow::{anyhow, Result};
+use crate::ParsedSymbol;
+use anyhow::{anyhow, bail, Result};
 use std::collections::{HashMap, HashSet};
+use std::ops::Range;
 use streaming_iterator::StreamingIterator;
-use tree_sitter::{Parser, Query, QueryCursor};
+use tree_sitter::{CaptureQuantifier, Query, QueryCursor, Tree};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum CallType {
@@ -12,10 +19,17 @@ pub enum CallType {
     Import,
     Inherits,
     Implements,
+    /// A type named in an annotation, signature or generic argument.
+    TypeReference,
+    /// A field or property read through `obj.field`.
+    FieldAccess,
+    Read,
+    Write,
+    /// A decorator, annotation or attribute applied without a call.
+    Decorator,
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
-#[allow(dead_code)]
 pub enum Confidence {
     Direct,   // Explicit call/import found in AST
     Inferred, // Pattern-based or indirect (dynamic dispatch, string-based require)
@@ -28,6 +42,96 @@ pub struct CallSite {
     pub column: u32,
     pub call_type: CallType,
     pub confidence: Confidence,
+    /// What the name is looked up on. For method calls, the receiver's
+    /// declared type when one is in scope, else the receiver as written
+    /// (`this`, `repo`, `Repo`); for imports, the module imported from.
+    pub qualifier: Option<String>,
+    /// The innermost symbol around the site, or the file's `<module>`.
+    pub enclosing: EnclosingSymbol,
+}
+
+/// Name of the synthetic symbol that owns sites outside every declaration,
+/// such as top-level imports and module initialization code.
+pub const MODULE_SYMBOL_NAME: &str = "<module>";
+/// Its kind, distinct from real `module` declarations so graph, listing and
+/// lookup queries can leave it out.
+pub const MODULE_SYMBOL_KIND: &str = "file_scope";
+
+/// The declaration a site sits in, as `parse_files` reports its symbols.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnclosingSymbol {
+    pub name: String,
+    pub kind: String,
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+    pub start_byte: u32,
+    pub end_byte: u32,
+}
+
+/// The symbols of one file, for attributing sites to the innermost one.
+pub(crate) struct Enclosers {
+    symbols: Vec<EnclosingSymbol>,
+    module: EnclosingSymbol,
+}
+
+impl Enclosers {
+    pub(crate) fn of(tree: &Tree, source: &str, language: &Language) -> Self {
+        Self::from_spans(
+            &parser::extract_symbol_spans(tree, source, language),
+            tree,
+            source,
+        )
+    }
+
+    /// From symbols `parser::extract_symbol_spans` already returned.
+    pub(crate) fn from_spans(
+        spans: &[(ParsedSymbol, Range<usize>)],
+        tree: &Tree,
+        source: &str,
+    ) -> Self {
+        let symbols = spans
+            .iter()
+            .map(|(symbol, range)| EnclosingSymbol {
+                name: symbol.name.clone(),
+                kind: symbol.kind.clone(),
+                start_line: symbol.start_line,
+                start_col: symbol.start_col,
+                end_line: symbol.end_line,
+                end_col: symbol.end_col,
+                start_byte: range.start as u32,
+                end_byte: range.end as u32,
+            })
+            .collect();
+        let end = tree.root_node().end_position();
+        let module = EnclosingSymbol {
+            name: MODULE_SYMBOL_NAME.to_string(),
+            kind: MODULE_SYMBOL_KIND.to_string(),
+            start_line: 1,
+            start_col: 0,
+            end_line: end.row as u32 + 1,
+            end_col: end.column as u32,
+            start_byte: 0,
+            end_byte: source.len() as u32,
+        };
+        Self { symbols, module }
+    }
+
+    /// The narrowest symbol around `byte`, the more deeply nested one when
+    /// two cover the same range, or the module outside every symbol.
+    pub(crate) fn at(&self, byte: usize) -> &EnclosingSymbol {
+        let byte = byte as u32;
+        let width = |symbol: &EnclosingSymbol| symbol.end_byte - symbol.start_byte;
+        self.symbols
+            .iter()
+            .filter(|symbol| symbol.start_byte <= byte && byte < symbol.end_byte)
+            .fold(None, |best: Option<&EnclosingSymbol>, symbol| match best {
+                Some(best) if width(best) < width(symbol) => Some(best),
+                _ => Some(symbol),
+            })
+            .unwrap_or(&self.module)
+    }
 }
 
 struct CallExclusion {
@@ -122,63 +226,305 @@ fn is_pipe_callable(mut node: tree_sitter::Node<'_>, source: &[u8]) -> bool {
     false
 }
 
-pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>> {
-    let language = Language::from_string(language_name);
-    let ts_language = match language {
-        Language::TypeScript | Language::TypeScriptTsx => {
-            tree_sitter_typescript::LANGUAGE_TSX.into()
+fn is_string_literal(node: tree_sitter::Node<'_>) -> bool {
+    matches!(
+        node.kind(),
+        "string" | "string_literal" | "encapsed_string" | "template_string"
+    )
+}
+
+/// The text of a string literal, unless it interpolates values.
+fn unquote(literal: &str) -> Option<String> {
+    let inner = literal.trim_matches(|c| c == '"' || c == '\'' || c == '`');
+    (!inner.is_empty() && !inner.contains(['{', '$'])).then(|| inner.to_string())
+}
+
+/// What a dynamic call or require names: a string literal's text, else the
+/// string last bound to the variable before `node`. A variable that cannot
+/// be traced to a string, such as a parameter or one last assigned a
+/// computed value, is kept in brackets (`[action]`), which names no symbol,
+/// so the site is recorded but never resolved.
+fn inferred_name(
+    node: tree_sitter::Node<'_>,
+    source: &[u8],
+    bindings: &mut StringBindings,
+) -> Option<String> {
+    let text = node.utf8_text(source).unwrap_or("");
+    if is_string_literal(node) {
+        return unquote(text);
+    }
+    let name = text.trim_start_matches('$');
+    if name.is_empty() {
+        return None;
+    }
+    Some(
+        bindings
+            .latest(node, name, source)
+            .unwrap_or_else(|| format!("[{}]", name)),
+    )
+}
+
+/// The name an inferred `require(path)` is bound to (`const plugin =
+/// require(path)`), else the last segment of the module it loads.
+fn required_name(node: tree_sitter::Node<'_>, module: &str, source: &[u8]) -> String {
+    let call = node.parent().and_then(|arguments| arguments.parent());
+    let binding = call.and_then(|call| {
+        let holder = call.parent()?;
+        let value = ["value", "right"]
+            .iter()
+            .find_map(|field| holder.child_by_field_name(field))?;
+        if value != call {
+            return None;
+        }
+        ["name", "left"]
+            .iter()
+            .find_map(|field| holder.child_by_field_name(field))
+            .filter(|target| target.kind() == "identifier")
+    });
+    match binding {
+        Some(target) => target.utf8_text(source).unwrap_or("").to_string(),
+        None => {
+            let file = module.rsplit('/').next().unwrap_or(module);
+            file.split('.').next().unwrap_or(file).to_string()
+        }
+    }
+}
+
+/// Bindings of each name in a scope, as (binding start byte, string) in
+/// source order. `None` marks a parameter or a value that is not a string.
+type ScopeBindings = HashMap<String, Vec<(usize, Option<String>)>>;
+
+/// String literals bound to names per scope, collected on first use so a file
+/// with many dynamic sites walks each scope once.
+#[derive(Default)]
+struct StringBindings {
+    scopes: HashMap<(usize, usize), ScopeBindings>,
+}
+
+impl StringBindings {
+    /// The string literal in the latest `name = ...` before `node`, looking
+    /// in each enclosing function from the innermost out, then the file. The
+    /// nearest binding wins, so a parameter or a later computed assignment
+    /// leaves the name untraced.
+    fn latest(&mut self, node: tree_sitter::Node<'_>, name: &str, source: &[u8]) -> Option<String> {
+        let mut scopes = Vec::new();
+        let mut current = node.parent();
+        while let Some(ancestor) = current {
+            if FUNCTION_SCOPES.contains(&ancestor.kind()) || ancestor.parent().is_none() {
+                scopes.push(ancestor);
+            }
+            current = ancestor.parent();
         }
-        Language::JavaScript | Language::JavaScriptJsx => tree_sitter_javascript::LANGUAGE.into(),
-        Language::Python => tree_sitter_python::LANGUAGE.into(),
-        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
-        Language::Swift => tree_sitter_swift::LANGUAGE.into(),
-        Language::Go => tree_sitter_go::LANGUAGE.into(),
-        Language::Metal => tree_sitter_cpp::LANGUAGE.into(),
-        Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
-        Language::Zig => tree_sitter_zig::LANGUAGE.into(),
-        Language::Gdscript => tree_sitter_gdscript::LANGUAGE.into(),
-        Language::Matlab => tree_sitter_matlab::LANGUAGE.into(),
-        Language::Apex => tree_sitter_sfapex::apex::LANGUAGE.into(),
-        Language::Bash => tree_sitter_bash::LANGUAGE.into(),
-        Language::C => tree_sitter_c::LANGUAGE.into(),
-        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-        _ => return Ok(vec![]),
-    };
 
-    let mut parser = Parser::new();
-    parser
-        .set_language(&ts_language)
-        .map_err(|e| anyhow!("Failed to set language: {}", e))?;
+        scopes
+            .into_iter()
+            .find_map(|scope| {
+                self.scopes
+                    .entry((scope.start_byte(), scope.end_byte()))
+                    .or_insert_with(|| string_bindings(scope, source))
+                    .get(name)?
+                    .iter()
+                    .rev()
+                    .find(|(start, _)| *start < node.start_byte())
+                    .map(|(_, literal)| literal.clone())
+            })
+            .flatten()
+    }
+}
 
-    let tree = parser
-        .parse(content, None)
-        .ok_or_else(|| anyhow!("Parse failed"))?;
+/// Every parameter of `scope` and every `name = ...` in its own body, by name
+/// and in source order. Nested functions are left to their own scope.
+fn string_bindings(scope: tree_sitter::Node<'_>, source: &[u8]) -> ScopeBindings {
+    let mut bindings = ScopeBindings::new();
+    let mut bind = |target: tree_sitter::Node<'_>, start: usize, literal: Option<String>| {
+        let bound = target
+            .utf8_text(source)
+            .unwrap_or("")
+            .trim_start_matches('$');
+        bindings
+            .entry(bound.to_string())
+            .or_default()
+            .push((start, literal));
+    };
 
-    let query_source = match language {
-        Language::TypeScript | Language::TypeScriptTsx => {
-            include_str!("../queries/typescript-calls.scm")
+    let parameters: Vec<_> = ["parameters", "parameter"]
+        .iter()
+        .filter_map(|field| scope.child_by_field_name(field))
+        .collect();
+    let mut stack = parameters.clone();
+    while let Some(candidate) = stack.pop() {
+        if matches!(candidate.kind(), "identifier" | "variable_name") {
+            bind(candidate, candidate.start_byte(), None);
+            continue;
         }
-        Language::JavaScript | Language::JavaScriptJsx => {
-            include_str!("../queries/javascript-calls.scm")
+        let mut cursor = candidate.walk();
+        stack.extend(candidate.named_children(&mut cursor));
+    }
+
+    let mut stack = vec![scope];
+    while let Some(candidate) = stack.pop() {
+        let target = ["name", "left"]
+            .iter()
+            .find_map(|field| candidate.child_by_field_name(field));
+        let value = ["value", "right"]
+            .iter()
+            .find_map(|field| candidate.child_by_field_name(field));
+        if let (Some(target), Some(value)) = (target, value) {
+            if candidate.kind() != "keyword_argument" {
+                let literal = (!candidate.kind().starts_with("augmented"))
+                    .then(|| bound_literal(value, source))
+                    .flatten();
+                bind(target, candidate.start_byte(), literal);
+            }
         }
-        Language::Python => include_str!("../queries/python-calls.scm"),
-        Language::Rust => include_str!("../queries/rust-calls.scm"),
-        Language::Swift => include_str!("../queries/swift-calls.scm"),
-        Language::Go => include_str!("../queries/go-calls.scm"),
-        Language::Metal => include_str!("../queries/metal-calls.scm"),
-        Language::Php => include_str!("../queries/php-calls.scm"),
-        Language::Zig => include_str!("../queries/zig-calls.scm"),
-        Language::Gdscript => include_str!("../queries/gdscript-calls.scm"),
-        Language::Matlab => include_str!("../queries/matlab-calls.scm"),
-        Language::Apex => include_str!("../queries/apex-calls.scm"),
-        Language::Bash => include_str!("../queries/bash-calls.scm"),
-        Language::C => include_str!("../queries/c-calls.scm"),
-        Language::Cpp => include_str!("../queries/cpp-calls.scm"),
-        _ => return Ok(vec![]),
+        let mut cursor = candidate.walk();
+        stack.extend(candidate.named_children(&mut cursor).filter(|child| {
+            !FUNCTION_SCOPES.contains(&child.kind()) && !parameters.contains(child)
+        }));
+    }
+    for sites in bindings.values_mut() {
+        sites.sort_by_key(|(start, _)| *start);
+    }
+    bindings
+}
+
+/// The string a variable is bound to: a literal, or the name passed to a
+/// lookup such as `getMethod("save")`.
+fn bound_literal(value: tree_sitter::Node<'_>, source: &[u8]) -> Option<String> {
+    let literal = if is_string_literal(value) {
+        value
+    } else {
+        value.child_by_field_name("arguments")?.named_child(0)?
+    };
+    is_string_literal(literal)
+        .then(|| unquote(literal.utf8_text(source).unwrap_or("")))
+        .flatten()
+}
+
+/// Capture names `extract_calls` interprets. Call queries may also use
+/// `_`-prefixed captures as predicate helpers; they are otherwise ignored.
+const CALL_QUERY_CAPTURES: &[&str] = &[
+    "call",
+    "call.suffix",
+    "callee.name",
+    "constructor",
+    "constructor.type",
+    "excluded.name",
+    "implements",
+    "implements.name",
+    "import",
+    "import.default",
+    "import.name",
+    "import.namespace",
+    "import.source",
+    "indirect.type",
+    "indirect.variable",
+    "indirect.variable_type",
+    "inferred",
+    "inherits",
+    "inherits.name",
+    "method.call",
+    "static.call",
+];
+
+/// Captures that decide the call type of a `@callee.name` match.
+const CALL_KIND_CAPTURES: &[&str] = &["call", "method.call", "static.call", "constructor"];
+
+/// Compiles a call query and checks that its captures mean something to
+/// `extract_calls`, so a typo fails when the query is loaded instead of
+/// silently dropping edges. `origin` names the query in error messages.
+pub(crate) fn validate_call_query(
+    grammar: &tree_sitter::Language,
+    source: &str,
+    origin: &str,
+) -> Result<Query> {
+    let query =
+        Query::new(grammar, source).map_err(|e| anyhow!("invalid call query {}: {}", origin, e))?;
+    let names = query.capture_names();
+
+    if let Some(unknown) = names
+        .iter()
+        .find(|name| !name.starts_with('_') && !CALL_QUERY_CAPTURES.contains(name))
+    {
+        bail!(
+            "invalid call query {}: unknown capture `@{}`; expected one of {}",
+            origin,
+            unknown,
+            CALL_QUERY_CAPTURES
+                .iter()
+                .map(|name| format!("`@{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+
+    for pattern in 0..query.pattern_count() {
+        let quantifiers = query.capture_quantifiers(pattern);
+        let uses = |capture: &str| {
+            names
+                .iter()
+                .position(|name| *name == capture)
+                .is_some_and(|index| quantifiers[index] != CaptureQuantifier::Zero)
+        };
+        let line = source[..query.start_byte_for_pattern(pattern)]
+            .matches('\n')
+            .count()
+            + 1;
+        let kind = CALL_KIND_CAPTURES.iter().find(|kind| uses(kind));
+        match (kind, uses("callee.name")) {
+            (Some(kind), false) => bail!(
+                "invalid call query {}:{}: capture `@{}` needs a `@callee.name` in the same pattern",
+                origin,
+                line,
+                kind
+            ),
+            (None, true) => bail!(
+                "invalid call query {}:{}: capture `@callee.name` needs one of `@call`, `@method.call`, `@static.call` or `@constructor` in the same pattern",
+                origin,
+                line
+            ),
+            _ => {}
+        }
+    }
+
+    Ok(query)
+}
+
+pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>> {
+    let language = Language::from_string(language_name);
+    let definition = registry::definition(&language);
+    let Some(ts_language) = &definition.grammar else {
+        return Ok(vec![]);
     };
+    if !registry::has_call_query(&language) {
+        return Ok(vec![]);
+    }
 
-    let query = Query::new(&ts_language, query_source)
-        .map_err(|e| anyhow!("Failed to compile query: {}", e))?;
+    let tree = parse_source(ts_language, content)?;
+    let enclosers = Enclosers::of(&tree, content, &language);
+    calls_in_tree(&tree, content, &language, &enclosers)
+}
+
+pub(crate) fn parse_source(grammar: &tree_sitter::Language, content: &str) -> Result<Tree> {
+    parser_pool::with_parser(grammar, |parser| {
+        parser
+            .parse(content, None)
+            .ok_or_else(|| anyhow!("Parse failed"))
+    })
+}
+
+/// The call sites of an already parsed file, attributed through `enclosers`.
+pub(crate) fn calls_in_tree(
+    tree: &Tree,
+    content: &str,
+    language: &Language,
+    enclosers: &Enclosers,
+) -> Result<Vec<CallSite>> {
+    let language = *language;
+    let definition = registry::definition(&language);
+    let Some(query) = registry::call_query(&language)? else {
+        return Ok(vec![]);
+    };
 
     let callee_name_idx = query.capture_index_for_name("callee.name");
     let call_idx = query.capture_index_for_name("call");
@@ -188,6 +534,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
     let import_name_idx = query.capture_index_for_name("import.name");
     let import_default_idx = query.capture_index_for_name("import.default");
     let import_namespace_idx = query.capture_index_for_name("import.namespace");
+    let import_source_idx = query.capture_index_for_name("import.source");
     let inherits_name_idx = query.capture_index_for_name("inherits.name");
     let implements_name_idx = query.capture_index_for_name("implements.name");
     let constructor_type_idx = query.capture_index_for_name("constructor.type");
@@ -195,6 +542,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
     let indirect_type_idx = query.capture_index_for_name("indirect.type");
     let indirect_variable_type_idx = query.capture_index_for_name("indirect.variable_type");
     let indirect_variable_idx = query.capture_index_for_name("indirect.variable");
+    let inferred_idx = query.capture_index_for_name("inferred");
     let text_bytes = content.as_bytes();
 
     let root = tree.root_node();
@@ -265,6 +613,8 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
 
     let mut cursor = QueryCursor::new();
     let mut calls = Vec::new();
+    let mut receiver_types = ReceiverTypes::default();
+    let mut string_bindings = StringBindings::default();
 
     let mut captures_iter = cursor.captures(&query, tree.root_node(), text_bytes);
 
@@ -289,10 +639,26 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
             continue;
         }
 
+        // Dynamic calls name their target with a string or variable, so
+        // the name is a best guess.
+        let inferred = match_
+            .captures
+            .iter()
+            .any(|capture| inferred_idx == Some(capture.index));
+        let mut name_of = |node: tree_sitter::Node<'_>, text: &str| {
+            if inferred {
+                inferred_name(node, text_bytes, &mut string_bindings)
+            } else {
+                Some(text.to_string())
+            }
+        };
+
         let mut callee_name: Option<String> = None;
         let mut call_type: Option<CallType> = None;
-        let mut position: Option<(u32, u32)> = None;
-        let mut callee_byte = None;
+        let mut site = None;
+        let mut callee_node = None;
+        let mut import_source = None;
+        let mut required_module = None;
         let mut constructor_type = None;
 
         for capture in match_.captures {
@@ -303,14 +669,17 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 constructor_type = Some(text);
             }
 
+            if import_source_idx == Some(capture.index) {
+                import_source = Some(text.trim_matches(|c| c == '"' || c == '\'' || c == '`'));
+            }
+
             if let Some(idx) = callee_name_idx {
                 if capture.index == idx {
-                    callee_name = Some(text.to_string());
-                    if position.is_none() {
-                        let start = node.start_position();
-                        position = Some((start.row as u32 + 1, start.column as u32));
+                    callee_name = name_of(node, text);
+                    if site.is_none() {
+                        site = Some(node);
                     }
-                    callee_byte = Some(node.start_byte());
+                    callee_node = Some(node);
                 }
             }
 
@@ -351,10 +720,23 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
 
             if let Some(idx) = import_name_idx {
                 if capture.index == idx {
-                    callee_name = Some(text.to_string());
+                    if inferred {
+                        // `require(path)` imports the module the path names;
+                        // an untraced path has no module to record.
+                        let module = name_of(node, text);
+                        callee_name = module.as_ref().map(|module| {
+                            if module.starts_with('[') {
+                                module.clone()
+                            } else {
+                                required_name(node, module, text_bytes)
+                            }
+                        });
+                        required_module = module.filter(|module| !module.starts_with('['));
+                    } else {
+                        callee_name = name_of(node, text);
+                    }
                     call_type = Some(CallType::Import);
-                    let start = node.start_position();
-                    position = Some((start.row as u32 + 1, start.column as u32));
+                    site = Some(node);
                 }
             }
 
@@ -362,8 +744,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 if capture.index == idx {
                     callee_name = Some(text.to_string());
                     call_type = Some(CallType::Import);
-                    let start = node.start_position();
-                    position = Some((start.row as u32 + 1, start.column as u32));
+                    site = Some(node);
                 }
             }
 
@@ -371,8 +752,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 if capture.index == idx {
                     callee_name = Some(text.to_string());
                     call_type = Some(CallType::Import);
-                    let start = node.start_position();
-                    position = Some((start.row as u32 + 1, start.column as u32));
+                    site = Some(node);
                 }
             }
 
@@ -380,8 +760,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 if capture.index == idx {
                     callee_name = Some(text.to_string());
                     call_type = Some(CallType::Inherits);
-                    let start = node.start_position();
-                    position = Some((start.row as u32 + 1, start.column as u32));
+                    site = Some(node);
                 }
             }
 
@@ -389,8 +768,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 if capture.index == idx {
                     callee_name = Some(text.to_string());
                     call_type = Some(CallType::Implements);
-                    let start = node.start_position();
-                    position = Some((start.row as u32 + 1, start.column as u32));
+                    site = Some(node);
                 }
             }
         }
@@ -398,7 +776,7 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
         // PHP method calls are already marked in query (@method.call, @static.call)
         // @call is only for direct function calls
         // So we need to check if the call was already classified as a method call
-        if let (Some(name), Some(ct), Some(pos)) = (callee_name, call_type, position) {
+        if let (Some(name), Some(ct), Some(site)) = (callee_name, call_type, site) {
             if ct == CallType::Constructor
                 && constructor_type.is_some_and(|type_name| type_name != name)
             {
@@ -407,22 +785,24 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
 
             if matches!(language, Language::C | Language::Cpp)
                 && matches!(ct, CallType::Call | CallType::MethodCall)
-                && callee_byte.is_some_and(|byte| {
-                    exclusions.get(&name).is_some_and(|matching_exclusions| {
-                        matching_exclusions.iter().any(|exclusion| {
-                            byte >= exclusion.start_byte
-                                && byte <= exclusion.end_byte
-                                && (ct == CallType::Call || exclusion.include_method_calls)
+                && callee_node
+                    .map(|node| node.start_byte())
+                    .is_some_and(|byte| {
+                        exclusions.get(&name).is_some_and(|matching_exclusions| {
+                            matching_exclusions.iter().any(|exclusion| {
+                                byte >= exclusion.start_byte
+                                    && byte <= exclusion.end_byte
+                                    && (ct == CallType::Call || exclusion.include_method_calls)
+                            })
                         })
                     })
-                })
             {
                 continue;
             }
 
-            // PHP and Apex are case-insensitive, so normalize ordinary calls
+            // Case-insensitive languages (PHP, Apex) normalize ordinary calls
             // to lowercase to match their indexed symbols.
-            let normalized_name = if (language == Language::Php || language == Language::Apex)
+            let normalized_name = if definition.case_insensitive
                 && ct != CallType::Import
                 && ct != CallType::Constructor
                 && ct != CallType::Inherits
@@ -433,12 +813,29 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
                 name.clone()
             };
 
+            let qualifier = match ct {
+                CallType::Import if inferred => required_module,
+                _ if inferred => None,
+                // Python reports `obj.method()` as a plain call.
+                CallType::Call | CallType::MethodCall => {
+                    callee_node.and_then(|node| receiver_types.qualifier(node, text_bytes))
+                }
+                CallType::Import => import_source.map(str::to_string),
+                _ => None,
+            };
+
             calls.push(CallSite {
                 callee_name: normalized_name,
-                line: pos.0,
-                column: pos.1,
+                line: site.start_position().row as u32 + 1,
+                column: site.start_position().column as u32,
                 call_type: ct,
-                confidence: Confidence::Direct,
+                confidence: if inferred {
+                    Confidence::Inferred
+                } else {
+                    Confidence::Direct
+                },
+                qualifier,
+                enclosing: enclosers.at(site.start_byte()).clone(),
             });
         }
     }
@@ -452,6 +849,10 @@ pub fn extract_calls(content: &str, language_name: &str) -> Result<Vec<CallSite>
         }) {
             if call_type_specificity(call.call_type) > call_type_specificity(existing.call_type) {
                 *existing = call;
+            } else if existing.qualifier.is_none() && existing.call_type == call.call_type {
+                // Captures can report a match before its later captures
+                // (such as `@import.source`) are in.
+                existing.qualifier = call.qualifier;
             }
         } else {
             deduped.push(call);
@@ -469,6 +870,11 @@ fn call_type_specificity(call_type: CallType) -> u8 {
         CallType::Import => 2,
         CallType::Inherits => 3,
         CallType::Implements => 3,
+        CallType::Read
+        | CallType::Write
+        | CallType::FieldAccess
+        | CallType::TypeReference
+        | CallType::Decorator => 0,
     }
 }
 
@@ -1511,4 +1917,434 @@ int run(Widget* widget, Callback callback, CallbackSignature* signature) {
                 | "identity"
         )));
     }
+
+    fn has_call(calls: &[CallSite], name: &str, call_type: CallType) -> bool {
+        calls
+            .iter()
+            .any(|c| c.callee_name == name && c.call_type == call_type)
+    }
+
+    #[test]
+    fn test_java_calls_constructors_and_imports() {
+        let code = r#"
+import java.util.List;
+import java.util.*;
+import static org.junit.Assert.assertEquals;
+
+class OrderService extends BaseService<Order> implements Runnable, Comparable<OrderService> {
+    void run() {
+        validate(order);
+        repository.save(order);
+        this.notifyListeners();
+        Util.parse("1");
+        List<String> names = new ArrayList<>();
+        Object w = new java.io.StringWriter();
+        Order o = new Order(1);
+    }
+}
+
+interface Audited extends Serializable, Comparable<Audited> {}
+"#;
+        let calls = extract_calls(code, "java").unwrap();
+
+        assert!(has_call(&calls, "validate", CallType::Call), "{calls:?}");
+        assert!(has_call(&calls, "save", CallType::MethodCall), "{calls:?}");
+        assert!(has_call(&calls, "notifyListeners", CallType::MethodCall));
+        assert!(has_call(&calls, "parse", CallType::MethodCall));
+        assert!(has_call(&calls, "ArrayList", CallType::Constructor));
+        assert!(has_call(&calls, "StringWriter", CallType::Constructor));
+        assert!(has_call(&calls, "Order", CallType::Constructor));
+        assert!(has_call(&calls, "List", CallType::Import));
+        assert!(has_call(&calls, "assertEquals", CallType::Import));
+        assert!(!has_call(&calls, "util", CallType::Import), "{calls:?}");
+        assert!(has_call(&calls, "BaseService", CallType::Inherits));
+        assert!(has_call(&calls, "Runnable", CallType::Implements));
+        assert!(has_call(&calls, "Comparable", CallType::Implements));
+        assert!(has_call(&calls, "Serializable", CallType::Inherits));
+    }
+
+    #[test]
+    fn test_csharp_calls_constructors_and_usings() {
+        let code = r#"
+using System;
+using System.Collections.Generic;
+using Json = Newtonsoft.Json;
+
+public class OrderService : BaseService, IDisposable, IComparable<OrderService>
+{
+    public void Run()
+    {
+        Validate(order);
+        Process<int>(1);
+        repository.Save(order);
+        Console.WriteLine("done");
+        logger?.Flush();
+        var names = new List<string>();
+        var builder = new System.Text.StringBuilder();
+        var order = new Order(1);
+    }
+}
+"#;
+        let calls = extract_calls(code, "csharp").unwrap();
+
+        assert!(has_call(&calls, "Validate", CallType::Call), "{calls:?}");
+        assert!(has_call(&calls, "Process", CallType::Call));
+        assert!(has_call(&calls, "Save", CallType::MethodCall));
+        assert!(has_call(&calls, "WriteLine", CallType::MethodCall));
+        assert!(has_call(&calls, "Flush", CallType::MethodCall), "{calls:?}");
+        assert!(has_call(&calls, "List", CallType::Constructor));
+        assert!(has_call(&calls, "StringBuilder", CallType::Constructor));
+        assert!(has_call(&calls, "Order", CallType::Constructor));
+        assert!(has_call(&calls, "System", CallType::Import));
+        assert!(has_call(&calls, "Generic", CallType::Import));
+        assert!(has_call(&calls, "Json", CallType::Import), "{calls:?}");
+        assert!(!has_call(&calls, "Json", CallType::Call));
+        assert!(has_call(&calls, "BaseService", CallType::Inherits));
+        assert!(has_call(&calls, "IDisposable", CallType::Implements));
+        assert!(has_call(&calls, "IComparable", CallType::Implements));
+    }
+
+    #[test]
+    fn test_ruby_calls_constructors_requires_and_mixins() {
+        let code = r#"
+require "json"
+require_relative "lib/helper"
+
+class Admin < User
+  include Comparable
+  extend Concerns::Auditable
+
+  def promote(level)
+    validate!(level)
+    log "promoting"
+    self.save
+    Notifier.deliver(level)
+    User.new(name)
+    Admin::Role.new(level)
+  end
+end
+
+class Job < ActiveJob::Base; end
+"#;
+        let calls = extract_calls(code, "ruby").unwrap();
+
+        assert!(has_call(&calls, "validate!", CallType::Call), "{calls:?}");
+        assert!(has_call(&calls, "log", CallType::Call));
+        assert!(has_call(&calls, "save", CallType::MethodCall));
+        assert!(has_call(&calls, "deliver", CallType::MethodCall));
+        assert!(has_call(&calls, "User", CallType::Constructor));
+        assert!(has_call(&calls, "Role", CallType::Constructor));
+        assert!(!calls.iter().any(|c| c.callee_name == "new"), "{calls:?}");
+        assert!(has_call(&calls, "json", CallType::Import));
+        assert!(has_call(&calls, "lib/helper", CallType::Import));
+        assert!(!calls.iter().any(|c| c.callee_name == "require"));
+        assert!(has_call(&calls, "User", CallType::Inherits));
+        assert!(has_call(&calls, "Base", CallType::Inherits));
+        assert!(has_call(&calls, "Comparable", CallType::Implements));
+        assert!(has_call(&calls, "Auditable", CallType::Implements));
+    }
+
+    #[test]
+    fn test_method_calls_and_imports_carry_qualifiers() {
+        let qualifier = |calls: &[CallSite], name: &str| {
+            calls
+                .iter()
+                .find(|call| call.callee_name == name)
+                .unwrap_or_else(|| panic!("no call to {name}: {calls:?}"))
+                .qualifier
+                .clone()
+        };
+
+        let typescript = r#"
+import { format } from "./util";
+class UserService {
+  constructor(private repo: UserRepo) {}
+  run(cache: Map<string, User>) {
+    this.repo.save();
+    this.validate();
+    const orders = new OrderRepo();
+    orders.load();
+    cache.get("id");
+    Logger.info(format("done"));
+    fetchAll().then();
+  }
+}
+"#;
+        let calls = extract_calls(typescript, "typescript").unwrap();
+        assert_eq!(qualifier(&calls, "save").as_deref(), Some("UserRepo"));
+        assert_eq!(qualifier(&calls, "validate").as_deref(), Some("this"));
+        assert_eq!(qualifier(&calls, "load").as_deref(), Some("OrderRepo"));
+        assert_eq!(qualifier(&calls, "get").as_deref(), Some("Map"));
+        assert_eq!(qualifier(&calls, "info").as_deref(), Some("Logger"));
+        assert_eq!(qualifier(&calls, "then"), None);
+        assert!(calls.iter().any(|call| call.callee_name == "format"
+            && call.call_type == CallType::Call
+            && call.qualifier.is_none()));
+        let import = calls
+            .iter()
+            .find(|call| call.call_type == CallType::Import)
+            .unwrap();
+        assert_eq!(import.qualifier.as_deref(), Some("./util"));
+
+        let python = "from .models import User\n\ndef promote(repo: UserRepo, name):\n    user = User(name)\n    user.save()\n    repo.flush()\n    self.audit()\n";
+        let calls = extract_calls(python, "python").unwrap();
+        assert_eq!(qualifier(&calls, "save").as_deref(), Some("User"));
+        assert_eq!(qualifier(&calls, "flush").as_deref(), Some("UserRepo"));
+        assert_eq!(qualifier(&calls, "audit").as_deref(), Some("self"));
+        let import = calls
+            .iter()
+            .find(|call| call.call_type == CallType::Import)
+            .unwrap();
+        assert_eq!(import.qualifier.as_deref(), Some(".models"));
+
+        let java = "import com.acme.users.UserRepo;\nclass Service {\n  private UserRepo repo;\n  void run(Cache cache) {\n    repo.save();\n    this.repo.save();\n    cache.clear();\n  }\n}\n";
+        let calls = extract_calls(java, "java").unwrap();
+        assert_eq!(qualifier(&calls, "clear").as_deref(), Some("Cache"));
+        assert!(
+            calls
+                .iter()
+                .filter(|call| call.callee_name == "save")
+                .all(|call| call.qualifier.as_deref() == Some("UserRepo")),
+            "{calls:?}"
+        );
+        assert_eq!(
+            qualifier(&calls, "UserRepo").as_deref(),
+            Some("com.acme.users")
+        );
+
+        let rust = "fn sync(store: &mut Store) {\n    store.flush();\n    Store::open();\n}\n";
+        let calls = extract_calls(rust, "rust").unwrap();
+        assert_eq!(qualifier(&calls, "flush").as_deref(), Some("Store"));
+        assert_eq!(qualifier(&calls, "open").as_deref(), Some("Store"));
+    }
+
+    #[test]
+    fn test_dynamic_calls_are_inferred() {
+        let inferred = |calls: &[CallSite], name: &str, call_type: CallType| {
+            calls.iter().any(|call| {
+                call.callee_name == name
+                    && call.call_type == call_type
+                    && call.confidence == Confidence::Inferred
+            })
+        };
+
+        let python = "def run(obj, action):\n    name = 'save'\n    getattr(obj, 'load')()\n    getattr(obj, name)(1)\n    getattr(obj, action)()\n    obj.flush()\n";
+        let calls = extract_calls(python, "python").unwrap();
+        assert!(inferred(&calls, "load", CallType::Call), "{calls:?}");
+        assert!(inferred(&calls, "save", CallType::Call));
+        // An untraced variable names no symbol.
+        assert!(inferred(&calls, "[action]", CallType::Call));
+        assert!(calls
+            .iter()
+            .any(|call| call.callee_name == "flush" && call.confidence == Confidence::Direct));
+
+        let javascript = "const method = 'save';\nrepo[method]();\nrepo['load']();\nconst plugin = './plugins/' + name;\nconst handlers = './handlers';\nconst loaded = require(handlers);\nrequire(handlers);\nrequire(plugin);\nrequire('fs');\n";
+        let calls = extract_calls(javascript, "javascript").unwrap();
+        assert!(inferred(&calls, "save", CallType::Call), "{calls:?}");
+        assert!(inferred(&calls, "load", CallType::Call));
+        // Required modules follow imports: the bound name, or the module's
+        // last segment, imported from the module.
+        let required = |name: &str| {
+            calls
+                .iter()
+                .find(|call| call.callee_name == name && call.call_type == CallType::Import)
+                .map(|call| (call.confidence, call.qualifier.clone()))
+        };
+        let from_handlers = Some((Confidence::Inferred, Some("./handlers".to_string())));
+        assert_eq!(required("loaded"), from_handlers);
+        assert_eq!(required("handlers"), from_handlers);
+        // A computed path names no module.
+        assert_eq!(required("[plugin]"), Some((Confidence::Inferred, None)));
+        assert!(!calls
+            .iter()
+            .any(|call| call.call_type == CallType::Import && call.callee_name == "fs"));
+
+        let typescript = "function run(repo: Repo, key: string) {\n  repo[key]();\n}\n";
+        let calls = extract_calls(typescript, "typescript").unwrap();
+        let call = calls
+            .iter()
+            .find(|call| call.callee_name == "[key]")
+            .unwrap();
+        assert_eq!(call.call_type, CallType::MethodCall);
+        assert_eq!(call.confidence, Confidence::Inferred);
+        assert_eq!(call.qualifier, None);
+
+        // Another function's binding does not leak in, and a parameter or a
+        // computed reassignment leaves the name untraced. Closures still see
+        // their enclosing function's bindings.
+        let javascript = "function a() { const m = 'save'; }\nfunction b(repo, m) { repo[m](); }\nlet k = 'load';\nk = compute();\nrepo[k]();\nfunction c(repo) { const n = 'flush'; items.forEach(() => repo[n]()); }\n";
+        let calls = extract_calls(javascript, "javascript").unwrap();
+        let dynamic: Vec<&str> = calls
+            .iter()
+            .filter(|call| call.confidence == Confidence::Inferred)
+            .map(|call| call.callee_name.as_str())
+            .collect();
+        assert_eq!(dynamic, vec!["[m]", "[k]", "flush"]);
+
+        let java = "class Runner {\n  void run(Object target) throws Exception {\n    target.getClass().getMethod(\"save\").invoke(target);\n    Method load = Repo.class.getDeclaredMethod(\"load\", int.class);\n    load.invoke(target, 1);\n  }\n}\n";
+        let calls = extract_calls(java, "java").unwrap();
+        assert!(inferred(&calls, "save", CallType::MethodCall), "{calls:?}");
+        assert!(inferred(&calls, "load", CallType::MethodCall));
+        assert!(calls
+            .iter()
+            .any(|call| call.callee_name == "getMethod" && call.confidence == Confidence::Direct));
+
+        let php = "<?php\n$fn = 'Helper';\n$fn();\n$obj->$method();\nhelper();\n";
+        let calls = extract_calls(php, "php").unwrap();
+        assert!(inferred(&calls, "helper", CallType::Call), "{calls:?}");
+        assert!(inferred(&calls, "[method]", CallType::MethodCall));
+        assert!(calls.iter().any(|call| call.callee_name == "helper"
+            && call.line == 5
+            && call.confidence == Confidence::Direct));
+    }
+
+    #[test]
+    fn test_sites_carry_their_enclosing_symbol() {
+        let typescript = r#"import { load } from "./store";
+configure();
+
+export class UserService {
+  save(user: User) {
+    const check = () => validate(user);
+    return persist(user);
+  }
+}
+"#;
+        let calls = extract_calls(typescript, "typescript").unwrap();
+        let enclosing = |name: &str| {
+            &calls
+                .iter()
+                .find(|call| call.callee_name == name)
+                .unwrap_or_else(|| panic!("no call to {name}: {calls:?}"))
+                .enclosing
+        };
+
+        let module = enclosing("load");
+        assert_eq!(module.name, MODULE_SYMBOL_NAME);
+        assert_eq!(module.kind, MODULE_SYMBOL_KIND);
+        assert_eq!(
+            (module.start_byte, module.end_byte),
+            (0, typescript.len() as u32)
+        );
+        assert_eq!(enclosing("configure"), module);
+
+        let save = enclosing("persist");
+        assert_eq!(
+            (save.name.as_str(), save.kind.as_str()),
+            ("save", "method_definition")
+        );
+        assert_eq!((save.start_line, save.start_col), (5, 2));
+        assert_eq!(
+            &typescript[save.start_byte as usize..save.start_byte as usize + 4],
+            "save"
+        );
+        let check = enclosing("validate");
+        assert_eq!(
+            (check.name.as_str(), check.kind.as_str()),
+            ("check", "arrow_function")
+        );
+
+        let python = "import os\n\nclass Loader:\n    def load(self):\n        return os.getcwd()\n\nLoader().load()\n";
+        let calls = extract_calls(python, "python").unwrap();
+        let getcwd = calls
+            .iter()
+            .find(|call| call.callee_name == "getcwd")
+            .unwrap();
+        assert_eq!(getcwd.enclosing.name, "load");
+        let import = calls.iter().find(|call| call.callee_name == "os").unwrap();
+        assert_eq!(import.enclosing.name, MODULE_SYMBOL_NAME);
+        let top_level = calls
+            .iter()
+            .find(|call| call.callee_name == "Loader")
+            .unwrap();
+        assert_eq!(top_level.enclosing.name, MODULE_SYMBOL_NAME);
+    }
+
+    #[test]
+    fn test_builtin_call_queries_pass_validation() {
+        for definition in registry::all_definitions() {
+            if let (Some(grammar), Some(source)) = (&definition.grammar, definition.call_query) {
+                validate_call_query(grammar, source, definition.name).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_validate_call_query_names_the_failing_capture() {
+        let grammar: tree_sitter::Language = tree_sitter_python::LANGUAGE.into();
+
+        let error = validate_call_query(
+            &grammar,
+            "(call function: (identifier) @callee.nam) @call",
+            "calls.scm",
+        )
+        .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid call query calls.scm: unknown capture `@callee.nam`"));
+
+        let error = validate_call_query(
+            &grammar,
+            "(call function: (identifier) @callee.name) @call\n\n(call function: (attribute)) @method.call",
+            "calls.scm",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid call query calls.scm:3: capture `@method.call` needs a `@callee.name` in the same pattern"
+        );
+
+        let error = validate_call_query(
+            &grammar,
+            "(call function: (identifer) @callee.name) @call",
+            "calls.scm",
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "invalid call query calls.scm: Query error at 1:18. Invalid node type \"identifer\""
+        );
+    }
+
+    /// Wall-clock benchmark, kept out of the default run so loaded CI
+    /// machines cannot flake it.
+    #[test]
+    #[ignore = "benchmark: cargo test --release -- --ignored"]
+    fn bench_small_files_reuse_parsers_and_queries() {
+        let files: Vec<String> = (0..50)
+            .map(|i| format!("def handler_{i}(event):\n    return dispatch(event, {i})\n"))
+            .collect();
+        let definition = registry::definition(&Language::Python);
+        let grammar = definition.grammar.clone().unwrap();
+        let query_source = definition.call_query.unwrap();
+
+        // What every file used to pay: a new parser and a fresh compilation.
+        let started = std::time::Instant::now();
+        for content in &files {
+            let mut parser = tree_sitter::Parser::new();
+            parser.set_language(&grammar).unwrap();
+            let tree = parser.parse(content, None).unwrap();
+            let query = Query::new(&grammar, query_source).unwrap();
+            let mut cursor = QueryCursor::new();
+            let mut matches = cursor.matches(&query, tree.root_node(), content.as_bytes());
+            while matches.next().is_some() {}
+        }
+        let uncached = started.elapsed();
+
+        extract_calls(&files[0], "python").unwrap();
+        let started = std::time::Instant::now();
+        for content in &files {
+            let calls = extract_calls(content, "python").unwrap();
+            assert!(calls.iter().any(|call| call.callee_name == "dispatch"));
+        }
+        let cached = started.elapsed();
+
+        assert!(
+            cached * 10 < uncached,
+            "expected an order of magnitude: cached {:?} vs uncached {:?} for {} small files",
+            cached,
+            uncached,
+            files.len()
+        );
+    }
 }
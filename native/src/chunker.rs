@@ -1,8 +1,190 @@
+use crate::tokenizer::SharedTokenizer;
+use crate::types::Language;
 use crate::CodeChunk;
+use anyhow::{bail, Result};
+use std::collections::HashMap;
+use std::time::Duration;
 
 pub const MIN_CHUNK_SIZE: usize = 50;
 pub const MAX_CHUNK_SIZE: usize = 2000;
 pub const TARGET_CHUNK_SIZE: usize = 500;
+pub const OVERLAP_LINES: usize = 3;
+/// Tokens of `max_tokens` kept free for the type, name and path prefix the
+/// embedding text puts before a chunk's content.
+pub const RESERVED_TOKENS: usize = 64;
+/// Window used when chunking by lines at the default target size.
+const TEXT_WINDOW_LINES: usize = 30;
+pub const PARSE_TIMEOUT_MS: u64 = 5_000;
+pub const MAX_PARSE_BYTES: usize = 4 * 1024 * 1024;
+pub const MAX_AST_NODES: usize = 1_000_000;
+pub const MINIFIED_LINE_LENGTH: usize = 500;
+/// Files shorter than this are never treated as minified.
+const MINIFIED_MIN_BYTES: usize = 2048;
+
+/// Size limits for one language, in bytes of chunk content.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ChunkConfig {
+    pub min_size: usize,
+    pub max_size: usize,
+    pub target_size: usize,
+    pub overlap_lines: usize,
+    pub merge_small_chunks: bool,
+    /// Token budget per embedded chunk under `tokenizer`; `None` sizes
+    /// chunks by bytes only and never counts tokens.
+    pub max_tokens: Option<usize>,
+    /// Tokens of `max_tokens` left for the embedding text's prefix.
+    pub reserved_tokens: usize,
+    pub tokenizer: SharedTokenizer,
+}
+
+impl Default for ChunkConfig {
+    fn default() -> Self {
+        Self {
+            min_size: MIN_CHUNK_SIZE,
+            max_size: MAX_CHUNK_SIZE,
+            target_size: TARGET_CHUNK_SIZE,
+            overlap_lines: OVERLAP_LINES,
+            merge_small_chunks: true,
+            max_tokens: None,
+            reserved_tokens: RESERVED_TOKENS,
+            tokenizer: SharedTokenizer::default(),
+        }
+    }
+}
+
+impl ChunkConfig {
+    /// Lines per piece when an oversized node is split by lines.
+    pub(crate) fn split_window_lines(&self) -> usize {
+        (self.target_size / 40).max(1)
+    }
+
+    /// Lines per chunk for files without a grammar; 30 at the default target.
+    pub(crate) fn text_window_lines(&self) -> usize {
+        (self.target_size * TEXT_WINDOW_LINES / TARGET_CHUNK_SIZE).max(1)
+    }
+
+    /// Tokens a chunk's content may take: `max_tokens` less the embedding
+    /// prefix and the model's special tokens.
+    pub(crate) fn content_token_budget(&self) -> Option<usize> {
+        self.max_tokens
+            .map(|max| max.saturating_sub(self.reserved_tokens + self.tokenizer.special_tokens()))
+    }
+
+    /// Whether `text` is over the content token budget. Without a budget
+    /// nothing is counted.
+    pub(crate) fn exceeds_token_budget(&self, text: &str) -> bool {
+        self.content_token_budget()
+            .is_some_and(|max| self.tokenizer.count_tokens(text) > max)
+    }
+
+    fn validate(&self, scope: &str) -> Result<()> {
+        if self.max_size == 0 || self.target_size == 0 || self.max_tokens == Some(0) {
+            bail!("{scope}: maxChunkSize, targetChunkSize and maxChunkTokens must be positive");
+        }
+        if self.content_token_budget() == Some(0) {
+            bail!(
+                "{scope}: maxChunkTokens ({}) leaves no room for content after {} reserved and {} special tokens",
+                self.max_tokens.unwrap_or_default(),
+                self.reserved_tokens,
+                self.tokenizer.special_tokens()
+            );
+        }
+        if self.min_size > self.max_size {
+            bail!(
+                "{scope}: minChunkSize ({}) exceeds maxChunkSize ({})",
+                self.min_size,
+                self.max_size
+            );
+        }
+        if self.target_size > self.max_size {
+            bail!(
+                "{scope}: targetChunkSize ({}) exceeds maxChunkSize ({})",
+                self.target_size,
+                self.max_size
+            );
+        }
+        Ok(())
+    }
+}
+
+/// Per-file limits past which a file is chunked by lines instead of parsed,
+/// so one pathological file cannot stall a whole parse batch. `None`
+/// disables a limit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseBudget {
+    pub timeout: Option<Duration>,
+    pub max_bytes: Option<usize>,
+    pub max_nodes: Option<usize>,
+    /// Average line length above which a file is treated as minified.
+    pub max_average_line_length: Option<usize>,
+}
+
+impl Default for ParseBudget {
+    fn default() -> Self {
+        Self {
+            timeout: Some(Duration::from_millis(PARSE_TIMEOUT_MS)),
+            max_bytes: Some(MAX_PARSE_BYTES),
+            max_nodes: Some(MAX_AST_NODES),
+            max_average_line_length: Some(MINIFIED_LINE_LENGTH),
+        }
+    }
+}
+
+impl ParseBudget {
+    /// Why `content` should not be parsed at all: `too-large` or `minified`.
+    pub(crate) fn precheck(&self, content: &str) -> Option<&'static str> {
+        if self.max_bytes.is_some_and(|max| content.len() > max) {
+            return Some("too-large");
+        }
+        if let Some(max) = self.max_average_line_length {
+            if content.len() >= MINIFIED_MIN_BYTES {
+                let lines = content.lines().count().max(1);
+                if content.len() / lines > max {
+                    return Some("minified");
+                }
+            }
+        }
+        None
+    }
+}
+
+/// Chunking limits with optional per-language overrides, keyed by
+/// `Language::as_str`, and the parse budget applied to every file.
+#[derive(Clone, Debug, Default)]
+pub struct ChunkingProfile {
+    pub base: ChunkConfig,
+    pub overrides: HashMap<String, ChunkConfig>,
+    pub budget: ParseBudget,
+    /// Also extract call sites and references from each parsed tree.
+    pub include_calls: bool,
+}
+
+impl ChunkingProfile {
+    pub fn new(base: ChunkConfig, overrides: HashMap<String, ChunkConfig>) -> Result<Self> {
+        base.validate("chunking options")?;
+        let mut normalized = HashMap::with_capacity(overrides.len());
+        for (language, config) in overrides {
+            let parsed = Language::from_string(&language);
+            let is_text = matches!(language.to_lowercase().as_str(), "text" | "txt");
+            if parsed == Language::Text && !is_text {
+                bail!("unknown language in chunking overrides: {language}");
+            }
+            let key = parsed.as_str().to_string();
+            config.validate(&format!("chunking override for {language}"))?;
+            normalized.insert(key, config);
+        }
+        Ok(Self {
+            base,
+            overrides: normalized,
+            budget: ParseBudget::default(),
+            include_calls: false,
+        })
+    }
+
+    pub fn for_language(&self, language: &Language) -> &ChunkConfig {
+        self.overrides.get(language.as_str()).unwrap_or(&self.base)
+    }
+}
 
 pub fn create_embedding_text(chunk: &CodeChunk) -> String {
     let mut text = String::with_capacity(chunk.content.len() + 100);
@@ -16,14 +198,16 @@ pub fn create_embedding_text(chunk: &CodeChunk) -> String {
     text
 }
 
-pub fn estimate_tokens(text: &str) -> usize {
-    text.len() / 4
+/// Token count under `tokenizer`.
+pub fn estimate_tokens(text: &str, tokenizer: &SharedTokenizer) -> usize {
+    tokenizer.count_tokens(text)
 }
 
-pub fn estimate_chunks_tokens(chunks: &[CodeChunk]) -> usize {
+/// Tokens the chunks take as model inputs, special tokens included.
+pub fn estimate_chunks_tokens(chunks: &[CodeChunk], tokenizer: &SharedTokenizer) -> usize {
     chunks
         .iter()
-        .map(|c| estimate_tokens(&create_embedding_text(c)))
+        .map(|c| tokenizer.count_input_tokens(&create_embedding_text(c)))
         .sum()
 }
 
@@ -42,6 +226,15 @@ mod tests {
             chunk_type: "function_declaration".to_string(),
             name: Some("greet".to_string()),
             language: "typescript".to_string(),
+            fence_language: None,
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
+            part_index: None,
+            part_count: None,
         };
 
         let text = create_embedding_text(&chunk);
@@ -50,10 +243,44 @@ mod tests {
         assert!(text.contains("function greet()"));
     }
 
+    #[test]
+    fn test_chunking_profile_applies_language_overrides() {
+        let python = ChunkConfig {
+            max_size: 800,
+            target_size: 300,
+            ..ChunkConfig::default()
+        };
+        let profile = ChunkingProfile::new(
+            ChunkConfig::default(),
+            HashMap::from([("py".to_string(), python)]),
+        )
+        .unwrap();
+
+        assert_eq!(profile.for_language(&Language::Python).max_size, 800);
+        assert_eq!(
+            profile.for_language(&Language::Rust),
+            &ChunkConfig::default()
+        );
+        assert_eq!(ChunkConfig::default().text_window_lines(), 30);
+    }
+
+    #[test]
+    fn test_chunking_profile_rejects_inconsistent_sizes() {
+        let inverted = ChunkConfig {
+            min_size: 3000,
+            ..ChunkConfig::default()
+        };
+        let error = ChunkingProfile::new(inverted, HashMap::new()).unwrap_err();
+        assert!(error.to_string().contains("minChunkSize"));
+
+        let unknown = HashMap::from([("klingon".to_string(), ChunkConfig::default())]);
+        assert!(ChunkingProfile::new(ChunkConfig::default(), unknown).is_err());
+    }
+
     #[test]
     fn test_token_estimation() {
         let text = "This is a test string for token estimation";
-        let tokens = estimate_tokens(text);
+        let tokens = estimate_tokens(text, &SharedTokenizer::default());
         assert!(tokens > 0);
         assert!(tokens < text.len());
     }
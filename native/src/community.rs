@@ -49,7 +49,7 @@ pub struct CentralityScore {
     pub total_connections: u32,
 }
 
-fn resolve_target_symbol(
+pub(crate) fn resolve_target_symbol(
     to_symbol_id: &Option<String>,
     target_name: &str,
     symbol_map: &HashMap<String, SymbolRow>,
@@ -73,7 +73,7 @@ fn resolve_target_symbol(
     None
 }
 
-fn build_symbol_maps(
+pub(crate) fn build_symbol_maps(
     symbols: &[SymbolRow],
 ) -> (HashMap<String, SymbolRow>, HashMap<String, Vec<String>>) {
     let symbol_map: HashMap<String, SymbolRow> =
@@ -179,15 +179,16 @@ fn bfs_callees(
         queue.push_back((id.clone(), 0));
     }
 
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&db::with_branch_targets(&db::with_calls_only(
         r#"
-        SELECT ce.target_name, ce.to_symbol_id
+        SELECT ce.target_name, {edge_target}
         FROM call_edges ce
         INNER JOIN symbols s ON ce.from_symbol_id = s.id
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?
-        WHERE ce.from_symbol_id = ?
+        {branch_targets}
+        WHERE ce.from_symbol_id = ? AND {calls_only}
         "#,
-    )?;
+    )))?;
 
     while let Some((current_id, depth)) = queue.pop_front() {
         if depth >= max_depth {
@@ -249,15 +250,16 @@ fn bfs_callers(
         queue.push_back((id.clone(), 0));
     }
 
-    let mut stmt = conn.prepare(
+    let mut stmt = conn.prepare(&db::with_branch_targets(&db::with_calls_only(
         r#"
-        SELECT ce.from_symbol_id, ce.to_symbol_id, ce.target_name
+        SELECT ce.from_symbol_id, {edge_target}, ce.target_name
         FROM call_edges ce
         INNER JOIN symbols s ON ce.from_symbol_id = s.id
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?
-        WHERE (ce.to_symbol_id = ? OR ce.target_name = ? COLLATE NOCASE)
+        {branch_targets}
+        WHERE ({edge_target} = ? OR ce.target_name = ? COLLATE NOCASE) AND {calls_only}
         "#,
-    )?;
+    )))?;
 
     while let Some((current_id, depth)) = queue.pop_front() {
         if depth >= max_depth {
@@ -295,7 +297,10 @@ fn bfs_callers(
                 }
             };
 
-            if is_match && !visited.contains_key(&from_symbol_id) {
+            if is_match
+                && symbol_map.contains_key(&from_symbol_id)
+                && !visited.contains_key(&from_symbol_id)
+            {
                 visited.insert(from_symbol_id.clone(), depth + 1);
                 queue.push_back((from_symbol_id, depth + 1));
             }
@@ -323,13 +328,15 @@ pub fn detect_communities(
         adjacency.insert(s.id.clone(), HashSet::new());
     }
 
-    let mut edges_stmt = conn.prepare(
+    let mut edges_stmt = conn.prepare(&db::with_branch_targets(&db::with_calls_only(
         r#"
-        SELECT ce.from_symbol_id, ce.target_name, ce.to_symbol_id
+        SELECT ce.from_symbol_id, ce.target_name, {edge_target}
         FROM call_edges ce
         INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+        {branch_targets}
+        WHERE {calls_only}
         "#,
-    )?;
+    )))?;
 
     let edge_rows: Vec<(String, String, Option<String>)> = edges_stmt
         .query_map(params![branch], |row| {
@@ -343,6 +350,10 @@ pub fn detect_communities(
         .collect();
 
     for (from_id, target_name, to_symbol_id) in edge_rows {
+        // Sites outside every declaration belong to no listed symbol.
+        if !symbol_map.contains_key(&from_id) {
+            continue;
+        }
         let resolved = resolve_target_symbol(&to_symbol_id, &target_name, &symbol_map, &name_map);
 
         if let Some(to_id) = resolved {
@@ -526,13 +537,15 @@ pub fn compute_centrality(conn: &Connection, branch: &str) -> DbResult<Vec<Centr
         callee_counts.insert(s.id.clone(), 0);
     }
 
-    let mut edges_stmt = conn.prepare(
+    let mut edges_stmt = conn.prepare(&db::with_branch_targets(&db::with_calls_only(
         r#"
-        SELECT ce.from_symbol_id, ce.target_name, ce.to_symbol_id
+        SELECT ce.from_symbol_id, ce.target_name, {edge_target}
         FROM call_edges ce
         INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+        {branch_targets}
+        WHERE {calls_only}
         "#,
-    )?;
+    )))?;
 
     let edge_rows: Vec<(String, String, Option<String>)> = edges_stmt
         .query_map(params![branch], |row| {
@@ -546,6 +559,9 @@ pub fn compute_centrality(conn: &Connection, branch: &str) -> DbResult<Vec<Centr
         .collect();
 
     for (from_id, target_name, to_symbol_id) in edge_rows {
+        if !symbol_map.contains_key(&from_id) {
+            continue;
+        }
         *callee_counts.entry(from_id.clone()).or_insert(0) += 1;
 
         let resolved = resolve_target_symbol(&to_symbol_id, &target_name, &symbol_map, &name_map);
@@ -597,13 +613,15 @@ pub fn detect_community_couplings(
         .map(|assignment| (assignment.symbol_id, assignment.community_id))
         .collect();
 
-    let mut edges_stmt = conn.prepare(
+    let mut edges_stmt = conn.prepare(&db::with_branch_targets(&db::with_calls_only(
         r#"
-        SELECT ce.from_symbol_id, ce.target_name, ce.to_symbol_id
+        SELECT ce.from_symbol_id, ce.target_name, {edge_target}
         FROM call_edges ce
         INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+        {branch_targets}
+        WHERE {calls_only}
         "#,
-    )?;
+    )))?;
 
     let edge_rows: Vec<(String, String, Option<String>)> = edges_stmt
         .query_map(params![branch], |row| {
@@ -745,6 +763,12 @@ mod tests {
             end_line: 10,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         }
     }
 
@@ -759,6 +783,9 @@ mod tests {
             line: 1,
             col: 0,
             is_resolved: to.is_some(),
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         }
     }
 
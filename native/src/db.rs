@@ -3,7 +3,11 @@ use std::path::Path;
 use thiserror::Error;
 
 mod call_graph;
+mod call_paths;
+mod call_resolution;
 pub use call_graph::*;
+pub use call_paths::*;
+pub use call_resolution::*;
 
 #[derive(Error, Debug)]
 pub enum DbError {
@@ -20,7 +24,7 @@ pub enum DbError {
 pub type DbResult<T> = Result<T, DbError>;
 
 /// Schema version for migrations
-const SCHEMA_VERSION: i32 = 7;
+const SCHEMA_VERSION: i32 = 10;
 
 /// Maximum number of SQL bind parameters per query.
 /// SQLite defaults to 999 (SQLITE_MAX_VARIABLE_NUMBER). We use 900 to stay safely under.
@@ -134,10 +138,9 @@ pub fn open_db_read_only(db_path: &Path) -> DbResult<Connection> {
         ))
     })?;
 
-    // v7 changes path-storage semantics without changing the SQLite layout.
-    // The TypeScript layer knows the index scope and decides whether v6 paths
-    // require a project rebuild or remain valid for a global index.
-    if current_version != 6 && current_version != SCHEMA_VERSION {
+    // Readers select the v8 nesting, v9 API-summary and v10 call resolution
+    // columns, so older layouts need a writer to migrate them first.
+    if current_version != SCHEMA_VERSION {
         return Err(DbError::ReadOnlySchema(format!(
             "found version {current_version}, expected {SCHEMA_VERSION}; a writer must migrate the index"
         )));
@@ -185,6 +188,12 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
                 node_type TEXT,
                 name TEXT,
                 language TEXT NOT NULL,
+                qualified_name TEXT,
+                parent_symbol TEXT,
+                depth INTEGER,
+                signature TEXT,
+                doc_comment TEXT,
+                visibility TEXT,
                 blame_sha TEXT,
                 blame_author TEXT,
                 blame_author_email TEXT,
@@ -204,6 +213,7 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
             CREATE INDEX IF NOT EXISTS idx_chunks_file_path ON chunks(file_path);
             CREATE INDEX IF NOT EXISTS idx_chunks_name ON chunks(name);
             CREATE INDEX IF NOT EXISTS idx_chunks_name_lower ON chunks(lower(name));
+            CREATE INDEX IF NOT EXISTS idx_chunks_qualified_name ON chunks(qualified_name);
             CREATE INDEX IF NOT EXISTS idx_branch_chunks_branch ON branch_chunks(branch);
             CREATE INDEX IF NOT EXISTS idx_branch_chunks_chunk_id ON branch_chunks(chunk_id);
             "#,
@@ -230,7 +240,13 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
                 start_col INTEGER NOT NULL,
                 end_line INTEGER NOT NULL,
                 end_col INTEGER NOT NULL,
-                language TEXT NOT NULL
+                language TEXT NOT NULL,
+                qualified_name TEXT,
+                parent_symbol TEXT,
+                depth INTEGER,
+                signature TEXT,
+                doc_comment TEXT,
+                visibility TEXT
             );
 
             -- Call edges: relationships between symbols (caller -> callee)
@@ -244,6 +260,7 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
                 line INTEGER NOT NULL,
                 col INTEGER NOT NULL,
                 is_resolved INTEGER NOT NULL DEFAULT 0,
+                qualifier TEXT,
                 FOREIGN KEY (from_symbol_id) REFERENCES symbols(id) ON DELETE CASCADE
             );
 
@@ -257,6 +274,7 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
             -- Indexes
             CREATE INDEX IF NOT EXISTS idx_symbols_file_path ON symbols(file_path);
             CREATE INDEX IF NOT EXISTS idx_symbols_name ON symbols(name);
+            CREATE INDEX IF NOT EXISTS idx_symbols_qualified_name ON symbols(qualified_name);
             CREATE INDEX IF NOT EXISTS idx_call_edges_from ON call_edges(from_symbol_id);
             CREATE INDEX IF NOT EXISTS idx_call_edges_to ON call_edges(to_symbol_id);
             CREATE INDEX IF NOT EXISTS idx_call_edges_target_name ON call_edges(target_name);
@@ -288,6 +306,7 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
                 line INTEGER NOT NULL,
                 col INTEGER NOT NULL,
                 is_resolved INTEGER NOT NULL DEFAULT 0,
+                qualifier TEXT,
                 FOREIGN KEY (from_symbol_id) REFERENCES symbols(id) ON DELETE CASCADE
             );
 
@@ -371,6 +390,92 @@ fn migrate_schema(conn: &Connection, from_version: i32) -> DbResult<()> {
         )?;
     }
 
+    // v8: Declaration nesting. Fresh installs get the columns from the CREATE
+    // TABLE statements above.
+    if (1..8).contains(&from_version) {
+        for table in ["chunks", "symbols"] {
+            add_column_if_missing(conn, table, "qualified_name", "TEXT")?;
+            add_column_if_missing(conn, table, "parent_symbol", "TEXT")?;
+            add_column_if_missing(conn, table, "depth", "INTEGER")?;
+        }
+        conn.execute_batch(
+            r#"
+            CREATE INDEX IF NOT EXISTS idx_chunks_qualified_name ON chunks(qualified_name);
+            CREATE INDEX IF NOT EXISTS idx_symbols_qualified_name ON symbols(qualified_name);
+            "#,
+        )?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('schema_version', ?)",
+            params![SCHEMA_VERSION.to_string()],
+        )?;
+    }
+
+    // v9: Declaration signatures, doc comments and visibility.
+    if (1..9).contains(&from_version) {
+        for table in ["chunks", "symbols"] {
+            add_column_if_missing(conn, table, "signature", "TEXT")?;
+            add_column_if_missing(conn, table, "doc_comment", "TEXT")?;
+            add_column_if_missing(conn, table, "visibility", "TEXT")?;
+        }
+
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('schema_version', ?)",
+            params![SCHEMA_VERSION.to_string()],
+        )?;
+    }
+
+    // v10: Call edge qualifiers and the targets each branch resolved its
+    // call edges to. Fresh installs and upgrades from v2 or below get the
+    // qualifier column from the v2/v3 CREATE TABLE statements above.
+    if from_version < 10 {
+        if from_version >= 3 {
+            add_column_if_missing(conn, "call_edges", "qualifier", "TEXT")?;
+        }
+        conn.execute_batch(
+            r#"
+            -- Call edge targets chosen by resolve_call_edges, per branch, so
+            -- branches sharing a caller keep their own resolution
+            CREATE TABLE IF NOT EXISTS branch_call_edge_targets (
+                branch TEXT NOT NULL,
+                edge_id TEXT NOT NULL,
+                to_symbol_id TEXT,
+                resolution_strategy TEXT NOT NULL,
+                resolution_confidence REAL NOT NULL,
+                PRIMARY KEY (branch, edge_id),
+                FOREIGN KEY (edge_id) REFERENCES call_edges(id) ON DELETE CASCADE
+            );
+
+            CREATE INDEX IF NOT EXISTS idx_branch_call_edge_targets_edge_id ON branch_call_edge_targets(edge_id);
+            CREATE INDEX IF NOT EXISTS idx_branch_call_edge_targets_to ON branch_call_edge_targets(to_symbol_id);
+            "#,
+        )?;
+
+        conn.execute(
+            "INSERT OR REPLACE INTO metadata (key, value) VALUES ('schema_version', ?)",
+            params![SCHEMA_VERSION.to_string()],
+        )?;
+    }
+
+    Ok(())
+}
+
+fn add_column_if_missing(
+    conn: &Connection,
+    table: &str,
+    column: &str,
+    declaration: &str,
+) -> DbResult<()> {
+    let exists: bool = conn.query_row(
+        &format!("SELECT COUNT(*) > 0 FROM pragma_table_info('{table}') WHERE name = ?"),
+        params![column],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        conn.execute_batch(&format!(
+            "ALTER TABLE {table} ADD COLUMN {column} {declaration};"
+        ))?;
+    }
     Ok(())
 }
 
@@ -520,27 +625,11 @@ pub fn get_missing_embeddings(
 // ============================================================================
 
 /// Insert or update a chunk
-#[allow(clippy::too_many_arguments)]
-pub fn upsert_chunk_with_blame(
-    conn: &Connection,
-    chunk_id: &str,
-    content_hash: &str,
-    file_path: &str,
-    start_line: u32,
-    end_line: u32,
-    node_type: Option<&str>,
-    name: Option<&str>,
-    language: &str,
-    blame_sha: Option<&str>,
-    blame_author: Option<&str>,
-    blame_author_email: Option<&str>,
-    blame_committed_at: Option<i64>,
-    blame_summary: Option<&str>,
-) -> DbResult<()> {
+pub fn upsert_chunk_row(conn: &Connection, chunk: &ChunkRow) -> DbResult<()> {
     conn.execute(
         r#"
-        INSERT INTO chunks (chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT INTO chunks (chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         ON CONFLICT(chunk_id) DO UPDATE SET
             content_hash = excluded.content_hash,
             file_path = excluded.file_path,
@@ -549,6 +638,12 @@ pub fn upsert_chunk_with_blame(
             node_type = excluded.node_type,
             name = excluded.name,
             language = excluded.language,
+            qualified_name = excluded.qualified_name,
+            parent_symbol = excluded.parent_symbol,
+            depth = excluded.depth,
+            signature = excluded.signature,
+            doc_comment = excluded.doc_comment,
+            visibility = excluded.visibility,
             blame_sha = excluded.blame_sha,
             blame_author = excluded.blame_author,
             blame_author_email = excluded.blame_author_email,
@@ -556,19 +651,25 @@ pub fn upsert_chunk_with_blame(
             blame_summary = excluded.blame_summary
         "#,
         params![
-            chunk_id,
-            content_hash,
-            file_path,
-            start_line,
-            end_line,
-            node_type,
-            name,
-            language,
-            blame_sha,
-            blame_author,
-            blame_author_email,
-            blame_committed_at,
-            blame_summary
+            chunk.chunk_id,
+            chunk.content_hash,
+            chunk.file_path,
+            chunk.start_line,
+            chunk.end_line,
+            chunk.node_type,
+            chunk.name,
+            chunk.language,
+            chunk.qualified_name,
+            chunk.parent_symbol,
+            chunk.depth,
+            chunk.signature,
+            chunk.doc_comment,
+            chunk.visibility,
+            chunk.blame_sha,
+            chunk.blame_author,
+            chunk.blame_author_email,
+            chunk.blame_committed_at,
+            chunk.blame_summary
         ],
     )?;
     Ok(())
@@ -583,8 +684,8 @@ pub fn upsert_chunks_batch(conn: &mut Connection, chunks: &[ChunkRow]) -> DbResu
     run_batch_with_write_transaction(conn, |conn| {
         let mut stmt = conn.prepare(
             r#"
-            INSERT INTO chunks (chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT INTO chunks (chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             ON CONFLICT(chunk_id) DO UPDATE SET
                 content_hash = excluded.content_hash,
                 file_path = excluded.file_path,
@@ -593,6 +694,12 @@ pub fn upsert_chunks_batch(conn: &mut Connection, chunks: &[ChunkRow]) -> DbResu
                 node_type = excluded.node_type,
                 name = excluded.name,
                 language = excluded.language,
+                qualified_name = excluded.qualified_name,
+                parent_symbol = excluded.parent_symbol,
+                depth = excluded.depth,
+                signature = excluded.signature,
+                doc_comment = excluded.doc_comment,
+                visibility = excluded.visibility,
                 blame_sha = excluded.blame_sha,
                 blame_author = excluded.blame_author,
                 blame_author_email = excluded.blame_author_email,
@@ -611,6 +718,12 @@ pub fn upsert_chunks_batch(conn: &mut Connection, chunks: &[ChunkRow]) -> DbResu
                 chunk.node_type,
                 chunk.name,
                 chunk.language,
+                chunk.qualified_name,
+                chunk.parent_symbol,
+                chunk.depth,
+                chunk.signature,
+                chunk.doc_comment,
+                chunk.visibility,
                 chunk.blame_sha,
                 chunk.blame_author,
                 chunk.blame_author_email,
@@ -628,7 +741,7 @@ pub fn get_chunk(conn: &Connection, chunk_id: &str) -> DbResult<Option<ChunkRow>
     let result = conn
         .query_row(
             r#"
-            SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
+            SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
             FROM chunks WHERE chunk_id = ?
             "#,
             params![chunk_id],
@@ -642,11 +755,17 @@ pub fn get_chunk(conn: &Connection, chunk_id: &str) -> DbResult<Option<ChunkRow>
                     node_type: row.get(5)?,
                     name: row.get(6)?,
                     language: row.get(7)?,
-                    blame_sha: row.get(8)?,
-                    blame_author: row.get(9)?,
-                    blame_author_email: row.get(10)?,
-                    blame_committed_at: row.get(11)?,
-                    blame_summary: row.get(12)?,
+                    qualified_name: row.get(8)?,
+                    parent_symbol: row.get(9)?,
+                    depth: row.get(10)?,
+                    signature: row.get(11)?,
+                    doc_comment: row.get(12)?,
+                    visibility: row.get(13)?,
+                    blame_sha: row.get(14)?,
+                    blame_author: row.get(15)?,
+                    blame_author_email: row.get(16)?,
+                    blame_committed_at: row.get(17)?,
+                    blame_summary: row.get(18)?,
                 })
             },
         )
@@ -658,7 +777,7 @@ pub fn get_chunk(conn: &Connection, chunk_id: &str) -> DbResult<Option<ChunkRow>
 pub fn get_chunks_by_file(conn: &Connection, file_path: &str) -> DbResult<Vec<ChunkRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
+        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
         FROM chunks WHERE file_path = ?
         ORDER BY start_line
         "#,
@@ -674,11 +793,17 @@ pub fn get_chunks_by_file(conn: &Connection, file_path: &str) -> DbResult<Vec<Ch
             node_type: row.get(5)?,
             name: row.get(6)?,
             language: row.get(7)?,
-            blame_sha: row.get(8)?,
-            blame_author: row.get(9)?,
-            blame_author_email: row.get(10)?,
-            blame_committed_at: row.get(11)?,
-            blame_summary: row.get(12)?,
+            qualified_name: row.get(8)?,
+            parent_symbol: row.get(9)?,
+            depth: row.get(10)?,
+            signature: row.get(11)?,
+            doc_comment: row.get(12)?,
+            visibility: row.get(13)?,
+            blame_sha: row.get(14)?,
+            blame_author: row.get(15)?,
+            blame_author_email: row.get(16)?,
+            blame_committed_at: row.get(17)?,
+            blame_summary: row.get(18)?,
         })
     })?;
 
@@ -692,7 +817,7 @@ pub fn get_chunks_by_file(conn: &Connection, file_path: &str) -> DbResult<Vec<Ch
 pub fn get_chunks_by_name(conn: &Connection, name: &str) -> DbResult<Vec<ChunkRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
+        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
         FROM chunks WHERE name = ?
         "#,
     )?;
@@ -707,11 +832,17 @@ pub fn get_chunks_by_name(conn: &Connection, name: &str) -> DbResult<Vec<ChunkRo
             node_type: row.get(5)?,
             name: row.get(6)?,
             language: row.get(7)?,
-            blame_sha: row.get(8)?,
-            blame_author: row.get(9)?,
-            blame_author_email: row.get(10)?,
-            blame_committed_at: row.get(11)?,
-            blame_summary: row.get(12)?,
+            qualified_name: row.get(8)?,
+            parent_symbol: row.get(9)?,
+            depth: row.get(10)?,
+            signature: row.get(11)?,
+            doc_comment: row.get(12)?,
+            visibility: row.get(13)?,
+            blame_sha: row.get(14)?,
+            blame_author: row.get(15)?,
+            blame_author_email: row.get(16)?,
+            blame_committed_at: row.get(17)?,
+            blame_summary: row.get(18)?,
         })
     })?;
 
@@ -725,7 +856,7 @@ pub fn get_chunks_by_name(conn: &Connection, name: &str) -> DbResult<Vec<ChunkRo
 pub fn get_chunks_by_name_ci(conn: &Connection, name: &str) -> DbResult<Vec<ChunkRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
+        SELECT chunk_id, content_hash, file_path, start_line, end_line, node_type, name, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility, blame_sha, blame_author, blame_author_email, blame_committed_at, blame_summary
         FROM chunks WHERE lower(name) = lower(?)
         "#,
     )?;
@@ -740,11 +871,17 @@ pub fn get_chunks_by_name_ci(conn: &Connection, name: &str) -> DbResult<Vec<Chun
             node_type: row.get(5)?,
             name: row.get(6)?,
             language: row.get(7)?,
-            blame_sha: row.get(8)?,
-            blame_author: row.get(9)?,
-            blame_author_email: row.get(10)?,
-            blame_committed_at: row.get(11)?,
-            blame_summary: row.get(12)?,
+            qualified_name: row.get(8)?,
+            parent_symbol: row.get(9)?,
+            depth: row.get(10)?,
+            signature: row.get(11)?,
+            doc_comment: row.get(12)?,
+            visibility: row.get(13)?,
+            blame_sha: row.get(14)?,
+            blame_author: row.get(15)?,
+            blame_author_email: row.get(16)?,
+            blame_committed_at: row.get(17)?,
+            blame_summary: row.get(18)?,
         })
     })?;
 
@@ -789,6 +926,12 @@ pub struct ChunkRow {
     pub node_type: Option<String>,
     pub name: Option<String>,
     pub language: String,
+    pub qualified_name: Option<String>,
+    pub parent_symbol: Option<String>,
+    pub depth: Option<u32>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
     pub blame_sha: Option<String>,
     pub blame_author: Option<String>,
     pub blame_author_email: Option<String>,
@@ -1014,6 +1157,7 @@ pub fn get_all_branches(conn: &Connection) -> DbResult<Vec<String>> {
 pub fn clear_all_indexed_data(conn: &Connection) -> DbResult<()> {
     conn.execute("DELETE FROM branch_symbols", [])?;
     conn.execute("DELETE FROM branch_chunks", [])?;
+    conn.execute("DELETE FROM branch_call_edge_targets", [])?;
     conn.execute("DELETE FROM call_edges", [])?;
     conn.execute("DELETE FROM symbols", [])?;
     conn.execute("DELETE FROM chunks", [])?;
@@ -1119,6 +1263,15 @@ pub fn gc_orphan_call_edges(conn: &Connection) -> DbResult<usize> {
         "#,
         [],
     )?;
+    conn.execute(
+        r#"
+        DELETE FROM branch_call_edge_targets
+        WHERE branch NOT IN (
+            SELECT DISTINCT branch FROM branch_symbols
+        )
+        "#,
+        [],
+    )?;
     Ok(count)
 }
 
@@ -1186,6 +1339,12 @@ mod tests {
             end_line: 5,
             end_col: 0,
             language: language.to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         }
     }
 
@@ -1205,6 +1364,9 @@ mod tests {
             line: 3,
             col: 0,
             is_resolved: to_symbol_id.is_some(),
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         }
     }
 
@@ -1220,26 +1382,34 @@ mod tests {
         name: Option<&str>,
         language: &str,
     ) -> DbResult<()> {
-        upsert_chunk_with_blame(
+        upsert_chunk_row(
             conn,
-            chunk_id,
-            content_hash,
-            file_path,
-            start_line,
-            end_line,
-            node_type,
-            name,
-            language,
-            None,
-            None,
-            None,
-            None,
-            None,
+            &ChunkRow {
+                chunk_id: chunk_id.to_string(),
+                content_hash: content_hash.to_string(),
+                file_path: file_path.to_string(),
+                start_line,
+                end_line,
+                node_type: node_type.map(str::to_string),
+                name: name.map(str::to_string),
+                language: language.to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
+                blame_sha: None,
+                blame_author: None,
+                blame_author_email: None,
+                blame_committed_at: None,
+                blame_summary: None,
+            },
         )
     }
 
     #[test]
-    fn test_schema_v7_fresh_database() {
+    fn test_schema_v10_fresh_database() {
         let (_temp_dir, conn) = setup_test_db();
         let version: String = conn
             .query_row(
@@ -1248,37 +1418,100 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(version, "7");
+        assert_eq!(version, "10");
+    }
+
+    #[test]
+    fn test_chunk_and_symbol_nesting_round_trip() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        upsert_chunks_batch(
+            &mut conn,
+            &[ChunkRow {
+                chunk_id: "chunk-save".to_string(),
+                content_hash: "hash-save".to_string(),
+                file_path: "src/user.ts".to_string(),
+                start_line: 4,
+                end_line: 9,
+                node_type: Some("method_definition".to_string()),
+                name: Some("save".to_string()),
+                language: "typescript".to_string(),
+                qualified_name: Some("UserService.save".to_string()),
+                parent_symbol: Some("UserService".to_string()),
+                depth: Some(1),
+                signature: None,
+                doc_comment: None,
+                visibility: None,
+                blame_sha: None,
+                blame_author: None,
+                blame_author_email: None,
+                blame_committed_at: None,
+                blame_summary: None,
+            }],
+        )
+        .unwrap();
+        let mut symbol = call_graph_symbol("sym-save", "save", "typescript");
+        symbol.qualified_name = Some("UserService.save".to_string());
+        symbol.parent_symbol = Some("UserService".to_string());
+        symbol.depth = Some(1);
+        upsert_symbol(&conn, &symbol).unwrap();
+
+        let chunk = get_chunk(&conn, "chunk-save").unwrap().unwrap();
+        assert_eq!(chunk.qualified_name.as_deref(), Some("UserService.save"));
+        assert_eq!(chunk.parent_symbol.as_deref(), Some("UserService"));
+        assert_eq!(chunk.depth, Some(1));
+
+        let stored = get_symbols_by_name(&conn, "save").unwrap();
+        assert_eq!(
+            stored[0].qualified_name.as_deref(),
+            Some("UserService.save")
+        );
+        assert_eq!(stored[0].depth, Some(1));
     }
 
     #[test]
-    fn test_schema_v7_read_only_accepts_structurally_compatible_v6() {
+    fn test_symbol_api_summary_round_trip() {
+        let (_temp_dir, conn) = setup_test_db();
+        let mut symbol = call_graph_symbol("sym-save", "save", "python");
+        symbol.signature = Some("(self, user: User) -> bool".to_string());
+        symbol.doc_comment = Some("Persists the user.".to_string());
+        symbol.visibility = Some("public".to_string());
+        upsert_symbol(&conn, &symbol).unwrap();
+
+        let stored = get_symbols_by_name(&conn, "save").unwrap();
+        assert_eq!(
+            stored[0].signature.as_deref(),
+            Some("(self, user: User) -> bool")
+        );
+        assert_eq!(stored[0].doc_comment.as_deref(), Some("Persists the user."));
+        assert_eq!(stored[0].visibility.as_deref(), Some("public"));
+    }
+
+    #[test]
+    fn test_schema_v10_read_only_requires_migrated_layout() {
         let (temp_dir, conn) = setup_test_db();
         let db_path = temp_dir.path().join("test.db");
-        set_metadata(&conn, "schema_version", "6").unwrap();
         drop(conn);
 
         let read_only = open_db_read_only(&db_path).unwrap();
         assert_eq!(
             get_metadata(&read_only, "schema_version").unwrap().unwrap(),
-            "6"
+            "10"
         );
         drop(read_only);
 
         let conn = Connection::open(&db_path).unwrap();
-        assert_eq!(get_metadata(&conn, "schema_version").unwrap().unwrap(), "6");
-        set_metadata(&conn, "schema_version", "5").unwrap();
+        set_metadata(&conn, "schema_version", "9").unwrap();
         drop(conn);
 
         let error = open_db_read_only(&db_path).err().unwrap();
         assert_eq!(
             error.to_string(),
-            "Read-only database schema error: found version 5, expected 7; a writer must migrate the index"
+            "Read-only database schema error: found version 9, expected 10; a writer must migrate the index"
         );
     }
 
     #[test]
-    fn test_schema_v7_migration_preserves_catalog_and_metadata() {
+    fn test_schema_v10_migration_preserves_catalog_and_metadata() {
         let temp_dir = TempDir::new().unwrap();
         let db_path = temp_dir.path().join("migration-v6.db");
         let legacy_path = "/legacy/worktree-link/../checkout/src/main.ts";
@@ -1319,6 +1552,12 @@ mod tests {
                     end_line: 3,
                     end_col: 1,
                     language: "typescript".to_string(),
+                    qualified_name: None,
+                    parent_symbol: None,
+                    depth: None,
+                    signature: None,
+                    doc_comment: None,
+                    visibility: None,
                 },
             )
             .unwrap();
@@ -1335,6 +1574,9 @@ mod tests {
                     line: 2,
                     col: 4,
                     is_resolved: false,
+                    qualifier: None,
+                    resolution_strategy: None,
+                    resolution_confidence: None,
                 },
             )
             .unwrap();
@@ -1344,7 +1586,10 @@ mod tests {
 
         let conn = init_db(&db_path).unwrap();
 
-        assert_eq!(get_metadata(&conn, "schema_version").unwrap().unwrap(), "7");
+        assert_eq!(
+            get_metadata(&conn, "schema_version").unwrap().unwrap(),
+            "10"
+        );
         assert_eq!(
             get_metadata(&conn, "index.embeddingModel")
                 .unwrap()
@@ -1480,6 +1725,12 @@ mod tests {
             end_line: 25,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
 
         // Insert
@@ -1531,6 +1782,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "s2".to_string(),
@@ -1542,6 +1799,12 @@ mod tests {
                 end_line: 12,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "s3".to_string(),
@@ -1553,6 +1816,12 @@ mod tests {
                 end_line: 50,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
         ];
 
@@ -1585,6 +1854,12 @@ mod tests {
                 end_line: 10,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "sym_helper".to_string(),
@@ -1596,6 +1871,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
         ];
         upsert_symbols_batch(&mut conn, &symbols).unwrap();
@@ -1619,6 +1900,9 @@ mod tests {
             line: 5,
             col: 4,
             is_resolved: false,
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         };
         upsert_call_edge(&conn, &edge).unwrap();
 
@@ -1802,6 +2086,237 @@ mod tests {
             vec!["swift_bridge_entry", "php_bridge", "php_bridge_target"]
         );
     }
+    #[test]
+    fn test_k_shortest_paths_accept_ids_and_report_ambiguous_names() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            call_graph_symbol("p_a", "alpha", "typescript"),
+            call_graph_symbol("p_b", "beta", "typescript"),
+            call_graph_symbol("p_c", "gamma", "typescript"),
+            call_graph_symbol("p_d", "delta", "typescript"),
+            call_graph_symbol("p_e", "epsilon", "typescript"),
+            call_graph_symbol("p_f", "phi", "typescript"),
+            call_graph_symbol("p_helper_1", "helper", "typescript"),
+            call_graph_symbol("p_helper_2", "helper", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        add_symbols_to_branch_batch(
+            &mut conn,
+            "main",
+            &symbols
+                .iter()
+                .map(|symbol| symbol.id.clone())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let edges = vec![
+            call_graph_edge("p_ab", "p_a", "beta", Some("p_b")),
+            call_graph_edge("p_ac", "p_a", "gamma", Some("p_c")),
+            call_graph_edge("p_ae", "p_a", "epsilon", Some("p_e")),
+            call_graph_edge("p_bc", "p_b", "gamma", Some("p_c")),
+            call_graph_edge("p_bd", "p_b", "delta", None),
+            call_graph_edge("p_cd", "p_c", "delta", Some("p_d")),
+            call_graph_edge("p_ef", "p_e", "phi", Some("p_f")),
+            call_graph_edge("p_fd", "p_f", "delta", Some("p_d")),
+            call_graph_edge("p_d_helper", "p_d", "helper", None),
+        ];
+        upsert_call_edges_batch(&mut conn, &edges).unwrap();
+        let ids = |found: &CallPathsRow| -> Vec<Vec<String>> {
+            found
+                .paths
+                .iter()
+                .map(|path| path.hops.iter().map(|hop| hop.symbol_id.clone()).collect())
+                .collect()
+        };
+
+        let found = find_k_shortest_paths(&conn, "alpha", "p_d", "main", 10, 10, None).unwrap();
+        assert_eq!(
+            ids(&found),
+            vec![
+                vec!["p_a", "p_b", "p_d"],
+                vec!["p_a", "p_c", "p_d"],
+                vec!["p_a", "p_b", "p_c", "p_d"],
+                vec!["p_a", "p_e", "p_f", "p_d"],
+            ]
+        );
+        assert_eq!(found.paths[0].hops[0].call_type, "source");
+        assert_eq!(found.paths[0].hops[2].call_type, "Call");
+        assert_eq!(found.paths[2].cost, 3.0);
+
+        let two = find_k_shortest_paths(&conn, "p_a", "delta", "main", 2, 10, None).unwrap();
+        assert_eq!(ids(&two).len(), 2);
+        let shallow = find_k_shortest_paths(&conn, "p_a", "delta", "main", 10, 2, None).unwrap();
+        assert_eq!(ids(&shallow).len(), 2);
+
+        let ambiguous =
+            find_k_shortest_paths(&conn, "alpha", "helper", "main", 3, 10, None).unwrap();
+        assert!(ambiguous.paths.is_empty());
+        assert!(ambiguous.from_candidates.is_empty());
+        assert_eq!(
+            ambiguous
+                .to_candidates
+                .iter()
+                .map(|candidate| (candidate.symbol_id.as_str(), candidate.file_path.as_str()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("p_helper_1", "src/p_helper_1"),
+                ("p_helper_2", "src/p_helper_2")
+            ]
+        );
+        // The unresolved call to the ambiguous `helper` is no edge at all.
+        assert!(
+            find_k_shortest_paths(&conn, "alpha", "p_helper_1", "main", 3, 10, None)
+                .unwrap()
+                .paths
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_weighted_paths_prefer_credible_edges() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            call_graph_symbol("w_handler", "handleRequest", "typescript"),
+            call_graph_symbol("w_service", "userService", "typescript"),
+            call_graph_symbol("w_repo", "userRepo", "typescript"),
+            call_graph_symbol("w_util", "util", "typescript"),
+            call_graph_symbol("w_db", "query", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        add_symbols_to_branch_batch(
+            &mut conn,
+            "main",
+            &symbols
+                .iter()
+                .map(|symbol| symbol.id.clone())
+                .collect::<Vec<_>>(),
+        )
+        .unwrap();
+        let mut service_repo = call_graph_edge("w_sr", "w_service", "userRepo", Some("w_repo"));
+        service_repo.call_type = "Import".to_string();
+        let mut handler_util = call_graph_edge("w_hu", "w_handler", "util", Some("w_util"));
+        handler_util.confidence = "Inferred".to_string();
+        // A resolved type reference is left out of default weighted searches.
+        let mut handler_db_type = call_graph_edge("w_ht", "w_handler", "query", Some("w_db"));
+        handler_db_type.call_type = "TypeReference".to_string();
+        let edges = vec![
+            // A bare `query` call that only matches `w_db` by name.
+            call_graph_edge("w_hd", "w_handler", "query", None),
+            call_graph_edge("w_hs", "w_handler", "userService", Some("w_service")),
+            service_repo,
+            call_graph_edge("w_rd", "w_repo", "query", Some("w_db")),
+            handler_util,
+            call_graph_edge("w_ud", "w_util", "query", Some("w_db")),
+            handler_db_type,
+        ];
+        upsert_call_edges_batch(&mut conn, &edges).unwrap();
+        conn.execute(
+            "INSERT INTO branch_call_edge_targets (branch, edge_id, to_symbol_id, resolution_strategy, resolution_confidence) VALUES ('main', 'w_ud', 'w_db', 'unique-name', 0.6)",
+            [],
+        )
+        .unwrap();
+        let ranked = |found: &CallPathsRow| -> Vec<(Vec<String>, f64)> {
+            found
+                .paths
+                .iter()
+                .map(|path| {
+                    let ids = path.hops.iter().map(|hop| hop.symbol_id.clone()).collect();
+                    (ids, (path.cost * 10.0).round() / 10.0)
+                })
+                .collect()
+        };
+        let ids = |path: &[&str]| path.iter().map(|id| id.to_string()).collect::<Vec<_>>();
+
+        let unweighted =
+            find_k_shortest_paths(&conn, "w_handler", "w_db", "main", 1, 10, None).unwrap();
+        assert_eq!(
+            ranked(&unweighted),
+            vec![(ids(&["w_handler", "w_db"]), 1.0)]
+        );
+
+        // Imports are left out by default.
+        let defaults = find_k_shortest_paths(
+            &conn,
+            "w_handler",
+            "w_db",
+            "main",
+            3,
+            10,
+            Some(&EdgeWeights::default()),
+        )
+        .unwrap();
+        assert_eq!(
+            ranked(&defaults),
+            vec![
+                (ids(&["w_handler", "w_db"]), 4.0),
+                (ids(&["w_handler", "w_util", "w_db"]), 5.2),
+            ]
+        );
+
+        let mut weights = EdgeWeights::default();
+        weights.call_types.insert("Import".to_string(), 0.5);
+        let weighted =
+            find_k_shortest_paths(&conn, "w_handler", "w_db", "main", 3, 10, Some(&weights))
+                .unwrap();
+        assert_eq!(
+            ranked(&weighted),
+            vec![
+                (ids(&["w_handler", "w_service", "w_repo", "w_db"]), 2.5),
+                (ids(&["w_handler", "w_db"]), 4.0),
+                (ids(&["w_handler", "w_util", "w_db"]), 5.2),
+            ]
+        );
+        assert_eq!(weighted.paths[0].hops[2].call_type, "Import");
+
+        let strict = EdgeWeights {
+            unresolved: None,
+            ..EdgeWeights::default()
+        };
+        let strict =
+            find_k_shortest_paths(&conn, "w_handler", "w_db", "main", 3, 10, Some(&strict))
+                .unwrap();
+        assert_eq!(
+            ranked(&strict),
+            vec![(ids(&["w_handler", "w_util", "w_db"]), 4.0)]
+        );
+
+        let reached = |direction: &str, max_cost: Option<f64>| -> Vec<(String, u32, f64)> {
+            let roots = match direction {
+                "callers" => vec!["w_db".to_string()],
+                _ => vec!["w_handler".to_string()],
+            };
+            get_weighted_reachability(&conn, &roots, "main", direction, &weights, 10, max_cost)
+                .unwrap()
+                .into_iter()
+                .map(|row| (row.symbol_id, row.depth, (row.cost * 10.0).round() / 10.0))
+                .collect()
+        };
+        assert_eq!(
+            reached("callees", None),
+            vec![
+                ("w_service".to_string(), 1, 1.0),
+                ("w_repo".to_string(), 2, 1.5),
+                ("w_db".to_string(), 3, 2.5),
+                ("w_util".to_string(), 1, 3.0),
+            ]
+        );
+        assert_eq!(
+            reached("callees", Some(2.0)),
+            vec![
+                ("w_service".to_string(), 1, 1.0),
+                ("w_repo".to_string(), 2, 1.5),
+            ]
+        );
+        assert_eq!(
+            reached("callers", None),
+            vec![
+                ("w_repo".to_string(), 1, 1.0),
+                ("w_service".to_string(), 2, 1.5),
+                ("w_util".to_string(), 1, 2.2),
+                ("w_handler".to_string(), 3, 2.5),
+            ]
+        );
+    }
 
     #[test]
     fn test_branch_symbols() {
@@ -1818,6 +2333,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "s2".to_string(),
@@ -1829,6 +2350,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
         ];
         upsert_symbols_batch(&mut conn, &symbols).unwrap();
@@ -1863,6 +2390,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "orphan".to_string(),
@@ -1874,6 +2407,12 @@ mod tests {
                 end_line: 5,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
         ];
         upsert_symbols_batch(&mut conn, &symbols).unwrap();
@@ -1893,6 +2432,9 @@ mod tests {
                 line: 3,
                 col: 4,
                 is_resolved: false,
+                qualifier: None,
+                resolution_strategy: None,
+                resolution_confidence: None,
             },
             CallEdgeRow {
                 id: "e2".to_string(),
@@ -1904,6 +2446,9 @@ mod tests {
                 line: 2,
                 col: 0,
                 is_resolved: false,
+                qualifier: None,
+                resolution_strategy: None,
+                resolution_confidence: None,
             },
         ];
         upsert_call_edges_batch(&mut conn, &edges).unwrap();
@@ -1952,6 +2497,12 @@ mod tests {
             end_line: 3,
             end_col: 0,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &symbol).unwrap();
         add_symbols_to_branch(&conn, "main", &["sym1".to_string()]).unwrap();
@@ -1966,6 +2517,9 @@ mod tests {
             line: 2,
             col: 0,
             is_resolved: false,
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         };
         upsert_call_edge(&conn, &edge).unwrap();
 
@@ -2001,6 +2555,12 @@ mod tests {
             end_line: 5,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &symbol).unwrap();
 
@@ -2014,6 +2574,9 @@ mod tests {
             line: 3,
             col: 0,
             is_resolved: false,
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         };
         upsert_call_edge(&conn, &edge).unwrap();
 
@@ -2097,7 +2660,24 @@ mod tests {
                 |row| row.get(0),
             )
             .unwrap();
-        assert_eq!(schema_version, "7");
+        assert_eq!(schema_version, "10");
+
+        let qualifier_columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('call_edges') WHERE name = 'qualifier'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(qualifier_columns, 1);
+        let resolution_columns: i64 = conn
+            .query_row(
+                "SELECT COUNT(*) FROM pragma_table_info('branch_call_edge_targets') WHERE name IN ('to_symbol_id', 'resolution_strategy', 'resolution_confidence')",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert_eq!(resolution_columns, 3);
 
         let on_delete: String = conn
             .query_row("PRAGMA foreign_key_list(call_edges)", [], |row| row.get(6))
@@ -2141,6 +2721,12 @@ mod tests {
                 end_line: 10,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
             SymbolRow {
                 id: "sym_target".to_string(),
@@ -2152,6 +2738,12 @@ mod tests {
                 end_line: 20,
                 end_col: 1,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             },
         ];
         upsert_symbols_batch(&mut conn, &symbols).unwrap();
@@ -2172,6 +2764,9 @@ mod tests {
             line: 5,
             col: 2,
             is_resolved: false,
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
         };
         upsert_call_edge(&conn, &edge).unwrap();
         let before = get_callees(&conn, "sym_caller", "main", None).unwrap();
@@ -2210,6 +2805,12 @@ mod tests {
             end_line: 5,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         let sym2 = SymbolRow {
             id: "s2".to_string(),
@@ -2221,6 +2822,12 @@ mod tests {
             end_line: 15,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &sym1).unwrap();
         upsert_symbol(&conn, &sym2).unwrap();
@@ -2246,6 +2853,12 @@ mod tests {
             end_line: 5,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         let sym2 = SymbolRow {
             id: "s2".to_string(),
@@ -2257,6 +2870,12 @@ mod tests {
             end_line: 15,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &sym1).unwrap();
         upsert_symbol(&conn, &sym2).unwrap();
@@ -2293,6 +2912,12 @@ mod tests {
             end_line: 5,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         let sym2 = SymbolRow {
             id: "s2".to_string(),
@@ -2304,6 +2929,12 @@ mod tests {
             end_line: 15,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &sym1).unwrap();
         upsert_symbol(&conn, &sym2).unwrap();
@@ -2328,6 +2959,12 @@ mod tests {
             end_line: 5,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         let sym2 = SymbolRow {
             id: "s2".to_string(),
@@ -2339,6 +2976,12 @@ mod tests {
             end_line: 15,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         let sym3 = SymbolRow {
             id: "s3".to_string(),
@@ -2350,6 +2993,12 @@ mod tests {
             end_line: 25,
             end_col: 1,
             language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
         };
         upsert_symbol(&conn, &sym1).unwrap();
         upsert_symbol(&conn, &sym2).unwrap();
@@ -2394,6 +3043,12 @@ mod tests {
                 end_line: i as u32 + 1,
                 end_col: 10,
                 language: "typescript".to_string(),
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
             };
             symbols.push(sym);
             file_paths.push(file_path);
@@ -2406,4 +3061,439 @@ mod tests {
         let result = get_symbols_for_files(&conn, &file_paths, "main").unwrap();
         assert_eq!(result.len(), 1000);
     }
+
+    fn scoped_symbol(id: &str, file_path: &str, qualified_name: &str, kind: &str) -> SymbolRow {
+        let (parent, name) = match qualified_name.rsplit_once('.') {
+            Some((parent, name)) => (Some(parent.to_string()), name),
+            None => (None, qualified_name),
+        };
+        SymbolRow {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            name: name.to_string(),
+            kind: kind.to_string(),
+            start_line: 1,
+            start_col: 0,
+            end_line: 5,
+            end_col: 0,
+            language: "typescript".to_string(),
+            qualified_name: Some(qualified_name.to_string()),
+            parent_symbol: parent,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
+        }
+    }
+
+    fn qualified_edge(
+        id: &str,
+        from_symbol_id: &str,
+        target_name: &str,
+        call_type: &str,
+        qualifier: Option<&str>,
+    ) -> CallEdgeRow {
+        CallEdgeRow {
+            call_type: call_type.to_string(),
+            qualifier: qualifier.map(str::to_string),
+            ..call_graph_edge(id, from_symbol_id, target_name, None)
+        }
+    }
+
+    #[test]
+    fn test_resolve_call_edges_uses_receivers_files_and_imports() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            scoped_symbol("user_repo", "src/repos.ts", "UserRepo", "class_declaration"),
+            scoped_symbol(
+                "user_save",
+                "src/repos.ts",
+                "UserRepo.save",
+                "method_definition",
+            ),
+            scoped_symbol(
+                "order_repo",
+                "src/repos.ts",
+                "OrderRepo",
+                "class_declaration",
+            ),
+            scoped_symbol(
+                "order_save",
+                "src/repos.ts",
+                "OrderRepo.save",
+                "method_definition",
+            ),
+            scoped_symbol(
+                "service",
+                "src/service.ts",
+                "UserService",
+                "class_declaration",
+            ),
+            scoped_symbol(
+                "run",
+                "src/service.ts",
+                "UserService.run",
+                "method_definition",
+            ),
+            scoped_symbol(
+                "validate",
+                "src/service.ts",
+                "UserService.validate",
+                "method_definition",
+            ),
+            scoped_symbol("handle", "src/service.ts", "handle", "function_declaration"),
+            scoped_symbol(
+                "other_handle",
+                "src/other.ts",
+                "handle",
+                "function_declaration",
+            ),
+            scoped_symbol(
+                "other_validate",
+                "src/other.ts",
+                "validate",
+                "function_declaration",
+            ),
+            scoped_symbol(
+                "format_a",
+                "src/a/util.ts",
+                "format",
+                "function_declaration",
+            ),
+            scoped_symbol(
+                "format_b",
+                "src/b/util.ts",
+                "format",
+                "function_declaration",
+            ),
+            scoped_symbol("only", "src/only.ts", "onlyOnce", "function_declaration"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|symbol| symbol.id.clone()).collect();
+        add_symbols_to_branch_batch(&mut conn, "main", &ids).unwrap();
+
+        let edges = vec![
+            qualified_edge("typed", "run", "save", "MethodCall", Some("OrderRepo")),
+            qualified_edge("self", "run", "validate", "MethodCall", Some("this")),
+            qualified_edge("local", "run", "handle", "Call", None),
+            qualified_edge("import", "service", "format", "Import", Some("./a/util")),
+            qualified_edge("imported", "run", "format", "Call", None),
+            qualified_edge("unique", "run", "onlyOnce", "Call", None),
+            qualified_edge("untyped", "run", "save", "MethodCall", Some("repo")),
+            qualified_edge("missing", "run", "external", "Call", None),
+            qualified_edge("type_only", "run", "save", "TypeReference", None),
+        ];
+        upsert_call_edges_batch(&mut conn, &edges).unwrap();
+
+        let stats = resolve_call_edges(&mut conn, "main", None).unwrap();
+        assert_eq!(
+            stats,
+            CallResolutionStats {
+                edges: 9,
+                receiver_type: 2,
+                same_file: 1,
+                import: 2,
+                unique_name: 1,
+                unresolved: 3,
+                updated: 9,
+            }
+        );
+
+        let callees = get_callees(&conn, "run", "main", None).unwrap();
+        let resolved = |id: &str| {
+            let edge = callees.iter().find(|edge| edge.id == id).unwrap();
+            (
+                edge.to_symbol_id.clone(),
+                edge.resolution_strategy.clone().unwrap(),
+                edge.resolution_confidence.unwrap(),
+                edge.is_resolved,
+            )
+        };
+        let target = |id: &str| resolved(id).0;
+        let strategy = |id: &str| resolved(id).1;
+
+        assert_eq!(target("typed").as_deref(), Some("order_save"));
+        assert_eq!(strategy("typed"), "receiver-type");
+        assert_eq!(target("self").as_deref(), Some("validate"));
+        assert_eq!(resolved("self").2, 0.95);
+        assert_eq!(target("local").as_deref(), Some("handle"));
+        assert_eq!(strategy("local"), "same-file");
+        assert_eq!(target("imported").as_deref(), Some("format_a"));
+        assert_eq!(strategy("imported"), "import");
+        assert_eq!(target("unique").as_deref(), Some("only"));
+        assert_eq!(strategy("unique"), "unique-name");
+        // Two `save` members and nothing naming the receiver's type.
+        assert_eq!(
+            resolved("untyped"),
+            (None, "unresolved".to_string(), 0.0, false)
+        );
+        assert_eq!(target("missing"), None);
+        // Reference edges only come back when asked for by type.
+        assert!(!callees.iter().any(|edge| edge.id == "type_only"));
+        let type_only = get_callees(&conn, "run", "main", Some("TypeReference")).unwrap();
+        assert_eq!(type_only[0].to_symbol_id, None);
+
+        let import = get_callees(&conn, "service", "main", None).unwrap();
+        assert_eq!(import[0].to_symbol_id.as_deref(), Some("format_a"));
+
+        // A second run finds nothing to change.
+        let again = resolve_call_edges(&mut conn, "main", None).unwrap();
+        assert_eq!(again.updated, 0);
+        let incremental = resolve_call_edges(&mut conn, "main", Some(&[])).unwrap();
+        assert_eq!(incremental.edges, 0);
+    }
+
+    #[test]
+    fn test_resolve_call_edges_keeps_each_branch_resolution() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            call_graph_symbol("run", "run", "typescript"),
+            call_graph_symbol("helper_a", "helper", "typescript"),
+            call_graph_symbol("helper_b", "helper", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        add_symbols_to_branch_batch(&mut conn, "a", &["run".into(), "helper_a".into()]).unwrap();
+        add_symbols_to_branch_batch(&mut conn, "b", &["run".into(), "helper_b".into()]).unwrap();
+        upsert_call_edges_batch(&mut conn, &[call_graph_edge("call", "run", "helper", None)])
+            .unwrap();
+
+        resolve_call_edges(&mut conn, "a", None).unwrap();
+        resolve_call_edges(&mut conn, "b", None).unwrap();
+
+        let target = |branch: &str| {
+            let callees = get_callees(&conn, "run", branch, None).unwrap();
+            (
+                callees[0].to_symbol_id.clone(),
+                callees[0].resolution_strategy.clone(),
+            )
+        };
+        assert_eq!(
+            target("a"),
+            (
+                Some("helper_a".to_string()),
+                Some("unique-name".to_string())
+            )
+        );
+        assert_eq!(
+            target("b"),
+            (
+                Some("helper_b".to_string()),
+                Some("unique-name".to_string())
+            )
+        );
+        assert_eq!(
+            get_callers(&conn, "helper", "a", None).unwrap()[0]
+                .to_symbol_id
+                .as_deref(),
+            Some("helper_a")
+        );
+    }
+
+    #[test]
+    fn test_resolve_call_edges_only_revisits_edges_changed_symbols_affect() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            call_graph_symbol("run", "run", "typescript"),
+            call_graph_symbol("helper", "helper", "typescript"),
+            call_graph_symbol("format", "format", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|symbol| symbol.id.clone()).collect();
+        add_symbols_to_branch_batch(&mut conn, "main", &ids).unwrap();
+        upsert_call_edges_batch(
+            &mut conn,
+            &[
+                call_graph_edge("to_helper", "run", "helper", None),
+                call_graph_edge("to_format", "run", "format", None),
+            ],
+        )
+        .unwrap();
+        let first = resolve_call_edges(&mut conn, "main", Some(&ids)).unwrap();
+        assert_eq!((first.edges, first.unique_name), (2, 2));
+
+        // A second `helper` makes the first ambiguous; `format` is untouched.
+        upsert_symbol(
+            &conn,
+            &call_graph_symbol("helper_2", "helper", "typescript"),
+        )
+        .unwrap();
+        add_symbols_to_branch(&conn, "main", &["helper_2".into()]).unwrap();
+        let second = resolve_call_edges(&mut conn, "main", Some(&["helper_2".into()])).unwrap();
+        assert_eq!((second.edges, second.unresolved, second.updated), (1, 1, 1));
+
+        // Removing it again brings the unique match back.
+        clear_call_edge_targets_for_symbols(&conn, &["helper_2".into()]).unwrap();
+        delete_branch_symbols_for_branch(&conn, "main", &["helper_2".into()]).unwrap();
+        let third = resolve_call_edges(&mut conn, "main", Some(&["helper_2".into()])).unwrap();
+        assert_eq!((third.edges, third.unique_name), (1, 1));
+        let callees = get_callees(&conn, "run", "main", None).unwrap();
+        assert!(callees
+            .iter()
+            .all(|edge| edge.to_symbol_id.as_deref() == Some(edge.target_name.as_str())));
+    }
+
+    #[test]
+    fn test_field_reads_and_writes_do_not_resolve_by_name_alone() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            scoped_symbol("render", "src/view.ts", "render", "function_declaration"),
+            scoped_symbol(
+                "account_name",
+                "src/account.ts",
+                "Account.name",
+                "method_definition",
+            ),
+            scoped_symbol("total", "src/totals.ts", "total", "function_declaration"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|symbol| symbol.id.clone()).collect();
+        add_symbols_to_branch_batch(&mut conn, "main", &ids).unwrap();
+        let edges = vec![
+            qualified_edge("field", "render", "name", "FieldAccess", Some("user")),
+            qualified_edge("read", "render", "total", "Read", None),
+            qualified_edge("call", "render", "total", "Call", None),
+        ];
+        upsert_call_edges_batch(&mut conn, &edges).unwrap();
+
+        resolve_call_edges(&mut conn, "main", None).unwrap();
+
+        let target = |call_type: &str, id: &str| {
+            get_callees(&conn, "render", "main", Some(call_type))
+                .unwrap()
+                .into_iter()
+                .find(|edge| edge.id == id)
+                .unwrap()
+                .to_symbol_id
+        };
+        assert_eq!(target("FieldAccess", "field"), None);
+        assert_eq!(target("Read", "read"), None);
+        assert_eq!(target("Call", "call").as_deref(), Some("total"));
+    }
+
+    #[test]
+    fn test_file_scope_symbols_stay_out_of_graph_consumers() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let module = SymbolRow {
+            name: crate::call_extractor::MODULE_SYMBOL_NAME.to_string(),
+            kind: crate::call_extractor::MODULE_SYMBOL_KIND.to_string(),
+            ..call_graph_symbol("module", "", "typescript")
+        };
+        let symbols = vec![
+            module,
+            call_graph_symbol("helper", "helper", "typescript"),
+            call_graph_symbol("main", "main", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|symbol| symbol.id.clone()).collect();
+        add_symbols_to_branch_batch(&mut conn, "main", &ids).unwrap();
+        upsert_call_edges_batch(
+            &mut conn,
+            &[
+                call_graph_edge("top_level", "module", "helper", None),
+                call_graph_edge("call", "main", "helper", None),
+            ],
+        )
+        .unwrap();
+
+        // Top-level sites still resolve.
+        let stats = resolve_call_edges(&mut conn, "main", None).unwrap();
+        assert_eq!(stats.unique_name, 2);
+
+        let listed = |symbols: Vec<SymbolRow>| {
+            symbols
+                .into_iter()
+                .map(|symbol| symbol.id)
+                .collect::<Vec<_>>()
+        };
+        assert!(!listed(get_symbols_for_branch(&conn, "main").unwrap()).contains(&"module".into()));
+        assert!(
+            listed(get_symbols_for_branch_with_file_scopes(&conn, "main").unwrap())
+                .contains(&"module".into())
+        );
+        assert!(get_symbols_by_name(&conn, "<module>").unwrap().is_empty());
+
+        let centrality = crate::community::compute_centrality(&conn, "main").unwrap();
+        assert!(centrality.iter().all(|score| score.symbol_id != "module"));
+        let helper = centrality
+            .iter()
+            .find(|score| score.symbol_id == "helper")
+            .unwrap();
+        assert_eq!(helper.caller_count, 1);
+        let communities = crate::community::detect_communities(&conn, "main", None).unwrap();
+        assert!(communities
+            .iter()
+            .all(|assignment| assignment.symbol_id != "module"));
+        let exported = crate::graph_export::export_call_graph(
+            &conn,
+            "main",
+            crate::graph_export::ExportFormat::Json,
+            &crate::graph_export::ExportFilter::default(),
+        )
+        .unwrap();
+        assert!(!exported.contains("<module>"));
+        assert!(exported.contains("helper"));
+        assert!(
+            find_k_shortest_paths(&conn, "module", "helper", "main", 1, 5, None)
+                .unwrap()
+                .paths
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_call_graph_consumers_leave_reference_edges_out_by_default() {
+        let (_temp_dir, mut conn) = setup_test_db();
+        let symbols = vec![
+            call_graph_symbol("handler", "handler", "typescript"),
+            call_graph_symbol("config", "Config", "typescript"),
+            call_graph_symbol("helper", "helper", "typescript"),
+        ];
+        upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|symbol| symbol.id.clone()).collect();
+        add_symbols_to_branch_batch(&mut conn, "main", &ids).unwrap();
+        let edges = vec![
+            qualified_edge("uses", "handler", "Config", "TypeReference", None),
+            qualified_edge("calls", "handler", "helper", "Call", None),
+        ];
+        upsert_call_edges_batch(&mut conn, &edges).unwrap();
+
+        let callees = get_callees(&conn, "handler", "main", None).unwrap();
+        assert_eq!(
+            callees
+                .iter()
+                .map(|edge| edge.id.as_str())
+                .collect::<Vec<_>>(),
+            vec!["calls"]
+        );
+        assert!(get_callers(&conn, "Config", "main", None)
+            .unwrap()
+            .is_empty());
+        assert!(get_callers_with_context(&conn, "Config", "main", None)
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            get_callers(&conn, "Config", "main", Some("TypeReference"))
+                .unwrap()
+                .len(),
+            1
+        );
+        assert!(find_shortest_path(&conn, "handler", "Config", "main", 5)
+            .unwrap()
+            .is_empty());
+        assert!(
+            find_k_shortest_paths(&conn, "handler", "Config", "main", 1, 5, None)
+                .unwrap()
+                .paths
+                .is_empty()
+        );
+
+        let communities = crate::community::detect_communities(&conn, "main", None).unwrap();
+        let community_of = |id: &str| {
+            communities
+                .iter()
+                .find(|assignment| assignment.symbol_id == id)
+                .map(|assignment| assignment.community_id)
+        };
+        assert_ne!(community_of("handler"), community_of("config"));
+    }
 }
@@ -1,4 +1,6 @@
 use super::{DbResult, SQL_BIND_PARAM_BATCH_SIZE};
+use crate::registry;
+use crate::types::Language;
 use rusqlite::{params, Connection, OptionalExtension};
 
 // ============================================================================
@@ -16,6 +18,12 @@ pub struct SymbolRow {
     pub end_line: u32,
     pub end_col: u32,
     pub language: String,
+    pub qualified_name: Option<String>,
+    pub parent_symbol: Option<String>,
+    pub depth: Option<u32>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
 }
 
 #[derive(Debug, Clone)]
@@ -29,6 +37,12 @@ pub struct CallEdgeRow {
     pub line: u32,
     pub col: u32,
     pub is_resolved: bool,
+    pub qualifier: Option<String>,
+    /// How `to_symbol_id` was chosen on the branch it was read for; see
+    /// `ResolutionStrategy`. `None` until `resolve_call_edges` has seen the
+    /// edge there. Not stored by `upsert_call_edge`.
+    pub resolution_strategy: Option<String>,
+    pub resolution_confidence: Option<f64>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,14 +58,20 @@ pub struct CallerRow {
     pub line: u32,
     pub col: u32,
     pub is_resolved: bool,
+    pub qualifier: Option<String>,
+    /// How `to_symbol_id` was chosen on the branch it was read for; see
+    /// `ResolutionStrategy`. `None` until `resolve_call_edges` has seen the
+    /// edge there. Not stored by `upsert_call_edge`.
+    pub resolution_strategy: Option<String>,
+    pub resolution_confidence: Option<f64>,
 }
 
 /// Insert or replace a symbol
 pub fn upsert_symbol(conn: &Connection, symbol: &SymbolRow) -> DbResult<()> {
     conn.execute(
         r#"
-        INSERT OR REPLACE INTO symbols (id, file_path, name, kind, start_line, start_col, end_line, end_col, language)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT OR REPLACE INTO symbols (id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         params![
             symbol.id,
@@ -62,7 +82,13 @@ pub fn upsert_symbol(conn: &Connection, symbol: &SymbolRow) -> DbResult<()> {
             symbol.start_col,
             symbol.end_line,
             symbol.end_col,
-            symbol.language
+            symbol.language,
+            symbol.qualified_name,
+            symbol.parent_symbol,
+            symbol.depth,
+            symbol.signature,
+            symbol.doc_comment,
+            symbol.visibility
         ],
     )?;
     Ok(())
@@ -77,8 +103,8 @@ pub fn upsert_symbols_batch(conn: &mut Connection, symbols: &[SymbolRow]) -> DbR
     super::run_batch_with_write_transaction(conn, |conn| {
         let mut stmt = conn.prepare(
             r#"
-            INSERT OR REPLACE INTO symbols (id, file_path, name, kind, start_line, start_col, end_line, end_col, language)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT OR REPLACE INTO symbols (id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )?;
 
@@ -92,7 +118,13 @@ pub fn upsert_symbols_batch(conn: &mut Connection, symbols: &[SymbolRow]) -> DbR
                 symbol.start_col,
                 symbol.end_line,
                 symbol.end_col,
-                symbol.language
+                symbol.language,
+                symbol.qualified_name,
+                symbol.parent_symbol,
+                symbol.depth,
+                symbol.signature,
+                symbol.doc_comment,
+                symbol.visibility
             ])?;
         }
         Ok(())
@@ -103,7 +135,7 @@ pub fn upsert_symbols_batch(conn: &mut Connection, symbols: &[SymbolRow]) -> DbR
 pub fn get_symbols_by_file(conn: &Connection, file_path: &str) -> DbResult<Vec<SymbolRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language
+        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility
         FROM symbols WHERE file_path = ?
         ORDER BY start_line
         "#,
@@ -120,6 +152,12 @@ pub fn get_symbols_by_file(conn: &Connection, file_path: &str) -> DbResult<Vec<S
             end_line: row.get(6)?,
             end_col: row.get(7)?,
             language: row.get(8)?,
+            qualified_name: row.get(9)?,
+            parent_symbol: row.get(10)?,
+            depth: row.get(11)?,
+            signature: row.get(12)?,
+            doc_comment: row.get(13)?,
+            visibility: row.get(14)?,
         })
     })?;
 
@@ -139,8 +177,8 @@ pub fn get_symbol_by_name(
     let result = conn
         .query_row(
             r#"
-            SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language
-            FROM symbols WHERE name = ? AND file_path = ?
+            SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility
+            FROM symbols WHERE name = ? AND file_path = ? AND kind != 'file_scope'
             "#,
             params![name, file_path],
             |row| {
@@ -154,6 +192,12 @@ pub fn get_symbol_by_name(
                     end_line: row.get(6)?,
                     end_col: row.get(7)?,
                     language: row.get(8)?,
+                    qualified_name: row.get(9)?,
+                    parent_symbol: row.get(10)?,
+                    depth: row.get(11)?,
+                    signature: row.get(12)?,
+                    doc_comment: row.get(13)?,
+                    visibility: row.get(14)?,
                 })
             },
         )
@@ -164,8 +208,8 @@ pub fn get_symbol_by_name(
 pub fn get_symbols_by_name(conn: &Connection, name: &str) -> DbResult<Vec<SymbolRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language
-        FROM symbols WHERE name = ?
+        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility
+        FROM symbols WHERE name = ? AND kind != 'file_scope'
         "#,
     )?;
 
@@ -180,6 +224,12 @@ pub fn get_symbols_by_name(conn: &Connection, name: &str) -> DbResult<Vec<Symbol
             end_line: row.get(6)?,
             end_col: row.get(7)?,
             language: row.get(8)?,
+            qualified_name: row.get(9)?,
+            parent_symbol: row.get(10)?,
+            depth: row.get(11)?,
+            signature: row.get(12)?,
+            doc_comment: row.get(13)?,
+            visibility: row.get(14)?,
         })
     })?;
 
@@ -193,8 +243,8 @@ pub fn get_symbols_by_name(conn: &Connection, name: &str) -> DbResult<Vec<Symbol
 pub fn get_symbols_by_name_ci(conn: &Connection, name: &str) -> DbResult<Vec<SymbolRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language
-        FROM symbols WHERE lower(name) = lower(?)
+        SELECT id, file_path, name, kind, start_line, start_col, end_line, end_col, language, qualified_name, parent_symbol, depth, signature, doc_comment, visibility
+        FROM symbols WHERE lower(name) = lower(?) AND kind != 'file_scope'
         "#,
     )?;
 
@@ -209,6 +259,12 @@ pub fn get_symbols_by_name_ci(conn: &Connection, name: &str) -> DbResult<Vec<Sym
             end_line: row.get(6)?,
             end_col: row.get(7)?,
             language: row.get(8)?,
+            qualified_name: row.get(9)?,
+            parent_symbol: row.get(10)?,
+            depth: row.get(11)?,
+            signature: row.get(12)?,
+            doc_comment: row.get(13)?,
+            visibility: row.get(14)?,
         })
     })?;
 
@@ -219,19 +275,36 @@ pub fn get_symbols_by_name_ci(conn: &Connection, name: &str) -> DbResult<Vec<Sym
     Ok(results)
 }
 
-/// Get all symbols for a branch
+/// Get all symbols for a branch, leaving out the synthetic `<module>` symbols
+/// that own each file's top-level call sites.
 pub fn get_symbols_for_branch(conn: &Connection, branch: &str) -> DbResult<Vec<SymbolRow>> {
+    branch_symbols(conn, branch, false)
+}
+
+/// All symbols for a branch, including its `<module>` symbols.
+pub fn get_symbols_for_branch_with_file_scopes(
+    conn: &Connection,
+    branch: &str,
+) -> DbResult<Vec<SymbolRow>> {
+    branch_symbols(conn, branch, true)
+}
+
+fn branch_symbols(
+    conn: &Connection,
+    branch: &str,
+    include_file_scopes: bool,
+) -> DbResult<Vec<SymbolRow>> {
     let mut stmt = conn.prepare(
         r#"
-        SELECT s.id, s.file_path, s.name, s.kind, s.start_line, s.start_col, s.end_line, s.end_col, s.language
+        SELECT s.id, s.file_path, s.name, s.kind, s.start_line, s.start_col, s.end_line, s.end_col, s.language, s.qualified_name, s.parent_symbol, s.depth, s.signature, s.doc_comment, s.visibility
         FROM symbols s
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id
-        WHERE bs.branch = ?
+        WHERE bs.branch = ? AND (? OR s.kind != 'file_scope')
         ORDER BY s.start_line
         "#,
     )?;
 
-    let rows = stmt.query_map(params![branch], |row| {
+    let rows = stmt.query_map(params![branch, include_file_scopes], |row| {
         Ok(SymbolRow {
             id: row.get(0)?,
             file_path: row.get(1)?,
@@ -242,6 +315,12 @@ pub fn get_symbols_for_branch(conn: &Connection, branch: &str) -> DbResult<Vec<S
             end_line: row.get(6)?,
             end_col: row.get(7)?,
             language: row.get(8)?,
+            qualified_name: row.get(9)?,
+            parent_symbol: row.get(10)?,
+            depth: row.get(11)?,
+            signature: row.get(12)?,
+            doc_comment: row.get(13)?,
+            visibility: row.get(14)?,
         })
     })?;
 
@@ -252,16 +331,39 @@ pub fn get_symbols_for_branch(conn: &Connection, branch: &str) -> DbResult<Vec<S
     Ok(results)
 }
 
-/// Get symbols for specific files on a branch
+/// Get symbols for specific files on a branch, leaving out `<module>` symbols
 pub fn get_symbols_for_files(
     conn: &Connection,
     file_paths: &[String],
     branch: &str,
+) -> DbResult<Vec<SymbolRow>> {
+    file_symbols(conn, file_paths, branch, false)
+}
+
+/// Symbols for specific files on a branch, including their `<module>` symbols.
+pub fn get_symbols_for_files_with_file_scopes(
+    conn: &Connection,
+    file_paths: &[String],
+    branch: &str,
+) -> DbResult<Vec<SymbolRow>> {
+    file_symbols(conn, file_paths, branch, true)
+}
+
+fn file_symbols(
+    conn: &Connection,
+    file_paths: &[String],
+    branch: &str,
+    include_file_scopes: bool,
 ) -> DbResult<Vec<SymbolRow>> {
     if file_paths.is_empty() {
         return Ok(Vec::new());
     }
 
+    let scope_filter = if include_file_scopes {
+        ""
+    } else {
+        "AND s.kind != 'file_scope'"
+    };
     let mut results = Vec::new();
     for chunk in file_paths.chunks(SQL_BIND_PARAM_BATCH_SIZE) {
         let placeholders = std::iter::repeat_n("?", chunk.len())
@@ -269,13 +371,13 @@ pub fn get_symbols_for_files(
             .join(", ");
         let sql = format!(
             r#"
-            SELECT s.id, s.file_path, s.name, s.kind, s.start_line, s.start_col, s.end_line, s.end_col, s.language
+            SELECT s.id, s.file_path, s.name, s.kind, s.start_line, s.start_col, s.end_line, s.end_col, s.language, s.qualified_name, s.parent_symbol, s.depth, s.signature, s.doc_comment, s.visibility
             FROM symbols s
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id
-            WHERE bs.branch = ? AND s.file_path IN ({})
+            WHERE bs.branch = ? AND s.file_path IN ({}) {}
             ORDER BY s.start_line
             "#,
-            placeholders
+            placeholders, scope_filter
         );
         let params = rusqlite::params_from_iter(
             std::iter::once(branch).chain(chunk.iter().map(|s| s.as_str())),
@@ -293,6 +395,12 @@ pub fn get_symbols_for_files(
                 end_line: row.get(6)?,
                 end_col: row.get(7)?,
                 language: row.get(8)?,
+                qualified_name: row.get(9)?,
+                parent_symbol: row.get(10)?,
+                depth: row.get(11)?,
+                signature: row.get(12)?,
+                doc_comment: row.get(13)?,
+                visibility: row.get(14)?,
             })
         })?;
 
@@ -321,8 +429,8 @@ pub fn delete_symbols_by_file(conn: &Connection, file_path: &str) -> DbResult<us
 pub fn upsert_call_edge(conn: &Connection, edge: &CallEdgeRow) -> DbResult<()> {
     conn.execute(
         r#"
-        INSERT OR REPLACE INTO call_edges (id, from_symbol_id, target_name, to_symbol_id, call_type, confidence, line, col, is_resolved)
-        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        INSERT OR REPLACE INTO call_edges (id, from_symbol_id, target_name, to_symbol_id, call_type, confidence, line, col, is_resolved, qualifier)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
         "#,
         params![
             edge.id,
@@ -333,7 +441,8 @@ pub fn upsert_call_edge(conn: &Connection, edge: &CallEdgeRow) -> DbResult<()> {
             edge.confidence,
             edge.line,
             edge.col,
-            edge.is_resolved as i32
+            edge.is_resolved as i32,
+            edge.qualifier
         ],
     )?;
     Ok(())
@@ -348,8 +457,8 @@ pub fn upsert_call_edges_batch(conn: &mut Connection, edges: &[CallEdgeRow]) ->
     super::run_batch_with_write_transaction(conn, |conn| {
         let mut stmt = conn.prepare(
             r#"
-            INSERT OR REPLACE INTO call_edges (id, from_symbol_id, target_name, to_symbol_id, call_type, confidence, line, col, is_resolved)
-            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+            INSERT OR REPLACE INTO call_edges (id, from_symbol_id, target_name, to_symbol_id, call_type, confidence, line, col, is_resolved, qualifier)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
             "#,
         )?;
 
@@ -363,7 +472,8 @@ pub fn upsert_call_edges_batch(conn: &mut Connection, edges: &[CallEdgeRow]) ->
                 edge.confidence,
                 edge.line,
                 edge.col,
-                edge.is_resolved as i32
+                edge.is_resolved as i32,
+                edge.qualifier
             ])?;
         }
         Ok(())
@@ -371,11 +481,63 @@ pub fn upsert_call_edges_batch(conn: &mut Connection, edges: &[CallEdgeRow]) ->
 }
 
 fn is_case_insensitive_language(language: &str) -> bool {
-    // Keep this aligned with the SQL IN lists below and CASE_INSENSITIVE_LANGUAGES in the indexer.
-    matches!(language, "apex" | "php")
+    // Keep CASE_INSENSITIVE_LANGUAGES in the indexer aligned with the built-ins.
+    registry::definition(&Language::from_string(language)).case_insensitive
+}
+
+/// Expands `{case_insensitive_languages}` in `sql` to the quoted SQL list of
+/// case-insensitive language names. Registered names are restricted to
+/// `[a-z0-9_-]`, so quoting them inline is safe.
+fn with_case_insensitive_languages(sql: &str) -> String {
+    let names = registry::case_insensitive_languages()
+        .iter()
+        .map(|name| format!("'{}'", name))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sql.replace("{case_insensitive_languages}", &format!("({})", names))
+}
+
+/// Edge types that record a reference to a symbol rather than a call to it.
+/// Call graph consumers leave them out unless a caller filters on one.
+pub const REFERENCE_CALL_TYPES: [&str; 5] =
+    ["TypeReference", "FieldAccess", "Read", "Write", "Decorator"];
+
+pub(crate) fn is_reference_call_type(call_type: &str) -> bool {
+    REFERENCE_CALL_TYPES.contains(&call_type)
+}
+
+/// Expands `{calls_only}` in `sql` to a condition on `ce.call_type` that
+/// drops reference edges.
+pub(crate) fn with_calls_only(sql: &str) -> String {
+    let types = REFERENCE_CALL_TYPES
+        .iter()
+        .map(|call_type| format!("'{}'", call_type))
+        .collect::<Vec<_>>()
+        .join(", ");
+    sql.replace("{calls_only}", &format!("ce.call_type NOT IN ({})", types))
+}
+
+/// Expands `{branch_targets}` in `sql`, placed after the join on
+/// `branch_symbols bs`, to a join of what `resolve_call_edges` chose for each
+/// edge on `bs.branch` as `bt`, and `{edge_target}` / `{edge_resolved}` to
+/// the edge's target and resolved flag on that branch. Edges the resolver
+/// has not seen there keep the target they were stored with.
+pub(crate) fn with_branch_targets(sql: &str) -> String {
+    sql.replace(
+        "{branch_targets}",
+        "LEFT JOIN branch_call_edge_targets bt ON bt.edge_id = ce.id AND bt.branch = bs.branch",
+    )
+    .replace(
+        "{edge_target}",
+        "CASE WHEN bt.edge_id IS NULL THEN ce.to_symbol_id ELSE bt.to_symbol_id END",
+    )
+    .replace(
+        "{edge_resolved}",
+        "CASE WHEN bt.edge_id IS NULL THEN ce.is_resolved ELSE bt.to_symbol_id IS NOT NULL END",
+    )
 }
 
-fn symbol_names_match(language: &str, left: &str, right: &str) -> bool {
+pub(super) fn symbol_names_match(language: &str, left: &str, right: &str) -> bool {
     if is_case_insensitive_language(language) {
         left.eq_ignore_ascii_case(right)
     } else {
@@ -394,14 +556,15 @@ pub fn get_callers(
     let (sql, params) = if let Some(ct) = call_type_filter {
         (
             r#"
-            SELECT ce.id, ce.from_symbol_id, ce.target_name, ce.to_symbol_id, ce.call_type, ce.confidence, ce.line, ce.col, ce.is_resolved
+            SELECT ce.id, ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.confidence, ce.line, ce.col, {edge_resolved}, ce.qualifier, bt.resolution_strategy, bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
+            {branch_targets}
             WHERE (
-                (s.language IN ('apex', 'php') AND ce.target_name = ?2 COLLATE NOCASE)
+                (s.language IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE NOCASE)
                 OR
-                (s.language NOT IN ('apex', 'php') AND ce.target_name = ?2 COLLATE BINARY)
+                (s.language NOT IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE BINARY)
             ) AND ce.call_type = ?3
             "#,
             vec![branch.to_string(), symbol_name.to_string(), ct.to_string()],
@@ -409,20 +572,24 @@ pub fn get_callers(
     } else {
         (
             r#"
-            SELECT ce.id, ce.from_symbol_id, ce.target_name, ce.to_symbol_id, ce.call_type, ce.confidence, ce.line, ce.col, ce.is_resolved
+            SELECT ce.id, ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.confidence, ce.line, ce.col, {edge_resolved}, ce.qualifier, bt.resolution_strategy, bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
-            WHERE
-                (s.language IN ('apex', 'php') AND ce.target_name = ?2 COLLATE NOCASE)
+            {branch_targets}
+            WHERE (
+                (s.language IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE NOCASE)
                 OR
-                (s.language NOT IN ('apex', 'php') AND ce.target_name = ?2 COLLATE BINARY)
+                (s.language NOT IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE BINARY)
+            ) AND {calls_only}
             "#,
             vec![branch.to_string(), symbol_name.to_string()],
         )
     };
 
-    let mut stmt = conn.prepare(sql)?;
+    let mut stmt = conn.prepare(&with_branch_targets(&with_calls_only(
+        &with_case_insensitive_languages(sql),
+    )))?;
     let params_refs: Vec<&dyn rusqlite::types::ToSql> = params
         .iter()
         .map(|s| s as &dyn rusqlite::types::ToSql)
@@ -439,6 +606,9 @@ pub fn get_callers(
             line: row.get(6)?,
             col: row.get(7)?,
             is_resolved: row.get::<_, i32>(8)? != 0,
+            qualifier: row.get(9)?,
+            resolution_strategy: row.get(10)?,
+            resolution_confidence: row.get(11)?,
         })
     })?;
 
@@ -464,19 +634,23 @@ pub fn get_callers_with_context(
                 s.name,
                 s.file_path,
                 ce.target_name,
-                ce.to_symbol_id,
+                {edge_target},
                 ce.call_type,
                 ce.confidence,
                 ce.line,
                 ce.col,
-                ce.is_resolved
+                {edge_resolved},
+                ce.qualifier,
+                bt.resolution_strategy,
+                bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
+            {branch_targets}
             WHERE (
-                (s.language IN ('apex', 'php') AND ce.target_name = ?2 COLLATE NOCASE)
+                (s.language IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE NOCASE)
                 OR
-                (s.language NOT IN ('apex', 'php') AND ce.target_name = ?2 COLLATE BINARY)
+                (s.language NOT IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE BINARY)
             ) AND ce.call_type = ?3
             "#,
             vec![branch.to_string(), symbol_name.to_string(), ct.to_string()],
@@ -490,25 +664,32 @@ pub fn get_callers_with_context(
                 s.name,
                 s.file_path,
                 ce.target_name,
-                ce.to_symbol_id,
+                {edge_target},
                 ce.call_type,
                 ce.confidence,
                 ce.line,
                 ce.col,
-                ce.is_resolved
+                {edge_resolved},
+                ce.qualifier,
+                bt.resolution_strategy,
+                bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
-            WHERE
-                (s.language IN ('apex', 'php') AND ce.target_name = ?2 COLLATE NOCASE)
+            {branch_targets}
+            WHERE (
+                (s.language IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE NOCASE)
                 OR
-                (s.language NOT IN ('apex', 'php') AND ce.target_name = ?2 COLLATE BINARY)
+                (s.language NOT IN {case_insensitive_languages} AND ce.target_name = ?2 COLLATE BINARY)
+            ) AND {calls_only}
             "#,
             vec![branch.to_string(), symbol_name.to_string()],
         )
     };
 
-    let mut stmt = conn.prepare(sql)?;
+    let mut stmt = conn.prepare(&with_branch_targets(&with_calls_only(
+        &with_case_insensitive_languages(sql),
+    )))?;
     let params_refs: Vec<&dyn rusqlite::types::ToSql> = params
         .iter()
         .map(|s| s as &dyn rusqlite::types::ToSql)
@@ -527,6 +708,9 @@ pub fn get_callers_with_context(
             line: row.get(8)?,
             col: row.get(9)?,
             is_resolved: row.get::<_, i32>(10)? != 0,
+            qualifier: row.get(11)?,
+            resolution_strategy: row.get(12)?,
+            resolution_confidence: row.get(13)?,
         })
     })?;
 
@@ -547,10 +731,11 @@ pub fn get_callees(
     let (sql, params) = if let Some(ct) = call_type_filter {
         (
             r#"
-            SELECT ce.id, ce.from_symbol_id, ce.target_name, ce.to_symbol_id, ce.call_type, ce.confidence, ce.line, ce.col, ce.is_resolved
+            SELECT ce.id, ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.confidence, ce.line, ce.col, {edge_resolved}, ce.qualifier, bt.resolution_strategy, bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
+            {branch_targets}
             WHERE ce.from_symbol_id = ?2 AND ce.call_type = ?3
             "#,
             vec![branch.to_string(), symbol_id.to_string(), ct.to_string()],
@@ -558,17 +743,18 @@ pub fn get_callees(
     } else {
         (
             r#"
-            SELECT ce.id, ce.from_symbol_id, ce.target_name, ce.to_symbol_id, ce.call_type, ce.confidence, ce.line, ce.col, ce.is_resolved
+            SELECT ce.id, ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.confidence, ce.line, ce.col, {edge_resolved}, ce.qualifier, bt.resolution_strategy, bt.resolution_confidence
             FROM call_edges ce
             INNER JOIN symbols s ON ce.from_symbol_id = s.id
             INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
-            WHERE ce.from_symbol_id = ?2
+            {branch_targets}
+            WHERE ce.from_symbol_id = ?2 AND {calls_only}
             "#,
             vec![branch.to_string(), symbol_id.to_string()],
         )
     };
 
-    let mut stmt = conn.prepare(sql)?;
+    let mut stmt = conn.prepare(&with_branch_targets(&with_calls_only(sql)))?;
     let params_refs: Vec<&dyn rusqlite::types::ToSql> = params
         .iter()
         .map(|s| s as &dyn rusqlite::types::ToSql)
@@ -585,6 +771,9 @@ pub fn get_callees(
             line: row.get(6)?,
             col: row.get(7)?,
             is_resolved: row.get::<_, i32>(8)? != 0,
+            qualifier: row.get(9)?,
+            resolution_strategy: row.get(10)?,
+            resolution_confidence: row.get(11)?,
         })
     })?;
 
@@ -626,8 +815,13 @@ pub fn clear_call_edge_targets_for_symbols(
             "UPDATE call_edges SET to_symbol_id = NULL, is_resolved = 0 WHERE to_symbol_id IN ({})",
             placeholders
         );
-        let params = rusqlite::params_from_iter(chunk.iter());
-        total += conn.execute(&sql, params)?;
+        total += conn.execute(&sql, rusqlite::params_from_iter(chunk.iter()))?;
+        // The next resolution run on each branch picks a new target.
+        let sql = format!(
+            "DELETE FROM branch_call_edge_targets WHERE to_symbol_id IN ({})",
+            placeholders
+        );
+        conn.execute(&sql, rusqlite::params_from_iter(chunk.iter()))?;
     }
 
     Ok(total)
@@ -639,6 +833,10 @@ pub fn resolve_call_edge(conn: &Connection, edge_id: &str, to_symbol_id: &str) -
         "UPDATE call_edges SET to_symbol_id = ?, is_resolved = 1 WHERE id = ?",
         params![to_symbol_id, edge_id],
     )?;
+    conn.execute(
+        "DELETE FROM branch_call_edge_targets WHERE edge_id = ?",
+        params![edge_id],
+    )?;
     Ok(())
 }
 
@@ -667,17 +865,17 @@ pub fn find_shortest_path(
     use std::collections::{HashMap, VecDeque};
 
     // Find all source symbols matching from_name on this branch
-    let mut start_stmt = conn.prepare(
+    let mut start_stmt = conn.prepare(&with_case_insensitive_languages(
         r#"
         SELECT s.id, s.name, s.file_path, s.start_line, s.language
         FROM symbols s
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
         WHERE
-            (s.language IN ('apex', 'php') AND s.name = ?2 COLLATE NOCASE)
+            (s.language IN {case_insensitive_languages} AND s.name = ?2 COLLATE NOCASE)
             OR
-            (s.language NOT IN ('apex', 'php') AND s.name = ?2 COLLATE BINARY)
+            (s.language NOT IN {case_insensitive_languages} AND s.name = ?2 COLLATE BINARY)
         "#,
-    )?;
+    ))?;
     let starts: Vec<(String, String, String, u32, String)> = start_stmt
         .query_map(params![branch, from_name], |row| {
             Ok((
@@ -703,28 +901,29 @@ pub fn find_shortest_path(
 
     // Prepare statements for BFS expansion
     // Get callees of a symbol (by symbol_id), filtered by branch
-    let mut callees_stmt = conn.prepare(
+    let mut callees_stmt = conn.prepare(&with_branch_targets(&with_calls_only(
         r#"
-        SELECT ce.target_name, ce.to_symbol_id, ce.call_type, ce.line
+        SELECT ce.target_name, {edge_target}, ce.call_type, ce.line
         FROM call_edges ce
         INNER JOIN symbols s ON ce.from_symbol_id = s.id
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?
-        WHERE ce.from_symbol_id = ?
+        {branch_targets}
+        WHERE ce.from_symbol_id = ? AND {calls_only}
         "#,
-    )?;
+    )))?;
 
     // Resolve a target_name to symbol IDs on this branch
-    let mut resolve_stmt = conn.prepare(
+    let mut resolve_stmt = conn.prepare(&with_case_insensitive_languages(
         r#"
         SELECT s.id, s.name, s.file_path, s.start_line, s.language
         FROM symbols s
         INNER JOIN branch_symbols bs ON s.id = bs.symbol_id AND bs.branch = ?1
         WHERE
-            (?2 IN ('apex', 'php') AND s.name = ?3 COLLATE NOCASE)
+            (?2 IN {case_insensitive_languages} AND s.name = ?3 COLLATE NOCASE)
             OR
-            (?2 NOT IN ('apex', 'php') AND s.name = ?3 COLLATE BINARY)
+            (?2 NOT IN {case_insensitive_languages} AND s.name = ?3 COLLATE BINARY)
         "#,
-    )?;
+    ))?;
 
     let mut branch_symbol_language_stmt = conn.prepare(
         r#"
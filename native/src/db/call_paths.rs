@@ -0,0 +1,625 @@
+use super::call_graph::{is_reference_call_type, symbol_names_match, with_branch_targets};
+use super::{get_symbols_for_branch, DbResult, PathHopRow, SymbolRow};
+use rusqlite::{params, Connection};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+// ============================================================================
+// Path Queries (Call Graph)
+// ============================================================================
+
+/// A symbol an ambiguous name could refer to.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SymbolCandidateRow {
+    pub symbol_id: String,
+    pub symbol_name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+#[derive(Debug, Clone)]
+pub struct CallPathRow {
+    /// Starts with a `source` hop.
+    pub hops: Vec<PathHopRow>,
+    /// The number of edges, or their summed cost under `EdgeWeights`.
+    pub cost: f64,
+}
+
+/// Paths between two symbols, or the candidates for whichever endpoint was
+/// ambiguous. Candidates are only listed when a name matches more than one
+/// symbol, and then no paths are searched.
+#[derive(Debug, Clone, Default)]
+pub struct CallPathsRow {
+    /// Loopless paths, cheapest first.
+    pub paths: Vec<CallPathRow>,
+    pub from_candidates: Vec<SymbolCandidateRow>,
+    pub to_candidates: Vec<SymbolCandidateRow>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WeightedReachabilityRow {
+    pub symbol_id: String,
+    pub symbol_name: String,
+    pub file_path: String,
+    /// Edges on the cheapest path to the symbol.
+    pub depth: u32,
+    pub cost: f64,
+}
+
+/// What each call edge costs in weighted path and reachability searches, so
+/// the cheapest chain is the most credible one rather than the shortest.
+///
+/// An edge pays its call type's cost, plus `inferred` when the call site was
+/// inferred, plus `unresolved` scaled by how unsure its target is: in full
+/// when no target is stored, by `1 - resolution_confidence` when
+/// `resolve_call_edges` chose it (0.4 of it for a `unique-name` match), and
+/// not at all for a target stored without a confidence.
+#[derive(Debug, Clone, PartialEq)]
+pub struct EdgeWeights {
+    /// Cost per call type; edges of call types missing here are left out.
+    pub call_types: HashMap<String, f64>,
+    /// `None` leaves inferred edges out.
+    pub inferred: Option<f64>,
+    /// `None` leaves edges without a stored target out.
+    pub unresolved: Option<f64>,
+}
+
+impl Default for EdgeWeights {
+    /// Calls cost 1. Imports, inheritance and reference edges are left out
+    /// unless given a cost.
+    fn default() -> Self {
+        let costs: [(&str, f64); 3] = [("Call", 1.0), ("MethodCall", 1.0), ("Constructor", 1.0)];
+        Self {
+            call_types: costs
+                .into_iter()
+                .map(|(call_type, cost)| (call_type.to_string(), cost))
+                .collect(),
+            inferred: Some(2.0),
+            unresolved: Some(3.0),
+        }
+    }
+}
+
+impl EdgeWeights {
+    /// `None` when the edge is left out.
+    fn cost(&self, edge: &GraphEdge) -> Option<f64> {
+        let mut cost = *self.call_types.get(&edge.call_type)?;
+        if edge.confidence == "Inferred" {
+            cost += self.inferred?;
+        }
+        if !edge.is_resolved {
+            cost += self.unresolved?;
+        } else if let (Some(penalty), Some(certainty)) =
+            (self.unresolved, edge.resolution_confidence)
+        {
+            cost += penalty * (1.0 - certainty.clamp(0.0, 1.0));
+        }
+        Some(cost)
+    }
+}
+
+/// Unweighted searches count call edges and leave reference edges out.
+fn edge_cost(weights: Option<&EdgeWeights>, edge: &GraphEdge) -> Option<f64> {
+    match weights {
+        Some(weights) => weights.cost(edge),
+        None => (!is_reference_call_type(&edge.call_type)).then_some(1.0),
+    }
+}
+
+/// A call edge whose target is a symbol on the branch.
+#[derive(Debug, Clone)]
+pub(crate) struct GraphEdge {
+    pub to: String,
+    pub call_type: String,
+    pub confidence: String,
+    pub is_resolved: bool,
+    pub resolution_confidence: Option<f64>,
+    pub line: u32,
+    pub col: u32,
+}
+
+/// A partial path in a search: its last symbol, cost and length, and the
+/// label it extends.
+struct Label<'g> {
+    node: &'g str,
+    cost: f64,
+    hops: usize,
+    parent: Option<usize>,
+}
+
+/// Heap entry ordering labels cheapest first, then shortest, then oldest.
+#[derive(PartialEq)]
+struct Queued {
+    cost: f64,
+    hops: usize,
+    label: usize,
+}
+
+impl Eq for Queued {}
+
+impl Ord for Queued {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other
+            .cost
+            .total_cmp(&self.cost)
+            .then_with(|| other.hops.cmp(&self.hops))
+            .then_with(|| other.label.cmp(&self.label))
+    }
+}
+
+impl PartialOrd for Queued {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// A branch's symbols and the edges between them, loaded once so path
+/// searches never go back to SQLite per hop.
+///
+/// An edge's target is its `to_symbol_id` when that symbol is on the branch,
+/// otherwise the only branch symbol its target name matches in the caller's
+/// language. Edges with no such target are left out.
+pub(crate) struct BranchGraph {
+    pub symbols: HashMap<String, SymbolRow>,
+    /// Outgoing edges per symbol, ordered by target id then call site.
+    pub edges: HashMap<String, Vec<GraphEdge>>,
+}
+
+impl BranchGraph {
+    pub(crate) fn load(conn: &Connection, branch: &str) -> DbResult<Self> {
+        let symbols: HashMap<String, SymbolRow> = get_symbols_for_branch(conn, branch)?
+            .into_iter()
+            .map(|symbol| (symbol.id.clone(), symbol))
+            .collect();
+        let mut by_lower_name: HashMap<String, Vec<&SymbolRow>> = HashMap::new();
+        for symbol in symbols.values() {
+            by_lower_name
+                .entry(symbol.name.to_lowercase())
+                .or_default()
+                .push(symbol);
+        }
+
+        let mut stmt = conn.prepare(&with_branch_targets(
+            r#"
+            SELECT ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.confidence, {edge_resolved}, bt.resolution_confidence, ce.line, ce.col
+            FROM call_edges ce
+            INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+            {branch_targets}
+            "#,
+        ))?;
+        let rows = stmt.query_map(params![branch], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, Option<String>>(2)?,
+                GraphEdge {
+                    to: String::new(),
+                    call_type: row.get(3)?,
+                    confidence: row.get(4)?,
+                    is_resolved: row.get::<_, i32>(5)? != 0,
+                    resolution_confidence: row.get(6)?,
+                    line: row.get(7)?,
+                    col: row.get(8)?,
+                },
+            ))
+        })?;
+
+        let mut edges: HashMap<String, Vec<GraphEdge>> = HashMap::new();
+        for row in rows {
+            let (from, target_name, to_symbol_id, edge) = row?;
+            let Some(caller) = symbols.get(&from) else {
+                continue;
+            };
+            let to = match to_symbol_id {
+                Some(to) if symbols.contains_key(&to) => to,
+                Some(_) => continue,
+                None => {
+                    let mut matches = by_lower_name
+                        .get(&target_name.to_lowercase())
+                        .into_iter()
+                        .flatten()
+                        .filter(|symbol| {
+                            symbol_names_match(&caller.language, &symbol.name, &target_name)
+                        });
+                    match (matches.next(), matches.next()) {
+                        (Some(only), None) => only.id.clone(),
+                        _ => continue,
+                    }
+                }
+            };
+            if to == from {
+                continue;
+            }
+            edges
+                .entry(from)
+                .or_default()
+                .push(GraphEdge { to, ..edge });
+        }
+
+        for outgoing in edges.values_mut() {
+            outgoing.sort_by(|a, b| (&a.to, a.line, a.col).cmp(&(&b.to, b.line, b.col)));
+        }
+        Ok(Self { symbols, edges })
+    }
+
+    /// The symbol `reference` names: an id on the branch, or the only symbol
+    /// with that name. `Err` lists the candidates when several share it.
+    pub(crate) fn endpoint(
+        &self,
+        reference: &str,
+    ) -> Result<Option<&SymbolRow>, Vec<SymbolCandidateRow>> {
+        if let Some(symbol) = self.symbols.get(reference) {
+            return Ok(Some(symbol));
+        }
+        let mut named: Vec<&SymbolRow> = self
+            .symbols
+            .values()
+            .filter(|symbol| symbol_names_match(&symbol.language, &symbol.name, reference))
+            .collect();
+        if named.len() <= 1 {
+            return Ok(named.pop());
+        }
+        named.sort_by(|a, b| {
+            (&a.file_path, a.start_line, &a.id).cmp(&(&b.file_path, b.start_line, &b.id))
+        });
+        Err(named
+            .into_iter()
+            .map(|symbol| SymbolCandidateRow {
+                symbol_id: symbol.id.clone(),
+                symbol_name: symbol.name.clone(),
+                kind: symbol.kind.clone(),
+                file_path: symbol.file_path.clone(),
+                line: symbol.start_line,
+            })
+            .collect())
+    }
+
+    /// Hops for a path given as symbol ids, taking each hop's call type from
+    /// the cheapest edge into it, and the path's total cost.
+    fn path(&self, path: &[&str], weights: Option<&EdgeWeights>) -> CallPathRow {
+        let mut cost = 0.0;
+        let hops = path
+            .iter()
+            .enumerate()
+            .filter_map(|(index, id)| {
+                let symbol = self.symbols.get(*id)?;
+                let call_type = match index {
+                    0 => "source".to_string(),
+                    _ => {
+                        let (edge, edge_cost) = self.cheapest_edge(path[index - 1], id, weights)?;
+                        cost += edge_cost;
+                        edge.call_type.clone()
+                    }
+                };
+                Some(PathHopRow {
+                    symbol_id: symbol.id.clone(),
+                    symbol_name: symbol.name.clone(),
+                    file_path: symbol.file_path.clone(),
+                    line: symbol.start_line,
+                    call_type,
+                })
+            })
+            .collect();
+        CallPathRow { hops, cost }
+    }
+
+    fn cheapest_edge(
+        &self,
+        from: &str,
+        to: &str,
+        weights: Option<&EdgeWeights>,
+    ) -> Option<(&GraphEdge, f64)> {
+        self.edges
+            .get(from)?
+            .iter()
+            .filter(|edge| edge.to == to)
+            .filter_map(|edge| Some((edge, edge_cost(weights, edge)?)))
+            .min_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    /// Label-setting Dijkstra from `starts` along `neighbors`, bounded by
+    /// `max_hops`. A label survives only if no other label at its symbol is
+    /// both cheaper and shorter, so the hop limit never hides a path.
+    /// `settle` sees labels cheapest first and stops the search by
+    /// returning `true`; the labels and that last label are returned.
+    #[allow(clippy::too_many_arguments)]
+    fn search<'g>(
+        &'g self,
+        starts: &[&'g str],
+        neighbors: impl Fn(&'g str) -> Vec<(&'g str, &'g GraphEdge)>,
+        max_hops: usize,
+        weights: Option<&EdgeWeights>,
+        removed_nodes: &HashSet<&str>,
+        removed_edges: &HashSet<(&str, &str)>,
+        mut settle: impl FnMut(&Label<'g>) -> bool,
+    ) -> (Vec<Label<'g>>, Option<usize>) {
+        let mut labels: Vec<Label<'g>> = Vec::new();
+        let mut frontier: HashMap<&str, Vec<(f64, usize)>> = HashMap::new();
+        let mut heap = BinaryHeap::new();
+        for start in starts {
+            heap.push(Queued {
+                cost: 0.0,
+                hops: 0,
+                label: labels.len(),
+            });
+            frontier.entry(start).or_default().push((0.0, 0));
+            labels.push(Label {
+                node: start,
+                cost: 0.0,
+                hops: 0,
+                parent: None,
+            });
+        }
+
+        while let Some(Queued { label: index, .. }) = heap.pop() {
+            if settle(&labels[index]) {
+                return (labels, Some(index));
+            }
+            let (node, cost, hops) = (labels[index].node, labels[index].cost, labels[index].hops);
+            if hops >= max_hops {
+                continue;
+            }
+            for (next, edge) in neighbors(node) {
+                if removed_nodes.contains(next) || removed_edges.contains(&(node, next)) {
+                    continue;
+                }
+                let Some(edge_cost) = edge_cost(weights, edge) else {
+                    continue;
+                };
+                let (cost, hops) = (cost + edge_cost, hops + 1);
+                let seen = frontier.entry(next).or_default();
+                if seen
+                    .iter()
+                    .any(|(seen_cost, seen_hops)| *seen_cost <= cost && *seen_hops <= hops)
+                {
+                    continue;
+                }
+                seen.push((cost, hops));
+                heap.push(Queued {
+                    cost,
+                    hops,
+                    label: labels.len(),
+                });
+                labels.push(Label {
+                    node: next,
+                    cost,
+                    hops,
+                    parent: Some(index),
+                });
+            }
+        }
+        (labels, None)
+    }
+
+    fn callees<'g>(&'g self) -> impl Fn(&'g str) -> Vec<(&'g str, &'g GraphEdge)> + 'g {
+        |node| {
+            self.edges
+                .get(node)
+                .into_iter()
+                .flatten()
+                .map(|edge| (edge.to.as_str(), edge))
+                .collect()
+        }
+    }
+
+    /// Cheapest path from `from` to `to` within `max_hops`, avoiding the
+    /// given symbols and `(from, to)` pairs.
+    fn cheapest<'g>(
+        &'g self,
+        from: &'g str,
+        to: &str,
+        max_hops: usize,
+        weights: Option<&EdgeWeights>,
+        removed_nodes: &HashSet<&str>,
+        removed_edges: &HashSet<(&str, &str)>,
+    ) -> Option<Vec<&'g str>> {
+        let (labels, found) = self.search(
+            &[from],
+            self.callees(),
+            max_hops,
+            weights,
+            removed_nodes,
+            removed_edges,
+            |label| label.node == to,
+        );
+        let mut path = Vec::new();
+        let mut current = found;
+        while let Some(index) = current {
+            path.push(labels[index].node);
+            current = labels[index].parent;
+        }
+        path.reverse();
+        Some(path).filter(|path| !path.is_empty())
+    }
+}
+
+/// Up to `k` loopless call paths from `from` to `to` (symbol ids or names)
+/// using Yen's algorithm. Paths are ranked by hop count, or by summed edge
+/// cost under `weights`; paths longer than `max_depth` hops are not
+/// considered.
+pub fn find_k_shortest_paths(
+    conn: &Connection,
+    from: &str,
+    to: &str,
+    branch: &str,
+    k: u32,
+    max_depth: u32,
+    weights: Option<&EdgeWeights>,
+) -> DbResult<CallPathsRow> {
+    let graph = BranchGraph::load(conn, branch)?;
+    let (from, to) = match (graph.endpoint(from), graph.endpoint(to)) {
+        (Ok(Some(from)), Ok(Some(to))) => (from.id.as_str(), to.id.as_str()),
+        (from, to) => {
+            return Ok(CallPathsRow {
+                paths: Vec::new(),
+                from_candidates: from.err().unwrap_or_default(),
+                to_candidates: to.err().unwrap_or_default(),
+            })
+        }
+    };
+    if k == 0 || from == to {
+        return Ok(CallPathsRow::default());
+    }
+
+    let max_hops = max_depth as usize;
+    let none = HashSet::new();
+    let Some(first) = graph.cheapest(from, to, max_hops, weights, &none, &HashSet::new()) else {
+        return Ok(CallPathsRow::default());
+    };
+    let mut found: Vec<(Vec<&str>, CallPathRow)> =
+        vec![(first.clone(), graph.path(&first, weights))];
+    let mut candidates: Vec<(Vec<&str>, CallPathRow)> = Vec::new();
+
+    while found.len() < k as usize {
+        let previous = found.last().unwrap().0.clone();
+        for spur_index in 0..previous.len() - 1 {
+            let root = &previous[..=spur_index];
+            let removed_edges: HashSet<(&str, &str)> = found
+                .iter()
+                .map(|(path, _)| path)
+                .filter(|path| path.len() > spur_index + 1 && path[..=spur_index] == *root)
+                .map(|path| (path[spur_index], path[spur_index + 1]))
+                .collect();
+            let removed_nodes: HashSet<&str> = root[..spur_index].iter().copied().collect();
+            let Some(spur) = graph.cheapest(
+                previous[spur_index],
+                to,
+                max_hops - spur_index,
+                weights,
+                &removed_nodes,
+                &removed_edges,
+            ) else {
+                continue;
+            };
+            let mut path = root[..spur_index].to_vec();
+            path.extend(spur);
+            if !found
+                .iter()
+                .chain(&candidates)
+                .any(|(seen, _)| *seen == path)
+            {
+                let row = graph.path(&path, weights);
+                candidates.push((path, row));
+            }
+        }
+        let Some(best) = candidates
+            .iter()
+            .enumerate()
+            .min_by(|(_, (a, a_row)), (_, (b, b_row))| {
+                a_row
+                    .cost
+                    .total_cmp(&b_row.cost)
+                    .then_with(|| a.len().cmp(&b.len()))
+                    .then_with(|| a.cmp(b))
+            })
+            .map(|(index, _)| index)
+        else {
+            break;
+        };
+        found.push(candidates.swap_remove(best));
+    }
+
+    Ok(CallPathsRow {
+        paths: found.into_iter().map(|(_, row)| row).collect(),
+        ..CallPathsRow::default()
+    })
+}
+
+/// Symbols reachable from `root_symbol_ids` in `direction` (`callees`,
+/// `callers` or `both`) within `max_depth` edges, each with the cost of its
+/// cheapest chain under `weights`. Edges `weights` leaves out are never
+/// followed, and symbols costing more than `max_cost` are dropped. Sorted by
+/// cost, then symbol id.
+pub fn get_weighted_reachability(
+    conn: &Connection,
+    root_symbol_ids: &[String],
+    branch: &str,
+    direction: &str,
+    weights: &EdgeWeights,
+    max_depth: u32,
+    max_cost: Option<f64>,
+) -> DbResult<Vec<WeightedReachabilityRow>> {
+    let graph = BranchGraph::load(conn, branch)?;
+    let roots: Vec<&str> = root_symbol_ids
+        .iter()
+        .map(String::as_str)
+        .filter(|id| graph.symbols.contains_key(*id))
+        .collect();
+    if roots.is_empty() || max_depth == 0 {
+        return Ok(vec![]);
+    }
+
+    let mut callers: HashMap<&str, Vec<(&str, &GraphEdge)>> = HashMap::new();
+    for (from, edges) in &graph.edges {
+        for edge in edges {
+            callers
+                .entry(edge.to.as_str())
+                .or_default()
+                .push((from.as_str(), edge));
+        }
+    }
+    let callers_of = |node: &str| callers.get(node).cloned().unwrap_or_default();
+
+    let mut reached: HashMap<&str, (f64, usize)> = HashMap::new();
+    let mut record = |label: &Label<'_>| {
+        if max_cost.is_some_and(|max_cost| label.cost > max_cost) {
+            return true;
+        }
+        if let Some(symbol) = graph.symbols.get(label.node) {
+            let entry = reached
+                .entry(symbol.id.as_str())
+                .or_insert((label.cost, label.hops));
+            if label.cost < entry.0 {
+                *entry = (label.cost, label.hops);
+            }
+        }
+        false
+    };
+    let none = HashSet::new();
+    let no_edges = HashSet::new();
+    let max_hops = max_depth as usize;
+    if direction != "callers" {
+        graph.search(
+            &roots,
+            graph.callees(),
+            max_hops,
+            Some(weights),
+            &none,
+            &no_edges,
+            &mut record,
+        );
+    }
+    if direction != "callees" {
+        graph.search(
+            &roots,
+            callers_of,
+            max_hops,
+            Some(weights),
+            &none,
+            &no_edges,
+            &mut record,
+        );
+    }
+
+    let mut results: Vec<WeightedReachabilityRow> = reached
+        .into_iter()
+        .filter(|(id, _)| !roots.contains(id))
+        .map(|(id, (cost, hops))| {
+            let symbol = &graph.symbols[id];
+            WeightedReachabilityRow {
+                symbol_id: symbol.id.clone(),
+                symbol_name: symbol.name.clone(),
+                file_path: symbol.file_path.clone(),
+                depth: hops as u32,
+                cost,
+            }
+        })
+        .collect();
+    results.sort_by(|a, b| {
+        a.cost
+            .total_cmp(&b.cost)
+            .then_with(|| a.symbol_id.cmp(&b.symbol_id))
+    });
+    Ok(results)
+}
@@ -0,0 +1,722 @@
+use super::call_graph::with_branch_targets;
+use super::{
+    get_symbols_for_branch_with_file_scopes, DbResult, SymbolRow, SQL_BIND_PARAM_BATCH_SIZE,
+};
+use crate::receiver::SELF_RECEIVERS;
+use crate::registry;
+use crate::types::Language;
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+
+// ============================================================================
+// Call Edge Resolution (Call Graph)
+// ============================================================================
+
+/// How `resolve_call_edges` chose an edge's target, most specific first.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResolutionStrategy {
+    /// A member of the receiver's type: the caller's own class for
+    /// `this`/`self`, or the type a parameter, variable or field declares.
+    ReceiverType,
+    /// The definition visible from the caller's innermost scope, or the only
+    /// one in the caller's file.
+    SameFile,
+    /// A definition in the module the caller's file imports the name, or the
+    /// receiver namespace, from.
+    Import,
+    /// The only compatible definition on the branch.
+    UniqueName,
+    Unresolved,
+}
+
+impl ResolutionStrategy {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            ResolutionStrategy::ReceiverType => "receiver-type",
+            ResolutionStrategy::SameFile => "same-file",
+            ResolutionStrategy::Import => "import",
+            ResolutionStrategy::UniqueName => "unique-name",
+            ResolutionStrategy::Unresolved => "unresolved",
+        }
+    }
+}
+
+/// Edges seen by `resolve_call_edges`, counted by the strategy that
+/// resolved them.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct CallResolutionStats {
+    pub edges: usize,
+    pub receiver_type: usize,
+    pub same_file: usize,
+    pub import: usize,
+    pub unique_name: usize,
+    pub unresolved: usize,
+    /// Edges whose stored target, strategy or confidence changed.
+    pub updated: usize,
+}
+
+struct Resolution {
+    target: Option<usize>,
+    strategy: ResolutionStrategy,
+    confidence: f64,
+}
+
+impl Resolution {
+    fn to(target: usize, strategy: ResolutionStrategy, confidence: f64) -> Self {
+        Self {
+            target: Some(target),
+            strategy,
+            confidence,
+        }
+    }
+
+    fn unresolved() -> Self {
+        Self {
+            target: None,
+            strategy: ResolutionStrategy::Unresolved,
+            confidence: 0.0,
+        }
+    }
+}
+
+struct EdgeToResolve {
+    id: String,
+    from_symbol_id: String,
+    target_name: String,
+    to_symbol_id: Option<String>,
+    call_type: String,
+    qualifier: Option<String>,
+    resolution_strategy: Option<String>,
+    resolution_confidence: Option<f64>,
+}
+
+/// Lookups over a branch's symbols.
+struct SymbolIndex<'a> {
+    symbols: &'a [SymbolRow],
+    by_id: HashMap<&'a str, usize>,
+    by_name: HashMap<&'a str, Vec<usize>>,
+    by_lowercase_name: HashMap<String, Vec<usize>>,
+    case_insensitive: HashMap<&'a str, bool>,
+}
+
+impl<'a> SymbolIndex<'a> {
+    fn new(symbols: &'a [SymbolRow]) -> Self {
+        let mut index = Self {
+            symbols,
+            by_id: HashMap::new(),
+            by_name: HashMap::new(),
+            by_lowercase_name: HashMap::new(),
+            case_insensitive: HashMap::new(),
+        };
+        for (position, symbol) in symbols.iter().enumerate() {
+            index.by_id.insert(&symbol.id, position);
+            index
+                .by_name
+                .entry(&symbol.name)
+                .or_default()
+                .push(position);
+            index
+                .by_lowercase_name
+                .entry(symbol.name.to_lowercase())
+                .or_default()
+                .push(position);
+            index
+                .case_insensitive
+                .entry(&symbol.language)
+                .or_insert_with(|| {
+                    registry::definition(&Language::from_string(&symbol.language)).case_insensitive
+                });
+        }
+        index
+    }
+
+    fn is_case_insensitive(&self, language: &str) -> bool {
+        self.case_insensitive
+            .get(language)
+            .copied()
+            .unwrap_or(false)
+    }
+
+    fn names_match(&self, language: &str, left: &str, right: &str) -> bool {
+        if self.is_case_insensitive(language) {
+            left.eq_ignore_ascii_case(right)
+        } else {
+            left == right
+        }
+    }
+
+    /// Symbols named `name` that an edge of `call_type` from `caller` can
+    /// point at. Case-insensitive languages only fold case among themselves.
+    fn candidates(&self, caller: &SymbolRow, name: &str, call_type: &str) -> Vec<usize> {
+        let matches = if self.is_case_insensitive(&caller.language) {
+            self.by_lowercase_name
+                .get(&name.to_lowercase())
+                .map(|positions| {
+                    positions
+                        .iter()
+                        .copied()
+                        .filter(|&position| {
+                            let symbol = &self.symbols[position];
+                            symbol.name == name || self.is_case_insensitive(&symbol.language)
+                        })
+                        .collect()
+                })
+                .unwrap_or_default()
+        } else {
+            self.by_name.get(name).cloned().unwrap_or_default()
+        };
+        matches
+            .into_iter()
+            .filter(|&position| {
+                is_compatible_target(&caller.language, call_type, &self.symbols[position].kind)
+            })
+            .collect()
+    }
+}
+
+/// Modules each file imports a name from, read off the file's `Import` edges.
+type ImportMap<'a> = HashMap<(&'a str, &'a str), Vec<&'a str>>;
+
+/// Picks a target for the call edges whose caller is on `branch` and records
+/// it, with the strategy and confidence, for `branch` alone: branches that
+/// share a caller can resolve its calls differently. Only edges whose outcome
+/// changed are written.
+///
+/// `changed_symbol_ids` lists the symbols added to or removed from the branch
+/// since its last run, and must be passed before removed symbols are
+/// collected. Only edges whose outcome can then differ are resolved: those
+/// the branch has no resolution for yet, those whose target left the branch,
+/// and those naming a changed symbol. `None` resolves every edge.
+pub fn resolve_call_edges(
+    conn: &mut Connection,
+    branch: &str,
+    changed_symbol_ids: Option<&[String]>,
+) -> DbResult<CallResolutionStats> {
+    let symbols = get_symbols_for_branch_with_file_scopes(conn, branch)?;
+    let edges = branch_edges(conn, branch)?;
+    let index = SymbolIndex::new(&symbols);
+    let changed_names = changed_symbol_ids
+        .map(|ids| symbol_names(conn, ids))
+        .transpose()?;
+
+    let mut imports: ImportMap = HashMap::new();
+    for edge in edges.iter().filter(|edge| edge.call_type == "Import") {
+        if let (Some(&caller), Some(module)) = (
+            index.by_id.get(edge.from_symbol_id.as_str()),
+            edge.qualifier.as_deref(),
+        ) {
+            imports
+                .entry((&symbols[caller].file_path, &edge.target_name))
+                .or_default()
+                .push(module);
+        }
+    }
+
+    let mut stats = CallResolutionStats::default();
+    let mut updates = Vec::new();
+    for edge in &edges {
+        let Some(&caller) = index.by_id.get(edge.from_symbol_id.as_str()) else {
+            continue;
+        };
+        if let Some(changed_names) = &changed_names {
+            let names_changed = [Some(edge.target_name.as_str()), edge.qualifier.as_deref()]
+                .into_iter()
+                .flatten()
+                .any(|name| changed_names.contains(&last_segment(name).to_lowercase()));
+            let target_left = edge
+                .to_symbol_id
+                .as_deref()
+                .is_some_and(|target| !index.by_id.contains_key(target));
+            if edge.resolution_strategy.is_some() && !names_changed && !target_left {
+                continue;
+            }
+        }
+        let resolution = resolve_edge(&index, &imports, &symbols[caller], edge);
+        stats.edges += 1;
+        match resolution.strategy {
+            ResolutionStrategy::ReceiverType => stats.receiver_type += 1,
+            ResolutionStrategy::SameFile => stats.same_file += 1,
+            ResolutionStrategy::Import => stats.import += 1,
+            ResolutionStrategy::UniqueName => stats.unique_name += 1,
+            ResolutionStrategy::Unresolved => stats.unresolved += 1,
+        }
+
+        let target = resolution.target.map(|target| symbols[target].id.as_str());
+        let unchanged = edge.to_symbol_id.as_deref() == target
+            && edge.resolution_strategy.as_deref() == Some(resolution.strategy.as_str())
+            && edge.resolution_confidence == Some(resolution.confidence);
+        if !unchanged {
+            updates.push((edge.id.as_str(), target, resolution));
+        }
+    }
+    stats.updated = updates.len();
+
+    super::run_batch_with_write_transaction(conn, |conn| {
+        // Edges whose caller left the branch.
+        conn.execute(
+            r#"
+            DELETE FROM branch_call_edge_targets
+            WHERE branch = ?1 AND edge_id NOT IN (
+                SELECT ce.id
+                FROM call_edges ce
+                INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?1
+            )
+            "#,
+            params![branch],
+        )?;
+        let mut stmt = conn.prepare(
+            r#"
+            INSERT OR REPLACE INTO branch_call_edge_targets (branch, edge_id, to_symbol_id, resolution_strategy, resolution_confidence)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+        )?;
+        for (id, target, resolution) in &updates {
+            stmt.execute(params![
+                branch,
+                id,
+                target,
+                resolution.strategy.as_str(),
+                resolution.confidence
+            ])?;
+        }
+        Ok(())
+    })?;
+
+    Ok(stats)
+}
+
+/// Edges from the branch's symbols, with the resolution the branch last
+/// recorded for each.
+fn branch_edges(conn: &Connection, branch: &str) -> DbResult<Vec<EdgeToResolve>> {
+    let mut stmt = conn.prepare(&with_branch_targets(
+        r#"
+        SELECT ce.id, ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type, ce.qualifier, bt.resolution_strategy, bt.resolution_confidence
+        FROM call_edges ce
+        INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+        {branch_targets}
+        "#,
+    ))?;
+    let rows = stmt.query_map(params![branch], |row| {
+        Ok(EdgeToResolve {
+            id: row.get(0)?,
+            from_symbol_id: row.get(1)?,
+            target_name: row.get(2)?,
+            to_symbol_id: row.get(3)?,
+            call_type: row.get(4)?,
+            qualifier: row.get(5)?,
+            resolution_strategy: row.get(6)?,
+            resolution_confidence: row.get(7)?,
+        })
+    })?;
+
+    let mut results = Vec::new();
+    for row in rows {
+        results.push(row?);
+    }
+    Ok(results)
+}
+
+/// Lowercased names of the given symbols.
+fn symbol_names(conn: &Connection, symbol_ids: &[String]) -> DbResult<HashSet<String>> {
+    let mut names = HashSet::new();
+    for chunk in symbol_ids.chunks(SQL_BIND_PARAM_BATCH_SIZE) {
+        let placeholders = std::iter::repeat_n("?", chunk.len())
+            .collect::<Vec<_>>()
+            .join(", ");
+        let sql = format!("SELECT name FROM symbols WHERE id IN ({})", placeholders);
+        let mut stmt = conn.prepare(&sql)?;
+        let rows = stmt.query_map(rusqlite::params_from_iter(chunk.iter()), |row| {
+            row.get::<_, String>(0)
+        })?;
+        for row in rows {
+            names.insert(row?.to_lowercase());
+        }
+    }
+    Ok(names)
+}
+
+fn resolve_edge(
+    index: &SymbolIndex<'_>,
+    imports: &ImportMap<'_>,
+    caller: &SymbolRow,
+    edge: &EdgeToResolve,
+) -> Resolution {
+    let candidates = index.candidates(caller, &edge.target_name, &edge.call_type);
+    if candidates.is_empty() {
+        return Resolution::unresolved();
+    }
+
+    let qualifier = edge.qualifier.as_deref();
+    let resolution = match edge.call_type.as_str() {
+        "Import" => by_import(index, imports, caller, edge, &candidates),
+        // `user.name` only names a member of whatever `user` is.
+        "FieldAccess" => by_receiver(index, imports, caller, qualifier, &candidates),
+        "Call" | "MethodCall" if qualifier.is_some() => {
+            by_receiver(index, imports, caller, qualifier, &candidates)
+                .or_else(|| by_import(index, imports, caller, edge, &candidates))
+                .or_else(|| by_same_file(index, caller, edge, &candidates))
+        }
+        _ => by_same_file(index, caller, edge, &candidates)
+            .or_else(|| by_import(index, imports, caller, edge, &candidates)),
+    };
+    // A field or variable sharing a name with the only symbol of that name is
+    // not evidence of a reference to it.
+    let guess_by_name = !matches!(edge.call_type.as_str(), "FieldAccess" | "Read" | "Write");
+    resolution
+        .or_else(|| {
+            (guess_by_name && candidates.len() == 1)
+                .then(|| Resolution::to(candidates[0], ResolutionStrategy::UniqueName, 0.6))
+        })
+        .unwrap_or_else(Resolution::unresolved)
+}
+
+fn by_receiver(
+    index: &SymbolIndex<'_>,
+    imports: &ImportMap<'_>,
+    caller: &SymbolRow,
+    qualifier: Option<&str>,
+    candidates: &[usize],
+) -> Option<Resolution> {
+    let qualifier = qualifier?;
+    if SELF_RECEIVERS.contains(&qualifier) {
+        // Walk out from the caller's class so nested classes see their own
+        // members first.
+        for scope in scope_chain(caller.parent_symbol.as_deref()) {
+            let members: Vec<usize> = candidates
+                .iter()
+                .copied()
+                .filter(|&position| index.symbols[position].parent_symbol.as_deref() == Some(scope))
+                .collect();
+            if !members.is_empty() {
+                return single(prefer_file(index, &caller.file_path, members))
+                    .map(|target| Resolution::to(target, ResolutionStrategy::ReceiverType, 0.95));
+            }
+        }
+        return None;
+    }
+
+    let members: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&position| {
+            index.symbols[position]
+                .parent_symbol
+                .as_deref()
+                .is_some_and(|parent| {
+                    index.names_match(&caller.language, last_segment(parent), qualifier)
+                })
+        })
+        .collect();
+    if members.is_empty() {
+        return None;
+    }
+    let members = prefer_file(index, &caller.file_path, members);
+    let members = match imports.get(&(caller.file_path.as_str(), qualifier)) {
+        Some(modules) if members.len() > 1 => {
+            let imported: Vec<usize> = members
+                .iter()
+                .copied()
+                .filter(|&position| {
+                    modules.iter().any(|module| {
+                        module_matches(
+                            &caller.file_path,
+                            module,
+                            &index.symbols[position].file_path,
+                        )
+                    })
+                })
+                .collect();
+            if imported.is_empty() {
+                members
+            } else {
+                imported
+            }
+        }
+        _ => members,
+    };
+    single(members).map(|target| Resolution::to(target, ResolutionStrategy::ReceiverType, 0.9))
+}
+
+fn by_same_file(
+    index: &SymbolIndex<'_>,
+    caller: &SymbolRow,
+    edge: &EdgeToResolve,
+    candidates: &[usize],
+) -> Option<Resolution> {
+    let local: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&position| index.symbols[position].file_path == caller.file_path)
+        .collect();
+    if local.is_empty() {
+        return None;
+    }
+    if edge.call_type == "MethodCall" || edge.qualifier.is_some() {
+        // A receiver we could not type still calls a member when the file
+        // has one.
+        let members: Vec<usize> = local
+            .iter()
+            .copied()
+            .filter(|&position| index.symbols[position].parent_symbol.is_some())
+            .collect();
+        let local = if members.is_empty() { local } else { members };
+        return single(local)
+            .map(|target| Resolution::to(target, ResolutionStrategy::SameFile, 0.9));
+    }
+
+    // The innermost enclosing scope that defines the name shadows the rest;
+    // top-level definitions come last.
+    let own_scope = caller.qualified_name.as_deref().or(Some(&caller.name));
+    let scopes = scope_chain(own_scope).map(Some).chain([None]);
+    for scope in scopes {
+        let visible: Vec<usize> = local
+            .iter()
+            .copied()
+            .filter(|&position| index.symbols[position].parent_symbol.as_deref() == scope)
+            .collect();
+        if !visible.is_empty() {
+            return single(visible)
+                .map(|target| Resolution::to(target, ResolutionStrategy::SameFile, 0.9));
+        }
+    }
+    single(local).map(|target| Resolution::to(target, ResolutionStrategy::SameFile, 0.9))
+}
+
+fn by_import(
+    index: &SymbolIndex<'_>,
+    imports: &ImportMap<'_>,
+    caller: &SymbolRow,
+    edge: &EdgeToResolve,
+    candidates: &[usize],
+) -> Option<Resolution> {
+    let file = caller.file_path.as_str();
+    let mut modules: Vec<&str> = Vec::new();
+    if edge.call_type == "Import" {
+        modules.extend(edge.qualifier.as_deref());
+    } else {
+        for name in [Some(edge.target_name.as_str()), edge.qualifier.as_deref()]
+            .into_iter()
+            .flatten()
+        {
+            if let Some(sources) = imports.get(&(file, name)) {
+                modules.extend(sources);
+            }
+        }
+    }
+    if modules.is_empty() {
+        return None;
+    }
+
+    let imported: Vec<usize> = candidates
+        .iter()
+        .copied()
+        .filter(|&position| {
+            modules
+                .iter()
+                .any(|module| module_matches(file, module, &index.symbols[position].file_path))
+        })
+        .collect();
+    single(imported).map(|target| Resolution::to(target, ResolutionStrategy::Import, 0.85))
+}
+
+fn single(positions: Vec<usize>) -> Option<usize> {
+    (positions.len() == 1).then(|| positions[0])
+}
+
+/// Narrows `positions` to those in `file` when any are.
+fn prefer_file(index: &SymbolIndex<'_>, file: &str, positions: Vec<usize>) -> Vec<usize> {
+    let local: Vec<usize> = positions
+        .iter()
+        .copied()
+        .filter(|&position| index.symbols[position].file_path == file)
+        .collect();
+    if local.is_empty() {
+        positions
+    } else {
+        local
+    }
+}
+
+/// `a.b.c`, `a.b`, `a` for a qualified name in either `.` or `::` style.
+fn scope_chain(qualified: Option<&str>) -> impl Iterator<Item = &str> {
+    std::iter::successors(qualified, |scope| {
+        let split = scope.rfind('.').max(scope.rfind("::"))?;
+        Some(&scope[..split]).filter(|parent| !parent.is_empty())
+    })
+}
+
+fn last_segment(qualified: &str) -> &str {
+    qualified.rsplit(['.', ':']).next().unwrap_or(qualified)
+}
+
+fn is_namespace_kind(kind: &str) -> bool {
+    matches!(
+        kind,
+        "namespace_definition" | "namespace_declaration" | "mod_item" | "module"
+    )
+}
+
+fn is_type_kind(kind: &str) -> bool {
+    [
+        "class",
+        "struct",
+        "interface",
+        "enum",
+        "trait",
+        "protocol",
+        "union",
+        "actor",
+        "record",
+    ]
+    .iter()
+    .any(|word| kind.contains(word))
+        || matches!(
+            kind,
+            "type_alias_declaration" | "type_declaration" | "type_spec"
+        )
+}
+
+/// Whether an edge of `call_type` can point at a symbol of `kind`. Mirrors
+/// the indexer's PHP and C-family filters and keeps type-only edges on types.
+fn is_compatible_target(language: &str, call_type: &str, kind: &str) -> bool {
+    if matches!(kind, "decorated_definition" | "template_declaration") {
+        return true;
+    }
+    if is_namespace_kind(kind) {
+        return call_type == "Import";
+    }
+    let is_type = is_type_kind(kind);
+    match (call_type, language) {
+        ("Inherits" | "Implements" | "TypeReference", _) => is_type,
+        ("Constructor", "c" | "cpp" | "php") => is_type,
+        ("Call", "php") => matches!(
+            kind,
+            "function_declaration" | "function" | "function_definition"
+        ),
+        ("Call" | "MethodCall", "c" | "cpp") => !is_type,
+        _ => true,
+    }
+}
+
+/// Whether `file` is the module `module`, as imported from `importer`:
+/// a relative path (`./dto`, `../lib/util.js`), a Python relative module
+/// (`.dto`, `..models`), or a dotted or slashed module path (`app.models`,
+/// `com.acme.users`) matched against the end of the file's path or package
+/// directory.
+fn module_matches(importer: &str, module: &str, file: &str) -> bool {
+    let stem = module_stem(file);
+    let directory = parent_path(&stem);
+
+    if module.starts_with("./") || module.starts_with("../") {
+        let joined = normalize_path(&format!("{}/{}", parent_path(importer), module));
+        return stem == joined || stem == strip_extension(&joined);
+    }
+
+    if let Some(relative) = module.strip_prefix('.') {
+        let rest = relative.trim_start_matches('.');
+        let mut base = parent_path(importer).to_string();
+        for _ in 0..relative.len() - rest.len() {
+            base = parent_path(&base).to_string();
+        }
+        let target =
+            rest.split('.')
+                .filter(|segment| !segment.is_empty())
+                .fold(base, |path, segment| {
+                    if path.is_empty() {
+                        segment.to_string()
+                    } else {
+                        format!("{}/{}", path, segment)
+                    }
+                });
+        return stem == target;
+    }
+
+    let suffix = module
+        .split(['.', '/', ':', '\\'])
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if suffix.is_empty() {
+        return false;
+    }
+    let ends_with = |path: &str| path == suffix || path.ends_with(&format!("/{}", suffix));
+    ends_with(&stem) || ends_with(directory)
+}
+
+/// `file` without its extension or a trailing `index`, `__init__` or `mod`.
+fn module_stem(file: &str) -> String {
+    let stem = strip_extension(file);
+    for index in ["/index", "/__init__", "/mod"] {
+        if let Some(package) = stem.strip_suffix(index) {
+            return package.to_string();
+        }
+    }
+    stem
+}
+
+fn strip_extension(path: &str) -> String {
+    let name_start = path.rfind('/').map_or(0, |index| index + 1);
+    match path[name_start..].rfind('.') {
+        Some(dot) if dot > 0 => path[..name_start + dot].to_string(),
+        _ => path.to_string(),
+    }
+}
+
+fn parent_path(path: &str) -> &str {
+    path.rfind('/').map_or("", |index| &path[..index])
+}
+
+fn normalize_path(path: &str) -> String {
+    let mut segments: Vec<&str> = Vec::new();
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_module_matches_relative_dotted_and_package_imports() {
+        assert!(module_matches("src/app.ts", "./dto", "src/dto.ts"));
+        assert!(module_matches(
+            "src/app.ts",
+            "../lib/util.js",
+            "lib/util.ts"
+        ));
+        assert!(module_matches(
+            "src/app.ts",
+            "./users",
+            "src/users/index.ts"
+        ));
+        assert!(module_matches(
+            "src/app.ts",
+            "./user.service",
+            "src/user.service.ts"
+        ));
+        assert!(!module_matches("src/app.ts", "./dto", "src/other/dto.ts"));
+
+        assert!(module_matches("app/views.py", ".models", "app/models.py"));
+        assert!(module_matches(
+            "app/api/views.py",
+            "..models",
+            "app/models/__init__.py"
+        ));
+        assert!(module_matches("main.py", "app.models", "src/app/models.py"));
+
+        assert!(module_matches(
+            "src/main/java/com/acme/App.java",
+            "com.acme.users",
+            "src/main/java/com/acme/users/UserRepo.java"
+        ));
+        assert!(!module_matches("main.py", "app.models", "app/views.py"));
+    }
+}
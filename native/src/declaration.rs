@@ -0,0 +1,305 @@
+use crate::types::Language;
+use tree_sitter::Node;
+
+/// API-facing details of a declaration: its signature, documentation and
+/// visibility, extracted once so tools can summarize an API without reparsing.
+#[derive(Debug, Default, Clone, PartialEq)]
+pub(crate) struct DeclarationDetails {
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
+}
+
+impl DeclarationDetails {
+    /// Describes the declaration at `node`. `leading_comment` is the raw
+    /// comment text found directly above it, markers included.
+    pub(crate) fn of(
+        node: Node<'_>,
+        leading_comment: Option<&str>,
+        source: &str,
+        language: &Language,
+    ) -> Self {
+        let declaration = unwrap_declaration(node);
+        let docstring = if *language == Language::Python {
+            python_docstring(declaration, source)
+        } else {
+            None
+        };
+        Self {
+            signature: signature(declaration, source),
+            doc_comment: docstring.or_else(|| leading_comment.and_then(normalize_comment)),
+            visibility: visibility(node, declaration, source, language),
+        }
+    }
+}
+
+/// Steps through wrappers that carry no signature of their own.
+fn unwrap_declaration(node: Node<'_>) -> Node<'_> {
+    let inner = match node.kind() {
+        "export_statement" => node.child_by_field_name("declaration"),
+        "decorated_definition" => node.child_by_field_name("definition"),
+        "template_declaration" => {
+            let mut cursor = node.walk();
+            let last = node.named_children(&mut cursor).last();
+            last
+        }
+        _ => None,
+    };
+    match inner {
+        Some(inner) => unwrap_declaration(inner),
+        None => node,
+    }
+}
+
+/// The parameter list and return type as written, whitespace collapsed.
+///
+/// Languages that put the return type first (C, Java, C#) yield the whole
+/// `type name(params)` span; the others yield `(params) -> ret` style text.
+fn signature(node: Node<'_>, source: &str) -> Option<String> {
+    let function = function_node(node)?;
+    let parameters = parameters_node(function)?;
+    let return_type = ["return_type", "result", "returns", "type"]
+        .iter()
+        .find_map(|field| function.child_by_field_name(field))
+        .or_else(|| {
+            // C declarators nest the parameters under the declaration.
+            (function != node)
+                .then(|| node.child_by_field_name("type"))
+                .flatten()
+        });
+
+    let (start, end) = match return_type {
+        Some(ret) if ret.start_byte() < parameters.start_byte() => {
+            (ret.start_byte(), parameters.end_byte())
+        }
+        Some(ret) => (parameters.start_byte(), ret.end_byte()),
+        None => (parameters.start_byte(), parameters.end_byte()),
+    };
+    Some(collapse_whitespace(&source[start..end]))
+}
+
+/// The node holding the parameter list: the declaration itself, the arrow
+/// function bound by a `const`, or the function declarator of a C definition.
+fn function_node(node: Node<'_>) -> Option<Node<'_>> {
+    if parameters_node(node).is_some() {
+        return Some(node);
+    }
+    if let Some(declarator) = node.child_by_field_name("declarator") {
+        return function_node(declarator);
+    }
+    if matches!(node.kind(), "lexical_declaration" | "variable_declaration") {
+        let mut cursor = node.walk();
+        let declarator = node
+            .named_children(&mut cursor)
+            .find(|child| child.kind() == "variable_declarator")?;
+        let value = declarator.child_by_field_name("value")?;
+        return parameters_node(value).map(|_| value);
+    }
+    None
+}
+
+fn parameters_node(node: Node<'_>) -> Option<Node<'_>> {
+    node.child_by_field_name("parameters")
+        .or_else(|| node.child_by_field_name("parameter"))
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// A Python docstring: the string literal opening a function or class body.
+fn python_docstring(node: Node<'_>, source: &str) -> Option<String> {
+    let body = node.child_by_field_name("body")?;
+    let first = body.named_child(0)?;
+    if first.kind() != "expression_statement" {
+        return None;
+    }
+    let literal = first.named_child(0)?;
+    if literal.kind() != "string" {
+        return None;
+    }
+    let text = source[literal.byte_range()].trim_start_matches(|c: char| c.is_ascii_alphabetic());
+    let quote = ["\"\"\"", "'''", "\"", "'"]
+        .into_iter()
+        .find(|quote| text.starts_with(quote) && text.ends_with(quote))?;
+    let inner = text.get(quote.len()..text.len() - quote.len())?;
+    // The first line follows the opening quotes, so only later lines share
+    // the body's indentation.
+    non_empty(clean_doc_lines(
+        inner.lines().map(str::to_string).collect(),
+        1,
+    ))
+}
+
+/// Strips comment markers (`///`, `//!`, `#`, `%`, `/** */`, leading `*`)
+/// from a run of leading comments and dedents what remains.
+fn normalize_comment(comment: &str) -> Option<String> {
+    let mut lines = Vec::new();
+    for raw in comment.lines() {
+        let mut line = raw.trim();
+        for opener in ["/**", "/*!", "/*"] {
+            if let Some(rest) = line.strip_prefix(opener) {
+                line = rest;
+                break;
+            }
+        }
+        if let Some(rest) = line.strip_suffix("*/") {
+            line = rest.trim_end();
+        }
+        for marker in ["///", "//!", "//", "##", "#", "%%", "%", "*"] {
+            if let Some(rest) = line.strip_prefix(marker) {
+                line = rest;
+                break;
+            }
+        }
+        lines.push(line.to_string());
+    }
+    non_empty(clean_doc_lines(lines, 0))
+}
+
+/// Removes the indentation shared by the non-blank lines after `skip` and
+/// trims blank lines from both ends, like Python's `inspect.cleandoc`.
+fn clean_doc_lines(mut lines: Vec<String>, skip: usize) -> String {
+    let indent = lines
+        .iter()
+        .skip(skip)
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.len() - line.trim_start().len())
+        .min()
+        .unwrap_or(0);
+    for (index, line) in lines.iter_mut().enumerate() {
+        *line = if index < skip {
+            line.trim().to_string()
+        } else {
+            line.get(indent..).unwrap_or("").trim_end().to_string()
+        };
+    }
+    while lines.last().is_some_and(|line| line.is_empty()) {
+        lines.pop();
+    }
+    let first = lines.iter().position(|line| !line.is_empty());
+    lines.drain(..first.unwrap_or(lines.len()));
+    lines.join("\n")
+}
+
+fn non_empty(text: String) -> Option<String> {
+    (!text.is_empty()).then_some(text)
+}
+
+/// Normalized visibility: `public`, `private`, `protected`, `internal`, or
+/// the language keyword when it has no equivalent (`fileprivate`, `open`).
+/// `None` when the language has no marker and no naming convention applies.
+fn visibility(
+    node: Node<'_>,
+    declaration: Node<'_>,
+    source: &str,
+    language: &Language,
+) -> Option<String> {
+    if let Some(keyword) = modifier_keyword(declaration, source) {
+        return Some(keyword);
+    }
+
+    let name = declaration
+        .child_by_field_name("name")
+        .map(|name| &source[name.byte_range()]);
+    match language {
+        // Trait items and trait impl items are as visible as the trait.
+        Language::Rust => {
+            let container = node.parent().and_then(|list| list.parent());
+            let via_trait = container.is_some_and(|container| {
+                container.kind() == "trait_item"
+                    || (container.kind() == "impl_item"
+                        && container.child_by_field_name("trait").is_some())
+            });
+            Some(if via_trait { "public" } else { "private" }.to_string())
+        }
+        Language::Go => name.map(|name| {
+            if name.starts_with(char::is_uppercase) {
+                "public".to_string()
+            } else {
+                "private".to_string()
+            }
+        }),
+        Language::Python | Language::Gdscript => name.map(|name| {
+            let dunder = name.starts_with("__") && name.ends_with("__");
+            if name.starts_with('_') && !dunder {
+                "private".to_string()
+            } else {
+                "public".to_string()
+            }
+        }),
+        Language::TypeScript
+        | Language::TypeScriptTsx
+        | Language::JavaScript
+        | Language::JavaScriptJsx => {
+            if name.is_some_and(|name| name.starts_with('#')) {
+                return Some("private".to_string());
+            }
+            let exported = node.kind() == "export_statement"
+                || node
+                    .parent()
+                    .is_some_and(|parent| parent.kind() == "export_statement");
+            if exported {
+                return Some("public".to_string());
+            }
+            let in_class = node
+                .parent()
+                .is_some_and(|parent| parent.kind() == "class_body");
+            Some(if in_class { "public" } else { "private" }.to_string())
+        }
+        _ => None,
+    }
+}
+
+/// Reads an explicit access modifier such as Rust's `pub(crate)`, Java's
+/// `protected` or TypeScript's `private`.
+fn modifier_keyword(node: Node<'_>, source: &str) -> Option<String> {
+    let mut cursor = node.walk();
+    for child in node.children(&mut cursor) {
+        let kind = child.kind();
+        let text = &source[child.byte_range()];
+        if kind == "visibility_modifier" && (text == "pub" || text.starts_with("pub(")) {
+            return Some(if text == "pub" { "public" } else { "internal" }.to_string());
+        }
+        if !matches!(
+            kind,
+            "modifiers" | "modifier" | "visibility_modifier" | "accessibility_modifier"
+        ) {
+            continue;
+        }
+        let keyword = text
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .find(|word| {
+                matches!(
+                    *word,
+                    "public" | "private" | "protected" | "internal" | "fileprivate" | "open"
+                )
+            });
+        if let Some(keyword) = keyword {
+            return Some(keyword.to_string());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_comment_strips_markers() {
+        assert_eq!(
+            normalize_comment("/**\n * Saves a user.\n *\n * @param user the user\n */").as_deref(),
+            Some("Saves a user.\n\n@param user the user")
+        );
+        assert_eq!(
+            normalize_comment("/// Returns the id.\n///   Indented.").as_deref(),
+            Some("Returns the id.\n  Indented.")
+        );
+        assert_eq!(
+            normalize_comment("# Loads config").as_deref(),
+            Some("Loads config")
+        );
+        assert_eq!(normalize_comment("//\n//"), None);
+    }
+}
@@ -0,0 +1,477 @@
+use crate::parser_pool;
+use crate::registry;
+use crate::types::Language;
+use std::path::Path;
+
+/// The language picked for a file and the evidence that decided it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Detection {
+    pub language: Language,
+    pub reason: DetectionReason,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DetectionReason {
+    /// A Vim or Emacs modeline names the language.
+    Modeline,
+    /// A well-known file name such as `Dockerfile` or `Rakefile`.
+    Filename,
+    /// The extension maps to exactly one language.
+    Extension,
+    /// The `#!` interpreter of an extensionless script.
+    Shebang,
+    /// Language-specific directives in an ambiguous file (`@interface`, `namespace`).
+    Content,
+    /// The candidate grammar that parsed an ambiguous file with the fewest errors.
+    ParseErrors,
+    /// Nothing matched; the file is chunked as text.
+    Fallback,
+}
+
+impl DetectionReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DetectionReason::Modeline => "modeline",
+            DetectionReason::Filename => "filename",
+            DetectionReason::Extension => "extension",
+            DetectionReason::Shebang => "shebang",
+            DetectionReason::Content => "content",
+            DetectionReason::ParseErrors => "parse-errors",
+            DetectionReason::Fallback => "fallback",
+        }
+    }
+}
+
+const FILENAMES: &[(&str, Language)] = &[
+    ("Dockerfile", Language::Dockerfile),
+    ("Containerfile", Language::Dockerfile),
+    ("Makefile", Language::Make),
+    ("makefile", Language::Make),
+    ("GNUmakefile", Language::Make),
+    ("Rakefile", Language::Ruby),
+    ("Gemfile", Language::Ruby),
+    ("Guardfile", Language::Ruby),
+    ("Podfile", Language::Ruby),
+    ("Vagrantfile", Language::Ruby),
+    ("Fastfile", Language::Ruby),
+    ("Brewfile", Language::Ruby),
+    ("Capfile", Language::Ruby),
+    ("BUILD", Language::Python),
+    ("BUILD.bazel", Language::Python),
+    ("WORKSPACE", Language::Python),
+    ("SConstruct", Language::Python),
+    ("SConscript", Language::Python),
+    ("PKGBUILD", Language::Bash),
+    (".bashrc", Language::Bash),
+    (".bash_profile", Language::Bash),
+    (".bash_aliases", Language::Bash),
+    (".zshrc", Language::Bash),
+    (".zshenv", Language::Bash),
+    (".zprofile", Language::Bash),
+    (".profile", Language::Bash),
+    ("Pipfile", Language::Toml),
+    ("Cargo.lock", Language::Toml),
+    ("poetry.lock", Language::Toml),
+    ("composer.lock", Language::Json),
+    (".babelrc", Language::Json),
+];
+
+/// Interpreter names (version suffixes stripped) that the language registry
+/// does not already know by name.
+const INTERPRETERS: &[(&str, Language)] = &[
+    ("node", Language::JavaScript),
+    ("nodejs", Language::JavaScript),
+    ("bun", Language::JavaScript),
+    ("deno", Language::TypeScript),
+    ("ts-node", Language::TypeScript),
+    ("tsx", Language::TypeScript),
+    ("pypy", Language::Python),
+    ("ksh", Language::Bash),
+    ("dash", Language::Bash),
+    ("octave", Language::Matlab),
+];
+
+/// Emacs mode names that differ from registry names.
+const MODE_ALIASES: &[(&str, Language)] = &[
+    ("shell-script", Language::Bash),
+    ("js2", Language::JavaScript),
+    ("c++", Language::Cpp),
+];
+
+struct Candidate {
+    language: Language,
+    /// Grammar the candidate is test-parsed with; a close relative when the
+    /// language itself has none (Objective-C is parsed as C).
+    grammar: Language,
+    /// Line prefixes only this candidate's sources use.
+    cues: &'static [&'static str],
+}
+
+/// Extensions shared by several languages. The first candidate is the
+/// default when the content gives no signal.
+const AMBIGUOUS_EXTENSIONS: &[(&str, &[Candidate])] = &[
+    (
+        "h",
+        &[
+            Candidate {
+                language: Language::C,
+                grammar: Language::C,
+                cues: &[],
+            },
+            Candidate {
+                language: Language::Cpp,
+                grammar: Language::Cpp,
+                cues: &[
+                    "namespace ",
+                    "template<",
+                    "template <",
+                    "class ",
+                    "public:",
+                    "private:",
+                    "protected:",
+                    "using ",
+                    "#include <string>",
+                    "#include <vector>",
+                    "#include <memory>",
+                ],
+            },
+        ],
+    ),
+    (
+        "m",
+        &[
+            Candidate {
+                language: Language::Matlab,
+                grammar: Language::Matlab,
+                cues: &["function ", "classdef ", "%"],
+            },
+            Candidate {
+                language: Language::ObjectiveC,
+                grammar: Language::C,
+                cues: &[
+                    "#import ",
+                    "@interface ",
+                    "@implementation ",
+                    "@protocol ",
+                    "@property",
+                    "@end",
+                    "- (",
+                    "+ (",
+                ],
+            },
+        ],
+    ),
+];
+
+/// How much of a file the parse-error heuristic looks at.
+const HEURISTIC_SAMPLE_BYTES: usize = 32 * 1024;
+/// Modelines are honored in this many lines at either end of a file.
+const MODELINE_LINES: usize = 5;
+
+/// Picks the language of `file_path` from, in order: a modeline, a well-known
+/// file name, an unambiguous extension, a shebang, and for extensions shared
+/// by several languages, directive cues and then the parse-error rate of each
+/// candidate grammar.
+pub fn detect_language(file_path: &str, content: &str) -> Detection {
+    let detected = |language, reason| Detection { language, reason };
+
+    if let Some(language) = modeline_language(content) {
+        return detected(language, DetectionReason::Modeline);
+    }
+
+    let path = Path::new(file_path);
+    let file_name = path
+        .file_name()
+        .and_then(|name| name.to_str())
+        .unwrap_or("");
+    if let Some(language) = filename_language(file_name) {
+        return detected(language, DetectionReason::Filename);
+    }
+
+    let extension = path
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .unwrap_or("")
+        .to_lowercase();
+    let ambiguous = AMBIGUOUS_EXTENSIONS
+        .iter()
+        .find(|(candidate, _)| *candidate == extension)
+        .map(|(_, candidates)| *candidates);
+    if ambiguous.is_none() {
+        let language = Language::from_extension(&extension);
+        if language != Language::Text {
+            return detected(language, DetectionReason::Extension);
+        }
+    }
+
+    if let Some(language) = shebang_language(content) {
+        return detected(language, DetectionReason::Shebang);
+    }
+
+    match ambiguous {
+        Some(candidates) => disambiguate(content, candidates),
+        None => detected(Language::Text, DetectionReason::Fallback),
+    }
+}
+
+fn filename_language(file_name: &str) -> Option<Language> {
+    if let Some((_, language)) = FILENAMES.iter().find(|(name, _)| *name == file_name) {
+        return Some(*language);
+    }
+    // `Dockerfile.dev`, `Makefile.linux`
+    let (stem, _) = file_name.split_once('.')?;
+    FILENAMES
+        .iter()
+        .find(|(name, language)| {
+            *name == stem && matches!(language, Language::Dockerfile | Language::Make)
+        })
+        .map(|(_, language)| *language)
+}
+
+fn shebang_language(content: &str) -> Option<Language> {
+    let line = content.lines().next()?.strip_prefix("#!")?;
+    let mut words = line.split_whitespace();
+    let mut program = words.next()?.rsplit('/').next()?;
+    if program == "env" {
+        program = words.find(|word| !word.starts_with('-'))?;
+    }
+    let name = program.trim_end_matches(|c: char| c.is_ascii_digit() || c == '.');
+    INTERPRETERS
+        .iter()
+        .find(|(interpreter, _)| *interpreter == name)
+        .map(|(_, language)| *language)
+        .or_else(|| known_language(name))
+}
+
+/// Reads `vim: ft=python`, `vim: set filetype=cpp:`, `-*- mode: ruby -*-` and
+/// `-*- python -*-` from the first or last few lines.
+fn modeline_language(content: &str) -> Option<Language> {
+    content
+        .lines()
+        .take(MODELINE_LINES)
+        .chain(content.lines().rev().take(MODELINE_LINES))
+        .find_map(|line| emacs_mode(line).or_else(|| vim_filetype(line)))
+        .and_then(known_language)
+}
+
+fn emacs_mode(line: &str) -> Option<&str> {
+    let (_, rest) = line.split_once("-*-")?;
+    let (body, _) = rest.split_once("-*-")?;
+    let body = body.trim();
+    if !body.contains(':') {
+        return Some(body);
+    }
+    body.split(';').find_map(|setting| {
+        let (key, value) = setting.split_once(':')?;
+        key.trim()
+            .eq_ignore_ascii_case("mode")
+            .then(|| value.trim())
+    })
+}
+
+fn vim_filetype(line: &str) -> Option<&str> {
+    let start = ["vim:", "vi:", "ex:"].iter().find_map(|marker| {
+        line.match_indices(marker)
+            .find(|(index, _)| *index == 0 || line[..*index].ends_with(char::is_whitespace))
+            .map(|(index, _)| index + marker.len())
+    })?;
+    line[start..]
+        .split(|c: char| c.is_whitespace() || c == ':')
+        .find_map(|option| {
+            let (key, value) = option.split_once('=')?;
+            matches!(key, "ft" | "filetype" | "syn" | "syntax").then_some(value)
+        })
+}
+
+fn known_language(name: &str) -> Option<Language> {
+    let name = name.to_lowercase();
+    MODE_ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == name)
+        .map(|(_, language)| *language)
+        .or_else(|| registry::language_for_name(&name))
+        .filter(|language| *language != Language::Text)
+}
+
+fn disambiguate(content: &str, candidates: &[Candidate]) -> Detection {
+    let sample = sample(content);
+    let cue_hits = |candidate: &Candidate| {
+        sample
+            .lines()
+            .map(str::trim_start)
+            .filter(|line| candidate.cues.iter().any(|cue| line.starts_with(cue)))
+            .count()
+    };
+    let best_cue = candidates
+        .iter()
+        .map(|candidate| (candidate, cue_hits(candidate)))
+        .filter(|(_, hits)| *hits > 0)
+        .fold(
+            None,
+            |best: Option<(&Candidate, usize)>, current| match best {
+                Some(best) if best.1 >= current.1 => Some(best),
+                _ => Some(current),
+            },
+        );
+    if let Some((candidate, _)) = best_cue {
+        return Detection {
+            language: candidate.language,
+            reason: DetectionReason::Content,
+        };
+    }
+
+    let default = &candidates[0];
+    let mut best = (default, error_rate(sample, &default.grammar));
+    for candidate in &candidates[1..] {
+        let rate = error_rate(sample, &candidate.grammar);
+        if rate < best.1 {
+            best = (candidate, rate);
+        }
+    }
+    Detection {
+        language: best.0.language,
+        reason: if best.0.language == default.language {
+            DetectionReason::Extension
+        } else {
+            DetectionReason::ParseErrors
+        },
+    }
+}
+
+fn sample(content: &str) -> &str {
+    if content.len() <= HEURISTIC_SAMPLE_BYTES {
+        return content;
+    }
+    let mut end = HEURISTIC_SAMPLE_BYTES;
+    while !content.is_char_boundary(end) {
+        end -= 1;
+    }
+    &content[..end]
+}
+
+/// Share of `source` covered by ERROR nodes, with each MISSING node counted
+/// as one byte. Languages without a grammar score as all errors.
+fn error_rate(source: &str, language: &Language) -> f64 {
+    let Some(grammar) = registry::definition(language).grammar.as_ref() else {
+        return 1.0;
+    };
+    let Ok(Some(tree)) = parser_pool::with_parser(grammar, |parser| Ok(parser.parse(source, None)))
+    else {
+        return 1.0;
+    };
+
+    let mut error_bytes = 0;
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        let descend = if node.is_error() {
+            error_bytes += node.byte_range().len();
+            false
+        } else {
+            if node.is_missing() {
+                error_bytes += 1;
+            }
+            node.has_error()
+        };
+        if descend && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+    error_bytes as f64 / source.len().max(1) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detect(path: &str, content: &str) -> (&'static str, &'static str) {
+        let detection = detect_language(path, content);
+        (detection.language.as_str(), detection.reason.as_str())
+    }
+
+    #[test]
+    fn test_detects_filenames_shebangs_and_modelines() {
+        assert_eq!(
+            detect("Dockerfile", "FROM rust:1"),
+            ("dockerfile", "filename")
+        );
+        assert_eq!(
+            detect("docker/Dockerfile.dev", ""),
+            ("dockerfile", "filename")
+        );
+        assert_eq!(
+            detect("Makefile", "all:\n\tcc main.c"),
+            ("make", "filename")
+        );
+        assert_eq!(detect("Rakefile", "task :build"), ("ruby", "filename"));
+        assert_eq!(
+            detect("bin/deploy", "#!/usr/bin/env python3.11\nprint('hi')\n"),
+            ("python", "shebang")
+        );
+        assert_eq!(
+            detect("bin/serve", "#!/usr/bin/env -S node --no-warnings\n"),
+            ("javascript", "shebang")
+        );
+        assert_eq!(
+            detect("scripts/setup", "#!/bin/bash\nset -e\n"),
+            ("bash", "shebang")
+        );
+        assert_eq!(
+            detect("tool.js", "#!/usr/bin/env python\n"),
+            ("javascript", "extension")
+        );
+        assert_eq!(
+            detect("config/hooks", "echo hi\n# vim: set ft=sh:\n"),
+            ("bash", "modeline")
+        );
+        assert_eq!(
+            detect(
+                "legacy.h",
+                "/* -*- mode: c++; indent-tabs-mode: nil -*- */\nint x;\n"
+            ),
+            ("cpp", "modeline")
+        );
+        assert_eq!(detect("notes", "just some words\n"), ("text", "fallback"));
+    }
+
+    #[test]
+    fn test_disambiguates_shared_extensions_from_content() {
+        assert_eq!(
+            detect(
+                "Widget.m",
+                "#import <Foundation/Foundation.h>\n@implementation Widget\n- (void)draw {}\n@end\n"
+            ),
+            ("objc", "content")
+        );
+        assert_eq!(
+            detect("solve.m", "function x = solve(a, b)\n  x = a \\ b;\nend\n"),
+            ("matlab", "content")
+        );
+        assert_eq!(
+            detect(
+                "vector.h",
+                "namespace geo {\nclass Vector {\npublic:\n  double x;\n};\n}\n"
+            ),
+            ("cpp", "content")
+        );
+        assert_eq!(
+            detect(
+                "point.h",
+                "struct point { int x; int y; };\nint distance(struct point a);\n"
+            ),
+            ("c", "extension")
+        );
+        assert_eq!(
+            detect(
+                "box.h",
+                "struct Box {\n  Box() = default;\n  virtual ~Box() {}\n};\n"
+            ),
+            ("cpp", "parse-errors")
+        );
+    }
+}
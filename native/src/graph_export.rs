@@ -0,0 +1,453 @@
+//! Call graph export for external tools: Graphviz DOT, GraphML (Gephi, yEd)
+//! and JSON Graph Format.
+//!
+//! Edges are resolved the way `detect_communities` resolves them, so an
+//! unresolved call only becomes an edge when exactly one symbol on the branch
+//! has its name. Call sites sharing a caller, callee and call type are merged
+//! into one edge whose `count` says how many there were.
+
+use rusqlite::{params, Connection};
+use std::collections::{BTreeMap, HashSet};
+
+use crate::community::{self, build_symbol_maps, resolve_target_symbol};
+use crate::db::{self, DbResult, SymbolRow};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportFormat {
+    Dot,
+    GraphMl,
+    Json,
+}
+
+impl ExportFormat {
+    pub fn from_string(format: &str) -> Option<Self> {
+        match format.to_ascii_lowercase().as_str() {
+            "dot" | "graphviz" => Some(ExportFormat::Dot),
+            "graphml" => Some(ExportFormat::GraphMl),
+            "json" | "jgf" => Some(ExportFormat::Json),
+            _ => None,
+        }
+    }
+}
+
+/// Which part of the branch's graph to export. Restrictions combine: a
+/// symbol is kept only when it passes every one that is set.
+#[derive(Debug, Clone, Default)]
+pub struct ExportFilter {
+    /// Keep symbols whose file path starts with this prefix.
+    pub file_prefix: Option<String>,
+    /// Keep one community, numbered as `detect_communities` numbers the
+    /// whole branch.
+    pub community_id: Option<u32>,
+    /// Keep these symbols and what they reach within `max_depth` hops in
+    /// `direction` (`callees`, `callers` or `both`).
+    pub root_symbol_ids: Option<Vec<String>>,
+    pub direction: String,
+    pub max_depth: u32,
+    /// Keep edges of these call types; `None` keeps all but the reference
+    /// types in `db::REFERENCE_CALL_TYPES`.
+    pub call_types: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+struct ExportEdge {
+    source: String,
+    target: String,
+    call_type: String,
+    count: u32,
+}
+
+/// The branch's call graph, restricted by `filter`, rendered as `format`.
+pub fn export_call_graph(
+    conn: &Connection,
+    branch: &str,
+    format: ExportFormat,
+    filter: &ExportFilter,
+) -> DbResult<String> {
+    let symbols = db::get_symbols_for_branch(conn, branch)?;
+    let (symbol_map, name_map) = build_symbol_maps(&symbols);
+
+    let mut kept: HashSet<&str> = symbols
+        .iter()
+        .filter(|symbol| {
+            filter
+                .file_prefix
+                .as_deref()
+                .is_none_or(|prefix| symbol.file_path.starts_with(prefix))
+        })
+        .map(|symbol| symbol.id.as_str())
+        .collect();
+    if let Some(community_id) = filter.community_id {
+        let members: HashSet<String> = community::detect_communities(conn, branch, None)?
+            .into_iter()
+            .filter(|assignment| assignment.community_id == community_id)
+            .map(|assignment| assignment.symbol_id)
+            .collect();
+        kept.retain(|id| members.contains(*id));
+    }
+    if let Some(roots) = &filter.root_symbol_ids {
+        let neighborhood: HashSet<String> = community::get_transitive_reachability(
+            conn,
+            roots,
+            branch,
+            &filter.direction,
+            filter.max_depth,
+        )?
+        .into_iter()
+        .map(|reached| reached.symbol_id)
+        .chain(roots.iter().cloned())
+        .collect();
+        kept.retain(|id| neighborhood.contains(*id));
+    }
+
+    let mut edges_stmt = conn.prepare(&db::with_branch_targets(
+        r#"
+        SELECT ce.from_symbol_id, ce.target_name, {edge_target}, ce.call_type
+        FROM call_edges ce
+        INNER JOIN branch_symbols bs ON ce.from_symbol_id = bs.symbol_id AND bs.branch = ?
+        {branch_targets}
+        "#,
+    ))?;
+    let edge_rows: Vec<(String, String, Option<String>, String)> = edges_stmt
+        .query_map(params![branch], |row| {
+            Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?))
+        })?
+        .filter_map(|r| r.ok())
+        .collect();
+
+    let mut merged: BTreeMap<(String, String, String), u32> = BTreeMap::new();
+    for (from_id, target_name, to_symbol_id, call_type) in edge_rows {
+        let wanted = match &filter.call_types {
+            Some(call_types) => call_types.contains(&call_type),
+            None => !db::is_reference_call_type(&call_type),
+        };
+        if !wanted {
+            continue;
+        }
+        let Some(to_id) =
+            resolve_target_symbol(&to_symbol_id, &target_name, &symbol_map, &name_map)
+        else {
+            continue;
+        };
+        if kept.contains(from_id.as_str()) && kept.contains(to_id.as_str()) {
+            *merged.entry((from_id, to_id, call_type)).or_insert(0) += 1;
+        }
+    }
+    let edges: Vec<ExportEdge> = merged
+        .into_iter()
+        .map(|((source, target, call_type), count)| ExportEdge {
+            source,
+            target,
+            call_type,
+            count,
+        })
+        .collect();
+
+    let mut nodes: Vec<&SymbolRow> = symbols
+        .iter()
+        .filter(|symbol| kept.contains(symbol.id.as_str()))
+        .collect();
+    nodes.sort_by(|a, b| {
+        (&a.file_path, a.start_line, &a.id).cmp(&(&b.file_path, b.start_line, &b.id))
+    });
+
+    Ok(match format {
+        ExportFormat::Dot => to_dot(&nodes, &edges),
+        ExportFormat::GraphMl => to_graphml(&nodes, &edges),
+        ExportFormat::Json => to_json_graph(branch, &nodes, &edges),
+    })
+}
+
+fn dot_quote(value: &str) -> String {
+    format!(
+        "\"{}\"",
+        value
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace('\n', "\\n")
+    )
+}
+
+/// One cluster per file, so Graphviz draws files as boxes around their
+/// symbols.
+fn to_dot(nodes: &[&SymbolRow], edges: &[ExportEdge]) -> String {
+    let mut out = String::from("digraph call_graph {\n  rankdir=LR;\n  node [shape=box];\n");
+    let mut cluster = 0;
+    let mut index = 0;
+    while index < nodes.len() {
+        let file_path = &nodes[index].file_path;
+        out.push_str(&format!(
+            "  subgraph cluster_{} {{\n    label={};\n",
+            cluster,
+            dot_quote(file_path)
+        ));
+        while index < nodes.len() && &nodes[index].file_path == file_path {
+            let symbol = nodes[index];
+            out.push_str(&format!(
+                "    {} [label={}, kind={}, file={}, line={}];\n",
+                dot_quote(&symbol.id),
+                dot_quote(&symbol.name),
+                dot_quote(&symbol.kind),
+                dot_quote(&symbol.file_path),
+                symbol.start_line
+            ));
+            index += 1;
+        }
+        out.push_str("  }\n");
+        cluster += 1;
+    }
+    for edge in edges {
+        out.push_str(&format!(
+            "  {} -> {} [label={}, weight={}];\n",
+            dot_quote(&edge.source),
+            dot_quote(&edge.target),
+            dot_quote(&edge.call_type),
+            edge.count
+        ));
+    }
+    out.push_str("}\n");
+    out
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&apos;")
+}
+
+const GRAPHML_KEYS: &[(&str, &str, &str)] = &[
+    ("name", "node", "string"),
+    ("kind", "node", "string"),
+    ("file", "node", "string"),
+    ("line", "node", "int"),
+    ("language", "node", "string"),
+    ("call_type", "edge", "string"),
+    ("count", "edge", "int"),
+];
+
+fn to_graphml(nodes: &[&SymbolRow], edges: &[ExportEdge]) -> String {
+    let mut out = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<graphml xmlns=\"http://graphml.graphdrawing.org/xmlns\">\n",
+    );
+    for (name, domain, kind) in GRAPHML_KEYS {
+        out.push_str(&format!(
+            "  <key id=\"{name}\" for=\"{domain}\" attr.name=\"{name}\" attr.type=\"{kind}\"/>\n"
+        ));
+    }
+    out.push_str("  <graph id=\"call_graph\" edgedefault=\"directed\">\n");
+    for symbol in nodes {
+        out.push_str(&format!(
+            "    <node id=\"{}\">\n      <data key=\"name\">{}</data>\n      <data key=\"kind\">{}</data>\n      <data key=\"file\">{}</data>\n      <data key=\"line\">{}</data>\n      <data key=\"language\">{}</data>\n    </node>\n",
+            xml_escape(&symbol.id),
+            xml_escape(&symbol.name),
+            xml_escape(&symbol.kind),
+            xml_escape(&symbol.file_path),
+            symbol.start_line,
+            xml_escape(&symbol.language)
+        ));
+    }
+    for (index, edge) in edges.iter().enumerate() {
+        out.push_str(&format!(
+            "    <edge id=\"e{}\" source=\"{}\" target=\"{}\">\n      <data key=\"call_type\">{}</data>\n      <data key=\"count\">{}</data>\n    </edge>\n",
+            index,
+            xml_escape(&edge.source),
+            xml_escape(&edge.target),
+            xml_escape(&edge.call_type),
+            edge.count
+        ));
+    }
+    out.push_str("  </graph>\n</graphml>\n");
+    out
+}
+
+/// JSON Graph Format v2: nodes keyed by symbol id, edges as a list.
+fn to_json_graph(branch: &str, nodes: &[&SymbolRow], edges: &[ExportEdge]) -> String {
+    let nodes: serde_json::Map<String, serde_json::Value> = nodes
+        .iter()
+        .map(|symbol| {
+            (
+                symbol.id.clone(),
+                serde_json::json!({
+                    "label": symbol.name,
+                    "metadata": {
+                        "kind": symbol.kind,
+                        "filePath": symbol.file_path,
+                        "line": symbol.start_line,
+                        "language": symbol.language,
+                        "qualifiedName": symbol.qualified_name,
+                    },
+                }),
+            )
+        })
+        .collect();
+    let edges: Vec<serde_json::Value> = edges
+        .iter()
+        .map(|edge| {
+            serde_json::json!({
+                "source": edge.source,
+                "target": edge.target,
+                "relation": edge.call_type,
+                "metadata": { "count": edge.count },
+            })
+        })
+        .collect();
+    serde_json::json!({
+        "graph": {
+            "id": branch,
+            "type": "call-graph",
+            "directed": true,
+            "nodes": nodes,
+            "edges": edges,
+        }
+    })
+    .to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::CallEdgeRow;
+    use tempfile::TempDir;
+
+    fn make_symbol(id: &str, name: &str, file_path: &str) -> SymbolRow {
+        SymbolRow {
+            id: id.to_string(),
+            file_path: file_path.to_string(),
+            name: name.to_string(),
+            kind: "function".to_string(),
+            start_line: 1,
+            start_col: 0,
+            end_line: 10,
+            end_col: 1,
+            language: "typescript".to_string(),
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
+        }
+    }
+
+    fn make_edge(id: &str, from: &str, target_name: &str, to: Option<&str>) -> CallEdgeRow {
+        CallEdgeRow {
+            id: id.to_string(),
+            from_symbol_id: from.to_string(),
+            target_name: target_name.to_string(),
+            to_symbol_id: to.map(|s| s.to_string()),
+            call_type: "Call".to_string(),
+            confidence: "Direct".to_string(),
+            line: 1,
+            col: 0,
+            is_resolved: to.is_some(),
+            qualifier: None,
+            resolution_strategy: None,
+            resolution_confidence: None,
+        }
+    }
+
+    /// `handle -> validate -> "check<>"`, `handle -> validate` twice, and a
+    /// `report` in `lib/` that calls nothing.
+    fn setup_graph() -> (TempDir, Connection) {
+        let temp_dir = TempDir::new().unwrap();
+        let mut conn = db::init_db(&temp_dir.path().join("test.db")).unwrap();
+        let symbols = vec![
+            make_symbol("s_handle", "handle", "src/api.ts"),
+            make_symbol("s_validate", "validate", "src/api.ts"),
+            make_symbol("s_check", "check<\"T\">", "src/rules.ts"),
+            make_symbol("s_report", "report", "lib/report.ts"),
+        ];
+        db::upsert_symbols_batch(&mut conn, &symbols).unwrap();
+        let ids: Vec<String> = symbols.iter().map(|s| s.id.clone()).collect();
+        db::add_symbols_to_branch(&conn, "main", &ids).unwrap();
+        db::upsert_call_edges_batch(
+            &mut conn,
+            &[
+                make_edge("e1", "s_handle", "validate", Some("s_validate")),
+                make_edge("e2", "s_handle", "validate", None),
+                make_edge("e3", "s_validate", "check<\"T\">", Some("s_check")),
+                make_edge("e4", "s_handle", "external", None),
+            ],
+        )
+        .unwrap();
+        (temp_dir, conn)
+    }
+
+    #[test]
+    fn test_export_json_graph_merges_call_sites() {
+        let (_temp, conn) = setup_graph();
+        let json =
+            export_call_graph(&conn, "main", ExportFormat::Json, &ExportFilter::default()).unwrap();
+        let graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(graph["graph"]["directed"], true);
+        assert_eq!(graph["graph"]["nodes"].as_object().unwrap().len(), 4);
+        assert_eq!(graph["graph"]["nodes"]["s_handle"]["label"], "handle");
+        let edges = graph["graph"]["edges"].as_array().unwrap();
+        assert_eq!(edges.len(), 2);
+        assert_eq!(edges[0]["source"], "s_handle");
+        assert_eq!(edges[0]["target"], "s_validate");
+        assert_eq!(edges[0]["metadata"]["count"], 2);
+    }
+
+    #[test]
+    fn test_export_dot_and_graphml_escape_names() {
+        let (_temp, conn) = setup_graph();
+        let filter = ExportFilter::default();
+
+        let dot = export_call_graph(&conn, "main", ExportFormat::Dot, &filter).unwrap();
+        assert!(dot.starts_with("digraph call_graph {"));
+        assert!(dot.contains("label=\"src/api.ts\""));
+        assert!(dot.contains("\"s_check\" [label=\"check<\\\"T\\\">\""));
+        assert!(dot.contains("\"s_handle\" -> \"s_validate\" [label=\"Call\", weight=2];"));
+
+        let graphml = export_call_graph(&conn, "main", ExportFormat::GraphMl, &filter).unwrap();
+        assert!(graphml.contains("<data key=\"name\">check&lt;&quot;T&quot;&gt;</data>"));
+        assert!(graphml.contains("<edge id=\"e0\" source=\"s_handle\" target=\"s_validate\">"));
+        assert!(graphml.trim_end().ends_with("</graphml>"));
+    }
+
+    #[test]
+    fn test_export_filters_by_prefix_community_and_neighborhood() {
+        let (_temp, conn) = setup_graph();
+        let node_ids = |filter: &ExportFilter| -> Vec<String> {
+            let json = export_call_graph(&conn, "main", ExportFormat::Json, filter).unwrap();
+            let graph: serde_json::Value = serde_json::from_str(&json).unwrap();
+            graph["graph"]["nodes"]
+                .as_object()
+                .unwrap()
+                .keys()
+                .cloned()
+                .collect()
+        };
+
+        let by_prefix = ExportFilter {
+            file_prefix: Some("src/".to_string()),
+            ..ExportFilter::default()
+        };
+        assert_eq!(node_ids(&by_prefix), ["s_check", "s_handle", "s_validate"]);
+
+        let report_community = community::detect_communities(&conn, "main", None)
+            .unwrap()
+            .into_iter()
+            .find(|assignment| assignment.symbol_id == "s_report")
+            .unwrap()
+            .community_id;
+        let by_community = ExportFilter {
+            community_id: Some(report_community),
+            ..ExportFilter::default()
+        };
+        assert_eq!(node_ids(&by_community), ["s_report"]);
+
+        let neighborhood = ExportFilter {
+            root_symbol_ids: Some(vec!["s_validate".to_string()]),
+            direction: "callers".to_string(),
+            max_depth: 1,
+            ..ExportFilter::default()
+        };
+        assert_eq!(node_ids(&neighborhood), ["s_handle", "s_validate"]);
+    }
+}
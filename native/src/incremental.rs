@@ -0,0 +1,716 @@
+use crate::chunker::ChunkingProfile;
+use crate::detection::detect_language;
+use crate::parser::{
+    chunk_by_lines, extract_chunks, extract_symbols, grammar_for, parse_within_budget,
+    point_at_byte, BudgetedParse,
+};
+use crate::types::Language;
+use crate::{CodeChunk, ParsedSymbol};
+use anyhow::Result;
+use std::collections::HashMap;
+use std::ops::Range;
+use tree_sitter::{InputEdit, Tree};
+
+/// A replacement of `old_text[start_byte..old_end_byte]` with `new_text`.
+/// Edits are applied in order, each in the coordinates produced by the
+/// previous one, like LSP `didChange` content changes.
+#[derive(Debug, Clone)]
+pub struct TextEditSpec {
+    pub start_byte: usize,
+    pub old_end_byte: usize,
+    pub new_text: String,
+}
+
+/// How the chunks or symbols of a file differ from its previous parse.
+/// Applying it means deleting `removed`, moving `shifted` entries to their
+/// new lines and storing `changed` ones.
+pub struct Delta<T> {
+    /// New entries and entries whose text changed.
+    pub changed: Vec<T>,
+    /// Unchanged entries at their new lines after an edit above them.
+    pub shifted: Vec<T>,
+    /// Previous entries that were edited or deleted, at their previous lines.
+    pub removed: Vec<T>,
+}
+
+pub struct IncrementalParse {
+    pub hash: String,
+    pub incremental: bool,
+    pub chunks: Delta<CodeChunk>,
+    pub symbols: Delta<ParsedSymbol>,
+    /// 1-based inclusive line spans of the new content that changed.
+    pub changed_lines: Vec<(u32, u32)>,
+}
+
+struct SessionEntry {
+    language: Language,
+    content: String,
+    tree: Tree,
+    chunks: Vec<CodeChunk>,
+    symbols: Vec<ParsedSymbol>,
+}
+
+/// Per-path cache of the last parse tree and its chunks and symbols, used to
+/// reparse edited files incrementally and report only the chunks and
+/// symbols that moved, changed or went away.
+#[derive(Default)]
+pub struct ParseSessionInner {
+    entries: HashMap<String, SessionEntry>,
+    profile: ChunkingProfile,
+}
+
+impl ParseSessionInner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_profile(profile: ChunkingProfile) -> Self {
+        Self {
+            entries: HashMap::new(),
+            profile,
+        }
+    }
+
+    pub fn update(
+        &mut self,
+        file_path: &str,
+        content: &str,
+        edits: Option<&[TextEditSpec]>,
+    ) -> Result<IncrementalParse> {
+        let language = detect_language(file_path, content).language;
+        let config = self.profile.for_language(&language);
+        let hash = crate::hasher::xxhash_content(content);
+
+        // Not incremental: the result replaces whatever was stored for the
+        // file, so everything previously reported is removed.
+        let line_chunked = |replaced: Option<SessionEntry>| {
+            let (chunks, symbols) = reported(replaced);
+            IncrementalParse {
+                hash: hash.clone(),
+                incremental: false,
+                chunks: Delta {
+                    removed: chunks,
+                    ..Delta::replacing(chunk_by_lines(content, &language, config))
+                },
+                symbols: Delta {
+                    removed: symbols,
+                    ..Delta::replacing(Vec::new())
+                },
+                changed_lines: full_span(content),
+            }
+        };
+        let stored = self.entries.remove(file_path);
+        let Some(ts_language) = grammar_for(&language) else {
+            return Ok(line_chunked(stored));
+        };
+        let (previous, replaced) = match stored {
+            Some(entry) if entry.language == language => (Some(entry), None),
+            other => (None, other),
+        };
+
+        let (tree, dirty, previous) = match previous {
+            Some(entry) if entry.content == content => {
+                self.entries.insert(file_path.to_string(), entry);
+                return Ok(IncrementalParse {
+                    hash,
+                    incremental: true,
+                    chunks: Delta::replacing(Vec::new()),
+                    symbols: Delta::replacing(Vec::new()),
+                    changed_lines: Vec::new(),
+                });
+            }
+            Some(mut entry) => {
+                let input_edits = edits
+                    .and_then(|edits| input_edits_from_specs(&entry.content, content, edits))
+                    .unwrap_or_else(|| vec![diff_edit(&entry.content, content)]);
+
+                let mut dirty = Vec::new();
+                for edit in &input_edits {
+                    entry.tree.edit(edit);
+                    track_dirty_range(&mut dirty, edit);
+                }
+
+                let tree = match parse_within_budget(
+                    &ts_language,
+                    file_path,
+                    content,
+                    Some(&entry.tree),
+                    &self.profile.budget,
+                )? {
+                    BudgetedParse::Tree(tree) => tree,
+                    BudgetedParse::OverBudget(_) => return Ok(line_chunked(Some(entry))),
+                };
+                dirty.extend(
+                    entry
+                        .tree
+                        .changed_ranges(&tree)
+                        .map(|range| range.start_byte..range.end_byte),
+                );
+                (tree, dirty, Some(entry))
+            }
+            None => {
+                let tree = match parse_within_budget(
+                    &ts_language,
+                    file_path,
+                    content,
+                    None,
+                    &self.profile.budget,
+                )? {
+                    BudgetedParse::Tree(tree) => tree,
+                    BudgetedParse::OverBudget(_) => return Ok(line_chunked(replaced)),
+                };
+                (tree, std::iter::once(0..content.len()).collect(), None)
+            }
+        };
+
+        let chunks = extract_chunks(&tree, content, &language, config)?;
+        let symbols = extract_symbols(&tree, content, &language);
+        let incremental = previous.is_some();
+        let (chunk_delta, symbol_delta) = match &previous {
+            Some(entry) => (
+                delta(
+                    &entry.chunks,
+                    &chunks,
+                    entry.chunks.iter().map(|chunk| chunk.content.as_str()),
+                    chunks.iter().map(|chunk| chunk.content.as_str()),
+                ),
+                delta(
+                    &entry.symbols,
+                    &symbols,
+                    symbol_texts(&entry.content, &entry.symbols),
+                    symbol_texts(content, &symbols),
+                ),
+            ),
+            None => {
+                let (removed_chunks, removed_symbols) = reported(replaced);
+                (
+                    Delta {
+                        removed: removed_chunks,
+                        ..Delta::replacing(chunks.clone())
+                    },
+                    Delta {
+                        removed: removed_symbols,
+                        ..Delta::replacing(symbols.clone())
+                    },
+                )
+            }
+        };
+
+        self.entries.insert(
+            file_path.to_string(),
+            SessionEntry {
+                language,
+                content: content.to_string(),
+                tree,
+                chunks,
+                symbols,
+            },
+        );
+
+        Ok(IncrementalParse {
+            hash,
+            incremental,
+            chunks: chunk_delta,
+            symbols: symbol_delta,
+            changed_lines: line_spans(content, &dirty),
+        })
+    }
+    pub fn remove(&mut self, file_path: &str) -> bool {
+        self.entries.remove(file_path).is_some()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// The chunks and symbols a replaced entry last reported, if any.
+fn reported(entry: Option<SessionEntry>) -> (Vec<CodeChunk>, Vec<ParsedSymbol>) {
+    entry
+        .map(|entry| (entry.chunks, entry.symbols))
+        .unwrap_or_default()
+}
+
+impl<T> Delta<T> {
+    fn replacing(entries: Vec<T>) -> Self {
+        Self {
+            changed: entries,
+            shifted: Vec::new(),
+            removed: Vec::new(),
+        }
+    }
+}
+
+/// Lines of a chunk or symbol, so entries can be compared wherever they sit.
+trait Located {
+    fn lines(&self) -> (u32, u32);
+    fn with_lines(&self, start_line: u32, end_line: u32) -> Self;
+}
+
+impl Located for CodeChunk {
+    fn lines(&self) -> (u32, u32) {
+        (self.start_line, self.end_line)
+    }
+
+    fn with_lines(&self, start_line: u32, end_line: u32) -> Self {
+        Self {
+            start_line,
+            end_line,
+            ..self.clone()
+        }
+    }
+}
+
+impl Located for ParsedSymbol {
+    fn lines(&self) -> (u32, u32) {
+        (self.start_line, self.end_line)
+    }
+
+    fn with_lines(&self, start_line: u32, end_line: u32) -> Self {
+        Self {
+            start_line,
+            end_line,
+            ..self.clone()
+        }
+    }
+}
+
+/// Pairs each current entry with the first unpaired previous entry that has
+/// the same text and is otherwise equal once both start on the same line.
+/// Unpaired current entries changed, pairs on different lines shifted, and
+/// unpaired previous entries were removed.
+fn delta<'a, T: Clone + PartialEq + Located>(
+    previous: &[T],
+    current: &[T],
+    previous_texts: impl IntoIterator<Item = &'a str>,
+    current_texts: impl IntoIterator<Item = &'a str>,
+) -> Delta<T> {
+    let mut by_text: HashMap<&str, Vec<usize>> = HashMap::new();
+    for (index, text) in previous_texts.into_iter().enumerate() {
+        by_text.entry(text).or_default().push(index);
+    }
+    let at_line_one = |entry: &T| {
+        let (start_line, end_line) = entry.lines();
+        entry.with_lines(1, end_line - start_line + 1)
+    };
+
+    let mut paired = vec![false; previous.len()];
+    let mut result = Delta::replacing(Vec::new());
+    for (entry, text) in current.iter().zip(current_texts) {
+        let candidate = at_line_one(entry);
+        let pair = by_text.get(text).and_then(|indexes| {
+            indexes
+                .iter()
+                .copied()
+                .find(|&index| !paired[index] && at_line_one(&previous[index]) == candidate)
+        });
+        match pair {
+            Some(index) => {
+                paired[index] = true;
+                if previous[index].lines() != entry.lines() {
+                    result.shifted.push(entry.clone());
+                }
+            }
+            None => result.changed.push(entry.clone()),
+        }
+    }
+    result.removed = previous
+        .iter()
+        .zip(paired)
+        .filter(|(_, paired)| !paired)
+        .map(|(entry, _)| entry.clone())
+        .collect();
+    result
+}
+
+/// Source text of each symbol, located by its 1-based lines and byte columns.
+fn symbol_texts<'a>(content: &'a str, symbols: &[ParsedSymbol]) -> Vec<&'a str> {
+    let line_starts: Vec<usize> = std::iter::once(0)
+        .chain(content.match_indices('\n').map(|(index, _)| index + 1))
+        .collect();
+    let byte_at = |line: u32, col: u32| {
+        let line_start = line_starts
+            .get(line.saturating_sub(1) as usize)
+            .copied()
+            .unwrap_or(content.len());
+        (line_start + col as usize).min(content.len())
+    };
+    symbols
+        .iter()
+        .map(|symbol| {
+            let start = byte_at(symbol.start_line, symbol.start_col);
+            let end = byte_at(symbol.end_line, symbol.end_col).max(start);
+            content.get(start..end).unwrap_or_default()
+        })
+        .collect()
+}
+
+/// Replays caller-supplied edits over the cached text. Returns `None` when an
+/// edit is out of range or the result does not reproduce `new_content`, in
+/// which case the caller falls back to diffing.
+fn input_edits_from_specs(
+    old_content: &str,
+    new_content: &str,
+    specs: &[TextEditSpec],
+) -> Option<Vec<InputEdit>> {
+    let mut text = old_content.to_string();
+    let mut edits = Vec::with_capacity(specs.len());
+
+    for spec in specs {
+        if spec.start_byte > spec.old_end_byte
+            || spec.old_end_byte > text.len()
+            || !text.is_char_boundary(spec.start_byte)
+            || !text.is_char_boundary(spec.old_end_byte)
+        {
+            return None;
+        }
+
+        let start_position = point_at_byte(&text, spec.start_byte);
+        let old_end_position = point_at_byte(&text, spec.old_end_byte);
+        text.replace_range(spec.start_byte..spec.old_end_byte, &spec.new_text);
+        let new_end_byte = spec.start_byte + spec.new_text.len();
+
+        edits.push(InputEdit {
+            start_byte: spec.start_byte,
+            old_end_byte: spec.old_end_byte,
+            new_end_byte,
+            start_position,
+            old_end_position,
+            new_end_position: point_at_byte(&text, new_end_byte),
+        });
+    }
+
+    (text == new_content).then_some(edits)
+}
+
+/// Single edit covering everything between the common prefix and suffix.
+fn diff_edit(old_content: &str, new_content: &str) -> InputEdit {
+    let old_bytes = old_content.as_bytes();
+    let new_bytes = new_content.as_bytes();
+
+    let mut prefix = old_bytes
+        .iter()
+        .zip(new_bytes)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_content.is_char_boundary(prefix) || !new_content.is_char_boundary(prefix) {
+        prefix -= 1;
+    }
+
+    let max_suffix = old_bytes.len().min(new_bytes.len()) - prefix;
+    let mut suffix = old_bytes
+        .iter()
+        .rev()
+        .zip(new_bytes.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+    while !old_content.is_char_boundary(old_bytes.len() - suffix)
+        || !new_content.is_char_boundary(new_bytes.len() - suffix)
+    {
+        suffix -= 1;
+    }
+
+    let old_end_byte = old_bytes.len() - suffix;
+    let new_end_byte = new_bytes.len() - suffix;
+    InputEdit {
+        start_byte: prefix,
+        old_end_byte,
+        new_end_byte,
+        start_position: point_at_byte(old_content, prefix),
+        old_end_position: point_at_byte(old_content, old_end_byte),
+        new_end_position: point_at_byte(new_content, new_end_byte),
+    }
+}
+
+/// Shifts previously dirtied ranges through `edit` and records the edited span,
+/// keeping every range in the coordinates of the latest text.
+fn track_dirty_range(ranges: &mut Vec<Range<usize>>, edit: &InputEdit) {
+    let shift = |byte: usize| byte - edit.old_end_byte + edit.new_end_byte;
+    let mut merged = edit.start_byte..edit.new_end_byte;
+
+    ranges.retain_mut(|range| {
+        if range.start >= edit.old_end_byte {
+            *range = shift(range.start)..shift(range.end);
+            true
+        } else if range.end < edit.start_byte {
+            true
+        } else {
+            merged.start = merged.start.min(range.start);
+            if range.end > edit.old_end_byte {
+                merged.end = merged.end.max(shift(range.end));
+            }
+            false
+        }
+    });
+    ranges.push(merged);
+}
+
+fn line_spans(content: &str, ranges: &[Range<usize>]) -> Vec<(u32, u32)> {
+    let mut spans: Vec<(u32, u32)> = ranges
+        .iter()
+        .map(|range| {
+            let start = range.start.min(content.len());
+            let end = range.end.min(content.len()).max(start);
+            (
+                point_at_byte(content, start).row as u32 + 1,
+                point_at_byte(content, end).row as u32 + 1,
+            )
+        })
+        .collect();
+    spans.sort_unstable();
+    spans.dedup();
+    spans
+}
+
+fn full_span(content: &str) -> Vec<(u32, u32)> {
+    vec![(1, point_at_byte(content, content.len()).row as u32 + 1)]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tree_sitter::Point;
+
+    const ORIGINAL: &str = r#"
+export function first(value: number): number {
+    const doubled = value * 2;
+    return doubled + 1;
+}
+
+export function second(value: number): number {
+    const tripled = value * 3;
+    return tripled + 1;
+}
+"#;
+
+    #[test]
+    fn test_first_parse_reports_everything() {
+        let mut session = ParseSessionInner::new();
+        let result = session.update("math.ts", ORIGINAL, None).unwrap();
+
+        assert!(!result.incremental);
+        assert_eq!(session.len(), 1);
+        for expected in ["first", "second"] {
+            assert!(result
+                .symbols
+                .changed
+                .iter()
+                .any(|symbol| symbol.name == expected));
+        }
+    }
+
+    #[test]
+    fn test_diff_reparse_reports_only_changed_function() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let updated = ORIGINAL.replace("value * 3", "value * 4");
+        let result = session.update("math.ts", &updated, None).unwrap();
+
+        assert!(result.incremental);
+        let names: Vec<&str> = result
+            .symbols
+            .changed
+            .iter()
+            .map(|symbol| symbol.name.as_str())
+            .collect();
+        assert!(names.contains(&"second"), "changed symbols: {names:?}");
+        assert!(!names.contains(&"first"), "changed symbols: {names:?}");
+        assert!(result
+            .chunks
+            .changed
+            .iter()
+            .all(|chunk| !chunk.content.contains("function first")));
+    }
+
+    #[test]
+    fn test_explicit_edits_match_diff_result() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let start = ORIGINAL.find("value * 2").unwrap();
+        let edits = [TextEditSpec {
+            start_byte: start,
+            old_end_byte: start + "value * 2".len(),
+            new_text: "value << 1".to_string(),
+        }];
+        let updated = ORIGINAL.replace("value * 2", "value << 1");
+        let result = session.update("math.ts", &updated, Some(&edits)).unwrap();
+
+        assert!(result.incremental);
+        assert!(result
+            .symbols
+            .changed
+            .iter()
+            .any(|symbol| symbol.name == "first"));
+        assert!(!result
+            .symbols
+            .changed
+            .iter()
+            .any(|symbol| symbol.name == "second"));
+    }
+
+    fn names(symbols: &[ParsedSymbol]) -> Vec<&str> {
+        symbols.iter().map(|symbol| symbol.name.as_str()).collect()
+    }
+
+    #[test]
+    fn test_insertion_above_untouched_function_shifts_it() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let inserted = "export function middle(): number {\n    return 0;\n}\n\n";
+        let at = ORIGINAL.find("export function second").unwrap();
+        let updated = format!("{}{}{}", &ORIGINAL[..at], inserted, &ORIGINAL[at..]);
+        let result = session.update("math.ts", &updated, None).unwrap();
+
+        assert_eq!(names(&result.symbols.changed), vec!["middle"]);
+        assert_eq!(names(&result.symbols.shifted), vec!["second"]);
+        assert!(result.symbols.removed.is_empty());
+        assert_eq!(result.symbols.shifted[0].start_line, 11);
+        let shifted: Vec<&CodeChunk> = result
+            .chunks
+            .shifted
+            .iter()
+            .filter(|chunk| chunk.content.contains("function second"))
+            .collect();
+        assert_eq!(shifted.len(), 1);
+        assert_eq!((shifted[0].start_line, shifted[0].end_line), (11, 14));
+        assert!(result
+            .chunks
+            .changed
+            .iter()
+            .chain(&result.chunks.shifted)
+            .all(|chunk| !chunk.content.contains("function first")));
+        assert!(result.chunks.removed.is_empty());
+    }
+
+    #[test]
+    fn test_deleted_function_is_reported_removed() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let start = ORIGINAL.find("export function first").unwrap();
+        let end = ORIGINAL.find("export function second").unwrap();
+        let updated = format!("{}{}", &ORIGINAL[..start], &ORIGINAL[end..]);
+        let result = session.update("math.ts", &updated, None).unwrap();
+
+        assert!(result.symbols.changed.is_empty());
+        assert_eq!(names(&result.symbols.removed), vec!["first"]);
+        assert_eq!(result.symbols.removed[0].start_line, 2);
+        assert_eq!(names(&result.symbols.shifted), vec!["second"]);
+        assert_eq!(result.symbols.shifted[0].start_line, 2);
+        assert!(result
+            .chunks
+            .removed
+            .iter()
+            .any(|chunk| chunk.content.contains("function first")));
+        assert!(result.chunks.changed.is_empty());
+    }
+
+    #[test]
+    fn test_mismatched_edits_fall_back_to_diff() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let edits = [TextEditSpec {
+            start_byte: 0,
+            old_end_byte: 0,
+            new_text: "// stale edit\n".to_string(),
+        }];
+        let updated = ORIGINAL.replace("value * 3", "value * 5");
+        let result = session.update("math.ts", &updated, Some(&edits)).unwrap();
+
+        assert!(result
+            .symbols
+            .changed
+            .iter()
+            .any(|symbol| symbol.name == "second"));
+        assert!(!result
+            .symbols
+            .changed
+            .iter()
+            .any(|symbol| symbol.name == "first"));
+    }
+
+    #[test]
+    fn test_unchanged_content_reports_nothing() {
+        let mut session = ParseSessionInner::new();
+        session.update("math.ts", ORIGINAL, None).unwrap();
+        let result = session.update("math.ts", ORIGINAL, None).unwrap();
+
+        assert!(result.incremental);
+        assert!(result.chunks.changed.is_empty());
+        assert!(result.symbols.changed.is_empty());
+        assert!(result.symbols.shifted.is_empty());
+        assert!(result.symbols.removed.is_empty());
+        assert!(result.changed_lines.is_empty());
+    }
+
+    #[test]
+    fn test_over_budget_update_removes_previous_parse() {
+        let mut session = ParseSessionInner::with_profile(ChunkingProfile {
+            budget: crate::chunker::ParseBudget {
+                max_bytes: Some(ORIGINAL.len() + 100),
+                ..Default::default()
+            },
+            ..ChunkingProfile::default()
+        });
+        let first = session.update("math.ts", ORIGINAL, None).unwrap();
+
+        let grown = format!("{ORIGINAL}{}", "// padding\n".repeat(20));
+        let result = session.update("math.ts", &grown, None).unwrap();
+
+        assert!(!result.incremental);
+        assert_eq!(session.len(), 0);
+        assert!(!result.chunks.changed.is_empty());
+        assert!(result.symbols.changed.is_empty());
+        assert_eq!(result.chunks.removed.len(), first.chunks.changed.len());
+        assert_eq!(
+            names(&result.symbols.removed),
+            names(&first.symbols.changed)
+        );
+    }
+
+    #[test]
+    fn test_remove_and_clear_drop_cached_trees() {
+        let mut session = ParseSessionInner::new();
+        session.update("a.ts", ORIGINAL, None).unwrap();
+        session.update("b.ts", ORIGINAL, None).unwrap();
+
+        assert!(session.remove("a.ts"));
+        assert!(!session.remove("a.ts"));
+        session.clear();
+        assert_eq!(session.len(), 0);
+    }
+
+    #[test]
+    fn test_diff_edit_respects_utf8_boundaries() {
+        let edit = diff_edit("let s = \"é\";", "let s = \"è\";");
+        assert!(edit.start_byte <= 9);
+        assert_eq!(edit.old_end_byte - edit.start_byte, 2);
+        assert_eq!(edit.new_end_byte - edit.start_byte, 2);
+    }
+
+    #[test]
+    fn test_track_dirty_range_shifts_earlier_ranges() {
+        let mut ranges: Vec<Range<usize>> = std::iter::once(10..20).collect();
+        track_dirty_range(
+            &mut ranges,
+            &InputEdit {
+                start_byte: 0,
+                old_end_byte: 0,
+                new_end_byte: 5,
+                start_position: Point::new(0, 0),
+                old_end_position: Point::new(0, 0),
+                new_end_position: Point::new(0, 5),
+            },
+        );
+        assert_eq!(ranges, vec![15..25, 0..5]);
+    }
+}
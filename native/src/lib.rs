@@ -5,15 +5,37 @@ mod call_extractor;
 mod chunker;
 mod community;
 mod db;
+mod declaration;
+mod detection;
+mod graph_export;
 mod hasher;
+mod incremental;
 mod inverted_index;
+mod markup;
+#[cfg(feature = "parser-metrics")]
+mod metrics;
 mod parser;
+mod parser_pool;
+mod query_cache;
+mod receiver;
+mod reference_extractor;
+mod registry;
 mod store;
+mod tokenizer;
 mod types;
+mod walker;
 
 use napi::bindgen_prelude::*;
+use napi::threadsafe_function::{ErrorStrategy, ThreadsafeFunction, ThreadsafeFunctionCallMode};
+use napi::{JsFunction, JsUnknown, Ref, Status};
 use napi_derive::napi;
-use std::path::PathBuf;
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+use tokenizer::SharedTokenizer;
 
 pub use bindings::database::*;
 pub use chunker::*;
@@ -24,19 +46,235 @@ pub use store::*;
 pub use types::*;
 
 #[napi]
-pub fn parse_file(file_path: String, content: String) -> Result<Vec<CodeChunk>> {
-    parser::parse_file_internal(&file_path, &content).map_err(|e| Error::from_reason(e.to_string()))
+pub fn parse_file(
+    file_path: String,
+    content: String,
+    options: Option<ChunkingOptions>,
+    parse_options: Option<ParseOptions>,
+) -> Result<Vec<CodeChunk>> {
+    let profile = parsing_profile(options, parse_options)?;
+    parser::parse_file_internal(&file_path, &content, &profile)
+        .map_err(|e| Error::from_reason(e.to_string()))
 }
 
 #[napi]
-pub fn parse_file_as_text(file_path: String, content: String) -> Result<Vec<CodeChunk>> {
-    parser::parse_file_as_text_internal(&file_path, &content)
+pub fn parse_file_as_text(
+    file_path: String,
+    content: String,
+    options: Option<ChunkingOptions>,
+) -> Result<Vec<CodeChunk>> {
+    let profile = chunking_profile(options)?;
+    parser::parse_file_as_text_internal(&file_path, &content, &profile)
         .map_err(|e| Error::from_reason(e.to_string()))
 }
 
 #[napi]
-pub fn parse_files(files: Vec<FileInput>) -> Result<Vec<ParsedFile>> {
-    parser::parse_files_parallel(files).map_err(|e| Error::from_reason(e.to_string()))
+pub fn parse_files(
+    files: Vec<FileInput>,
+    options: Option<ChunkingOptions>,
+    parse_options: Option<ParseOptions>,
+) -> Result<Vec<ParsedFile>> {
+    let profile = parsing_profile(options, parse_options)?;
+    parser::parse_files_parallel(files, &profile).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Builds the chunking profile for `options`, with unset fields (and unset
+/// override fields) falling back to the built-in defaults.
+fn chunking_profile(options: Option<ChunkingOptions>) -> Result<ChunkingProfile> {
+    let Some(options) = options else {
+        return Ok(ChunkingProfile::default());
+    };
+    let defaults = ChunkConfig {
+        reserved_tokens: options
+            .reserved_tokens
+            .map_or(chunker::RESERVED_TOKENS, |reserved| reserved as usize),
+        tokenizer: shared_tokenizer(options.tokenizer.as_ref())?,
+        ..ChunkConfig::default()
+    };
+    let base = ChunkingOverride {
+        min_chunk_size: options.min_chunk_size,
+        max_chunk_size: options.max_chunk_size,
+        target_chunk_size: options.target_chunk_size,
+        overlap_lines: options.overlap_lines,
+        merge_small_chunks: options.merge_small_chunks,
+        max_chunk_tokens: options.max_chunk_tokens,
+    }
+    .apply(&defaults);
+    let overrides = options
+        .language_overrides
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(language, chunking)| (language, chunking.apply(&base)))
+        .collect();
+    ChunkingProfile::new(base, overrides).map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// The chunking profile for `options` with the parse budget and call
+/// extraction from `parse_options` applied.
+fn parsing_profile(
+    options: Option<ChunkingOptions>,
+    parse_options: Option<ParseOptions>,
+) -> Result<ChunkingProfile> {
+    let mut profile = chunking_profile(options)?;
+    let Some(parse_options) = parse_options else {
+        return Ok(profile);
+    };
+
+    let limit = |value: Option<u32>, default: Option<usize>| match value {
+        Some(0) => None,
+        Some(value) => Some(value as usize),
+        None => default,
+    };
+    let defaults = ParseBudget::default();
+    profile.budget = ParseBudget {
+        timeout: limit(
+            parse_options.timeout_ms,
+            defaults.timeout.map(|timeout| timeout.as_millis() as usize),
+        )
+        .map(|ms| Duration::from_millis(ms as u64)),
+        max_bytes: limit(parse_options.max_bytes, defaults.max_bytes),
+        max_nodes: limit(parse_options.max_ast_nodes, defaults.max_nodes),
+        max_average_line_length: limit(
+            parse_options.minified_line_length,
+            defaults.max_average_line_length,
+        ),
+    };
+    profile.include_calls = parse_options.include_calls.unwrap_or(false);
+    Ok(profile)
+}
+
+/// Parses files read from disk on the rayon pool and hands results to
+/// `on_batch` in groups of `batch_size`, so at most one batch of file contents
+/// is resident before JavaScript consumes it. Resolves to the number of files
+/// that were parsed.
+///
+/// If `on_batch` throws, no further batches are parsed or delivered and the
+/// promise rejects with the thrown error.
+#[napi(
+    ts_args_type = "paths: string[], onBatch: (batch: ParsedFile[]) => void, batchSize?: number, options?: ChunkingOptions, parseOptions?: ParseOptions"
+)]
+pub fn parse_files_from_paths(
+    env: Env,
+    paths: Vec<String>,
+    on_batch: JsFunction,
+    batch_size: Option<u32>,
+    options: Option<ChunkingOptions>,
+    parse_options: Option<ParseOptions>,
+) -> Result<AsyncTask<ParseFilesFromPathsTask>> {
+    let profile = parsing_profile(options, parse_options)?;
+    let callback = Arc::new(Mutex::new(Some(env.create_reference(on_batch)?)));
+    let stopped = Arc::new(AtomicBool::new(false));
+    let (acknowledge, acknowledgements) = mpsc::channel();
+    // Calls `on_batch` and reports how it went instead of letting an
+    // exception escape into the threadsafe function, which would be fatal.
+    let deliver = {
+        let callback = Arc::clone(&callback);
+        let stopped = Arc::clone(&stopped);
+        env.create_function_from_closure("deliverParsedBatch", move |ctx| {
+            let batch = ctx.get::<JsUnknown>(0)?;
+            let outcome = match callback.lock().unwrap().as_ref() {
+                Some(callback) if !stopped.load(Ordering::Acquire) => ctx
+                    .env
+                    .get_reference_value::<JsFunction>(callback)
+                    .and_then(|on_batch| on_batch.call(None, &[batch]))
+                    .map(|_| ()),
+                _ => Ok(()),
+            };
+            let _ = acknowledge.send(outcome);
+            Ok(())
+        })?
+    };
+    let on_batch: ThreadsafeFunction<Vec<ParsedFile>, ErrorStrategy::Fatal> =
+        deliver.create_threadsafe_function(PARSE_STREAM_QUEUE_SIZE, |ctx| Ok(vec![ctx.value]))?;
+    Ok(AsyncTask::new(ParseFilesFromPathsTask {
+        paths,
+        batch_size: batch_size.unwrap_or(DEFAULT_PARSE_STREAM_BATCH_SIZE).max(1) as usize,
+        profile,
+        on_batch,
+        callback,
+        stopped,
+        acknowledgements,
+    }))
+}
+
+const DEFAULT_PARSE_STREAM_BATCH_SIZE: u32 = 64;
+/// Pending batches allowed in the JS queue before the worker blocks.
+const PARSE_STREAM_QUEUE_SIZE: usize = 2;
+
+pub struct ParseFilesFromPathsTask {
+    paths: Vec<String>,
+    batch_size: usize,
+    profile: ChunkingProfile,
+    on_batch: ThreadsafeFunction<Vec<ParsedFile>, ErrorStrategy::Fatal>,
+    callback: Arc<Mutex<Option<Ref<()>>>>,
+    stopped: Arc<AtomicBool>,
+    /// One outcome per batch `on_batch` has finished with.
+    acknowledgements: Receiver<Result<()>>,
+}
+
+impl ParseFilesFromPathsTask {
+    /// Counts batches JavaScript has finished with, failing with the first
+    /// error `on_batch` threw. With `wait`, blocks until none are pending.
+    fn settle(&self, pending: &mut usize, wait: bool) -> Result<()> {
+        while *pending > 0 {
+            let outcome = if wait {
+                self.acknowledgements.recv().ok()
+            } else {
+                self.acknowledgements.try_recv().ok()
+            };
+            let Some(outcome) = outcome else {
+                break;
+            };
+            *pending -= 1;
+            if let Err(error) = outcome {
+                self.stopped.store(true, Ordering::Release);
+                return Err(error);
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Task for ParseFilesFromPathsTask {
+    type Output = u32;
+    type JsValue = u32;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        let mut parsed_count = 0u32;
+        let mut pending = 0usize;
+        for paths in self.paths.chunks(self.batch_size) {
+            self.settle(&mut pending, false)?;
+            let batch = parser::parse_paths_parallel(paths, &self.profile);
+            if batch.is_empty() {
+                continue;
+            }
+            parsed_count += batch.len() as u32;
+            let status = self
+                .on_batch
+                .call(batch, ThreadsafeFunctionCallMode::Blocking);
+            if status != Status::Ok {
+                return Err(Error::new(
+                    status,
+                    "Failed to deliver parsed batch to JavaScript".to_string(),
+                ));
+            }
+            pending += 1;
+        }
+        self.settle(&mut pending, true)?;
+        Ok(parsed_count)
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(output)
+    }
+
+    fn finally(&mut self, env: Env) -> Result<()> {
+        self.stopped.store(true, Ordering::Release);
+        if let Some(mut callback) = self.callback.lock().unwrap().take() {
+            callback.unref(env)?;
+        }
+        Ok(())
+    }
 }
 
 #[napi]
@@ -49,21 +287,225 @@ pub fn hash_file(file_path: String) -> Result<String> {
     hasher::xxhash_file(&file_path).map_err(|e| Error::from_reason(e.to_string()))
 }
 
+/// The tokenizer `options` describe, or the `len / 4` estimate without one.
+/// Loaded vocabularies are reused across calls until their files change.
+fn shared_tokenizer(options: Option<&TokenizerOptions>) -> Result<SharedTokenizer> {
+    let Some(options) = options else {
+        return Ok(SharedTokenizer::default());
+    };
+    SharedTokenizer::load(
+        options.kind.as_deref(),
+        options.vocab_path.as_deref(),
+        options.merges_path.as_deref(),
+        options.lowercase,
+    )
+    .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Registers the languages described by a JSON config file (extensions,
+/// grammar, semantic and comment node kinds, call query) so later parses pick
+/// them up. Returns the registered language names.
 #[napi]
-pub fn extract_calls(content: String, language: String) -> Result<Vec<CallSiteData>> {
-    call_extractor::extract_calls(&content, &language)
-        .map(|sites| {
-            sites
+pub fn register_languages(config_path: String) -> Result<Vec<String>> {
+    registry::register_languages_from_file(Path::new(&config_path))
+        .map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+#[napi]
+pub fn list_languages() -> Vec<LanguageInfo> {
+    registry::all_definitions()
+        .into_iter()
+        .map(|definition| LanguageInfo {
+            name: definition.name.to_string(),
+            extensions: definition
+                .extensions
+                .iter()
+                .map(|extension| extension.to_string())
+                .collect(),
+            has_grammar: definition.grammar.is_some(),
+            has_call_query: registry::has_call_query(&definition.language),
+            has_reference_query: definition.reference_query.is_some(),
+            case_insensitive: definition.case_insensitive,
+            builtin: !matches!(definition.language, Language::Custom(_)),
+        })
+        .collect()
+}
+
+/// Loads project call query files that extend or replace the built-in
+/// patterns, replacing any loaded before. Every file is validated first; on
+/// error nothing changes. Returns the languages now using a custom query.
+#[napi]
+pub fn load_call_queries(files: Vec<CallQueryFile>) -> Result<Vec<String>> {
+    let configs = files
+        .into_iter()
+        .map(|file| {
+            let mode = match file.mode.as_deref() {
+                Some(mode) => registry::CallQueryMode::from_name(mode)?,
+                None => registry::CallQueryMode::default(),
+            };
+            Ok(registry::CallQueryConfig {
+                language: file.language,
+                path: PathBuf::from(file.path),
+                mode,
+            })
+        })
+        .collect::<anyhow::Result<Vec<_>>>()
+        .map_err(|e| Error::from_reason(e.to_string()))?;
+    registry::load_call_queries(&configs).map_err(|e| Error::from_reason(format!("{:#}", e)))
+}
+
+/// Per-language parse counters, for languages that parsed at least one file.
+/// Empty when the addon is built without the `parser-metrics` feature.
+#[napi]
+pub fn get_parser_metrics() -> Vec<ParserLanguageMetrics> {
+    #[cfg(feature = "parser-metrics")]
+    {
+        metrics::snapshot()
+            .into_iter()
+            .map(|metrics| ParserLanguageMetrics {
+                language: metrics.language.as_str().to_string(),
+                files_parsed: metrics.files_parsed as i64,
+                bytes: metrics.bytes as i64,
+                chunks: metrics.chunks as i64,
+                symbols: metrics.symbols as i64,
+                fallbacks: metrics.fallbacks as i64,
+                p50_parse_ms: metrics.p50_parse_ms,
+                p95_parse_ms: metrics.p95_parse_ms,
+            })
+            .collect()
+    }
+    #[cfg(not(feature = "parser-metrics"))]
+    {
+        Vec::new()
+    }
+}
+
+#[napi]
+pub fn reset_parser_metrics() {
+    #[cfg(feature = "parser-metrics")]
+    metrics::reset();
+}
+
+#[napi]
+pub fn count_tokens(text: String, tokenizer: Option<TokenizerOptions>) -> Result<u32> {
+    Ok(shared_tokenizer(tokenizer.as_ref())?.count_tokens(&text) as u32)
+}
+
+/// Tokens the chunks' embedding texts take, the model's special tokens
+/// included.
+#[napi]
+pub fn estimate_chunks_tokens(
+    chunks: Vec<CodeChunk>,
+    tokenizer: Option<TokenizerOptions>,
+) -> Result<u32> {
+    let tokenizer = shared_tokenizer(tokenizer.as_ref())?;
+    Ok(chunker::estimate_chunks_tokens(&chunks, &tokenizer) as u32)
+}
+
+/// Walks `root` on a worker thread; see [`walker::collect_files_parallel`].
+/// Files it could not list, stat or hash are reported in `skipped` with the
+/// error instead of being dropped.
+#[napi]
+pub fn walk_files(
+    root: String,
+    include_globs: Vec<String>,
+    exclude_globs: Vec<String>,
+    max_file_size: i64,
+    custom_ignore_filenames: Option<Vec<String>>,
+) -> AsyncTask<WalkFilesTask> {
+    AsyncTask::new(WalkFilesTask {
+        root,
+        include_globs,
+        exclude_globs,
+        max_file_size: max_file_size.max(0) as u64,
+        custom_ignore_filenames: custom_ignore_filenames.unwrap_or_default(),
+    })
+}
+
+pub struct WalkFilesTask {
+    root: String,
+    include_globs: Vec<String>,
+    exclude_globs: Vec<String>,
+    max_file_size: u64,
+    custom_ignore_filenames: Vec<String>,
+}
+
+impl Task for WalkFilesTask {
+    type Output = walker::WalkOutput;
+    type JsValue = WalkFilesResult;
+
+    fn compute(&mut self) -> Result<Self::Output> {
+        walker::collect_files_parallel(
+            &self.root,
+            &self.include_globs,
+            &self.exclude_globs,
+            self.max_file_size,
+            &self.custom_ignore_filenames,
+        )
+        .map_err(|e| Error::from_reason(e.to_string()))
+    }
+
+    fn resolve(&mut self, _env: Env, output: Self::Output) -> Result<Self::JsValue> {
+        Ok(WalkFilesResult {
+            files: output
+                .files
                 .into_iter()
-                .map(|s| CallSiteData {
-                    callee_name: s.callee_name,
-                    line: s.line,
-                    column: s.column,
-                    call_type: format!("{:?}", s.call_type),
-                    confidence: format!("{:?}", s.confidence),
+                .map(|f| WalkedFile {
+                    path: f.path,
+                    size: f.size as i64,
+                    mtime_ms: f.mtime_ms,
+                    hash: f.hash,
                 })
-                .collect()
+                .collect(),
+            skipped: output
+                .skipped
+                .into_iter()
+                .map(|s| SkippedWalkEntry {
+                    path: s.path,
+                    reason: s.reason.to_string(),
+                    error: s.error,
+                })
+                .collect(),
         })
+    }
+}
+
+pub(crate) fn call_site_data(site: call_extractor::CallSite) -> CallSiteData {
+    let enclosing = site.enclosing;
+    CallSiteData {
+        callee_name: site.callee_name,
+        line: site.line,
+        column: site.column,
+        call_type: format!("{:?}", site.call_type),
+        confidence: format!("{:?}", site.confidence),
+        qualifier: site.qualifier,
+        enclosing_symbol: EnclosingSymbolData {
+            name: enclosing.name,
+            kind: enclosing.kind,
+            start_line: enclosing.start_line,
+            start_col: enclosing.start_col,
+            end_line: enclosing.end_line,
+            end_col: enclosing.end_col,
+            start_byte: enclosing.start_byte,
+            end_byte: enclosing.end_byte,
+        },
+    }
+}
+
+#[napi]
+pub fn extract_calls(content: String, language: String) -> Result<Vec<CallSiteData>> {
+    call_extractor::extract_calls(&content, &language)
+        .map(|sites| sites.into_iter().map(call_site_data).collect())
+        .map_err(|e| Error::from_reason(e.to_string()))
+}
+
+/// Type uses, field accesses, reads, writes and decorators in `content`,
+/// reported like call sites with the matching `call_type`. Sites that
+/// `extract_calls` reports are not repeated.
+#[napi]
+pub fn extract_references(content: String, language: String) -> Result<Vec<CallSiteData>> {
+    reference_extractor::extract_references(&content, &language)
+        .map(|sites| sites.into_iter().map(call_site_data).collect())
         .map_err(|e| Error::from_reason(e.to_string()))
 }
 
@@ -187,21 +629,290 @@ impl VectorStore {
     }
 }
 
+#[napi]
+pub struct ParseSession {
+    inner: incremental::ParseSessionInner,
+}
+
+#[napi]
+impl ParseSession {
+    #[napi(constructor)]
+    pub fn new(
+        options: Option<ChunkingOptions>,
+        parse_options: Option<ParseOptions>,
+    ) -> Result<Self> {
+        Ok(Self {
+            inner: incremental::ParseSessionInner::with_profile(parsing_profile(
+                options,
+                parse_options,
+            )?),
+        })
+    }
+
+    #[napi]
+    pub fn update(
+        &mut self,
+        file_path: String,
+        content: String,
+        edits: Option<Vec<TextEdit>>,
+    ) -> Result<IncrementalParseResult> {
+        let edits: Option<Vec<incremental::TextEditSpec>> = edits.map(|edits| {
+            edits
+                .into_iter()
+                .map(|e| incremental::TextEditSpec {
+                    start_byte: e.start_byte as usize,
+                    old_end_byte: e.old_end_byte as usize,
+                    new_text: e.new_text,
+                })
+                .collect()
+        });
+        let result = self
+            .inner
+            .update(&file_path, &content, edits.as_deref())
+            .map_err(|e| Error::from_reason(e.to_string()))?;
+
+        Ok(IncrementalParseResult {
+            path: file_path,
+            hash: result.hash,
+            incremental: result.incremental,
+            changed_chunks: result.chunks.changed,
+            changed_symbols: result.symbols.changed,
+            shifted_chunks: result.chunks.shifted,
+            shifted_symbols: result.symbols.shifted,
+            removed_chunks: result.chunks.removed,
+            removed_symbols: result.symbols.removed,
+            changed_ranges: result
+                .changed_lines
+                .into_iter()
+                .map(|(start_line, end_line)| ChangedLineRange {
+                    start_line,
+                    end_line,
+                })
+                .collect(),
+        })
+    }
+
+    #[napi]
+    pub fn remove(&mut self, file_path: String) -> bool {
+        self.inner.remove(&file_path)
+    }
+
+    #[napi]
+    pub fn clear(&mut self) {
+        self.inner.clear();
+    }
+
+    #[napi]
+    pub fn size(&self) -> u32 {
+        self.inner.len() as u32
+    }
+}
+
+impl Default for ParseSession {
+    fn default() -> Self {
+        Self {
+            inner: incremental::ParseSessionInner::new(),
+        }
+    }
+}
+
 #[napi(object)]
 pub struct FileInput {
     pub path: String,
     pub content: String,
 }
 
+/// Chunk size limits in bytes of chunk content. Unset fields keep the
+/// built-in defaults (50 / 2000 / 500 bytes, 3 overlap lines, merging on).
+#[napi(object)]
+pub struct ChunkingOptions {
+    pub min_chunk_size: Option<u32>,
+    pub max_chunk_size: Option<u32>,
+    pub target_chunk_size: Option<u32>,
+    pub overlap_lines: Option<u32>,
+    pub merge_small_chunks: Option<bool>,
+    /// Split chunks so none's embedding text exceeds this many tokens under
+    /// `tokenizer`. Unset (or `0`) sizes chunks by bytes only.
+    pub max_chunk_tokens: Option<u32>,
+    /// Tokens of `max_chunk_tokens` kept free for the type, name and path
+    /// prefix of the embedding text; the tokenizer's special tokens are
+    /// reserved as well. Default 64.
+    pub reserved_tokens: Option<u32>,
+    /// Counts tokens for `max_chunk_tokens`; the `len / 4` estimate when unset.
+    pub tokenizer: Option<TokenizerOptions>,
+    /// Per-language settings keyed by language name (`"python"`, `"rust"`,
+    /// ...); unset fields inherit from the top-level options.
+    pub language_overrides: Option<HashMap<String, ChunkingOverride>>,
+}
+
+/// How files are parsed. The budget limits apply per file: a file over any
+/// limit is chunked by lines and its diagnostics name the limit. `0` disables
+/// a limit. Defaults: 5000 ms, 4 MiB, 1,000,000 AST nodes, 500-byte average
+/// lines.
+#[napi(object)]
+pub struct ParseOptions {
+    pub timeout_ms: Option<u32>,
+    pub max_bytes: Option<u32>,
+    pub max_ast_nodes: Option<u32>,
+    /// Average line length above which a file is treated as minified.
+    pub minified_line_length: Option<u32>,
+    /// Fill `ParsedFile.calls` with the call sites and references of each
+    /// file, extracted from the tree already parsed for its chunks.
+    pub include_calls: Option<bool>,
+}
+
+#[napi(object)]
+pub struct ChunkingOverride {
+    pub min_chunk_size: Option<u32>,
+    pub max_chunk_size: Option<u32>,
+    pub target_chunk_size: Option<u32>,
+    pub overlap_lines: Option<u32>,
+    pub merge_small_chunks: Option<bool>,
+    /// `0` disables the inherited token budget for this language.
+    pub max_chunk_tokens: Option<u32>,
+}
+
+impl ChunkingOverride {
+    fn apply(&self, base: &ChunkConfig) -> ChunkConfig {
+        let size = |value: Option<u32>, default: usize| value.map_or(default, |v| v as usize);
+        ChunkConfig {
+            min_size: size(self.min_chunk_size, base.min_size),
+            max_size: size(self.max_chunk_size, base.max_size),
+            target_size: size(self.target_chunk_size, base.target_size),
+            overlap_lines: size(self.overlap_lines, base.overlap_lines),
+            merge_small_chunks: self.merge_small_chunks.unwrap_or(base.merge_small_chunks),
+            max_tokens: match self.max_chunk_tokens {
+                Some(0) => None,
+                Some(max) => Some(max as usize),
+                None => base.max_tokens,
+            },
+            reserved_tokens: base.reserved_tokens,
+            tokenizer: base.tokenizer.clone(),
+        }
+    }
+}
+
+#[napi(object)]
+pub struct TokenizerOptions {
+    /// `"wordpiece"`, `"bpe"` or `"chars"`; inferred from the paths when omitted.
+    pub kind: Option<String>,
+    /// `vocab.txt` for WordPiece, or a Hugging Face `tokenizer.json`.
+    pub vocab_path: Option<String>,
+    /// BPE `merges.txt`.
+    pub merges_path: Option<String>,
+    pub lowercase: Option<bool>,
+}
+
+#[napi(object)]
+pub struct WalkedFile {
+    pub path: String,
+    pub size: i64,
+    pub mtime_ms: i64,
+    pub hash: String,
+}
+
+#[napi(object)]
+pub struct SkippedWalkEntry {
+    pub path: String,
+    /// `too_large`, `walk_error`, `metadata_error` or `hash_error`.
+    pub reason: String,
+    /// The underlying error, for every reason but `too_large`.
+    pub error: Option<String>,
+}
+
+#[napi(object)]
+pub struct WalkFilesResult {
+    pub files: Vec<WalkedFile>,
+    pub skipped: Vec<SkippedWalkEntry>,
+}
+
 #[napi(object)]
 pub struct ParsedFile {
     pub path: String,
     pub chunks: Vec<CodeChunk>,
     pub symbols: Vec<ParsedSymbol>,
     pub hash: String,
+    /// Detected language name.
+    pub language: String,
+    /// What decided `language`: `modeline`, `filename`, `extension`,
+    /// `shebang`, `content`, `parse-errors` or `fallback`.
+    pub language_reason: String,
+    pub diagnostics: ParseDiagnostics,
+    /// Call sites followed by references, as `extract_calls` and
+    /// `extract_references` report them; set only with
+    /// `ParseOptions.include_calls`.
+    pub calls: Option<Vec<CallSiteData>>,
+    /// Lines in the file, counting the empty one after a trailing newline.
+    pub line_count: u32,
+    /// Length of the last line in UTF-16 code units, as JavaScript measures
+    /// it, so callers that never see the content still know where it ends.
+    pub last_line_length: u32,
+}
+
+/// How well a file parsed, so poorly indexed files can be listed.
+#[napi(object)]
+#[derive(Debug, Clone, Default)]
+pub struct ParseDiagnostics {
+    /// Time spent parsing and chunking the file.
+    pub parse_duration_ms: f64,
+    pub error_node_count: u32,
+    pub missing_node_count: u32,
+    /// The first ERROR/MISSING nodes in document order.
+    pub syntax_errors: Vec<SyntaxErrorRange>,
+    /// Syntax errors or a parse budget forced line chunks. A clean file with
+    /// no declarations is chunked by lines without counting as a fallback.
+    pub fell_back_to_lines: bool,
+    /// Why it was chunked by lines: `syntax-errors`, or a parse budget
+    /// limit: `too-large`, `minified`, `timeout` or `too-many-nodes`.
+    pub fallback_reason: Option<String>,
+    /// Why the file could not be read or parsed; it then has no chunks.
+    pub error: Option<String>,
+}
+
+#[napi(object)]
+#[derive(Debug, Clone)]
+pub struct SyntaxErrorRange {
+    /// `error` for an ERROR node, `missing` for a MISSING node.
+    pub kind: String,
+    pub start_byte: u32,
+    pub end_byte: u32,
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[napi(object)]
+pub struct TextEdit {
+    pub start_byte: u32,
+    pub old_end_byte: u32,
+    pub new_text: String,
+}
+
+#[napi(object)]
+pub struct ChangedLineRange {
+    pub start_line: u32,
+    pub end_line: u32,
+}
+
+#[napi(object)]
+pub struct IncrementalParseResult {
+    pub path: String,
+    pub hash: String,
+    pub incremental: bool,
+    /// New chunks and chunks whose text changed.
+    pub changed_chunks: Vec<CodeChunk>,
+    pub changed_symbols: Vec<ParsedSymbol>,
+    /// Unchanged chunks at the lines an edit above them moved them to.
+    pub shifted_chunks: Vec<CodeChunk>,
+    pub shifted_symbols: Vec<ParsedSymbol>,
+    /// Chunks of the previous parse that were edited or deleted, at their
+    /// previous lines.
+    pub removed_chunks: Vec<CodeChunk>,
+    pub removed_symbols: Vec<ParsedSymbol>,
+    pub changed_ranges: Vec<ChangedLineRange>,
 }
 
 #[napi(object)]
+#[derive(Clone, PartialEq)]
 pub struct ParsedSymbol {
     pub name: String,
     pub kind: String,
@@ -210,9 +921,23 @@ pub struct ParsedSymbol {
     pub end_line: u32,
     pub end_col: u32,
     pub language: String,
+    /// Name prefixed by its enclosing declarations (`UserService.save`,
+    /// `outer::Inner::method`).
+    pub qualified_name: String,
+    /// Qualified name of the innermost enclosing declaration.
+    pub parent_symbol: Option<String>,
+    /// Number of enclosing named declarations; 0 at file scope.
+    pub depth: u32,
+    /// Parameter list and return type as written, whitespace collapsed.
+    pub signature: Option<String>,
+    /// Leading doc comment or Python docstring with comment markers stripped.
+    pub doc_comment: Option<String>,
+    /// `public`, `private`, `protected`, `internal`, or a language keyword.
+    pub visibility: Option<String>,
 }
 
 #[napi(object)]
+#[derive(Clone, PartialEq)]
 pub struct CodeChunk {
     pub content: String,
     pub start_line: u32,
@@ -222,6 +947,55 @@ pub struct CodeChunk {
     pub chunk_type: String,
     pub name: Option<String>,
     pub language: String,
+    /// Info-string language of a Markdown fenced code block (`bash`,
+    /// `typescript`); `language` stays the file's.
+    pub fence_language: Option<String>,
+    pub qualified_name: Option<String>,
+    pub parent_symbol: Option<String>,
+    /// Declaration nesting depth; `None` for chunks not cut from a declaration.
+    pub depth: Option<u32>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
+    /// 0-based position of this piece when an oversized node was split.
+    pub part_index: Option<u32>,
+    /// Number of pieces the node was split into; `None` for whole chunks.
+    pub part_count: Option<u32>,
+}
+
+#[napi(object)]
+pub struct LanguageInfo {
+    pub name: String,
+    pub extensions: Vec<String>,
+    pub has_grammar: bool,
+    pub has_call_query: bool,
+    pub has_reference_query: bool,
+    pub case_insensitive: bool,
+    pub builtin: bool,
+}
+
+#[napi(object)]
+pub struct CallQueryFile {
+    pub language: String,
+    pub path: String,
+    /// `extend` (default) or `replace`.
+    pub mode: Option<String>,
+}
+
+/// Parse counters for one language, summed over every `parse_files` and
+/// `parse_files_from_paths` call since start-up or the last reset.
+#[napi(object)]
+pub struct ParserLanguageMetrics {
+    pub language: String,
+    pub files_parsed: i64,
+    pub bytes: i64,
+    pub chunks: i64,
+    pub symbols: i64,
+    /// Files chunked by lines instead of by syntax, for any reason.
+    pub fallbacks: i64,
+    /// Parse time percentiles, accurate to about 20%.
+    pub p50_parse_ms: f64,
+    pub p95_parse_ms: f64,
 }
 
 #[napi(object)]
@@ -244,6 +1018,23 @@ pub struct CallSiteData {
     pub column: u32,
     pub call_type: String,
     pub confidence: String,
+    pub qualifier: Option<String>,
+    /// The innermost symbol around the site; `<module>` outside all of them.
+    pub enclosing_symbol: EnclosingSymbolData,
+}
+
+/// A symbol as `parse_files` reports it, plus its byte range, so a site can
+/// be matched to the exact symbol id it was stored under.
+#[napi(object)]
+pub struct EnclosingSymbolData {
+    pub name: String,
+    pub kind: String,
+    pub start_line: u32,
+    pub start_col: u32,
+    pub end_line: u32,
+    pub end_col: u32,
+    pub start_byte: u32,
+    pub end_byte: u32,
 }
 
 #[napi(object)]
@@ -257,6 +1048,12 @@ pub struct SymbolData {
     pub end_line: u32,
     pub end_col: u32,
     pub language: String,
+    pub qualified_name: Option<String>,
+    pub parent_symbol: Option<String>,
+    pub depth: Option<u32>,
+    pub signature: Option<String>,
+    pub doc_comment: Option<String>,
+    pub visibility: Option<String>,
 }
 
 #[napi(object)]
@@ -272,6 +1069,25 @@ pub struct CallEdgeData {
     pub line: u32,
     pub col: u32,
     pub is_resolved: bool,
+    pub qualifier: Option<String>,
+    /// `receiver-type`, `same-file`, `import`, `unique-name` or `unresolved`
+    /// once `resolveCallEdges` has run for a branch holding the caller.
+    pub resolution_strategy: Option<String>,
+    /// How sure the resolver is of `to_symbol_id`, from 0 to 1.
+    pub resolution_confidence: Option<f64>,
+}
+
+/// Edges a `resolveCallEdges` run saw, by the strategy that resolved them.
+#[napi(object)]
+pub struct CallResolutionStatsData {
+    pub edges: u32,
+    pub receiver_type: u32,
+    pub same_file: u32,
+    pub import: u32,
+    pub unique_name: u32,
+    pub unresolved: u32,
+    /// Edges whose stored target, strategy or confidence changed.
+    pub updated: u32,
 }
 
 #[napi(object)]
@@ -283,6 +1099,33 @@ pub struct PathHopData {
     pub call_type: String,
 }
 
+/// A symbol an ambiguous path endpoint could refer to.
+#[napi(object)]
+pub struct SymbolCandidateData {
+    pub symbol_id: String,
+    pub symbol_name: String,
+    pub kind: String,
+    pub file_path: String,
+    pub line: u32,
+}
+
+#[napi(object)]
+pub struct CallPathData {
+    pub hops: Vec<PathHopData>,
+    /// The number of edges, or their summed cost when weights were given.
+    pub cost: f64,
+}
+
+#[napi(object)]
+pub struct CallPathsData {
+    /// Loopless paths, cheapest first.
+    pub paths: Vec<CallPathData>,
+    /// Set, with no paths, when `from` names several symbols.
+    pub from_candidates: Vec<SymbolCandidateData>,
+    /// Set, with no paths, when `to` names several symbols.
+    pub to_candidates: Vec<SymbolCandidateData>,
+}
+
 #[napi(object)]
 pub struct ReachabilityData {
     pub symbol_id: String,
@@ -291,6 +1134,53 @@ pub struct ReachabilityData {
     pub depth: u32,
 }
 
+#[napi(object)]
+pub struct WeightedReachabilityData {
+    pub symbol_id: String,
+    pub symbol_name: String,
+    pub file_path: String,
+    pub depth: u32,
+    pub cost: f64,
+}
+
+/// Edge costs for weighted path and reachability searches, layered over the
+/// defaults: calls cost 1, plus 2 for an inferred call site and up to 3 for
+/// an unresolved target. Imports, inheritance and reference edges are only
+/// followed when given a cost in `call_types`.
+#[napi(object)]
+pub struct EdgeWeightsData {
+    /// Cost per call type; must be positive.
+    pub call_types: Option<HashMap<String, f64>>,
+    /// Call types whose edges are never followed.
+    pub exclude_call_types: Option<Vec<String>>,
+    /// Added to inferred edges.
+    pub inferred: Option<f64>,
+    pub exclude_inferred: Option<bool>,
+    /// Added to edges without a stored target, and in part to targets
+    /// resolved with less than full confidence.
+    pub unresolved: Option<f64>,
+    pub exclude_unresolved: Option<bool>,
+}
+
+/// Restricts `export_call_graph` to part of a branch; restrictions combine.
+#[napi(object)]
+pub struct CallGraphFilter {
+    /// Symbols in files under this path prefix.
+    pub file_prefix: Option<String>,
+    /// One community as numbered by `detect_communities` for the branch.
+    pub community_id: Option<u32>,
+    /// These symbols and what they reach, as `get_transitive_reachability`
+    /// finds it.
+    pub root_symbol_ids: Option<Vec<String>>,
+    /// `callees`, `callers` or `both` (the default).
+    pub direction: Option<String>,
+    /// Defaults to 10.
+    pub max_depth: Option<u32>,
+    /// Edge types to export; defaults to every type but the reference types
+    /// (`TypeReference`, `FieldAccess`, `Read`, `Write`, `Decorator`).
+    pub call_types: Option<Vec<String>>,
+}
+
 #[napi(object)]
 pub struct CommunityData {
     pub symbol_id: String,
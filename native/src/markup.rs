@@ -0,0 +1,528 @@
+use crate::chunker::ChunkConfig;
+use crate::parser::{fits_chunk_budget, split_large_chunk};
+use crate::types::Language;
+use crate::CodeChunk;
+use tree_sitter::{Node, Point, Tree};
+
+/// Byte offsets of every line start, so a region's position is a binary
+/// search rather than a scan from the top of the document.
+struct LineIndex {
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    fn new(source: &str) -> Self {
+        let line_starts = std::iter::once(0)
+            .chain(
+                source
+                    .bytes()
+                    .enumerate()
+                    .filter(|(_, byte)| *byte == b'\n')
+                    .map(|(idx, _)| idx + 1),
+            )
+            .collect();
+        Self { line_starts }
+    }
+
+    fn point(&self, byte: usize) -> Point {
+        let row = self.line_starts.partition_point(|start| *start <= byte) - 1;
+        Point::new(row, byte - self.line_starts[row])
+    }
+}
+
+/// Chunks a Markdown document into heading sections and fenced code blocks.
+///
+/// Each section is named by its heading path (`Guide > Install > From source`).
+/// Fenced code blocks become separate `code_block` chunks that carry the
+/// section's heading path and, in `fence_language`, the info-string language,
+/// so prose and the snippets it introduces are searchable independently.
+pub(crate) fn extract_markdown_chunks(
+    tree: &Tree,
+    source: &str,
+    config: &ChunkConfig,
+) -> Vec<CodeChunk> {
+    let mut state = MarkdownState {
+        source,
+        lines: LineIndex::new(source),
+        config,
+        headings: Vec::new(),
+        piece_start: 0,
+        chunks: Vec::new(),
+    };
+    state.walk(tree.root_node());
+    state.flush(source.len());
+    state.chunks
+}
+
+struct MarkdownState<'a> {
+    source: &'a str,
+    lines: LineIndex,
+    config: &'a ChunkConfig,
+    headings: Vec<(usize, String)>,
+    piece_start: usize,
+    chunks: Vec<CodeChunk>,
+}
+
+impl MarkdownState<'_> {
+    fn walk(&mut self, node: Node<'_>) {
+        let mut cursor = node.walk();
+        for child in node.named_children(&mut cursor) {
+            match child.kind() {
+                "section" => self.walk(child),
+                "atx_heading" | "setext_heading" => {
+                    self.flush(child.start_byte());
+                    let level = heading_level(child);
+                    let title = child
+                        .child_by_field_name("heading_content")
+                        .map(|content| collapse_whitespace(&self.source[content.byte_range()]))
+                        .unwrap_or_default();
+                    while self
+                        .headings
+                        .last()
+                        .is_some_and(|(depth, _)| *depth >= level)
+                    {
+                        self.headings.pop();
+                    }
+                    self.headings.push((level, title));
+                    self.piece_start = child.start_byte();
+                }
+                "fenced_code_block" => {
+                    self.flush(child.start_byte());
+                    if let Some(mut chunk) = region_chunk(
+                        self.source,
+                        &self.lines,
+                        child.start_byte(),
+                        child.end_byte(),
+                        "code_block",
+                        self.heading_path(),
+                        Language::Markdown.as_str().to_string(),
+                    ) {
+                        chunk.fence_language = fence_language(child, self.source);
+                        push_chunk(chunk, self.config, &mut self.chunks);
+                    }
+                    self.piece_start = child.end_byte();
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn flush(&mut self, end_byte: usize) {
+        if end_byte <= self.piece_start {
+            return;
+        }
+        if let Some(chunk) = region_chunk(
+            self.source,
+            &self.lines,
+            self.piece_start,
+            end_byte,
+            "section",
+            self.heading_path(),
+            Language::Markdown.as_str().to_string(),
+        ) {
+            push_chunk(chunk, self.config, &mut self.chunks);
+        }
+        self.piece_start = end_byte;
+    }
+
+    fn heading_path(&self) -> Option<String> {
+        let titles: Vec<&str> = self
+            .headings
+            .iter()
+            .map(|(_, title)| title.as_str())
+            .filter(|title| !title.is_empty())
+            .collect();
+        (!titles.is_empty()).then(|| titles.join(" > "))
+    }
+}
+
+fn heading_level(heading: Node<'_>) -> usize {
+    let mut cursor = heading.walk();
+    let level = heading
+        .children(&mut cursor)
+        .find_map(|child| match child.kind() {
+            "setext_h1_underline" => Some(1),
+            "setext_h2_underline" => Some(2),
+            kind => kind
+                .strip_prefix("atx_h")
+                .and_then(|rest| rest.strip_suffix("_marker"))
+                .and_then(|digit| digit.parse().ok()),
+        });
+    level.unwrap_or(1)
+}
+
+fn fence_language(code_block: Node<'_>, source: &str) -> Option<String> {
+    let mut cursor = code_block.walk();
+    let info_string = code_block
+        .children(&mut cursor)
+        .find(|child| child.kind() == "info_string")?;
+    let mut info_cursor = info_string.walk();
+    let tag = info_string
+        .children(&mut info_cursor)
+        .find(|child| child.kind() == "language")
+        .map(|language| &source[language.byte_range()])?
+        .trim();
+    if tag.is_empty() {
+        return None;
+    }
+
+    Some(match Language::from_string(tag) {
+        Language::Text => tag.to_lowercase(),
+        language => language.as_str().to_string(),
+    })
+}
+
+/// Chunks an HTML document by element: `<script>`, `<style>` and `<template>`
+/// blocks and elements carrying an `id` become named chunks. Markup between
+/// them is kept as `block` chunks so no text drops out of the index.
+pub(crate) fn extract_html_chunks(
+    tree: &Tree,
+    source: &str,
+    config: &ChunkConfig,
+) -> Vec<CodeChunk> {
+    let mut regions = Vec::new();
+    collect_html_regions(tree.root_node(), source, config, &mut regions);
+    regions.sort_by_key(|region| region.0);
+
+    let language = Language::Html.as_str();
+    let lines = LineIndex::new(source);
+    let mut chunks = Vec::new();
+    let mut covered_until = 0;
+    for (start_byte, end_byte, chunk_type, name) in regions {
+        push_gap_chunk(
+            source,
+            &lines,
+            covered_until,
+            start_byte,
+            config,
+            &mut chunks,
+        );
+        if let Some(chunk) = region_chunk(
+            source,
+            &lines,
+            start_byte,
+            end_byte,
+            chunk_type,
+            name,
+            language.to_string(),
+        ) {
+            push_chunk(chunk, config, &mut chunks);
+        }
+        covered_until = end_byte;
+    }
+    push_gap_chunk(
+        source,
+        &lines,
+        covered_until,
+        source.len(),
+        config,
+        &mut chunks,
+    );
+
+    chunks
+}
+
+type HtmlRegion = (usize, usize, &'static str, Option<String>);
+
+fn collect_html_regions(
+    node: Node<'_>,
+    source: &str,
+    config: &ChunkConfig,
+    regions: &mut Vec<HtmlRegion>,
+) {
+    let mut cursor = node.walk();
+    for child in node.named_children(&mut cursor) {
+        let attribute = |name: &str| start_tag(child).and_then(|tag| attribute(tag, name, source));
+        match child.kind() {
+            "script_element" => regions.push((
+                child.start_byte(),
+                child.end_byte(),
+                "script",
+                attribute("id").or_else(|| attribute("src")),
+            )),
+            "style_element" => regions.push((
+                child.start_byte(),
+                child.end_byte(),
+                "style",
+                attribute("id"),
+            )),
+            "element" => {
+                let tag_name = start_tag(child)
+                    .and_then(|tag| tag.named_child(0))
+                    .filter(|name| name.kind() == "tag_name")
+                    .map(|name| source[name.byte_range()].to_lowercase())
+                    .unwrap_or_default();
+                let id = attribute("id");
+
+                if tag_name == "template" {
+                    regions.push((child.start_byte(), child.end_byte(), "template", id));
+                } else if let Some(id) =
+                    id.filter(|_| child.end_byte() - child.start_byte() <= config.max_size)
+                {
+                    regions.push((
+                        child.start_byte(),
+                        child.end_byte(),
+                        "element",
+                        Some(format!("{}#{}", tag_name, id)),
+                    ));
+                } else {
+                    collect_html_regions(child, source, config, regions);
+                }
+            }
+            _ => collect_html_regions(child, source, config, regions),
+        }
+    }
+}
+
+fn start_tag(element: Node<'_>) -> Option<Node<'_>> {
+    element
+        .named_child(0)
+        .filter(|child| child.kind() == "start_tag")
+}
+
+fn attribute(start_tag: Node<'_>, name: &str, source: &str) -> Option<String> {
+    let mut cursor = start_tag.walk();
+    let found = start_tag
+        .named_children(&mut cursor)
+        .filter(|child| child.kind() == "attribute")
+        .find_map(|attribute| {
+            let attribute_name = attribute.named_child(0)?;
+            if !source[attribute_name.byte_range()].eq_ignore_ascii_case(name) {
+                return None;
+            }
+            let value = attribute.named_child(1)?;
+            let value = match value.kind() {
+                "quoted_attribute_value" => value.named_child(0)?,
+                _ => value,
+            };
+            let text = source[value.byte_range()].trim();
+            (!text.is_empty()).then(|| text.to_string())
+        });
+    found
+}
+
+fn push_gap_chunk(
+    source: &str,
+    lines: &LineIndex,
+    start_byte: usize,
+    end_byte: usize,
+    config: &ChunkConfig,
+    chunks: &mut Vec<CodeChunk>,
+) {
+    if end_byte <= start_byte || source[start_byte..end_byte].trim().len() < config.min_size {
+        return;
+    }
+    if let Some(chunk) = region_chunk(
+        source,
+        lines,
+        start_byte,
+        end_byte,
+        "block",
+        None,
+        Language::Html.as_str().to_string(),
+    ) {
+        push_chunk(chunk, config, chunks);
+    }
+}
+
+/// Builds a chunk from `source[start_byte..end_byte]` with surrounding
+/// whitespace trimmed, or `None` when the region is blank.
+fn region_chunk(
+    source: &str,
+    lines: &LineIndex,
+    start_byte: usize,
+    end_byte: usize,
+    chunk_type: &str,
+    name: Option<String>,
+    language: String,
+) -> Option<CodeChunk> {
+    let region = &source[start_byte..end_byte];
+    let trimmed_start = region.trim_start();
+    if trimmed_start.is_empty() {
+        return None;
+    }
+    let start_byte = start_byte + (region.len() - trimmed_start.len());
+    let end_byte = start_byte + trimmed_start.trim_end().len();
+
+    let start = lines.point(start_byte);
+    let end = lines.point(end_byte);
+    Some(CodeChunk {
+        content: source[start_byte..end_byte].to_string(),
+        start_line: start.row as u32 + 1,
+        start_col: start.column as u32,
+        end_line: end.row as u32 + 1,
+        end_col: end.column as u32,
+        chunk_type: chunk_type.to_string(),
+        name,
+        language,
+        fence_language: None,
+        qualified_name: None,
+        parent_symbol: None,
+        depth: None,
+        signature: None,
+        doc_comment: None,
+        visibility: None,
+        part_index: None,
+        part_count: None,
+    })
+}
+
+fn push_chunk(chunk: CodeChunk, config: &ChunkConfig, chunks: &mut Vec<CodeChunk>) {
+    if fits_chunk_budget(&chunk.content, config) {
+        chunks.push(chunk);
+    } else {
+        split_large_chunk(chunk, config, chunks);
+    }
+}
+
+fn collapse_whitespace(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::LineIndex;
+    use crate::chunker::ChunkingProfile;
+    use crate::parser::{parse_file_internal, point_at_byte};
+
+    const README: &str = r#"Intro paragraph before any heading.
+
+# Guide
+
+Welcome to the project guide.
+
+## Install
+
+Run the installer:
+
+```bash
+npm install my-project
+```
+
+### From source
+
+```ts
+import { build } from "./build";
+build();
+```
+
+Setext Usage
+------------
+
+Call the library from your code.
+
+```mermaid
+graph TD; A-->B;
+```
+"#;
+
+    #[test]
+    fn test_markdown_sections_are_named_by_heading_path() {
+        let chunks = parse_file_internal("README.md", README, &ChunkingProfile::default()).unwrap();
+
+        let names: Vec<(&str, Option<&str>)> = chunks
+            .iter()
+            .map(|chunk| (chunk.chunk_type.as_str(), chunk.name.as_deref()))
+            .collect();
+        assert_eq!(
+            names,
+            vec![
+                ("section", None),
+                ("section", Some("Guide")),
+                ("section", Some("Guide > Install")),
+                ("code_block", Some("Guide > Install")),
+                ("section", Some("Guide > Install > From source")),
+                ("code_block", Some("Guide > Install > From source")),
+                ("section", Some("Guide > Setext Usage")),
+                ("code_block", Some("Guide > Setext Usage")),
+            ]
+        );
+
+        let install = &chunks[2];
+        assert_eq!(install.content, "## Install\n\nRun the installer:");
+        assert_eq!(install.start_line, 7);
+        assert_eq!(install.end_line, 9);
+    }
+
+    #[test]
+    fn test_line_index_matches_byte_scan() {
+        let lines = LineIndex::new(README);
+        for byte in 0..=README.len() {
+            assert_eq!(
+                lines.point(byte),
+                point_at_byte(README, byte),
+                "byte {byte}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_markdown_code_blocks_carry_info_string_language() {
+        let chunks = parse_file_internal("README.md", README, &ChunkingProfile::default()).unwrap();
+        let languages: Vec<Option<&str>> = chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == "code_block")
+            .map(|chunk| chunk.fence_language.as_deref())
+            .collect();
+
+        assert_eq!(
+            languages,
+            vec![Some("bash"), Some("typescript"), Some("mermaid")]
+        );
+        assert!(chunks.iter().all(|chunk| chunk.language == "markdown"));
+        assert!(chunks
+            .iter()
+            .filter(|chunk| chunk.chunk_type == "section")
+            .all(|chunk| chunk.fence_language.is_none()));
+    }
+
+    #[test]
+    fn test_html_chunks_scripts_styles_templates_and_ids() {
+        let content = r##"<!DOCTYPE html>
+<html>
+<head>
+  <style>
+    body { font-family: sans-serif; margin: 0; }
+  </style>
+  <script src="/static/app.js"></script>
+</head>
+<body>
+  <section id="pricing">
+    <h2>Pricing</h2>
+    <p>Plans start at $5 per month.</p>
+  </section>
+  <template id="row-template">
+    <tr><td class="name"></td><td class="price"></td></tr>
+  </template>
+  <p>This paragraph lives outside any named element and must stay indexed.</p>
+  <script>
+    document.querySelector("#pricing").addEventListener("click", track);
+  </script>
+</body>
+</html>
+"##;
+        let chunks =
+            parse_file_internal("index.html", content, &ChunkingProfile::default()).unwrap();
+        let kinds: Vec<(&str, Option<&str>)> = chunks
+            .iter()
+            .map(|chunk| (chunk.chunk_type.as_str(), chunk.name.as_deref()))
+            .collect();
+
+        assert!(kinds.contains(&("style", None)), "{kinds:?}");
+        assert!(kinds.contains(&("script", Some("/static/app.js"))));
+        assert!(kinds.contains(&("element", Some("section#pricing"))));
+        assert!(kinds.contains(&("template", Some("row-template"))));
+        assert!(kinds.contains(&("script", None)));
+        assert!(chunks
+            .iter()
+            .any(|chunk| chunk.chunk_type == "block" && chunk.content.contains("outside any")));
+
+        let pricing = chunks
+            .iter()
+            .find(|chunk| chunk.name.as_deref() == Some("section#pricing"))
+            .unwrap();
+        assert_eq!(pricing.start_line, 10);
+        assert_eq!(pricing.end_line, 13);
+        assert!(pricing.content.starts_with("<section id=\"pricing\">"));
+    }
+}
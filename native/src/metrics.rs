@@ -0,0 +1,240 @@
+//! Per-language parser counters for tracking indexing throughput.
+//!
+//! Each thread that parses records into its own block of atomics, so the
+//! parse path never contends on a lock; the registry of blocks is only locked
+//! when a thread first records and when a report is taken. Blocks outlive
+//! their threads, so rayon workers that exit keep their counts.
+//!
+//! Parse times go into a log-linear histogram (four buckets per power of two
+//! of microseconds), which bounds percentile error to about 20%.
+
+use crate::registry;
+use crate::types::Language;
+use crate::ParsedFile;
+use lazy_static::lazy_static;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+/// Languages beyond this many (built-ins plus registrations) go unrecorded.
+const MAX_LANGUAGES: usize = 128;
+const SUB_BUCKET_BITS: u32 = 2;
+const SUB_BUCKETS: usize = 1 << SUB_BUCKET_BITS;
+/// Covers parse times up to 2^32 microseconds, a little over an hour.
+const HISTOGRAM_BUCKETS: usize = 32 * SUB_BUCKETS;
+
+const FILES: usize = 0;
+const BYTES: usize = 1;
+const CHUNKS: usize = 2;
+const SYMBOLS: usize = 3;
+const FALLBACKS: usize = 4;
+const HISTOGRAM: usize = 5;
+const COUNTERS_PER_LANGUAGE: usize = HISTOGRAM + HISTOGRAM_BUCKETS;
+
+/// Aggregated counters for one language since start-up or the last reset.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct LanguageMetrics {
+    pub language: Language,
+    pub files_parsed: u64,
+    pub bytes: u64,
+    pub chunks: u64,
+    pub symbols: u64,
+    /// Files that fell back to line chunks, whatever the reason.
+    pub fallbacks: u64,
+    pub p50_parse_ms: f64,
+    pub p95_parse_ms: f64,
+}
+
+struct ThreadCounters {
+    counters: Box<[AtomicU64]>,
+}
+
+impl ThreadCounters {
+    fn new() -> Self {
+        Self {
+            counters: (0..MAX_LANGUAGES * COUNTERS_PER_LANGUAGE)
+                .map(|_| AtomicU64::new(0))
+                .collect(),
+        }
+    }
+
+    fn language(&self, index: usize) -> &[AtomicU64] {
+        let start = index * COUNTERS_PER_LANGUAGE;
+        &self.counters[start..start + COUNTERS_PER_LANGUAGE]
+    }
+}
+
+lazy_static! {
+    static ref THREADS: Mutex<Vec<Arc<ThreadCounters>>> = Mutex::new(Vec::new());
+}
+
+thread_local! {
+    static LOCAL: Arc<ThreadCounters> = {
+        let counters = Arc::new(ThreadCounters::new());
+        THREADS.lock().unwrap().push(Arc::clone(&counters));
+        counters
+    };
+}
+
+/// Records one file from a parse batch.
+pub(crate) fn record(file: &ParsedFile, language: &Language, bytes: usize) {
+    let index = registry::language_index(language);
+    if index >= MAX_LANGUAGES {
+        return;
+    }
+    let micros = (file.diagnostics.parse_duration_ms * 1000.0) as u64;
+    LOCAL.with(|local| {
+        let counters = local.language(index);
+        counters[FILES].fetch_add(1, Ordering::Relaxed);
+        counters[BYTES].fetch_add(bytes as u64, Ordering::Relaxed);
+        counters[CHUNKS].fetch_add(file.chunks.len() as u64, Ordering::Relaxed);
+        counters[SYMBOLS].fetch_add(file.symbols.len() as u64, Ordering::Relaxed);
+        if file.diagnostics.fell_back_to_lines {
+            counters[FALLBACKS].fetch_add(1, Ordering::Relaxed);
+        }
+        counters[HISTOGRAM + bucket_for(micros)].fetch_add(1, Ordering::Relaxed);
+    });
+}
+
+/// Totals across every thread, for each language that parsed at least one
+/// file, in `registry::all_definitions()` order.
+pub(crate) fn snapshot() -> Vec<LanguageMetrics> {
+    let threads = THREADS.lock().unwrap();
+    registry::all_definitions()
+        .into_iter()
+        .take(MAX_LANGUAGES)
+        .enumerate()
+        .filter_map(|(index, definition)| {
+            let mut totals = [0u64; COUNTERS_PER_LANGUAGE];
+            for thread in threads.iter() {
+                for (total, counter) in totals.iter_mut().zip(thread.language(index)) {
+                    *total += counter.load(Ordering::Relaxed);
+                }
+            }
+            if totals[FILES] == 0 {
+                return None;
+            }
+            let histogram = &totals[HISTOGRAM..];
+            Some(LanguageMetrics {
+                language: definition.language,
+                files_parsed: totals[FILES],
+                bytes: totals[BYTES],
+                chunks: totals[CHUNKS],
+                symbols: totals[SYMBOLS],
+                fallbacks: totals[FALLBACKS],
+                p50_parse_ms: percentile(histogram, 0.50),
+                p95_parse_ms: percentile(histogram, 0.95),
+            })
+        })
+        .collect()
+}
+
+/// Zeroes every counter. Parses still in flight may land on either side.
+pub(crate) fn reset() {
+    for thread in THREADS.lock().unwrap().iter() {
+        for counter in thread.counters.iter() {
+            counter.store(0, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Values below `SUB_BUCKETS` get a bucket each; above that, each power of
+/// two is split into `SUB_BUCKETS` equal ranges.
+fn bucket_for(micros: u64) -> usize {
+    if micros < SUB_BUCKETS as u64 {
+        return micros as usize;
+    }
+    let magnitude = 63 - micros.leading_zeros();
+    let sub = (micros >> (magnitude - SUB_BUCKET_BITS)) as usize & (SUB_BUCKETS - 1);
+    let bucket = (magnitude - SUB_BUCKET_BITS + 1) as usize * SUB_BUCKETS + sub;
+    bucket.min(HISTOGRAM_BUCKETS - 1)
+}
+
+/// Inclusive lower and exclusive upper bound of `bucket`, in microseconds.
+fn bucket_bounds(bucket: usize) -> (f64, f64) {
+    if bucket < SUB_BUCKETS {
+        return (bucket as f64, bucket as f64 + 1.0);
+    }
+    let magnitude = (bucket / SUB_BUCKETS) as u32 + SUB_BUCKET_BITS - 1;
+    let width = (1u64 << (magnitude - SUB_BUCKET_BITS)) as f64;
+    let low = (1u64 << magnitude) as f64 + (bucket % SUB_BUCKETS) as f64 * width;
+    (low, low + width)
+}
+
+/// The midpoint of the bucket holding the `quantile`th sample, in ms.
+fn percentile(histogram: &[u64], quantile: f64) -> f64 {
+    let total: u64 = histogram.iter().sum();
+    let rank = ((total as f64 * quantile).ceil() as u64).max(1);
+    let mut seen = 0;
+    for (bucket, count) in histogram.iter().enumerate() {
+        seen += count;
+        if seen >= rank {
+            let (low, high) = bucket_bounds(bucket);
+            return (low + high) / 2.0 / 1000.0;
+        }
+    }
+    0.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::ChunkingProfile;
+    use crate::parser::parse_files_parallel;
+    use crate::FileInput;
+
+    fn zig_totals() -> (u64, u64, u64) {
+        snapshot()
+            .into_iter()
+            .find(|metrics| metrics.language == Language::Zig)
+            .map_or((0, 0, 0), |metrics| {
+                (metrics.files_parsed, metrics.bytes, metrics.symbols)
+            })
+    }
+
+    #[test]
+    fn test_parse_batches_are_counted_per_language() {
+        let content = "pub fn add(a: i32, b: i32) i32 {\n    return a + b;\n}\n";
+        let files = (0..3)
+            .map(|index| FileInput {
+                path: format!("math{index}.zig"),
+                content: content.to_string(),
+            })
+            .collect();
+        let (files_before, bytes_before, symbols_before) = zig_totals();
+
+        let parsed = parse_files_parallel(files, &ChunkingProfile::default()).unwrap();
+
+        let (files_after, bytes_after, symbols_after) = zig_totals();
+        assert!(files_after >= files_before + 3);
+        assert!(bytes_after >= bytes_before + 3 * content.len() as u64);
+        let symbols: usize = parsed.iter().map(|file| file.symbols.len()).sum();
+        assert!(symbols > 0);
+        assert!(symbols_after >= symbols_before + symbols as u64);
+        let zig = snapshot()
+            .into_iter()
+            .find(|metrics| metrics.language == Language::Zig)
+            .unwrap();
+        assert!(zig.p50_parse_ms > 0.0 && zig.p50_parse_ms <= zig.p95_parse_ms);
+    }
+
+    #[test]
+    fn test_histogram_buckets_contain_their_values() {
+        for micros in [0, 1, 3, 4, 5, 7, 8, 100, 1_000, 12_345, 999_999] {
+            let (low, high) = bucket_bounds(bucket_for(micros));
+            assert!(
+                low <= micros as f64 && (micros as f64) < high,
+                "{micros} outside [{low}, {high})"
+            );
+        }
+        assert_eq!(bucket_for(u64::MAX), HISTOGRAM_BUCKETS - 1);
+
+        let mut histogram = [0u64; HISTOGRAM_BUCKETS];
+        for micros in (1..=100).map(|ms| ms * 1000) {
+            histogram[bucket_for(micros)] += 1;
+        }
+        let p50 = percentile(&histogram, 0.50);
+        let p95 = percentile(&histogram, 0.95);
+        assert!((40.0..60.0).contains(&p50), "p50 = {p50}");
+        assert!((80.0..110.0).contains(&p95), "p95 = {p95}");
+    }
+}
@@ -1,150 +1,373 @@
+use crate::call_extractor::{self, Enclosers};
+use crate::chunker::{ChunkConfig, ChunkingProfile, ParseBudget};
+use crate::declaration::DeclarationDetails;
+use crate::detection::detect_language;
+use crate::parser_pool;
+use crate::reference_extractor;
+use crate::registry;
+use crate::tokenizer::Tokenizer;
 use crate::types::Language;
-use crate::{CodeChunk, FileInput, ParsedFile, ParsedSymbol};
-use anyhow::{anyhow, Result};
-use lazy_static::lazy_static;
+use crate::{
+    CallSiteData, CodeChunk, FileInput, ParseDiagnostics, ParsedFile, ParsedSymbol,
+    SyntaxErrorRange,
+};
+use anyhow::{bail, Result};
 use rayon::prelude::*;
-use std::collections::HashSet;
-use std::path::Path;
-#[cfg(debug_assertions)]
+use std::ops::{ControlFlow, Range};
 use std::time::Instant;
-use tree_sitter::{Parser, Tree};
+use tree_sitter::{ParseOptions, ParseState, Point, Tree};
+
+/// ERROR/MISSING ranges reported per file; the counts cover all of them.
+const MAX_REPORTED_SYNTAX_ERRORS: usize = 64;
 
-const MIN_CHUNK_SIZE: usize = 50;
-const MAX_CHUNK_SIZE: usize = 2000;
-const TARGET_CHUNK_SIZE: usize = 500;
-const OVERLAP_LINES: usize = 3;
+/// Tree-sitter grammar for `language`, or `None` for languages that are
+/// chunked by lines only.
+pub(crate) fn grammar_for(language: &Language) -> Option<tree_sitter::Language> {
+    registry::definition(language).grammar.clone()
+}
 
-pub fn parse_file_internal(file_path: &str, content: &str) -> Result<Vec<CodeChunk>> {
-    let ext = Path::new(file_path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
+/// Row/column (both 0-based, column in bytes) of `byte` within `text`.
+pub(crate) fn point_at_byte(text: &str, byte: usize) -> Point {
+    let prefix = &text.as_bytes()[..byte];
+    let row = prefix.iter().filter(|b| **b == b'\n').count();
+    let line_start = prefix
+        .iter()
+        .rposition(|b| *b == b'\n')
+        .map(|idx| idx + 1)
+        .unwrap_or(0);
+    Point::new(row, byte - line_start)
+}
 
-    let language = Language::from_extension(ext);
+pub fn parse_file_internal(
+    file_path: &str,
+    content: &str,
+    profile: &ChunkingProfile,
+) -> Result<Vec<CodeChunk>> {
+    let language = detect_language(file_path, content).language;
+    let config = profile.for_language(&language);
 
     if language == Language::Text {
-        return Ok(chunk_by_lines(content, &language));
-    }
-
-    let mut parser = Parser::new();
-
-    let ts_language = match language {
-        Language::TypeScript | Language::TypeScriptTsx => {
-            tree_sitter_typescript::LANGUAGE_TSX.into()
-        }
-        Language::JavaScript | Language::JavaScriptJsx => tree_sitter_javascript::LANGUAGE.into(),
-        Language::Python => tree_sitter_python::LANGUAGE.into(),
-        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
-        Language::Swift => tree_sitter_swift::LANGUAGE.into(),
-        Language::Go => tree_sitter_go::LANGUAGE.into(),
-        Language::Json => tree_sitter_json::LANGUAGE.into(),
-        Language::Java => tree_sitter_java::LANGUAGE.into(),
-        Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
-        Language::Ruby => tree_sitter_ruby::LANGUAGE.into(),
-        Language::Bash => tree_sitter_bash::LANGUAGE.into(),
-        Language::C => tree_sitter_c::LANGUAGE.into(),
-        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-        Language::Metal => tree_sitter_cpp::LANGUAGE.into(),
-        Language::Toml => tree_sitter_toml_ng::LANGUAGE.into(),
-        Language::Yaml => tree_sitter_yaml::LANGUAGE.into(),
-        Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
-        Language::Zig => tree_sitter_zig::LANGUAGE.into(),
-        Language::Gdscript => tree_sitter_gdscript::LANGUAGE.into(),
-        Language::Matlab => tree_sitter_matlab::LANGUAGE.into(),
-        Language::Apex => tree_sitter_sfapex::apex::LANGUAGE.into(),
-        _ => return Ok(chunk_by_lines(content, &language)),
-    };
+        return Ok(chunk_by_lines(content, &language, config));
+    }
 
-    parser.set_language(&ts_language)?;
+    let Some(ts_language) = grammar_for(&language) else {
+        return Ok(chunk_by_lines(content, &language, config));
+    };
 
-    let tree = parser
-        .parse(content, None)
-        .ok_or_else(|| anyhow!("Failed to parse file: {}", file_path))?;
+    match parse_within_budget(&ts_language, file_path, content, None, &profile.budget)? {
+        BudgetedParse::Tree(tree) => extract_chunks(&tree, content, &language, config),
+        BudgetedParse::OverBudget(_) => Ok(chunk_by_lines(content, &language, config)),
+    }
+}
 
-    extract_chunks(&tree, content, &language)
+/// Outcome of parsing a file under its [`ParseBudget`].
+pub(crate) enum BudgetedParse {
+    Tree(Tree),
+    /// A limit was hit (`too-large`, `minified`, `timeout`, `too-many-nodes`);
+    /// the file should be chunked by lines.
+    OverBudget(&'static str),
 }
 
-pub fn parse_file_as_text_internal(file_path: &str, content: &str) -> Result<Vec<CodeChunk>> {
-    let ext = Path::new(file_path)
-        .extension()
-        .and_then(|e| e.to_str())
-        .unwrap_or("");
+/// Parses `content` with this thread's pooled parser for `grammar`,
+/// cancelling through tree-sitter's progress callback once the budget's
+/// timeout passes and rejecting trees with too many nodes before any
+/// recursive extraction runs over them.
+pub(crate) fn parse_within_budget(
+    grammar: &tree_sitter::Language,
+    file_path: &str,
+    content: &str,
+    old_tree: Option<&Tree>,
+    budget: &ParseBudget,
+) -> Result<BudgetedParse> {
+    if let Some(reason) = budget.precheck(content) {
+        return Ok(BudgetedParse::OverBudget(reason));
+    }
+
+    let deadline = budget.timeout.map(|timeout| Instant::now() + timeout);
+    let mut timed_out = false;
+    let mut progress = |_: &ParseState| {
+        if deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+            timed_out = true;
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    };
+    let bytes = content.as_bytes();
+    let tree = parser_pool::with_parser(grammar, |parser| {
+        Ok(parser.parse_with_options(
+            &mut |offset, _| &bytes[offset.min(bytes.len())..],
+            old_tree,
+            Some(ParseOptions::new().progress_callback(&mut progress)),
+        ))
+    })?;
+
+    let Some(tree) = tree else {
+        if timed_out {
+            return Ok(BudgetedParse::OverBudget("timeout"));
+        }
+        bail!("Failed to parse file: {}", file_path);
+    };
+    if budget
+        .max_nodes
+        .is_some_and(|max| tree.root_node().descendant_count() > max)
+    {
+        return Ok(BudgetedParse::OverBudget("too-many-nodes"));
+    }
+    Ok(BudgetedParse::Tree(tree))
+}
 
-    let language = Language::from_extension(ext);
-    Ok(chunk_by_lines(content, &language))
+pub fn parse_file_as_text_internal(
+    file_path: &str,
+    content: &str,
+    profile: &ChunkingProfile,
+) -> Result<Vec<CodeChunk>> {
+    let language = detect_language(file_path, content).language;
+    Ok(chunk_by_lines(
+        content,
+        &language,
+        profile.for_language(&language),
+    ))
 }
 
-pub fn parse_files_parallel(files: Vec<FileInput>) -> Result<Vec<ParsedFile>> {
+pub fn parse_files_parallel(
+    files: Vec<FileInput>,
+    profile: &ChunkingProfile,
+) -> Result<Vec<ParsedFile>> {
     let results: Vec<ParsedFile> = files
         .par_iter()
-        .filter_map(|file| {
-            let (chunks, symbols) =
-                parse_file_with_symbols_internal(&file.path, &file.content).ok()?;
-            let hash = crate::hasher::xxhash_content(&file.content);
-            Some(ParsedFile {
-                path: file.path.clone(),
-                chunks,
-                symbols,
-                hash,
-            })
-        })
+        .map(|file| parse_file_with_hash(&file.path, &file.content, profile))
         .collect();
 
     Ok(results)
 }
 
+/// Reads, decodes, hashes and parses `paths` under rayon. Files that cannot be
+/// read or parsed come back without chunks and with `diagnostics.error` set,
+/// matching `parse_files_parallel`.
+pub fn parse_paths_parallel(paths: &[String], profile: &ChunkingProfile) -> Vec<ParsedFile> {
+    paths
+        .par_iter()
+        .map(|path| match read_source_file(path) {
+            Ok(content) => parse_file_with_hash(path, &content, profile),
+            Err(error) => {
+                let detection = detect_language(path, "");
+                ParsedFile {
+                    path: path.to_string(),
+                    chunks: Vec::new(),
+                    symbols: Vec::new(),
+                    hash: String::new(),
+                    language: detection.language.as_str().to_string(),
+                    language_reason: detection.reason.as_str().to_string(),
+                    diagnostics: ParseDiagnostics {
+                        error: Some(format!("Failed to read {}: {}", path, error)),
+                        ..ParseDiagnostics::default()
+                    },
+                    calls: None,
+                    line_count: 0,
+                    last_line_length: 0,
+                }
+            }
+        })
+        .collect()
+}
+
+/// Reads a source file as UTF-8, replacing invalid sequences the same way
+/// Node's `readFile(path, "utf-8")` does so content hashes stay comparable.
+pub fn read_source_file(path: &str) -> Result<String> {
+    let bytes = std::fs::read(path)?;
+    Ok(match String::from_utf8(bytes) {
+        Ok(content) => content,
+        Err(error) => String::from_utf8_lossy(error.as_bytes()).into_owned(),
+    })
+}
+
+fn parse_file_with_hash(path: &str, content: &str, profile: &ChunkingProfile) -> ParsedFile {
+    let detection = detect_language(path, content);
+    let started = Instant::now();
+    let (chunks, symbols, mut diagnostics, calls) =
+        match parse_detected_file(path, content, &detection.language, profile) {
+            Ok(parsed) => (
+                parsed.chunks,
+                parsed.symbols,
+                parsed.diagnostics,
+                parsed.calls,
+            ),
+            Err(error) => (
+                Vec::new(),
+                Vec::new(),
+                ParseDiagnostics {
+                    error: Some(error.to_string()),
+                    ..ParseDiagnostics::default()
+                },
+                None,
+            ),
+        };
+    diagnostics.parse_duration_ms = started.elapsed().as_secs_f64() * 1000.0;
+    let parsed = ParsedFile {
+        path: path.to_string(),
+        chunks,
+        symbols,
+        hash: crate::hasher::xxhash_content(content),
+        language: detection.language.as_str().to_string(),
+        language_reason: detection.reason.as_str().to_string(),
+        diagnostics,
+        calls,
+        line_count: content.split('\n').count() as u32,
+        last_line_length: content
+            .rsplit('\n')
+            .next()
+            .map_or(0, |line| line.encode_utf16().count()) as u32,
+    };
+    #[cfg(feature = "parser-metrics")]
+    crate::metrics::record(&parsed, &detection.language, content.len());
+    parsed
+}
+
+#[cfg(test)]
 fn parse_file_with_symbols_internal(
     file_path: &str,
     content: &str,
+    profile: &ChunkingProfile,
 ) -> Result<(Vec<CodeChunk>, Vec<ParsedSymbol>)> {
-    let ext = Path::new(file_path)
-        .extension()
-        .and_then(|extension| extension.to_str())
-        .unwrap_or("");
-    let language = Language::from_extension(ext);
+    let language = detect_language(file_path, content).language;
+    let parsed = parse_detected_file(file_path, content, &language, profile)?;
+    Ok((parsed.chunks, parsed.symbols))
+}
 
-    if language == Language::Text {
-        return Ok((chunk_by_lines(content, &language), Vec::new()));
-    }
-
-    let mut parser = Parser::new();
-    let ts_language = match language {
-        Language::TypeScript | Language::TypeScriptTsx => {
-            tree_sitter_typescript::LANGUAGE_TSX.into()
-        }
-        Language::JavaScript | Language::JavaScriptJsx => tree_sitter_javascript::LANGUAGE.into(),
-        Language::Python => tree_sitter_python::LANGUAGE.into(),
-        Language::Rust => tree_sitter_rust::LANGUAGE.into(),
-        Language::Swift => tree_sitter_swift::LANGUAGE.into(),
-        Language::Go => tree_sitter_go::LANGUAGE.into(),
-        Language::Json => tree_sitter_json::LANGUAGE.into(),
-        Language::Java => tree_sitter_java::LANGUAGE.into(),
-        Language::CSharp => tree_sitter_c_sharp::LANGUAGE.into(),
-        Language::Ruby => tree_sitter_ruby::LANGUAGE.into(),
-        Language::Bash => tree_sitter_bash::LANGUAGE.into(),
-        Language::C => tree_sitter_c::LANGUAGE.into(),
-        Language::Cpp => tree_sitter_cpp::LANGUAGE.into(),
-        Language::Metal => tree_sitter_cpp::LANGUAGE.into(),
-        Language::Toml => tree_sitter_toml_ng::LANGUAGE.into(),
-        Language::Yaml => tree_sitter_yaml::LANGUAGE.into(),
-        Language::Php => tree_sitter_php::LANGUAGE_PHP.into(),
-        Language::Zig => tree_sitter_zig::LANGUAGE.into(),
-        Language::Gdscript => tree_sitter_gdscript::LANGUAGE.into(),
-        Language::Matlab => tree_sitter_matlab::LANGUAGE.into(),
-        Language::Apex => tree_sitter_sfapex::apex::LANGUAGE.into(),
-        _ => return Ok((chunk_by_lines(content, &language), Vec::new())),
+struct FileParse {
+    chunks: Vec<CodeChunk>,
+    symbols: Vec<ParsedSymbol>,
+    diagnostics: ParseDiagnostics,
+    calls: Option<Vec<CallSiteData>>,
+}
+
+fn parse_detected_file(
+    file_path: &str,
+    content: &str,
+    language: &Language,
+    profile: &ChunkingProfile,
+) -> Result<FileParse> {
+    let language = *language;
+    let config = profile.for_language(&language);
+
+    // Files without a tree have no call sites to report.
+    let no_calls = profile.include_calls.then(Vec::new);
+    let Some(ts_language) = grammar_for(&language) else {
+        return Ok(FileParse {
+            chunks: chunk_by_lines(content, &language, config),
+            symbols: Vec::new(),
+            diagnostics: ParseDiagnostics::default(),
+            calls: no_calls,
+        });
+    };
+
+    let tree = match parse_within_budget(&ts_language, file_path, content, None, &profile.budget)? {
+        BudgetedParse::Tree(tree) => tree,
+        BudgetedParse::OverBudget(reason) => {
+            return Ok(FileParse {
+                chunks: chunk_by_lines(content, &language, config),
+                symbols: Vec::new(),
+                diagnostics: ParseDiagnostics {
+                    fell_back_to_lines: true,
+                    fallback_reason: Some(reason.to_string()),
+                    ..ParseDiagnostics::default()
+                },
+                calls: no_calls,
+            });
+        }
     };
+    let (chunks, fell_back_to_lines) = extract_chunks_or_lines(&tree, content, &language, config)?;
+    let symbol_spans = extract_symbol_spans(&tree, content, &language);
+    let calls = if profile.include_calls {
+        let enclosers = Enclosers::from_spans(&symbol_spans, &tree, content);
+        let calls = call_extractor::calls_in_tree(&tree, content, &language, &enclosers)?;
+        let references =
+            reference_extractor::references_in_tree(&tree, content, &language, &calls, &enclosers)?;
+        Some(
+            calls
+                .into_iter()
+                .chain(references)
+                .map(crate::call_site_data)
+                .collect(),
+        )
+    } else {
+        None
+    };
+    let symbols = symbol_spans.into_iter().map(|(symbol, _)| symbol).collect();
+    let mut diagnostics = syntax_diagnostics(&tree, content);
+    // A clean file with no declarations (a config module of assignments, say)
+    // is chunked by lines by design; only a broken tree makes it a fallback.
+    if fell_back_to_lines && diagnostics.error_node_count + diagnostics.missing_node_count > 0 {
+        diagnostics.fell_back_to_lines = true;
+        diagnostics.fallback_reason = Some("syntax-errors".to_string());
+    }
+    Ok(FileParse {
+        chunks,
+        symbols,
+        diagnostics,
+        calls,
+    })
+}
+
+/// Counts the ERROR and MISSING nodes of `tree` and records the byte and line
+/// ranges of the first [`MAX_REPORTED_SYNTAX_ERRORS`] in document order.
+pub(crate) fn syntax_diagnostics(tree: &Tree, source: &str) -> ParseDiagnostics {
+    let mut diagnostics = ParseDiagnostics::default();
+    if !tree.root_node().has_error() {
+        return diagnostics;
+    }
+
+    let mut cursor = tree.walk();
+    'walk: loop {
+        let node = cursor.node();
+        let kind = if node.is_error() {
+            diagnostics.error_node_count += 1;
+            Some("error")
+        } else if node.is_missing() {
+            diagnostics.missing_node_count += 1;
+            Some("missing")
+        } else {
+            None
+        };
+        if let Some(kind) = kind {
+            if diagnostics.syntax_errors.len() < MAX_REPORTED_SYNTAX_ERRORS {
+                diagnostics.syntax_errors.push(SyntaxErrorRange {
+                    kind: kind.to_string(),
+                    start_byte: node.start_byte() as u32,
+                    end_byte: node.end_byte() as u32,
+                    start_line: node.start_position().row as u32 + 1,
+                    end_line: point_at_byte(source, node.end_byte()).row as u32 + 1,
+                });
+            }
+        }
+        // ERROR nodes may nest further errors; MISSING nodes are leaves.
+        if node.has_error() && cursor.goto_first_child() {
+            continue;
+        }
+        while !cursor.goto_next_sibling() {
+            if !cursor.goto_parent() {
+                break 'walk;
+            }
+        }
+    }
+    diagnostics
+}
 
-    parser.set_language(&ts_language)?;
-    let tree = parser
-        .parse(content, None)
-        .ok_or_else(|| anyhow!("Failed to parse file: {}", file_path))?;
-    let chunks = extract_chunks(&tree, content, &language)?;
-    let symbols = extract_symbols(&tree, content, &language);
-    Ok((chunks, symbols))
+pub(crate) fn extract_symbols(tree: &Tree, source: &str, language: &Language) -> Vec<ParsedSymbol> {
+    extract_symbol_spans(tree, source, language)
+        .into_iter()
+        .map(|(symbol, _)| symbol)
+        .collect()
 }
 
-fn extract_symbols(tree: &Tree, source: &str, language: &Language) -> Vec<ParsedSymbol> {
+/// Symbols with the byte range of their declaration, outer declarations
+/// before the ones they contain.
+pub(crate) fn extract_symbol_spans(
+    tree: &Tree,
+    source: &str,
+    language: &Language,
+) -> Vec<(ParsedSymbol, Range<usize>)> {
     let mut symbols = Vec::new();
     let mut cursor = tree.root_node().walk();
     extract_symbol_nodes(&mut cursor, source, language, &mut symbols, 0);
@@ -155,7 +378,7 @@ fn extract_symbol_nodes(
     cursor: &mut tree_sitter::TreeCursor,
     source: &str,
     language: &Language,
-    symbols: &mut Vec<ParsedSymbol>,
+    symbols: &mut Vec<(ParsedSymbol, Range<usize>)>,
     depth: usize,
 ) {
     const MAX_RECURSION_DEPTH: usize = 1024;
@@ -164,15 +387,30 @@ fn extract_symbol_nodes(
         let node = cursor.node();
         if is_semantic_node(node.kind(), language) && node.kind() != "export_statement" {
             if let Some(name) = extract_name(cursor, source, language) {
-                symbols.push(ParsedSymbol {
-                    name,
+                let scope = SymbolScope::of(node, Some(&name), source, language);
+                let comment = find_leading_comment(&declaration_anchor(node), source, language);
+                let details = DeclarationDetails::of(
+                    node,
+                    comment.as_ref().map(|(_, text)| text.as_str()),
+                    source,
+                    language,
+                );
+                let symbol = ParsedSymbol {
                     kind: semantic_chunk_type(&node, source, language),
                     start_line: node.start_position().row as u32 + 1,
                     start_col: node.start_position().column as u32,
                     end_line: node.end_position().row as u32 + 1,
                     end_col: node.end_position().column as u32,
                     language: language.as_str().to_string(),
-                });
+                    qualified_name: scope.qualified_name.unwrap_or_else(|| name.clone()),
+                    parent_symbol: scope.parent_symbol,
+                    depth: scope.depth,
+                    signature: details.signature,
+                    doc_comment: details.doc_comment,
+                    visibility: details.visibility,
+                    name,
+                };
+                symbols.push((symbol, node.byte_range()));
             }
         }
 
@@ -187,46 +425,62 @@ fn extract_symbol_nodes(
     }
 }
 
-fn extract_chunks(tree: &Tree, source: &str, language: &Language) -> Result<Vec<CodeChunk>> {
+pub(crate) fn extract_chunks(
+    tree: &Tree,
+    source: &str,
+    language: &Language,
+    config: &ChunkConfig,
+) -> Result<Vec<CodeChunk>> {
+    extract_chunks_or_lines(tree, source, language, config).map(|(chunks, _)| chunks)
+}
+
+/// Like [`extract_chunks`], also reporting whether no semantic chunk was
+/// found and the file was chunked by lines instead.
+fn extract_chunks_or_lines(
+    tree: &Tree,
+    source: &str,
+    language: &Language,
+    config: &ChunkConfig,
+) -> Result<(Vec<CodeChunk>, bool)> {
+    let markup_chunks = match language {
+        Language::Markdown => Some(crate::markup::extract_markdown_chunks(tree, source, config)),
+        Language::Html => Some(crate::markup::extract_html_chunks(tree, source, config)),
+        _ => None,
+    };
+    if let Some(chunks) = markup_chunks {
+        if chunks.is_empty() {
+            return Ok((chunk_by_lines(source, language, config), true));
+        }
+        return Ok((chunks, false));
+    }
+
     let mut chunks = Vec::new();
     let root = tree.root_node();
     let mut cursor = root.walk();
 
-    extract_semantic_nodes(&mut cursor, source, language, &mut chunks, 0);
+    extract_semantic_nodes(&mut cursor, source, language, config, &mut chunks, 0);
 
     if chunks.is_empty() {
-        return Ok(chunk_by_lines(source, language));
+        return Ok((chunk_by_lines(source, language, config), true));
     }
 
-    merge_small_chunks(&mut chunks);
+    if config.merge_small_chunks {
+        merge_small_chunks(&mut chunks, config);
+    }
 
-    Ok(chunks)
+    Ok((chunks, false))
 }
 
 fn extract_semantic_nodes(
     cursor: &mut tree_sitter::TreeCursor,
     source: &str,
     language: &Language,
+    config: &ChunkConfig,
     chunks: &mut Vec<CodeChunk>,
     depth: usize,
 ) {
-    #[cfg(debug_assertions)]
-    let start = Instant::now();
-    #[cfg(debug_assertions)]
-    {
-        let mut stats = PERF_STATS.lock().unwrap();
-        stats.extract_semantic_nodes_calls += 1;
-        stats.max_depth_reached = stats.max_depth_reached.max(depth);
-    }
-
     const MAX_RECURSION_DEPTH: usize = 1024;
     let skip_children = depth > MAX_RECURSION_DEPTH;
-    if skip_children {
-        #[cfg(debug_assertions)]
-        {
-            PERF_STATS.lock().unwrap().recursion_depth_exceeded_count += 1;
-        }
-    }
     loop {
         let node = cursor.node();
         let node_type = node.kind();
@@ -245,7 +499,7 @@ fn extract_semantic_nodes(
             let end_byte = node.end_byte();
 
             let leading_comment = find_leading_comment(&node, source, language);
-            if let Some((comment_start, _comment_text)) = &leading_comment {
+            if let Some((comment_start, _)) = &leading_comment {
                 start_byte = *comment_start;
             }
 
@@ -263,7 +517,7 @@ fn extract_semantic_nodes(
                 _ => false,
             };
 
-            if content.len() >= MIN_CHUNK_SIZE || preserve_small_declaration {
+            if content.len() >= config.min_size || preserve_small_declaration {
                 let (start_line, start_col) = if leading_comment.is_some() {
                     let prefix = &source[..start_byte];
                     let line = prefix.bytes().filter(|byte| *byte == b'\n').count() as u32 + 1;
@@ -276,6 +530,13 @@ fn extract_semantic_nodes(
                     )
                 };
 
+                let scope = SymbolScope::of(node, name.as_deref(), source, language);
+                let details = DeclarationDetails::of(
+                    node,
+                    leading_comment.as_ref().map(|(_, text)| text.as_str()),
+                    source,
+                    language,
+                );
                 let chunk = CodeChunk {
                     content: content.to_string(),
                     start_line,
@@ -285,12 +546,21 @@ fn extract_semantic_nodes(
                     chunk_type: semantic_chunk_type(&node, source, language),
                     name,
                     language: language.as_str().to_string(),
+                    fence_language: None,
+                    qualified_name: scope.qualified_name,
+                    parent_symbol: scope.parent_symbol,
+                    depth: Some(scope.depth),
+                    signature: details.signature,
+                    doc_comment: details.doc_comment,
+                    visibility: details.visibility,
+                    part_index: None,
+                    part_count: None,
                 };
 
-                if content.len() <= MAX_CHUNK_SIZE {
+                if fits_chunk_budget(content, config) {
                     chunks.push(chunk);
                 } else {
-                    split_large_chunk(chunk, chunks);
+                    split_by_syntax(node, chunk, start_byte, source, config, chunks);
                 }
             }
         }
@@ -308,7 +578,7 @@ fn extract_semantic_nodes(
             || descend_into_metal_type
             || descend_into_ts_abstract_class;
         if should_descend && !skip_children && cursor.goto_first_child() {
-            extract_semantic_nodes(cursor, source, language, chunks, depth + 1);
+            extract_semantic_nodes(cursor, source, language, config, chunks, depth + 1);
             cursor.goto_parent();
         }
 
@@ -316,12 +586,6 @@ fn extract_semantic_nodes(
             break;
         }
     }
-
-    #[cfg(debug_assertions)]
-    {
-        let elapsed = start.elapsed().as_micros();
-        PERF_STATS.lock().unwrap().extract_semantic_nodes_time += elapsed;
-    }
 }
 
 fn find_leading_comment(
@@ -329,13 +593,6 @@ fn find_leading_comment(
     source: &str,
     language: &Language,
 ) -> Option<(usize, String)> {
-    #[cfg(debug_assertions)]
-    let start = Instant::now();
-    #[cfg(debug_assertions)]
-    {
-        PERF_STATS.lock().unwrap().find_leading_comment_calls += 1;
-    }
-
     let mut prev = node.prev_sibling();
     let mut comments = Vec::new();
     let max_comment_siblings = if *language == Language::Swift {
@@ -366,16 +623,11 @@ fn find_leading_comment(
     let first_start = comments.first().map(|(s, _)| *s)?;
     let combined: String = comments
         .into_iter()
-        .map(|(start, end)| &source[start..end])
+        // Rust line comments include their newline.
+        .map(|(start, end)| source[start..end].trim_end_matches(['\r', '\n']))
         .collect::<Vec<_>>()
         .join("\n");
 
-    #[cfg(debug_assertions)]
-    {
-        let elapsed = start.elapsed().as_micros();
-        PERF_STATS.lock().unwrap().find_leading_comment_time += elapsed;
-    }
-
     Some((first_start, combined))
 }
 
@@ -427,329 +679,11 @@ fn find_matlab_leading_comment(
 }
 
 fn is_comment_node(node_type: &str, language: &Language) -> bool {
-    match language {
-        Language::TypeScript
-        | Language::TypeScriptTsx
-        | Language::JavaScript
-        | Language::JavaScriptJsx => matches!(node_type, "comment"),
-        Language::Python => matches!(node_type, "comment"),
-        Language::Rust => matches!(node_type, "line_comment" | "block_comment"),
-        Language::Swift => matches!(node_type, "comment" | "multiline_comment"),
-        Language::Go => matches!(node_type, "comment"),
-        Language::Java => matches!(node_type, "line_comment" | "block_comment"),
-        Language::CSharp => matches!(node_type, "comment"),
-        Language::Ruby => matches!(node_type, "comment"),
-        Language::Bash => matches!(node_type, "comment"),
-        Language::C | Language::Cpp | Language::Metal => matches!(node_type, "comment"),
-        Language::Toml => matches!(node_type, "comment"),
-        Language::Yaml => matches!(node_type, "comment"),
-        Language::Php => matches!(node_type, "comment"),
-        Language::Zig => matches!(node_type, "comment"),
-        Language::Gdscript => matches!(node_type, "comment"),
-        Language::Matlab => matches!(node_type, "comment"),
-        Language::Apex => matches!(node_type, "line_comment" | "block_comment"),
-        _ => false,
-    }
-}
-
-struct PerfStats {
-    extract_semantic_nodes_calls: usize,
-    extract_semantic_nodes_time: u128,
-    find_leading_comment_calls: usize,
-    find_leading_comment_time: u128,
-    extract_name_calls: usize,
-    extract_name_time: u128,
-    is_semantic_node_calls: usize,
-    is_semantic_node_time: u128,
-    recursion_depth_exceeded_count: usize,
-    max_depth_reached: usize,
-}
-
-impl PerfStats {
-    fn new() -> Self {
-        Self {
-            extract_semantic_nodes_calls: 0,
-            extract_semantic_nodes_time: 0,
-            find_leading_comment_calls: 0,
-            find_leading_comment_time: 0,
-            extract_name_calls: 0,
-            extract_name_time: 0,
-            is_semantic_node_calls: 0,
-            is_semantic_node_time: 0,
-            recursion_depth_exceeded_count: 0,
-            max_depth_reached: 0,
-        }
-    }
-
-    fn print(&self) {
-        eprintln!("=== Parser Performance Stats ===");
-        eprintln!(
-            "extract_semantic_nodes: {} calls, {} us",
-            self.extract_semantic_nodes_calls, self.extract_semantic_nodes_time
-        );
-        eprintln!(
-            "find_leading_comment: {} calls, {} us",
-            self.find_leading_comment_calls, self.find_leading_comment_time
-        );
-        eprintln!(
-            "extract_name: {} calls, {} us",
-            self.extract_name_calls, self.extract_name_time
-        );
-        eprintln!(
-            "is_semantic_node: {} calls, {} us",
-            self.is_semantic_node_calls, self.is_semantic_node_time
-        );
-        eprintln!(
-            "recursion_depth_exceeded: {} times",
-            self.recursion_depth_exceeded_count
-        );
-        eprintln!("max_depth_reached: {}", self.max_depth_reached);
-    }
-}
-
-pub fn print_parser_perf_stats() {
-    PERF_STATS.lock().unwrap().print();
-}
-
-lazy_static! {
-    static ref PERF_STATS: std::sync::Mutex<PerfStats> = std::sync::Mutex::new(PerfStats::new());
-    static ref TS_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        // Original 10 types
-        set.insert("function_declaration");
-        set.insert("function");
-        set.insert("arrow_function");
-        set.insert("method_definition");
-        set.insert("class_declaration");
-        set.insert("interface_declaration");
-        set.insert("type_alias_declaration");
-        set.insert("enum_declaration");
-        set.insert("export_statement");
-        set.insert("lexical_declaration");
-        set.insert("abstract_class_declaration");
-        // Added 5 most common statement types
-        set.insert("expression_statement");
-        set.insert("if_statement");
-        set.insert("for_statement");
-        set.insert("return_statement");
-        set.insert("try_statement");
-        set.insert("while_statement");
-        set.insert("statement_block");
-        set.insert("for_in_statement");
-        set
-    };
-    static ref PYTHON_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("class_definition");
-        set.insert("decorated_definition");
-        set
-    };
-    static ref RUST_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_item");
-        set.insert("impl_item");
-        set.insert("struct_item");
-        set.insert("enum_item");
-        set.insert("trait_item");
-        set.insert("mod_item");
-        set.insert("macro_definition");
-        set
-    };
-    static ref SWIFT_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("class_declaration");
-        set.insert("protocol_declaration");
-        set.insert("function_declaration");
-        set.insert("protocol_function_declaration");
-        set.insert("init_declaration");
-        set.insert("deinit_declaration");
-        set.insert("subscript_declaration");
-        set
-    };
-    static ref GO_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_declaration");
-        set.insert("method_declaration");
-        set.insert("type_declaration");
-        set.insert("type_spec");
-        set
-    };
-    static ref JAVA_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("class_declaration");
-        set.insert("method_declaration");
-        set.insert("constructor_declaration");
-        set.insert("interface_declaration");
-        set.insert("enum_declaration");
-        set.insert("annotation_type_declaration");
-        set
-    };
-    static ref CSHARP_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("class_declaration");
-        set.insert("method_declaration");
-        set.insert("constructor_declaration");
-        set.insert("interface_declaration");
-        set.insert("enum_declaration");
-        set.insert("struct_declaration");
-        set.insert("record_declaration");
-        set.insert("property_declaration");
-        set
-    };
-    static ref RUBY_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("method");
-        set.insert("singleton_method");
-        set.insert("class");
-        set.insert("module");
-        set
-    };
-    static ref BASH_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set
-    };
-    static ref C_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("struct_specifier");
-        set.insert("enum_specifier");
-        set.insert("type_definition");
-        set
-    };
-    static ref CPP_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("class_specifier");
-        set.insert("struct_specifier");
-        set.insert("enum_specifier");
-        set.insert("namespace_definition");
-        set.insert("template_declaration");
-        set
-    };
-    static ref METAL_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("class_specifier");
-        set.insert("struct_specifier");
-        set.insert("union_specifier");
-        set.insert("enum_specifier");
-        set.insert("type_definition");
-        set.insert("alias_declaration");
-        set
-    };
-    static ref TOML_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("table");
-        set.insert("table_array_element");
-        set
-    };
-    static ref YAML_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("block_mapping_pair");
-        set.insert("block_sequence");
-        set
-    };
-    static ref PHP_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("method_declaration");
-        set.insert("class_declaration");
-        set.insert("interface_declaration");
-        set.insert("trait_declaration");
-        set.insert("enum_declaration");
-        set
-    };
-    static ref ZIG_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_declaration");
-        set.insert("test_declaration");
-        set.insert("struct_declaration");
-        set.insert("enum_declaration");
-        set.insert("union_declaration");
-        set.insert("opaque_declaration");
-        set.insert("error_set_declaration");
-        set
-    };
-    // GDScript grammar (PrestonKnopp/tree-sitter-gdscript). Declaration-like
-    // nodes only — variable_statement is intentionally excluded because
-    // module-level `var foo = ...` lines would generate one chunk per
-    // variable and drown out real declarations.
-    static ref GDSCRIPT_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("constructor_definition");
-        set.insert("class_definition");
-        set.insert("enum_definition");
-        set.insert("signal_statement");
-        set.insert("const_statement");
-        set.insert("class_name_statement");
-        set
-    };
-    static ref MATLAB_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("function_definition");
-        set.insert("class_definition");
-        set
-    };
-    // Apex grammar (tree-sitter-sfapex) is Java-derived: the declaration node
-    // kinds match Java exactly, plus `trigger_declaration` which is unique to
-    // Apex (Salesforce database triggers). Verified against tree-sitter-sfapex
-    // 3.0 by parsing representative classes/triggers/interfaces.
-    static ref APEX_SEMANTIC_NODES: HashSet<&'static str> = {
-        let mut set = HashSet::new();
-        set.insert("class_declaration");
-        set.insert("method_declaration");
-        set.insert("constructor_declaration");
-        set.insert("interface_declaration");
-        set.insert("enum_declaration");
-        set.insert("trigger_declaration");
-        set
-    };
+    registry::definition(language).is_comment_node(node_type)
 }
 
 fn is_semantic_node(node_type: &str, language: &Language) -> bool {
-    #[cfg(debug_assertions)]
-    let start = Instant::now();
-    #[cfg(debug_assertions)]
-    {
-        PERF_STATS.lock().unwrap().is_semantic_node_calls += 1;
-    }
-
-    let result = match language {
-        Language::TypeScript
-        | Language::TypeScriptTsx
-        | Language::JavaScript
-        | Language::JavaScriptJsx => TS_SEMANTIC_NODES.contains(node_type),
-        Language::Python => PYTHON_SEMANTIC_NODES.contains(node_type),
-        Language::Rust => RUST_SEMANTIC_NODES.contains(node_type),
-        Language::Swift => SWIFT_SEMANTIC_NODES.contains(node_type),
-        Language::Go => GO_SEMANTIC_NODES.contains(node_type),
-        Language::Java => JAVA_SEMANTIC_NODES.contains(node_type),
-        Language::CSharp => CSHARP_SEMANTIC_NODES.contains(node_type),
-        Language::Ruby => RUBY_SEMANTIC_NODES.contains(node_type),
-        Language::Bash => BASH_SEMANTIC_NODES.contains(node_type),
-        Language::C => C_SEMANTIC_NODES.contains(node_type),
-        Language::Cpp => CPP_SEMANTIC_NODES.contains(node_type),
-        Language::Metal => METAL_SEMANTIC_NODES.contains(node_type),
-        Language::Toml => TOML_SEMANTIC_NODES.contains(node_type),
-        Language::Yaml => YAML_SEMANTIC_NODES.contains(node_type),
-        Language::Php => PHP_SEMANTIC_NODES.contains(node_type),
-        Language::Zig => ZIG_SEMANTIC_NODES.contains(node_type),
-        Language::Gdscript => GDSCRIPT_SEMANTIC_NODES.contains(node_type),
-        Language::Matlab => MATLAB_SEMANTIC_NODES.contains(node_type),
-        Language::Apex => APEX_SEMANTIC_NODES.contains(node_type),
-        _ => false,
-    };
-
-    #[cfg(debug_assertions)]
-    {
-        let elapsed = start.elapsed().as_micros();
-        PERF_STATS.lock().unwrap().is_semantic_node_time += elapsed;
-    }
-
-    result
+    registry::definition(language).is_semantic_node(node_type)
 }
 
 fn semantic_chunk_type(node: &tree_sitter::Node, source: &str, language: &Language) -> String {
@@ -794,13 +728,6 @@ fn extract_name(
     source: &str,
     language: &Language,
 ) -> Option<String> {
-    #[cfg(debug_assertions)]
-    let start = Instant::now();
-    #[cfg(debug_assertions)]
-    {
-        PERF_STATS.lock().unwrap().extract_name_calls += 1;
-    }
-
     let node = cursor.node();
 
     let fixed_name = match node.kind() {
@@ -810,21 +737,11 @@ fn extract_name(
         _ => None,
     };
     if let Some(name) = fixed_name {
-        #[cfg(debug_assertions)]
-        {
-            let elapsed = start.elapsed().as_micros();
-            PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-        }
         return Some(name.to_string());
     }
 
     if node.kind() == "arrow_function" {
         let name = extract_arrow_binding_name(node, source);
-        #[cfg(debug_assertions)]
-        {
-            let elapsed = start.elapsed().as_micros();
-            PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-        }
         return name;
     }
 
@@ -832,31 +749,16 @@ fn extract_name(
         if matches!(node.kind(), "function_definition" | "type_definition") {
             if let Some(declarator) = node.child_by_field_name("declarator") {
                 if let Some(name) = extract_declarator_name(declarator, source) {
-                    #[cfg(debug_assertions)]
-                    {
-                        let elapsed = start.elapsed().as_micros();
-                        PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-                    }
                     return Some(name);
                 }
             }
         }
         if let Some(name_node) = node.child_by_field_name("name") {
             if let Some(name) = extract_declarator_name(name_node, source) {
-                #[cfg(debug_assertions)]
-                {
-                    let elapsed = start.elapsed().as_micros();
-                    PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-                }
                 return Some(name);
             }
         }
         if let Some(name) = extract_declarator_name(node, source) {
-            #[cfg(debug_assertions)]
-            {
-                let elapsed = start.elapsed().as_micros();
-                PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-            }
             return Some(name);
         }
     }
@@ -891,11 +793,6 @@ fn extract_name(
 
     if let Some(name_node) = node.child_by_field_name("name") {
         if let Some(name) = extract_identifier(name_node) {
-            #[cfg(debug_assertions)]
-            {
-                let elapsed = start.elapsed().as_micros();
-                PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-            }
             return Some(name);
         }
 
@@ -904,11 +801,6 @@ fn extract_name(
             "function_declaration" | "protocol_function_declaration"
         ) && !name_node.is_named()
         {
-            #[cfg(debug_assertions)]
-            {
-                let elapsed = start.elapsed().as_micros();
-                PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-            }
             return Some(source[name_node.byte_range()].to_string());
         }
     }
@@ -916,11 +808,6 @@ fn extract_name(
     if node.kind() == "function_definition" {
         if let Some(declarator) = node.child_by_field_name("declarator") {
             if let Some(name) = extract_declarator_name(declarator, source) {
-                #[cfg(debug_assertions)]
-                {
-                    let elapsed = start.elapsed().as_micros();
-                    PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-                }
                 return Some(name);
             }
         }
@@ -929,11 +816,6 @@ fn extract_name(
     for i in 0..node.child_count() {
         if let Some(child) = node.child(i.try_into().unwrap()) {
             if let Some(name) = extract_identifier(child) {
-                #[cfg(debug_assertions)]
-                {
-                    let elapsed = start.elapsed().as_micros();
-                    PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-                }
                 return Some(name);
             }
         }
@@ -956,11 +838,6 @@ fn extract_name(
                     for j in 0..child.child_count() {
                         if let Some(grandchild) = child.child(j.try_into().unwrap()) {
                             if let Some(name) = extract_identifier(grandchild) {
-                                #[cfg(debug_assertions)]
-                                {
-                                    let elapsed = start.elapsed().as_micros();
-                                    PERF_STATS.lock().unwrap().extract_name_time += elapsed;
-                                }
                                 return Some(name);
                             }
                         }
@@ -975,12 +852,6 @@ fn extract_name(
                                             declarator.child(k.try_into().unwrap())
                                         {
                                             if name_node.kind() == "identifier" {
-                                                #[cfg(debug_assertions)]
-                                                {
-                                                    let elapsed = start.elapsed().as_micros();
-                                                    PERF_STATS.lock().unwrap().extract_name_time +=
-                                                        elapsed;
-                                                }
                                                 return Some(
                                                     source[name_node.start_byte()
                                                         ..name_node.end_byte()]
@@ -998,13 +869,85 @@ fn extract_name(
         }
     }
 
-    #[cfg(debug_assertions)]
-    {
-        let elapsed = start.elapsed().as_micros();
-        PERF_STATS.lock().unwrap().extract_name_time += elapsed;
+    None
+}
+
+/// The outermost wrapper (`export`, decorators, `template<>`) around a
+/// declaration; comments documenting the declaration sit before it.
+fn declaration_anchor(node: tree_sitter::Node<'_>) -> tree_sitter::Node<'_> {
+    match node.parent() {
+        Some(parent)
+            if matches!(
+                parent.kind(),
+                "export_statement" | "decorated_definition" | "template_declaration"
+            ) =>
+        {
+            declaration_anchor(parent)
+        }
+        _ => node,
     }
+}
 
-    None
+/// Where a declaration sits among the named declarations enclosing it.
+struct SymbolScope {
+    qualified_name: Option<String>,
+    parent_symbol: Option<String>,
+    depth: u32,
+}
+
+impl SymbolScope {
+    fn of(
+        node: tree_sitter::Node<'_>,
+        name: Option<&str>,
+        source: &str,
+        language: &Language,
+    ) -> Self {
+        let mut scopes = Vec::new();
+        let mut ancestor = node.parent();
+        while let Some(current) = ancestor {
+            // Wrappers share their name with the declaration they wrap.
+            let is_wrapper = matches!(
+                current.kind(),
+                "export_statement" | "decorated_definition" | "template_declaration"
+            );
+            if !is_wrapper && is_semantic_node(current.kind(), language) {
+                if let Some(scope) = scope_name(current, source, language) {
+                    scopes.push(scope);
+                }
+            }
+            ancestor = current.parent();
+        }
+        scopes.reverse();
+
+        let separator = scope_separator(language);
+        let parent_symbol = (!scopes.is_empty()).then(|| scopes.join(separator));
+        let qualified_name = name.map(|name| match &parent_symbol {
+            Some(parent) => format!("{}{}{}", parent, separator, name),
+            None => name.to_string(),
+        });
+        Self {
+            qualified_name,
+            parent_symbol,
+            depth: scopes.len() as u32,
+        }
+    }
+}
+
+fn scope_name(node: tree_sitter::Node<'_>, source: &str, language: &Language) -> Option<String> {
+    // `impl Trait for Type` scopes its items under the implementing type.
+    if node.kind() == "impl_item" {
+        return node
+            .child_by_field_name("type")
+            .and_then(|type_node| extract_declarator_name(type_node, source));
+    }
+    extract_name(&node.walk(), source, language)
+}
+
+fn scope_separator(language: &Language) -> &'static str {
+    match language {
+        Language::Rust | Language::C | Language::Cpp | Language::Metal | Language::Php => "::",
+        _ => ".",
+    }
 }
 
 fn extract_declarator_name(node: tree_sitter::Node<'_>, source: &str) -> Option<String> {
@@ -1052,52 +995,626 @@ fn extract_arrow_binding_name(node: tree_sitter::Node<'_>, source: &str) -> Opti
     extract_declarator_name(parent, source)
 }
 
-fn split_large_chunk(chunk: CodeChunk, chunks: &mut Vec<CodeChunk>) {
-    let lines: Vec<&str> = chunk.content.lines().collect();
-    let total_lines = lines.len();
+/// Whether `content` fits in one chunk under both the byte cap and the
+/// configured token budget.
+pub(crate) fn fits_chunk_budget(content: &str, config: &ChunkConfig) -> bool {
+    content.len() <= config.max_size && !config.exceeds_token_budget(content)
+}
+
+/// A byte range of an oversized node that becomes one sub-chunk, with the
+/// signature lines of its enclosing declarations to prepend.
+struct SyntaxSegment {
+    start: usize,
+    end: usize,
+    header: Option<String>,
+    /// A single statement that is still over budget and has no body to
+    /// descend into; it is split by lines instead.
+    oversized: bool,
+}
+
+/// Splits an oversized semantic node along the statements of its body so no
+/// piece ends mid-statement. Every piece after the first is prefixed with the
+/// signature line(s) of the node (and of any nested block it was cut from).
+/// Falls back to line windows when the node has no splittable body.
+fn split_by_syntax(
+    node: tree_sitter::Node<'_>,
+    chunk: CodeChunk,
+    start_byte: usize,
+    source: &str,
+    config: &ChunkConfig,
+    chunks: &mut Vec<CodeChunk>,
+) {
+    let mut segments = Vec::new();
+    let end_byte = node.end_byte();
+    if !collect_syntax_segments(
+        node,
+        start_byte..end_byte,
+        None,
+        None,
+        source,
+        config,
+        &mut segments,
+    ) {
+        split_large_chunk(chunk, config, chunks);
+        return;
+    }
+
+    let first = chunks.len();
+    for segment in segments {
+        let text = source[segment.start..segment.end].trim_end();
+        if text.trim().is_empty() {
+            continue;
+        }
+        let start = if segment.start == start_byte {
+            Point::new(chunk.start_line as usize - 1, chunk.start_col as usize)
+        } else {
+            point_at_byte(source, segment.start)
+        };
+        let end = point_at_byte(source, segment.start + text.len());
+        let piece = CodeChunk {
+            content: text.to_string(),
+            start_line: start.row as u32 + 1,
+            start_col: start.column as u32,
+            end_line: end.row as u32 + 1,
+            end_col: end.column as u32,
+            chunk_type: chunk.chunk_type.clone(),
+            name: chunk.name.clone(),
+            language: chunk.language.clone(),
+            fence_language: chunk.fence_language.clone(),
+            qualified_name: chunk.qualified_name.clone(),
+            parent_symbol: chunk.parent_symbol.clone(),
+            depth: chunk.depth,
+            signature: chunk.signature.clone(),
+            doc_comment: chunk.doc_comment.clone(),
+            visibility: chunk.visibility.clone(),
+            part_index: None,
+            part_count: None,
+        };
+
+        let from = chunks.len();
+        if segment.oversized {
+            match &segment.header {
+                Some(header) => {
+                    split_large_chunk(piece, &budget_after_header(config, header), chunks)
+                }
+                None => split_large_chunk(piece, config, chunks),
+            }
+        } else {
+            chunks.push(piece);
+        }
+        if let Some(header) = &segment.header {
+            for piece in &mut chunks[from..] {
+                piece.content = format!("{}\n{}", header, piece.content);
+            }
+        }
+    }
+    number_parts(&mut chunks[first..]);
+}
+
+/// The limits left for a piece once `header` and its newline are prepended to
+/// it. The extra token covers a merge across the newline.
+fn budget_after_header(config: &ChunkConfig, header: &str) -> ChunkConfig {
+    let max_size = config.max_size.saturating_sub(header.len() + 1).max(1);
+    ChunkConfig {
+        max_size,
+        target_size: config.target_size.min(max_size),
+        reserved_tokens: match config.max_tokens {
+            Some(_) => config.reserved_tokens + config.tokenizer.count_tokens(header) + 1,
+            None => config.reserved_tokens,
+        },
+        ..config.clone()
+    }
+}
+
+/// Cuts `range` (which covers `node`) at the statement boundaries of the
+/// node's body, packing consecutive statements while the piece and its header
+/// stay within budget. `lead` is the header for a piece starting at
+/// `range.start`; `context` holds the headers of enclosing nodes. Returns
+/// `false` when the node has no body with at least two statements.
+fn collect_syntax_segments(
+    node: tree_sitter::Node<'_>,
+    range: std::ops::Range<usize>,
+    lead: Option<&str>,
+    context: Option<&str>,
+    source: &str,
+    config: &ChunkConfig,
+    segments: &mut Vec<SyntaxSegment>,
+) -> bool {
+    let Some(body) = splittable_body(node) else {
+        return false;
+    };
+    let mut cursor = body.walk();
+    let statements: Vec<_> = body.named_children(&mut cursor).collect();
+    if statements.len() < 2 {
+        return false;
+    }
+
+    let own_header = source[line_start(source, node.start_byte())
+        ..statement_boundary(source, statements[0].start_byte())]
+        .trim_end();
+    if own_header.trim().is_empty() {
+        return false;
+    }
+    let header = match context {
+        Some(context) => format!("{}\n{}", context, own_header),
+        None => own_header.to_string(),
+    };
+    // Pieces carry the header, so it may take at most half of either budget.
+    if header.len() > config.max_size / 2
+        || config
+            .content_token_budget()
+            .is_some_and(|max| config.tokenizer.count_tokens(&header) + 1 > max / 2)
+    {
+        return false;
+    }
+
+    // Unit k spans statement k, from its line start to the next statement's.
+    let mut bounds = vec![range.start];
+    for statement in &statements[1..] {
+        let boundary = statement_boundary(source, statement.start_byte());
+        if boundary > *bounds.last().unwrap() && boundary < range.end {
+            bounds.push(boundary);
+        }
+    }
+    bounds.push(range.end);
+    let header_at = |start: usize| {
+        if start == range.start {
+            lead
+        } else {
+            Some(header.as_str())
+        }
+    };
+    let fits = |start: usize, end: usize| {
+        let text = source[start..end].trim_end();
+        let header_len = header_at(start).map_or(0, |header| header.len() + 1);
+        if header_len + text.len() > config.max_size {
+            return false;
+        }
+        match (config.max_tokens, header_at(start)) {
+            (None, _) => true,
+            (_, Some(header)) => !config.exceeds_token_budget(&format!("{}\n{}", header, text)),
+            (_, None) => !config.exceeds_token_budget(text),
+        }
+    };
+
+    let units = bounds.len() - 1;
+    let mut unit = 0;
+    while unit < units {
+        let start = bounds[unit];
+        if !fits(start, bounds[unit + 1]) {
+            let statement = statements
+                .iter()
+                .rev()
+                .find(|statement| statement.start_byte() < bounds[unit + 1])
+                .copied()
+                .unwrap_or(statements[0]);
+            let descended = collect_syntax_segments(
+                statement,
+                start..bounds[unit + 1],
+                header_at(start),
+                Some(&header),
+                source,
+                config,
+                segments,
+            );
+            if !descended {
+                segments.push(SyntaxSegment {
+                    start,
+                    end: bounds[unit + 1],
+                    header: header_at(start).map(str::to_string),
+                    oversized: true,
+                });
+            }
+            unit += 1;
+            continue;
+        }
+
+        let mut end_unit = unit + 1;
+        while end_unit < units && fits(start, bounds[end_unit + 1]) {
+            end_unit += 1;
+        }
+        segments.push(SyntaxSegment {
+            start,
+            end: bounds[end_unit],
+            header: header_at(start).map(str::to_string),
+            oversized: false,
+        });
+        unit = end_unit;
+    }
+    true
+}
+
+/// The block holding a node's statements or members, if it has one.
+fn splittable_body(node: tree_sitter::Node<'_>) -> Option<tree_sitter::Node<'_>> {
+    // `export function f() {}` and decorated definitions wrap the declaration.
+    if let Some(declaration) = node.child_by_field_name("declaration").or_else(|| {
+        (node.kind() == "decorated_definition")
+            .then(|| node.child_by_field_name("definition"))
+            .flatten()
+    }) {
+        return splittable_body(declaration);
+    }
+    node.child_by_field_name("body")
+        .or_else(|| node.child_by_field_name("consequence"))
+        .or_else(|| {
+            let mut cursor = node.walk();
+            let block = node.named_children(&mut cursor).find(|child| {
+                matches!(
+                    child.kind(),
+                    "block"
+                        | "statement_block"
+                        | "compound_statement"
+                        | "declaration_list"
+                        | "class_body"
+                        | "function_body"
+                )
+            });
+            block
+        })
+        .filter(|body| body.named_child_count() > 0)
+}
+
+fn line_start(source: &str, byte: usize) -> usize {
+    source[..byte].rfind('\n').map_or(0, |idx| idx + 1)
+}
+
+/// Where a piece starting with the statement at `byte` begins: the start of
+/// its line when only indentation precedes it, else the statement itself.
+fn statement_boundary(source: &str, byte: usize) -> usize {
+    let start = line_start(source, byte);
+    if source[start..byte].trim().is_empty() {
+        start
+    } else {
+        byte
+    }
+}
+
+/// Marks `pieces` as parts of one split node.
+fn number_parts(pieces: &mut [CodeChunk]) {
+    if pieces.len() < 2 {
+        return;
+    }
+    let count = pieces.len() as u32;
+    for (index, piece) in pieces.iter_mut().enumerate() {
+        piece.part_index = Some(index as u32);
+        piece.part_count = Some(count);
+    }
+}
+
+pub(crate) fn split_large_chunk(
+    chunk: CodeChunk,
+    config: &ChunkConfig,
+    chunks: &mut Vec<CodeChunk>,
+) {
+    let first = chunks.len();
+    if let Some(max_tokens) = config.content_token_budget() {
+        split_by_token_budget(chunk, &*config.tokenizer, max_tokens, config, chunks);
+    } else {
+        split_by_line_windows(chunk, config, chunks);
+    }
+    number_parts(&mut chunks[first..]);
+}
+
+fn split_by_line_windows(chunk: CodeChunk, config: &ChunkConfig, chunks: &mut Vec<CodeChunk>) {
+    let lines: Vec<&str> = chunk.content.lines().collect();
+    let total_lines = lines.len();
+
+    if total_lines <= 1 {
+        split_by_bytes(chunk, config, chunks);
+        return;
+    }
+
+    let lines_per_chunk = config.split_window_lines();
+    let step_size = if lines_per_chunk > config.overlap_lines {
+        lines_per_chunk - config.overlap_lines
+    } else {
+        lines_per_chunk
+    };
+    let mut start = 0;
+
+    while start < total_lines {
+        let end = std::cmp::min(start + lines_per_chunk, total_lines);
+        let sub_content: String = lines[start..end].join("\n");
+
+        if sub_content.len() >= config.min_size {
+            let window = CodeChunk {
+                content: sub_content,
+                start_line: chunk.start_line + start as u32,
+                start_col: if start == 0 { chunk.start_col } else { 0 },
+                end_line: chunk.start_line + end as u32 - 1,
+                end_col: if end == total_lines {
+                    chunk.end_col
+                } else {
+                    lines[end - 1].len() as u32
+                },
+                chunk_type: chunk.chunk_type.clone(),
+                name: chunk.name.clone(),
+                language: chunk.language.clone(),
+                fence_language: chunk.fence_language.clone(),
+                qualified_name: chunk.qualified_name.clone(),
+                parent_symbol: chunk.parent_symbol.clone(),
+                depth: chunk.depth,
+                signature: chunk.signature.clone(),
+                doc_comment: chunk.doc_comment.clone(),
+                visibility: chunk.visibility.clone(),
+                part_index: None,
+                part_count: None,
+            };
+            split_by_bytes(window, config, chunks);
+        }
+
+        if end >= total_lines {
+            break;
+        }
+        start += step_size;
+    }
+}
+
+/// Pushes `chunk` as is when it fits `config.max_size`, and otherwise cuts it
+/// into pieces of at most that many bytes on char boundaries, breaking at the
+/// last whitespace of each window when one falls in its back half. Catches
+/// what line windows cannot, such as a minified one-line bundle.
+fn split_by_bytes(chunk: CodeChunk, config: &ChunkConfig, chunks: &mut Vec<CodeChunk>) {
+    if chunk.content.len() <= config.max_size {
+        chunks.push(chunk);
+        return;
+    }
+
+    let content = chunk.content.as_str();
+    let max_bytes = config.max_size.max(4);
+    let mut start = 0;
+    let mut line = chunk.start_line;
+    let mut col = chunk.start_col as usize;
+    while start < content.len() {
+        let (end, next) = if content.len() - start <= max_bytes {
+            (content.len(), content.len())
+        } else {
+            let mut end = start + max_bytes;
+            while !content.is_char_boundary(end) {
+                end -= 1;
+            }
+            match content[start..end].rfind(char::is_whitespace) {
+                Some(offset) if offset > max_bytes / 2 => {
+                    let cut = start + offset;
+                    let width = content[cut..].chars().next().map_or(1, char::len_utf8);
+                    (cut, cut + width)
+                }
+                _ => (end, end),
+            }
+        };
+
+        let piece = &content[start..end];
+        let (end_line, end_col) = match piece.rfind('\n') {
+            Some(newline) => (
+                line + piece.matches('\n').count() as u32,
+                piece.len() - newline - 1,
+            ),
+            None => (line, col + piece.len()),
+        };
+        if !piece.trim().is_empty() {
+            chunks.push(CodeChunk {
+                content: piece.to_string(),
+                start_line: line,
+                start_col: col as u32,
+                end_line,
+                end_col: end_col as u32,
+                chunk_type: chunk.chunk_type.clone(),
+                name: chunk.name.clone(),
+                language: chunk.language.clone(),
+                fence_language: chunk.fence_language.clone(),
+                qualified_name: chunk.qualified_name.clone(),
+                parent_symbol: chunk.parent_symbol.clone(),
+                depth: chunk.depth,
+                signature: chunk.signature.clone(),
+                doc_comment: chunk.doc_comment.clone(),
+                visibility: chunk.visibility.clone(),
+                part_index: None,
+                part_count: None,
+            });
+        }
+
+        // The whitespace broken at belongs to neither piece.
+        if content[end..next].contains('\n') {
+            (line, col) = (end_line + 1, 0);
+        } else {
+            (line, col) = (end_line, end_col + (next - end));
+        }
+        start = next;
+    }
+}
+
+/// A slice of one line of a chunk, measured in tokens.
+struct LineSegment<'a> {
+    line: usize,
+    col: usize,
+    text: &'a str,
+    tokens: usize,
+}
 
-    if total_lines <= 1 {
-        chunks.push(chunk);
-        return;
+/// Splits `chunk` into pieces of at most `max_tokens` tokens (and
+/// `config.max_size` bytes), packing whole lines where possible and carrying
+/// `config.overlap_lines` lines of context into the next piece. Lines that are too
+/// long on their own are broken at whitespace.
+fn split_by_token_budget(
+    chunk: CodeChunk,
+    tokenizer: &dyn Tokenizer,
+    max_tokens: usize,
+    config: &ChunkConfig,
+    chunks: &mut Vec<CodeChunk>,
+) {
+    let mut segments = Vec::new();
+    for (line, text) in chunk.content.lines().enumerate() {
+        segment_line(
+            line,
+            text,
+            tokenizer,
+            max_tokens,
+            config.max_size,
+            &mut segments,
+        );
     }
 
-    let lines_per_chunk = TARGET_CHUNK_SIZE / 40;
-    let step_size = if lines_per_chunk > OVERLAP_LINES {
-        lines_per_chunk - OVERLAP_LINES
-    } else {
-        lines_per_chunk
+    let join = |segments: &[LineSegment]| {
+        let mut content = String::new();
+        for (idx, segment) in segments.iter().enumerate() {
+            if idx > 0 && segments[idx - 1].line != segment.line {
+                content.push('\n');
+            }
+            content.push_str(segment.text);
+        }
+        content
+    };
+    let col_of = |segment: &LineSegment| {
+        segment.col as u32
+            + if segment.line == 0 {
+                chunk.start_col
+            } else {
+                0
+            }
     };
+
     let mut start = 0;
+    // Where the last piece pushed here starts.
+    let mut previous = None;
+    while start < segments.len() {
+        let mut end = start;
+        let mut tokens = 0;
+        let mut bytes = 0;
+        while end < segments.len() {
+            let segment = &segments[end];
+            if end > start
+                && (tokens + segment.tokens > max_tokens
+                    || bytes + segment.text.len() + 1 > config.max_size)
+            {
+                break;
+            }
+            tokens += segment.tokens;
+            bytes += segment.text.len() + 1;
+            end += 1;
+        }
 
-    while start < total_lines {
-        let end = std::cmp::min(start + lines_per_chunk, total_lines);
-        let sub_content: String = lines[start..end].join("\n");
+        // Per-line counts are an estimate of the joined count; trim until the
+        // real count fits.
+        let mut content = join(&segments[start..end]);
+        while end - start > 1 && tokenizer.count_tokens(&content) > max_tokens {
+            end -= 1;
+            content = join(&segments[start..end]);
+        }
 
-        if sub_content.len() >= MIN_CHUNK_SIZE {
+        // A short tail joins the previous piece when both fit together, and
+        // is kept on its own otherwise so no content is lost.
+        let mut piece_start = start;
+        if content.len() < config.min_size {
+            if let Some(previous_start) = previous {
+                let merged = join(&segments[previous_start..end]);
+                if merged.len() <= config.max_size && tokenizer.count_tokens(&merged) <= max_tokens
+                {
+                    chunks.pop();
+                    piece_start = previous_start;
+                    content = merged;
+                }
+            }
+        }
+
+        if !content.trim().is_empty() {
+            let first = &segments[piece_start];
+            let last = &segments[end - 1];
+            previous = Some(piece_start);
             chunks.push(CodeChunk {
-                content: sub_content,
-                start_line: chunk.start_line + start as u32,
-                start_col: if start == 0 { chunk.start_col } else { 0 },
-                end_line: chunk.start_line + end as u32 - 1,
-                end_col: if end == total_lines {
-                    chunk.end_col
-                } else {
-                    lines[end - 1].len() as u32
-                },
+                content,
+                start_line: chunk.start_line + first.line as u32,
+                start_col: col_of(first),
+                end_line: chunk.start_line + last.line as u32,
+                end_col: col_of(last) + last.text.len() as u32,
                 chunk_type: chunk.chunk_type.clone(),
                 name: chunk.name.clone(),
                 language: chunk.language.clone(),
+                fence_language: chunk.fence_language.clone(),
+                qualified_name: chunk.qualified_name.clone(),
+                parent_symbol: chunk.parent_symbol.clone(),
+                depth: chunk.depth,
+                signature: chunk.signature.clone(),
+                doc_comment: chunk.doc_comment.clone(),
+                visibility: chunk.visibility.clone(),
+                part_index: None,
+                part_count: None,
             });
         }
 
-        if end >= total_lines {
+        if end >= segments.len() {
             break;
         }
-        start += step_size;
+        start = end.saturating_sub(config.overlap_lines).max(start + 1);
+    }
+}
+
+fn segment_line<'a>(
+    line: usize,
+    text: &'a str,
+    tokenizer: &dyn Tokenizer,
+    max_tokens: usize,
+    max_bytes: usize,
+    segments: &mut Vec<LineSegment<'a>>,
+) {
+    let fits = |piece: &str| {
+        let tokens = tokenizer.count_tokens(piece);
+        (piece.len() <= max_bytes && tokens <= max_tokens).then_some(tokens)
+    };
+
+    if let Some(tokens) = fits(text) {
+        segments.push(LineSegment {
+            line,
+            col: 0,
+            text,
+            tokens,
+        });
+        return;
+    }
+
+    let mut push = |start: usize, end: usize| {
+        let piece = &text[start..end];
+        segments.push(LineSegment {
+            line,
+            col: start,
+            text: piece,
+            tokens: tokenizer.count_tokens(piece),
+        });
+    };
+
+    let mut start = 0;
+    let mut end = 0;
+    for word in text.split_inclusive(char::is_whitespace) {
+        let word_start = end;
+        let word_end = word_start + word.len();
+        if end > start && fits(&text[start..word_end]).is_none() {
+            push(start, end);
+            start = end;
+        }
+        if fits(word).is_none() {
+            // A single word (minified code, long literals) over budget is cut
+            // into character windows small enough for any byte-level vocab.
+            let window = (max_tokens / 4).clamp(1, (max_bytes / 4).max(1));
+            let mut piece_start = word_start;
+            let mut char_count = 0;
+            for (offset, _) in word.char_indices() {
+                if char_count == window {
+                    push(piece_start, word_start + offset);
+                    piece_start = word_start + offset;
+                    char_count = 0;
+                }
+                char_count += 1;
+            }
+            push(piece_start, word_end);
+            start = word_end;
+        }
+        end = word_end;
+    }
+    if end > start {
+        push(start, end);
     }
 }
 
-fn merge_small_chunks(chunks: &mut Vec<CodeChunk>) {
+fn merge_small_chunks(chunks: &mut Vec<CodeChunk>, config: &ChunkConfig) {
     if chunks.len() < 2 {
         return;
     }
@@ -1125,10 +1642,17 @@ fn merge_small_chunks(chunks: &mut Vec<CodeChunk>) {
         let can_merge_without_losing_symbol =
             !is_preserved_call_graph_symbol(&cur) && !is_preserved_call_graph_symbol(&chunk);
 
+        let is_part = cur.part_index.is_some() || chunk.part_index.is_some();
+
         if can_merge_without_losing_symbol
-            && cur.content.len() < MIN_CHUNK_SIZE * 2
-            && cur.content.len() + chunk.content.len() <= MAX_CHUNK_SIZE
+            && !is_part
+            && cur.content.len() < config.min_size * 2
+            && cur.content.len() + chunk.content.len() <= config.max_size
             && cur.end_line + 1 >= chunk.start_line
+            && (config.max_tokens.is_none()
+                || !config.exceeds_token_budget(
+                    &[cur.content.as_str(), "\n\n", chunk.content.as_str()].concat(),
+                ))
         {
             cur.content.push_str("\n\n");
             cur.content.push_str(&chunk.content);
@@ -1148,7 +1672,11 @@ fn merge_small_chunks(chunks: &mut Vec<CodeChunk>) {
     *chunks = merged;
 }
 
-fn chunk_by_lines(content: &str, language: &Language) -> Vec<CodeChunk> {
+pub(crate) fn chunk_by_lines(
+    content: &str,
+    language: &Language,
+    config: &ChunkConfig,
+) -> Vec<CodeChunk> {
     let lines: Vec<&str> = content.lines().collect();
     let total_lines = lines.len();
 
@@ -1156,9 +1684,9 @@ fn chunk_by_lines(content: &str, language: &Language) -> Vec<CodeChunk> {
         return Vec::new();
     }
 
-    let lines_per_chunk = 30;
-    let step_size = if lines_per_chunk > OVERLAP_LINES {
-        lines_per_chunk - OVERLAP_LINES
+    let lines_per_chunk = config.text_window_lines();
+    let step_size = if lines_per_chunk > config.overlap_lines {
+        lines_per_chunk - config.overlap_lines
     } else {
         lines_per_chunk
     };
@@ -1170,7 +1698,7 @@ fn chunk_by_lines(content: &str, language: &Language) -> Vec<CodeChunk> {
         let sub_content: String = lines[start..end].join("\n");
 
         if !sub_content.trim().is_empty() {
-            chunks.push(CodeChunk {
+            let chunk = CodeChunk {
                 content: sub_content,
                 start_line: start as u32 + 1,
                 start_col: 0,
@@ -1179,7 +1707,23 @@ fn chunk_by_lines(content: &str, language: &Language) -> Vec<CodeChunk> {
                 chunk_type: "block".to_string(),
                 name: None,
                 language: language.as_str().to_string(),
-            });
+                fence_language: None,
+                qualified_name: None,
+                parent_symbol: None,
+                depth: None,
+                signature: None,
+                doc_comment: None,
+                visibility: None,
+                part_index: None,
+                part_count: None,
+            };
+            if config.exceeds_token_budget(&chunk.content) {
+                split_large_chunk(chunk, config, &mut chunks);
+            } else {
+                let first = chunks.len();
+                split_by_bytes(chunk, config, &mut chunks);
+                number_parts(&mut chunks[first..]);
+            }
         }
 
         if end >= total_lines {
@@ -1194,6 +1738,7 @@ fn chunk_by_lines(content: &str, language: &Language) -> Vec<CodeChunk> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use tree_sitter::Parser;
 
     fn assert_php_parses_without_errors(content: &str) {
         let mut parser = Parser::new();
@@ -1383,10 +1928,63 @@ class Greeter {
 }
 "#;
 
-        let chunks = parse_file_internal("test.ts", content).unwrap();
+        let chunks = parse_file_internal("test.ts", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty());
     }
 
+    #[test]
+    fn test_parse_paths_parallel_reads_hashes_and_reports_unreadable_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let source = "export function greet(name: string): string {\n    return `Hello, ${name}! Nice to meet you today.`;\n}\n";
+        let good = dir.path().join("greet.ts");
+        std::fs::write(&good, source).unwrap();
+        let invalid = dir.path().join("latin1.py");
+        std::fs::write(&invalid, b"# caf\xe9\ndef run():\n    return 1\n").unwrap();
+        let emoji = dir.path().join("face.ts");
+        std::fs::write(&emoji, "export const face = \"\u{1F600}\";").unwrap();
+        let missing = dir.path().join("missing.ts");
+
+        let paths = [&good, &invalid, &emoji, &missing]
+            .iter()
+            .map(|path| path.to_string_lossy().to_string())
+            .collect::<Vec<_>>();
+        let parsed = parse_paths_parallel(&paths, &ChunkingProfile::default());
+
+        assert_eq!(parsed.len(), 4);
+        let greet = parsed
+            .iter()
+            .find(|file| file.path.ends_with("greet.ts"))
+            .unwrap();
+        assert_eq!(greet.hash, crate::hasher::xxhash_content(source));
+        assert!(greet.symbols.iter().any(|symbol| symbol.name == "greet"));
+        assert_eq!((greet.line_count, greet.last_line_length), (4, 0));
+
+        let latin1 = parsed
+            .iter()
+            .find(|file| file.path.ends_with("latin1.py"))
+            .unwrap();
+        assert!(latin1.symbols.iter().any(|symbol| symbol.name == "run"));
+
+        let face = parsed
+            .iter()
+            .find(|file| file.path.ends_with("face.ts"))
+            .unwrap();
+        assert_eq!((face.line_count, face.last_line_length), (1, 25));
+
+        let missing = parsed
+            .iter()
+            .find(|file| file.path.ends_with("missing.ts"))
+            .unwrap();
+        assert!(missing.chunks.is_empty());
+        assert!(missing.hash.is_empty());
+        assert_eq!(missing.language, "typescript");
+        assert!(missing
+            .diagnostics
+            .error
+            .as_deref()
+            .is_some_and(|error| error.starts_with("Failed to read")));
+    }
+
     #[test]
     fn test_extract_arrow_function_name_uses_variable_binding() {
         let content = r#"
@@ -1398,8 +1996,9 @@ class Greeter {
 	const values = [1, 2].map(item => item * 2);
 	"#;
 
-        let (_chunks, symbols) = parse_file_with_symbols_internal("arrows.ts", content)
-            .expect("should parse TypeScript arrow functions");
+        let (_chunks, symbols) =
+            parse_file_with_symbols_internal("arrows.ts", content, &ChunkingProfile::default())
+                .expect("should parse TypeScript arrow functions");
 
         let arrow_names: Vec<String> = symbols
             .iter()
@@ -1428,8 +2027,9 @@ class Greeter {
 	}
 	"#;
 
-        let (_chunks, symbols) = parse_file_with_symbols_internal("animal.ts", content)
-            .expect("should parse exported abstract class");
+        let (_chunks, symbols) =
+            parse_file_with_symbols_internal("animal.ts", content, &ChunkingProfile::default())
+                .expect("should parse exported abstract class");
 
         assert!(symbols.iter().any(|symbol| {
             symbol.kind == "class_declaration" && symbol.name == "AbstractAnimal"
@@ -1459,7 +2059,7 @@ class Greeter:
         return f"Hello, {self.name}!"
 "#;
 
-        let chunks = parse_file_internal("test.py", content).unwrap();
+        let chunks = parse_file_internal("test.py", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty());
     }
 
@@ -1473,7 +2073,9 @@ class Greeter:
     #[test]
     fn test_parse_php_8_semantic_chunks_have_names() {
         let content = include_str!("../../tests/fixtures/call-graph/php-8-features.php");
-        let chunks = parse_file_internal("php-8-features.php", content).unwrap();
+        let chunks =
+            parse_file_internal("php-8-features.php", content, &ChunkingProfile::default())
+                .unwrap();
 
         assert!(chunks.iter().any(|chunk| {
             chunk.chunk_type == "class_declaration" && chunk.name.as_deref() == Some("Job")
@@ -1493,6 +2095,35 @@ class Greeter:
         }));
     }
 
+    #[test]
+    fn test_chunk_by_lines_splits_one_line_bundle_by_bytes() {
+        let bundle: String = (0..3000)
+            .map(|i| format!("var a{i}=\"é{i}\";"))
+            .collect::<Vec<_>>()
+            .join(" ");
+        let config = ChunkConfig::default();
+
+        let chunks = chunk_by_lines(&bundle, &Language::JavaScript, &config);
+
+        assert!(chunks.len() > bundle.len() / config.max_size);
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.content.len() <= config.max_size));
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.start_line == 1 && chunk.end_line == 1));
+        assert_eq!(chunks[0].start_col, 0);
+        let last = chunks.last().unwrap();
+        assert_eq!(last.end_col as usize, bundle.len());
+        assert_eq!(last.part_count, Some(chunks.len() as u32));
+        let rejoined = chunks
+            .iter()
+            .map(|chunk| chunk.content.as_str())
+            .collect::<Vec<_>>()
+            .join(" ");
+        assert_eq!(rejoined, bundle);
+    }
+
     #[test]
     fn test_chunk_overlap() {
         let lines: Vec<String> = (0..100)
@@ -1500,7 +2131,7 @@ class Greeter:
             .collect();
         let content = lines.join("\n");
 
-        let chunks = chunk_by_lines(&content, &Language::Text);
+        let chunks = chunk_by_lines(&content, &Language::Text, &ChunkConfig::default());
 
         assert!(chunks.len() >= 2, "Should have multiple chunks");
 
@@ -1516,6 +2147,452 @@ class Greeter:
         }
     }
 
+    #[test]
+    fn test_symbols_carry_qualified_names_and_nesting() {
+        let content = r#"
+class UserService:
+    def save(self, user):
+        def validate(value):
+            return value is not None
+        return validate(user)
+
+def main():
+    pass
+"#;
+        let (_chunks, symbols) =
+            parse_file_with_symbols_internal("users.py", content, &ChunkingProfile::default())
+                .unwrap();
+        let find = |name: &str| symbols.iter().find(|symbol| symbol.name == name).unwrap();
+
+        let service = find("UserService");
+        assert_eq!(service.qualified_name, "UserService");
+        assert_eq!(service.parent_symbol, None);
+        assert_eq!(service.depth, 0);
+
+        let save = find("save");
+        assert_eq!(save.qualified_name, "UserService.save");
+        assert_eq!(save.parent_symbol.as_deref(), Some("UserService"));
+        assert_eq!(save.depth, 1);
+
+        assert_eq!(find("validate").qualified_name, "UserService.save.validate");
+        assert_eq!(find("validate").depth, 2);
+        assert_eq!(find("main").depth, 0);
+    }
+
+    #[test]
+    fn test_symbols_expose_signature_docs_and_visibility() {
+        let profile = ChunkingProfile::default();
+        let symbol = |path: &str, content: &str, name: &str| {
+            let (_chunks, symbols) =
+                parse_file_with_symbols_internal(path, content, &profile).unwrap();
+            symbols
+                .into_iter()
+                .find(|symbol| symbol.name == name)
+                .unwrap()
+        };
+
+        let python = symbol(
+            "users.py",
+            "class UserService:\n    def _save(self, user: User,\n              force: bool = False) -> bool:\n        \"\"\"Persists the user.\n\n        Returns whether a write happened.\n        \"\"\"\n        return True\n",
+            "_save",
+        );
+        assert_eq!(
+            python.signature.as_deref(),
+            Some("(self, user: User, force: bool = False) -> bool")
+        );
+        assert_eq!(
+            python.doc_comment.as_deref(),
+            Some("Persists the user.\n\nReturns whether a write happened.")
+        );
+        assert_eq!(python.visibility.as_deref(), Some("private"));
+
+        let rust = symbol(
+            "lib.rs",
+            "/// Loads the config.\n///\n/// Falls back to defaults.\npub(crate) fn load(path: &Path) -> Result<Config> {\n    todo!()\n}\n",
+            "load",
+        );
+        assert_eq!(
+            rust.signature.as_deref(),
+            Some("(path: &Path) -> Result<Config>")
+        );
+        assert_eq!(
+            rust.doc_comment.as_deref(),
+            Some("Loads the config.\n\nFalls back to defaults.")
+        );
+        assert_eq!(rust.visibility.as_deref(), Some("internal"));
+
+        let typescript = symbol(
+            "user.ts",
+            "/**\n * Saves a user.\n * @param user the user\n */\nexport function save(user: User): Promise<void> {\n  return store(user);\n}\n",
+            "save",
+        );
+        assert_eq!(
+            typescript.signature.as_deref(),
+            Some("(user: User): Promise<void>")
+        );
+        assert_eq!(
+            typescript.doc_comment.as_deref(),
+            Some("Saves a user.\n@param user the user")
+        );
+        assert_eq!(typescript.visibility.as_deref(), Some("public"));
+
+        let java = symbol(
+            "UserService.java",
+            "class UserService {\n    // Deletes a user.\n    protected int delete(String id) {\n        return 0;\n    }\n}\n",
+            "delete",
+        );
+        assert_eq!(java.signature.as_deref(), Some("int delete(String id)"));
+        assert_eq!(java.doc_comment.as_deref(), Some("Deletes a user."));
+        assert_eq!(java.visibility.as_deref(), Some("protected"));
+
+        let go = symbol(
+            "user.go",
+            "package users\n\n// Find looks a user up.\nfunc Find(id string) (*User, error) {\n\treturn nil, nil\n}\n",
+            "Find",
+        );
+        assert_eq!(go.signature.as_deref(), Some("(id string) (*User, error)"));
+        assert_eq!(go.doc_comment.as_deref(), Some("Find looks a user up."));
+        assert_eq!(go.visibility.as_deref(), Some("public"));
+    }
+
+    #[test]
+    fn test_chunks_carry_declaration_details() {
+        let content = "/// Adds two numbers together for the caller.\npub fn add(left: i32, right: i32) -> i32 {\n    left + right\n}\n";
+        let chunks = parse_file_internal("math.rs", content, &ChunkingProfile::default()).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(
+            chunks[0].signature.as_deref(),
+            Some("(left: i32, right: i32) -> i32")
+        );
+        assert_eq!(
+            chunks[0].doc_comment.as_deref(),
+            Some("Adds two numbers together for the caller.")
+        );
+        assert_eq!(chunks[0].visibility.as_deref(), Some("public"));
+    }
+
+    #[test]
+    fn test_rust_impl_items_qualify_under_implementing_type() {
+        let content = r#"
+mod storage {
+    pub struct Store;
+
+    impl Default for Store {
+        fn default() -> Self {
+            Store
+        }
+    }
+}
+"#;
+        let (_chunks, symbols) =
+            parse_file_with_symbols_internal("lib.rs", content, &ChunkingProfile::default())
+                .unwrap();
+        let default = symbols
+            .iter()
+            .find(|symbol| symbol.name == "default")
+            .unwrap();
+        assert_eq!(default.qualified_name, "storage::Store::default");
+        assert_eq!(default.parent_symbol.as_deref(), Some("storage::Store"));
+        assert_eq!(default.depth, 2);
+
+        let chunks = parse_file_internal("lib.rs", content, &ChunkingProfile::default()).unwrap();
+        let module = chunks
+            .iter()
+            .find(|chunk| chunk.name.as_deref() == Some("storage"))
+            .unwrap();
+        assert_eq!(module.qualified_name.as_deref(), Some("storage"));
+        assert_eq!(module.depth, Some(0));
+    }
+
+    #[test]
+    fn test_chunking_profile_overrides_sizes_per_language() {
+        let body = (0..40)
+            .map(|i| format!("    total += compute_value({i});"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!("def accumulate():\n    total = 0\n{body}\n    return total\n");
+        let small = ChunkConfig {
+            min_size: 10,
+            max_size: 400,
+            target_size: 200,
+            overlap_lines: 1,
+            merge_small_chunks: true,
+            ..ChunkConfig::default()
+        };
+        let profile = ChunkingProfile::new(
+            ChunkConfig::default(),
+            std::collections::HashMap::from([("python".to_string(), small)]),
+        )
+        .unwrap();
+
+        let defaults =
+            parse_file_internal("calc.py", &content, &ChunkingProfile::default()).unwrap();
+        assert_eq!(defaults.len(), 1);
+
+        let tuned = parse_file_internal("calc.py", &content, &profile).unwrap();
+        assert!(tuned.len() > 1);
+        assert!(tuned.iter().all(|chunk| chunk.content.len() <= 400));
+
+        let other = parse_file_internal("calc.rb", "x = 1\n", &profile).unwrap();
+        assert_eq!(other.len(), 1);
+    }
+
+    #[test]
+    fn test_merge_small_chunks_can_be_disabled() {
+        let content = "function first(input: number) { return input + 1; }\nfunction second(input: number) { return input * 2; }\n";
+        let chunks_for = |merge_small_chunks| {
+            let config = ChunkConfig {
+                merge_small_chunks,
+                ..ChunkConfig::default()
+            };
+            let profile = ChunkingProfile::new(config, std::collections::HashMap::new()).unwrap();
+            parse_file_internal("math.ts", content, &profile).unwrap()
+        };
+
+        assert_eq!(chunks_for(true).len(), 1);
+        assert_eq!(chunks_for(false).len(), 2);
+    }
+
+    #[test]
+    fn test_chunk_config_token_budget_splits_chunks() {
+        let statements = (0..40)
+            .map(|i| format!("  const value{i} = computeSomething({i}, options);"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!("function buildAll(options: Options) {{\n{statements}\n}}\n");
+        let config = ChunkConfig {
+            max_tokens: Some(100),
+            ..ChunkConfig::default()
+        };
+        let profile = ChunkingProfile::new(config, std::collections::HashMap::new()).unwrap();
+
+        let unbudgeted =
+            parse_file_internal("build.ts", &content, &ChunkingProfile::default()).unwrap();
+        let budgeted = parse_file_internal("build.ts", &content, &profile).unwrap();
+
+        assert!(budgeted.len() > unbudgeted.len());
+        // The embedding prefix and the model's special tokens must fit too.
+        let config = &profile.base;
+        assert!(budgeted.iter().all(|chunk| {
+            config.tokenizer.count_input_tokens(&chunk.content) + config.reserved_tokens <= 100
+        }));
+    }
+
+    #[test]
+    fn test_oversized_function_splits_at_statements_with_signature_header() {
+        let statements = (0..80)
+            .map(|i| format!("  const value{i} = computeSomething({i}, options);"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!(
+            "/** Builds everything. */\nexport function buildAll(options: Options): number {{\n{statements}\n  return 0;\n}}\n"
+        );
+
+        let chunks =
+            parse_file_internal("build.ts", &content, &ChunkingProfile::default()).unwrap();
+        assert!(chunks.len() > 1);
+
+        let count = chunks.len() as u32;
+        for (index, chunk) in chunks.iter().enumerate() {
+            assert_eq!(chunk.name.as_deref(), Some("buildAll"));
+            assert_eq!(chunk.part_index, Some(index as u32));
+            assert_eq!(chunk.part_count, Some(count));
+            assert!(chunk.content.len() <= 2000);
+            assert!(chunk
+                .content
+                .lines()
+                .skip(1)
+                .all(|line| line.ends_with(';') || line.ends_with('{') || line.ends_with('}')));
+            if index > 0 {
+                assert!(chunk
+                    .content
+                    .starts_with("export function buildAll(options: Options): number {\n"));
+                assert!(chunk
+                    .content
+                    .lines()
+                    .nth(1)
+                    .unwrap()
+                    .starts_with("  const value"));
+            }
+        }
+        assert!(chunks[0].content.starts_with("/** Builds everything. */"));
+        assert_eq!(chunks[0].start_line, 1);
+        assert_eq!(chunks[1].start_line, chunks[0].end_line + 1);
+        assert!(chunks.last().unwrap().content.ends_with("return 0;\n}"));
+        assert_eq!(chunks.last().unwrap().end_line, 84);
+    }
+
+    #[test]
+    fn test_oversized_statement_pieces_stay_within_budget_with_header() {
+        let rows = (0..40)
+            .map(|i| format!("    \"row-{i}-aaaaaaaaaaaaaaaaaaaaaaaa\","))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!(
+            "export function buildTable(options: TableOptions): string[] {{\n  const title = options.title;\n  const rows = [\n{rows}\n  ];\n  return rows;\n}}\n"
+        );
+        for max_tokens in [None, Some(120)] {
+            let config = ChunkConfig {
+                min_size: 20,
+                max_size: 400,
+                target_size: 200,
+                max_tokens,
+                ..ChunkConfig::default()
+            };
+            let profile = ChunkingProfile::new(config, std::collections::HashMap::new()).unwrap();
+
+            let chunks = parse_file_internal("table.ts", &content, &profile).unwrap();
+            assert!(chunks.len() > 2);
+            let config = &profile.base;
+            for chunk in &chunks {
+                assert!(chunk.content.len() <= 400, "{} bytes", chunk.content.len());
+                assert!(max_tokens.is_none_or(|max| {
+                    config.tokenizer.count_input_tokens(&chunk.content) + config.reserved_tokens
+                        <= max
+                }));
+            }
+            assert!(chunks[1..]
+                .iter()
+                .all(|chunk| chunk.content.starts_with("export function buildTable(")));
+        }
+    }
+
+    #[test]
+    fn test_oversized_nested_block_carries_enclosing_headers() {
+        let loop_body = (0..70)
+            .map(|i| format!("        total += weight_{i} * item.value"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let content = format!(
+            "def score(items):\n    total = 0\n    for item in items:\n{loop_body}\n    return total\n"
+        );
+
+        let chunks =
+            parse_file_internal("score.py", &content, &ChunkingProfile::default()).unwrap();
+        assert!(chunks.len() > 2);
+        let nested = &chunks[1];
+        assert!(
+            nested
+                .content
+                .starts_with("def score(items):\n    for item in items:\n        total += weight_"),
+            "{}",
+            nested.content
+        );
+        assert!(chunks
+            .iter()
+            .all(|chunk| chunk.part_count == Some(chunks.len() as u32)));
+        assert!(chunks.last().unwrap().content.ends_with("return total"));
+    }
+
+    fn oversized_chunk(content: String) -> CodeChunk {
+        let lines = content.lines().count() as u32;
+        CodeChunk {
+            content,
+            start_line: 10,
+            start_col: 4,
+            end_line: 10 + lines - 1,
+            end_col: 0,
+            chunk_type: "function_declaration".to_string(),
+            name: Some("big".to_string()),
+            language: "typescript".to_string(),
+            fence_language: None,
+            qualified_name: None,
+            parent_symbol: None,
+            depth: None,
+            signature: None,
+            doc_comment: None,
+            visibility: None,
+            part_index: None,
+            part_count: None,
+        }
+    }
+
+    #[test]
+    fn test_split_by_token_budget_packs_lines_under_budget() {
+        let tokenizer = crate::tokenizer::CharRatioTokenizer::default();
+        let content = (0..60)
+            .map(|i| format!("    const value{i} = compute({i}) + offset;"))
+            .collect::<Vec<_>>()
+            .join("\n");
+
+        let mut pieces = Vec::new();
+        split_by_token_budget(
+            oversized_chunk(content),
+            &tokenizer,
+            64,
+            &ChunkConfig::default(),
+            &mut pieces,
+        );
+
+        assert!(pieces.len() > 2);
+        for piece in &pieces {
+            assert!(
+                tokenizer.count_tokens(&piece.content) <= 64,
+                "{}",
+                piece.content
+            );
+            assert_eq!(piece.name.as_deref(), Some("big"));
+        }
+        assert_eq!(pieces[0].start_line, 10);
+        assert_eq!(pieces[0].start_col, 4);
+        assert_eq!(pieces.last().unwrap().end_line, 69);
+        assert!(
+            pieces[1].start_line <= pieces[0].end_line,
+            "pieces should overlap"
+        );
+    }
+
+    #[test]
+    fn test_split_by_token_budget_keeps_short_tail() {
+        let tokenizer = crate::tokenizer::CharRatioTokenizer::default();
+        let content = (0..12)
+            .map(|i| format!("    const value{i} = compute({i}) + offset;"))
+            .chain(std::iter::once("}".to_string()))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let config = ChunkConfig {
+            overlap_lines: 0,
+            ..ChunkConfig::default()
+        };
+
+        let mut pieces = Vec::new();
+        split_by_token_budget(
+            oversized_chunk(content),
+            &tokenizer,
+            64,
+            &config,
+            &mut pieces,
+        );
+
+        assert_eq!(pieces.len(), 2);
+        assert!(pieces[1].content.ends_with("offset;\n}"));
+        assert_eq!(pieces[1].end_line, 22);
+        assert!(pieces
+            .iter()
+            .all(|piece| tokenizer.count_tokens(&piece.content) <= 64));
+    }
+
+    #[test]
+    fn test_split_by_token_budget_breaks_overlong_lines() {
+        let tokenizer = crate::tokenizer::CharRatioTokenizer::default();
+        let minified = format!("{}\n{}", "a=1;".repeat(400), "x".repeat(700));
+
+        let mut pieces = Vec::new();
+        split_by_token_budget(
+            oversized_chunk(minified),
+            &tokenizer,
+            32,
+            &ChunkConfig::default(),
+            &mut pieces,
+        );
+
+        assert!(pieces.len() > 10);
+        assert!(pieces
+            .iter()
+            .all(|piece| tokenizer.count_tokens(&piece.content) <= 32));
+        assert!(pieces.iter().any(|piece| piece.start_line == 11));
+    }
+
     #[test]
     fn test_jsdoc_extraction() {
         let content = r#"
@@ -1529,7 +2606,7 @@ function validateEmail(email: string): boolean {
 }
 "#;
 
-        let chunks = parse_file_internal("test.ts", content).unwrap();
+        let chunks = parse_file_internal("test.ts", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have at least one chunk");
 
         let chunk = &chunks[0];
@@ -1555,7 +2632,7 @@ fn factorial(n: u64) -> Option<u64> {
 }
 "#;
 
-        let chunks = parse_file_internal("test.rs", content).unwrap();
+        let chunks = parse_file_internal("test.rs", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have at least one chunk");
 
         let chunk = &chunks[0];
@@ -1590,7 +2667,8 @@ public enum Operation {
 }
 "#;
 
-        let chunks = parse_file_internal("Calculator.java", content).unwrap();
+        let chunks =
+            parse_file_internal("Calculator.java", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Java");
 
         let has_class = chunks.iter().any(|c| c.chunk_type == "class_declaration");
@@ -1627,7 +2705,8 @@ public struct Point
 }
 "#;
 
-        let chunks = parse_file_internal("Person.cs", content).unwrap();
+        let chunks =
+            parse_file_internal("Person.cs", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for C#");
     }
 
@@ -1655,7 +2734,8 @@ module Utils
 end
 "#;
 
-        let chunks = parse_file_internal("greeter.rb", content).unwrap();
+        let chunks =
+            parse_file_internal("greeter.rb", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Ruby");
 
         let has_class = chunks.iter().any(|c| c.chunk_type == "class");
@@ -1681,7 +2761,8 @@ function add() {
 greet "World"
 "#;
 
-        let chunks = parse_file_internal("script.sh", content).unwrap();
+        let chunks =
+            parse_file_internal("script.sh", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Bash");
 
         let has_function = chunks.iter().any(|c| c.chunk_type == "function_definition");
@@ -1722,7 +2803,7 @@ void greet(const char* name) {
 }
 "#;
 
-        let chunks = parse_file_internal("main.c", content).unwrap();
+        let chunks = parse_file_internal("main.c", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for C");
 
         let has_function = chunks.iter().any(|c| c.chunk_type == "function_definition");
@@ -1768,7 +2849,7 @@ int main() {
 }
 "#;
 
-        let chunks = parse_file_internal("main.cpp", content).unwrap();
+        let chunks = parse_file_internal("main.cpp", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for C++");
 
         let has_class = chunks.iter().any(|c| c.chunk_type == "class_specifier");
@@ -1789,7 +2870,12 @@ int main() {
 
     #[test]
     fn test_parse_cpp_preserves_small_type_symbols() {
-        let chunks = parse_file_internal("small.cpp", "class Tag {};\nstruct Point {};\n").unwrap();
+        let chunks = parse_file_internal(
+            "small.cpp",
+            "class Tag {};\nstruct Point {};\n",
+            &ChunkingProfile::default(),
+        )
+        .unwrap();
 
         assert!(chunks.iter().any(|chunk| {
             chunk.chunk_type == "class_specifier" && chunk.name.as_deref() == Some("Tag")
@@ -1807,7 +2893,8 @@ inline T scaled_value(T value, constant float& scale) {
 }
 "#;
 
-        let chunks = parse_file_internal("shader.metal", content).unwrap();
+        let chunks =
+            parse_file_internal("shader.metal", content, &ChunkingProfile::default()).unwrap();
         let function = chunks
             .iter()
             .find(|chunk| chunk.name.as_deref() == Some("scaled_value"))
@@ -1844,7 +2931,8 @@ lto = true
 opt-level = 3
 "#;
 
-        let chunks = parse_file_internal("Cargo.toml", content).unwrap();
+        let chunks =
+            parse_file_internal("Cargo.toml", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for TOML");
 
         let has_table = chunks.iter().any(|c| c.chunk_type == "table");
@@ -1882,12 +2970,13 @@ spec:
             - containerPort: 8080
 "#;
 
-        let chunks = parse_file_internal("deployment.yaml", content).unwrap();
+        let chunks =
+            parse_file_internal("deployment.yaml", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for YAML");
     }
 
     #[test]
-    fn test_parse_markdown_fallback() {
+    fn test_parse_markdown_sections() {
         let content = r#"
 # My Project
 
@@ -1913,12 +3002,16 @@ myFunction();
 Please read CONTRIBUTING.md for details.
 "#;
 
-        let chunks = parse_file_internal("README.md", content).unwrap();
-        // Markdown falls back to line-based chunking
+        let chunks =
+            parse_file_internal("README.md", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Markdown");
-        // Should be block type since we use line-based chunking
-        let has_block = chunks.iter().any(|c| c.chunk_type == "block");
-        assert!(has_block, "Markdown should use block chunking");
+        assert!(chunks
+            .iter()
+            .any(|c| c.chunk_type == "section" && c.name.as_deref() == Some("My Project > Usage")));
+        assert!(chunks
+            .iter()
+            .any(|c| c.chunk_type == "code_block"
+                && c.fence_language.as_deref() == Some("typescript")));
     }
 
     #[test]
@@ -1939,7 +3032,9 @@ public with sharing class AccountService {
 }
 "#;
 
-        let chunks = parse_file_internal("AccountService.cls", content).unwrap();
+        let chunks =
+            parse_file_internal("AccountService.cls", content, &ChunkingProfile::default())
+                .unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Apex");
 
         let has_class = chunks.iter().any(|c| c.chunk_type == "class_declaration");
@@ -1956,7 +3051,12 @@ trigger AccountTrigger on Account (before insert, before update, after delete) {
 }
 "#;
 
-        let chunks = parse_file_internal("AccountTrigger.trigger", content).unwrap();
+        let chunks = parse_file_internal(
+            "AccountTrigger.trigger",
+            content,
+            &ChunkingProfile::default(),
+        )
+        .unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for Apex trigger");
 
         let has_trigger = chunks.iter().any(|c| c.chunk_type == "trigger_declaration");
@@ -1989,7 +3089,8 @@ class InnerThing:
         pass
 "#;
 
-        let chunks = parse_file_internal("player.gd", content).unwrap();
+        let chunks =
+            parse_file_internal("player.gd", content, &ChunkingProfile::default()).unwrap();
         assert!(!chunks.is_empty(), "Should have chunks for GDScript");
 
         let chunk_types: Vec<&str> = chunks.iter().map(|c| c.chunk_type.as_str()).collect();
@@ -2031,7 +3132,9 @@ public class AccountService {
 }
 "#;
 
-        let chunks = parse_file_internal("AccountService.cls", content).unwrap();
+        let chunks =
+            parse_file_internal("AccountService.cls", content, &ChunkingProfile::default())
+                .unwrap();
         let class_chunk = chunks.iter().find(|c| c.chunk_type == "class_declaration");
         assert!(class_chunk.is_some(), "Should find class_declaration");
         assert!(
@@ -2043,4 +3146,203 @@ public class AccountService {
             class_chunk.unwrap().content
         );
     }
+
+    #[test]
+    fn test_parsed_files_report_detected_language_and_reason() {
+        let files = vec![
+            FileInput {
+                path: "scripts/release".to_string(),
+                content: "#!/usr/bin/env python3\ndef main():\n    publish()\n".to_string(),
+            },
+            FileInput {
+                path: "include/shape.h".to_string(),
+                content: "namespace geo {\nclass Shape {\npublic:\n  virtual double area() const = 0;\n};\n}\n".to_string(),
+            },
+        ];
+        let mut parsed = parse_files_parallel(files, &ChunkingProfile::default()).unwrap();
+        parsed.sort_by(|a, b| a.path.cmp(&b.path));
+
+        assert_eq!(parsed[0].path, "include/shape.h");
+        assert_eq!(parsed[0].language, "cpp");
+        assert_eq!(parsed[0].language_reason, "content");
+        assert!(parsed[0].chunks.iter().all(|chunk| chunk.language == "cpp"));
+
+        assert_eq!(parsed[1].language, "python");
+        assert_eq!(parsed[1].language_reason, "shebang");
+        assert_eq!(parsed[1].symbols[0].name, "main");
+    }
+
+    #[test]
+    fn test_parsed_files_carry_parse_diagnostics() {
+        let files = vec![
+            FileInput {
+                path: "broken.py".to_string(),
+                content: "def load_settings(path):\n    with open(path) as handle:\n        return parse_settings(handle.read())\n\ndef broken(:\n    return 2\n".to_string(),
+            },
+            FileInput {
+                path: "settings.py".to_string(),
+                content: "DEBUG = True\nPORT = 8080\n".to_string(),
+            },
+            FileInput {
+                path: "unfinished.py".to_string(),
+                content: "DEBUG = (True\nPORT = 8080\n".to_string(),
+            },
+        ];
+        let mut parsed = parse_files_parallel(files, &ChunkingProfile::default()).unwrap();
+        parsed.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let broken = &parsed[0].diagnostics;
+        assert!(broken.error_node_count + broken.missing_node_count > 0);
+        assert!(!broken.fell_back_to_lines);
+        assert!(broken.error.is_none());
+        assert!(broken.parse_duration_ms >= 0.0);
+        let first = &broken.syntax_errors[0];
+        assert_eq!(first.start_line, 5);
+        assert!(first.start_byte < first.end_byte || first.kind == "missing");
+
+        let settings = &parsed[1].diagnostics;
+        assert_eq!(settings.error_node_count, 0);
+        assert!(settings.syntax_errors.is_empty());
+        assert!(!settings.fell_back_to_lines);
+        assert_eq!(settings.fallback_reason, None);
+
+        let unfinished = &parsed[2].diagnostics;
+        assert!(unfinished.fell_back_to_lines);
+        assert_eq!(unfinished.fallback_reason.as_deref(), Some("syntax-errors"));
+    }
+
+    #[test]
+    fn test_parse_files_returns_calls_from_the_same_tree() {
+        let content = "import { save } from './store';\n\nexport function run(user: User) {\n  save(user);\n  user.touch();\n}\n";
+        let files = || {
+            vec![FileInput {
+                path: "run.ts".to_string(),
+                content: content.to_string(),
+            }]
+        };
+
+        let without = parse_files_parallel(files(), &ChunkingProfile::default()).unwrap();
+        assert!(without[0].calls.is_none());
+
+        let profile = ChunkingProfile {
+            include_calls: true,
+            ..ChunkingProfile::default()
+        };
+        let parsed = parse_files_parallel(files(), &profile).unwrap();
+        let calls = parsed[0].calls.as_ref().unwrap();
+        let expected: Vec<_> = call_extractor::extract_calls(content, "typescript")
+            .unwrap()
+            .into_iter()
+            .chain(reference_extractor::extract_references(content, "typescript").unwrap())
+            .map(|site| {
+                (
+                    site.callee_name,
+                    site.line,
+                    site.column,
+                    site.enclosing.name,
+                )
+            })
+            .collect();
+        let actual: Vec<_> = calls
+            .iter()
+            .map(|site| {
+                (
+                    site.callee_name.clone(),
+                    site.line,
+                    site.column,
+                    site.enclosing_symbol.name.clone(),
+                )
+            })
+            .collect();
+        assert_eq!(actual, expected);
+        assert!(actual.iter().any(|(name, ..)| name == "touch"));
+        assert!(actual.iter().any(|(name, ..)| name == "User"));
+
+        let text = parse_files_parallel(
+            vec![FileInput {
+                path: "notes.txt".to_string(),
+                content: "plain text".to_string(),
+            }],
+            &profile,
+        )
+        .unwrap();
+        assert_eq!(text[0].calls.as_ref().map(Vec::len), Some(0));
+    }
+
+    #[test]
+    fn test_parse_budget_falls_back_to_line_chunks_with_reason() {
+        let parse = |path: &str, content: &str, budget: ParseBudget| {
+            let profile = ChunkingProfile {
+                budget,
+                ..ChunkingProfile::default()
+            };
+            let mut parsed = parse_files_parallel(
+                vec![FileInput {
+                    path: path.to_string(),
+                    content: content.to_string(),
+                }],
+                &profile,
+            )
+            .unwrap();
+            parsed.remove(0)
+        };
+        let unlimited = ParseBudget {
+            timeout: None,
+            max_bytes: None,
+            max_nodes: None,
+            max_average_line_length: None,
+        };
+        let source: String = (0..400)
+            .map(|i| {
+                format!("function handler{i}(event) {{\n  return dispatch(event, {i});\n}}\n\n")
+            })
+            .collect();
+
+        let parsed = parse("handlers.js", &source, unlimited.clone());
+        assert_eq!(parsed.diagnostics.fallback_reason, None);
+        assert!(!parsed.symbols.is_empty());
+
+        let minified = source.replace('\n', " ");
+        let parsed = parse("bundle.min.js", &minified, ParseBudget::default());
+        assert!(parsed.diagnostics.fell_back_to_lines);
+        assert_eq!(
+            parsed.diagnostics.fallback_reason.as_deref(),
+            Some("minified")
+        );
+        assert!(!parsed.chunks.is_empty());
+        assert!(parsed.symbols.is_empty());
+
+        let cases = [
+            (
+                ParseBudget {
+                    max_bytes: Some(1024),
+                    ..unlimited.clone()
+                },
+                "too-large",
+            ),
+            (
+                ParseBudget {
+                    max_nodes: Some(100),
+                    ..unlimited.clone()
+                },
+                "too-many-nodes",
+            ),
+            (
+                ParseBudget {
+                    timeout: Some(std::time::Duration::ZERO),
+                    ..unlimited.clone()
+                },
+                "timeout",
+            ),
+        ];
+        for (budget, reason) in cases {
+            let parsed = parse("handlers.js", &source, budget);
+            assert_eq!(parsed.diagnostics.fallback_reason.as_deref(), Some(reason));
+            assert!(parsed.diagnostics.fell_back_to_lines);
+            assert!(parsed
+                .chunks
+                .iter()
+                .all(|chunk| chunk.chunk_type == "block"));
+        }
+    }
 }
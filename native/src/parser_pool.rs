@@ -0,0 +1,68 @@
+//! Configured tree-sitter parsers, pooled per thread so a batch of small
+//! files reuses one parser per grammar instead of allocating one per file.
+
+use anyhow::{anyhow, Result};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use tree_sitter::Parser;
+
+thread_local! {
+    static PARSERS: RefCell<HashMap<tree_sitter::Language, Parser>> =
+        RefCell::new(HashMap::new());
+}
+
+/// Runs `parse` with this thread's parser for `grammar`.
+///
+/// The parser leaves the pool while `parse` runs, so a nested call gets a
+/// parser of its own, and is reset on return so a cancelled parse never
+/// resumes into the next file.
+pub(crate) fn with_parser<R>(
+    grammar: &tree_sitter::Language,
+    parse: impl FnOnce(&mut Parser) -> Result<R>,
+) -> Result<R> {
+    let pooled = PARSERS.with(|parsers| parsers.borrow_mut().remove(grammar));
+    let mut parser = match pooled {
+        Some(parser) => parser,
+        None => {
+            let mut parser = Parser::new();
+            parser
+                .set_language(grammar)
+                .map_err(|e| anyhow!("Failed to set language: {}", e))?;
+            parser
+        }
+    };
+    let result = parse(&mut parser);
+    parser.reset();
+    PARSERS.with(|parsers| parsers.borrow_mut().insert(grammar.clone(), parser));
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parsers_are_reused_per_grammar() {
+        let python: tree_sitter::Language = tree_sitter_python::LANGUAGE.into();
+        let rust: tree_sitter::Language = tree_sitter_rust::LANGUAGE.into();
+
+        for _ in 0..3 {
+            let tree = with_parser(&python, |parser| Ok(parser.parse("x = 1\n", None))).unwrap();
+            assert_eq!(tree.unwrap().root_node().kind(), "module");
+        }
+        let tree = with_parser(&rust, |parser| Ok(parser.parse("fn f() {}\n", None))).unwrap();
+        assert_eq!(tree.unwrap().root_node().kind(), "source_file");
+
+        // A parse inside another parse of the same grammar gets its own parser.
+        let nested = with_parser(&python, |_| {
+            with_parser(&python, |parser| Ok(parser.parse("y = 2\n", None)))
+        })
+        .unwrap();
+        assert!(nested.is_some());
+        assert_eq!(
+            PARSERS.with(|parsers| parsers.borrow().len()),
+            2,
+            "one pooled parser per grammar"
+        );
+    }
+}
@@ -0,0 +1,82 @@
+//! Compiled call and reference queries, shared by every thread so a batch of
+//! files compiles each language's query once per process instead of once per
+//! file.
+//!
+//! Entries are keyed by the query source's address: sources are the
+//! `'static` queries of language definitions, so a re-registered language's
+//! query is recompiled on its next use. Call queries loaded from project
+//! files are compiled as they load and never pass through here.
+
+use crate::types::Language;
+use anyhow::{anyhow, Result};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock, RwLock};
+use tree_sitter::Query;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub(crate) enum QueryKind {
+    Calls,
+    References,
+}
+
+type CachedQuery = (usize, Arc<Query>);
+
+static QUERIES: OnceLock<RwLock<HashMap<(Language, QueryKind), CachedQuery>>> = OnceLock::new();
+
+/// `source` compiled for `grammar`, reusing the cached compilation when
+/// `language` last used the same source.
+pub(crate) fn query(
+    language: Language,
+    kind: QueryKind,
+    grammar: &tree_sitter::Language,
+    source: &'static str,
+) -> Result<Arc<Query>> {
+    let queries = QUERIES.get_or_init(Default::default);
+    let address = source.as_ptr() as usize;
+    if let Some((cached_source, query)) = queries.read().unwrap().get(&(language, kind)) {
+        if *cached_source == address {
+            return Ok(Arc::clone(query));
+        }
+    }
+
+    // Compiled outside the lock; threads racing on a miss keep the first.
+    let compiled = Arc::new(
+        Query::new(grammar, source).map_err(|e| anyhow!("Failed to compile query: {}", e))?,
+    );
+    let mut queries = queries.write().unwrap();
+    let entry = queries
+        .entry((language, kind))
+        .or_insert_with(|| (address, Arc::clone(&compiled)));
+    if entry.0 != address {
+        *entry = (address, compiled);
+    }
+    Ok(Arc::clone(&entry.1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_queries_compile_once_per_source() {
+        let definition = crate::registry::definition(&Language::Python);
+        let grammar = definition.grammar.clone().unwrap();
+        let source = definition.reference_query.unwrap();
+
+        let first = query(Language::Python, QueryKind::References, &grammar, source).unwrap();
+        let second = std::thread::spawn(move || {
+            let grammar = crate::registry::definition(&Language::Python)
+                .grammar
+                .clone()
+                .unwrap();
+            query(Language::Python, QueryKind::References, &grammar, source).unwrap()
+        })
+        .join()
+        .unwrap();
+        assert!(Arc::ptr_eq(&first, &second));
+
+        let replaced: &'static str = Box::leak(source.to_string().into_boxed_str());
+        let third = query(Language::Python, QueryKind::References, &grammar, replaced).unwrap();
+        assert!(!Arc::ptr_eq(&first, &third));
+    }
+}
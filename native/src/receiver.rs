@@ -0,0 +1,243 @@
+//! Receiver hints for method calls: what `repo.save()` was called on, named
+//! by its declared type when the surrounding code declares one.
+
+use crate::reference_extractor::FUNCTION_SCOPES;
+use std::collections::HashMap;
+use tree_sitter::Node;
+
+/// Fields holding the object of a member access, across grammars.
+const RECEIVER_FIELDS: &[&str] = &[
+    "object",
+    "operand",
+    "value",
+    "receiver",
+    "expression",
+    "scope",
+    "path",
+    "argument",
+    "target",
+];
+
+/// Fields holding the member name of a member access.
+const MEMBER_FIELDS: &[&str] = &["property", "field", "attribute", "name"];
+
+/// Receivers that stand for the enclosing type rather than a value.
+pub(crate) const SELF_RECEIVERS: &[&str] = &["this", "self", "Self"];
+
+/// Type annotations that ask the compiler to infer the type.
+const INFERRED_TYPES: &[&str] = &["var", "let", "auto", "dynamic", "val"];
+
+/// Declared variable types per scope, filled on first use so a file with many
+/// method calls walks each scope once.
+#[derive(Default)]
+pub(crate) struct ReceiverTypes {
+    scopes: HashMap<(usize, usize), HashMap<String, String>>,
+}
+
+impl ReceiverTypes {
+    /// The qualifier of a method call whose name node is `callee`: the
+    /// receiver's declared type when a parameter, variable or field declares
+    /// it, otherwise the receiver as written (`this`, `user`, `Repo`).
+    /// `None` for receivers that are themselves calls or longer chains.
+    pub(crate) fn qualifier(&mut self, callee: Node<'_>, source: &[u8]) -> Option<String> {
+        let receiver = receiver(callee)?;
+        if let Some(field) = self_field(receiver, source) {
+            let class = ancestor(receiver, |kind| kind.contains("class"))?;
+            return self.declared_type(class, &field, source);
+        }
+
+        let name = simple_name(receiver, source)?;
+        if SELF_RECEIVERS.contains(&name.as_str()) {
+            return Some(name);
+        }
+        // Locals first, then fields of the enclosing class, then the file.
+        let scopes = [
+            ancestor(receiver, |kind| FUNCTION_SCOPES.contains(&kind)),
+            ancestor(receiver, |kind| kind.contains("class")),
+            Some(root(receiver)),
+        ];
+        let declared = scopes
+            .into_iter()
+            .flatten()
+            .find_map(|scope| self.declared_type(scope, &name, source));
+        Some(declared.unwrap_or(name))
+    }
+
+    fn declared_type(&mut self, scope: Node<'_>, name: &str, source: &[u8]) -> Option<String> {
+        self.scopes
+            .entry((scope.start_byte(), scope.end_byte()))
+            .or_insert_with(|| declared_types(scope, source))
+            .get(name)
+            .cloned()
+    }
+}
+
+fn text<'a>(node: Node<'_>, source: &'a [u8]) -> &'a str {
+    node.utf8_text(source).unwrap_or("")
+}
+
+fn receiver(callee: Node<'_>) -> Option<Node<'_>> {
+    let parent = callee.parent()?;
+    // Swift and Kotlin nest the member name one level below the access.
+    [Some(parent), parent.parent()]
+        .into_iter()
+        .flatten()
+        .find_map(|access| {
+            RECEIVER_FIELDS
+                .iter()
+                .find_map(|field| access.child_by_field_name(field))
+                .filter(|object| object.end_byte() <= callee.start_byte())
+        })
+}
+
+/// `repo` in `this.repo` or `self.repo`.
+fn self_field(receiver: Node<'_>, source: &[u8]) -> Option<String> {
+    let object = RECEIVER_FIELDS
+        .iter()
+        .find_map(|field| receiver.child_by_field_name(field))?;
+    if !SELF_RECEIVERS.contains(&text(object, source).trim_start_matches('$')) {
+        return None;
+    }
+    MEMBER_FIELDS
+        .iter()
+        .find_map(|field| receiver.child_by_field_name(field))
+        .map(|member| text(member, source).to_string())
+}
+
+/// The last segment of an identifier, path or `$variable`.
+fn simple_name(node: Node<'_>, source: &[u8]) -> Option<String> {
+    let kind = node.kind();
+    let named = kind.ends_with("identifier")
+        || matches!(
+            kind,
+            "this" | "self" | "variable_name" | "name" | "constant" | "simple_identifier"
+        );
+    if !named {
+        return None;
+    }
+    let name = text(node, source)
+        .trim_start_matches('$')
+        .rsplit(['.', ':', '\\'])
+        .next()?;
+    is_identifier(name).then(|| name.to_string())
+}
+
+fn is_identifier(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_alphanumeric() || c == '_')
+}
+
+fn ancestor<'a>(node: Node<'a>, matches: impl Fn(&str) -> bool) -> Option<Node<'a>> {
+    let mut current = node.parent();
+    while let Some(candidate) = current {
+        if matches(candidate.kind()) {
+            return Some(candidate);
+        }
+        current = candidate.parent();
+    }
+    None
+}
+
+fn root(node: Node<'_>) -> Node<'_> {
+    let mut current = node;
+    while let Some(parent) = current.parent() {
+        current = parent;
+    }
+    current
+}
+
+/// Names declared anywhere under `scope` with the type they are declared or
+/// constructed with. The first declaration of a name wins.
+fn declared_types(scope: Node<'_>, source: &[u8]) -> HashMap<String, String> {
+    let mut types = HashMap::new();
+    let mut stack = vec![scope];
+    while let Some(node) = stack.pop() {
+        if let Some((name, type_name)) = declaration(node, source) {
+            types.entry(name).or_insert(type_name);
+        }
+        let mut cursor = node.walk();
+        stack.extend(node.named_children(&mut cursor));
+    }
+    types
+}
+
+/// `(name, type)` for a typed parameter, variable or field declaration, or for
+/// a variable initialized with a constructor call.
+fn declaration(node: Node<'_>, source: &[u8]) -> Option<(String, String)> {
+    let declared = node
+        .child_by_field_name("type")
+        .and_then(|annotation| type_name(text(annotation, source)))
+        .filter(|type_name| !INFERRED_TYPES.contains(&type_name.as_str()));
+    let type_name = match declared {
+        Some(type_name) => type_name,
+        None => {
+            let value = node
+                .child_by_field_name("value")
+                .or_else(|| node.child_by_field_name("right"))?;
+            constructed_type(value, source)?
+        }
+    };
+
+    let name = ["name", "pattern", "left"]
+        .iter()
+        .find_map(|field| node.child_by_field_name(field))
+        .or_else(|| {
+            node.child_by_field_name("declarator")
+                .and_then(|declarator| declarator.child_by_field_name("name"))
+        })
+        // Python's typed parameters leave the name unlabelled.
+        .or_else(|| {
+            node.named_child(0)
+                .filter(|child| child.kind() == "identifier")
+        })?;
+    if !(name.kind().ends_with("identifier") || name.kind() == "variable_name") {
+        return None;
+    }
+    let name = text(name, source).trim_start_matches('$');
+    is_identifier(name).then(|| (name.to_string(), type_name))
+}
+
+/// The class a `new Repo()`, `Repo{}` or Python `Repo()` expression creates.
+fn constructed_type(value: Node<'_>, source: &[u8]) -> Option<String> {
+    let created = match value.kind() {
+        "new_expression" => value.child_by_field_name("constructor"),
+        "object_creation_expression" | "composite_literal" => value.child_by_field_name("type"),
+        "call" => value
+            .child_by_field_name("function")
+            .filter(|function| text(*function, source).starts_with(char::is_uppercase)),
+        _ => None,
+    }?;
+    type_name(text(created, source))
+}
+
+/// The base name of a written type: `Repo` for `: Repo`, `&mut Repo`,
+/// `*Repo`, `models.Repo` or `Repo<T>`.
+fn type_name(written: &str) -> Option<String> {
+    let mut written = written
+        .trim_start_matches(|c: char| matches!(c, ':' | '&' | '*' | '?') || c.is_whitespace());
+    if written.starts_with('\'') {
+        written = written.split_once(char::is_whitespace)?.1.trim_start();
+    }
+    let written = written.strip_prefix("mut ").unwrap_or(written).trim_start();
+    let path = written
+        .split(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '.' | ':' | '\\')))
+        .next()?;
+    let name = path.rsplit(['.', ':', '\\']).next()?;
+    is_identifier(name).then(|| name.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_type_name_strips_annotations_and_paths() {
+        assert_eq!(type_name(": UserRepo").as_deref(), Some("UserRepo"));
+        assert_eq!(type_name("&'a mut Repo").as_deref(), Some("Repo"));
+        assert_eq!(type_name("*store.Repo").as_deref(), Some("Repo"));
+        assert_eq!(type_name("Map<string, User>").as_deref(), Some("Map"));
+        assert_eq!(type_name("crate::db::Repo").as_deref(), Some("Repo"));
+        assert_eq!(type_name("(a, b)"), None);
+    }
+}
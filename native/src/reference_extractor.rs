@@ -0,0 +1,432 @@
+use crate::call_extractor::{self, CallSite, CallType, Confidence, Enclosers};
+use crate::query_cache::{self, QueryKind};
+use crate::registry;
+use crate::types::Language;
+use anyhow::{anyhow, bail, Result};
+use std::collections::{HashMap, HashSet};
+use streaming_iterator::StreamingIterator;
+use tree_sitter::{Node, Query, QueryCursor, Tree};
+
+/// Capture names `extract_references` interprets. Reference queries may also
+/// use `_`-prefixed captures as predicate helpers; they are otherwise ignored.
+const REFERENCE_QUERY_CAPTURES: &[&str] = &[
+    "local.definition",
+    "reference.decorator",
+    "reference.field",
+    "reference.read",
+    "reference.type",
+    "reference.write",
+];
+
+/// Node kinds that open a scope for `@local.definition` bindings.
+pub(crate) const FUNCTION_SCOPES: &[&str] = &[
+    "arrow_function",
+    "closure_expression",
+    "constructor_declaration",
+    "func_literal",
+    "function",
+    "function_declaration",
+    "function_definition",
+    "function_expression",
+    "function_item",
+    "generator_function",
+    "generator_function_declaration",
+    "lambda",
+    "lambda_expression",
+    "method_declaration",
+    "method_definition",
+];
+
+/// Node kinds that open a scope for block-scoped bindings: `let`/`const`,
+/// loop and catch variables, Rust `let`, Go `:=` and Java locals.
+const BLOCK_SCOPES: &[&str] = &[
+    "block",
+    "catch_clause",
+    "compound_statement",
+    "enhanced_for_statement",
+    "for_expression",
+    "for_in_statement",
+    "for_statement",
+    "if_expression",
+    "if_statement",
+    "match_arm",
+    "statement_block",
+    "switch_block",
+    "while_expression",
+];
+
+/// Python blocks share their function's scope; only comprehensions nest.
+const PYTHON_BLOCK_SCOPES: &[&str] = &[
+    "dictionary_comprehension",
+    "generator_expression",
+    "list_comprehension",
+    "set_comprehension",
+];
+
+/// Compiles a reference query and checks that its captures mean something to
+/// `extract_references`. `origin` names the query in error messages.
+pub(crate) fn validate_reference_query(
+    grammar: &tree_sitter::Language,
+    source: &str,
+    origin: &str,
+) -> Result<Query> {
+    let query = Query::new(grammar, source)
+        .map_err(|e| anyhow!("invalid reference query {}: {}", origin, e))?;
+    if let Some(unknown) = query
+        .capture_names()
+        .iter()
+        .find(|name| !name.starts_with('_') && !REFERENCE_QUERY_CAPTURES.contains(name))
+    {
+        bail!(
+            "invalid reference query {}: unknown capture `@{}`; expected one of {}",
+            origin,
+            unknown,
+            REFERENCE_QUERY_CAPTURES
+                .iter()
+                .map(|name| format!("`@{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        );
+    }
+    Ok(query)
+}
+
+fn reference_type(capture: &str) -> Option<CallType> {
+    match capture {
+        "reference.type" => Some(CallType::TypeReference),
+        "reference.field" => Some(CallType::FieldAccess),
+        "reference.read" => Some(CallType::Read),
+        "reference.write" => Some(CallType::Write),
+        "reference.decorator" => Some(CallType::Decorator),
+        _ => None,
+    }
+}
+
+/// The innermost function or block a binding at `node` is local to, if any.
+/// A function's own name belongs to the scope around it, and a JavaScript
+/// `var` to its function rather than its block.
+fn binding_scope<'tree>(node: Node<'tree>, language: &Language) -> Option<Node<'tree>> {
+    let block_scopes = match language {
+        Language::Python => PYTHON_BLOCK_SCOPES,
+        _ => BLOCK_SCOPES,
+    };
+    let function_scoped = node
+        .parent()
+        .and_then(|declarator| declarator.parent())
+        .is_some_and(|declaration| declaration.kind() == "variable_declaration");
+    let mut current = node.parent();
+    while let Some(ancestor) = current {
+        let kind = ancestor.kind();
+        if FUNCTION_SCOPES.contains(&kind) && ancestor.child_by_field_name("name") != Some(node) {
+            return Some(ancestor);
+        }
+        if !function_scoped && block_scopes.contains(&kind) {
+            return Some(ancestor);
+        }
+        current = ancestor.parent();
+    }
+    None
+}
+
+/// A type identifier naming the declaration it belongs to (`interface Foo`,
+/// `struct Foo`, `type Foo struct`, `<T>`) rather than using a type.
+fn is_declared_name(node: Node<'_>) -> bool {
+    node.parent().is_some_and(|parent| {
+        let kind = parent.kind();
+        let declares = kind.ends_with("_declaration")
+            || kind.ends_with("_item")
+            || kind.ends_with("_spec")
+            || matches!(kind, "type_alias" | "type_parameter" | "associated_type");
+        declares && parent.child_by_field_name("name") == Some(node)
+    })
+}
+
+/// Finds non-call references: type uses, field accesses, reads and writes of
+/// names not bound inside the enclosing function, and decorators.
+///
+/// Positions already reported by `extract_calls` (callees, imports, base
+/// types) are left to it, so merging both lists never yields two edges for
+/// one site.
+pub fn extract_references(content: &str, language_name: &str) -> Result<Vec<CallSite>> {
+    let language = Language::from_string(language_name);
+    let definition = registry::definition(&language);
+    let (Some(ts_language), Some(_)) = (&definition.grammar, definition.reference_query) else {
+        return Ok(vec![]);
+    };
+
+    let tree = call_extractor::parse_source(ts_language, content)?;
+    let enclosers = Enclosers::of(&tree, content, &language);
+    let calls = call_extractor::calls_in_tree(&tree, content, &language, &enclosers)?;
+    references_in_tree(&tree, content, &language, &calls, &enclosers)
+}
+
+/// The references of an already parsed file whose call sites are `calls`.
+pub(crate) fn references_in_tree(
+    tree: &Tree,
+    content: &str,
+    language: &Language,
+    calls: &[CallSite],
+    enclosers: &Enclosers,
+) -> Result<Vec<CallSite>> {
+    let definition = registry::definition(language);
+    let (Some(ts_language), Some(query_source)) = (&definition.grammar, definition.reference_query)
+    else {
+        return Ok(vec![]);
+    };
+    let query = query_cache::query(*language, QueryKind::References, ts_language, query_source)?;
+    let capture_names = query.capture_names();
+    let text_bytes = content.as_bytes();
+
+    let call_positions: HashSet<(u32, u32)> =
+        calls.iter().map(|call| (call.line, call.column)).collect();
+
+    let mut locals: HashMap<&str, Vec<(usize, usize)>> = HashMap::new();
+    let mut found = Vec::new();
+    let mut cursor = QueryCursor::new();
+    let mut captures = cursor.captures(&query, tree.root_node(), text_bytes);
+    while let Some((match_, index)) = captures.next() {
+        let capture = match_.captures[*index];
+        let node = capture.node;
+        let name = node.utf8_text(text_bytes).unwrap_or("");
+        let capture_name = capture_names[capture.index as usize];
+        if capture_name == "local.definition" {
+            if let Some(scope) = binding_scope(node, language) {
+                locals
+                    .entry(name)
+                    .or_default()
+                    .push((scope.start_byte(), scope.end_byte()));
+            }
+        } else if let Some(call_type) = reference_type(capture_name) {
+            if call_type == CallType::TypeReference && is_declared_name(node) {
+                continue;
+            }
+            found.push((name, node, call_type));
+        }
+    }
+
+    let mut references: Vec<CallSite> = Vec::new();
+    let mut by_position: HashMap<(u32, u32), usize> = HashMap::new();
+    for (name, node, call_type) in found {
+        let start = node.start_position();
+        let position = (start.row as u32 + 1, start.column as u32);
+        if name.is_empty() || call_positions.contains(&position) {
+            continue;
+        }
+        let shadowed = matches!(
+            call_type,
+            CallType::Read | CallType::Write | CallType::TypeReference
+        ) && locals.get(name).is_some_and(|scopes| {
+            scopes
+                .iter()
+                .any(|(start, end)| (*start..*end).contains(&node.start_byte()))
+        });
+        if shadowed {
+            continue;
+        }
+
+        let reference = CallSite {
+            callee_name: name.to_string(),
+            line: position.0,
+            column: position.1,
+            call_type,
+            confidence: Confidence::Direct,
+            qualifier: None,
+            enclosing: enclosers.at(node.start_byte()).clone(),
+        };
+        match by_position.get(&position) {
+            Some(&existing) => {
+                if reference_specificity(call_type)
+                    > reference_specificity(references[existing].call_type)
+                {
+                    references[existing] = reference;
+                }
+            }
+            None => {
+                by_position.insert(position, references.len());
+                references.push(reference);
+            }
+        }
+    }
+
+    Ok(references)
+}
+
+/// `self.x = 1` is both a field access and a write; the write wins.
+fn reference_specificity(call_type: CallType) -> u8 {
+    match call_type {
+        CallType::Read => 0,
+        CallType::FieldAccess => 1,
+        CallType::TypeReference => 2,
+        CallType::Write => 3,
+        _ => 4,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn has(references: &[CallSite], name: &str, call_type: CallType) -> bool {
+        references
+            .iter()
+            .any(|reference| reference.callee_name == name && reference.call_type == call_type)
+    }
+
+    #[test]
+    fn test_builtin_reference_queries_pass_validation() {
+        for definition in registry::all_definitions() {
+            if let (Some(grammar), Some(source)) = (&definition.grammar, definition.reference_query)
+            {
+                validate_reference_query(grammar, source, definition.name).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_typescript_references() {
+        let content = r#"
+import { UserDto } from "./dto";
+
+const MAX_USERS = 10;
+let loaded = 0;
+
+@Injectable
+export class UserService {
+  private cache: Map<string, UserDto>;
+
+  load(user: UserDto, limit: number): UserDto[] {
+    const count = limit;
+    loaded += count;
+    this.cache = new Map();
+    if (user.active && loaded < MAX_USERS) {
+      this.cache.set(user.id, user);
+    }
+    return [user];
+  }
+}
+"#;
+        let references = extract_references(content, "typescript").unwrap();
+
+        assert!(has(&references, "UserDto", CallType::TypeReference));
+        assert!(has(&references, "Map", CallType::TypeReference));
+        assert!(!has(&references, "UserService", CallType::TypeReference));
+        assert!(has(&references, "MAX_USERS", CallType::Read));
+        assert!(has(&references, "loaded", CallType::Write));
+        assert!(has(&references, "cache", CallType::Write));
+        assert!(has(&references, "active", CallType::FieldAccess));
+        assert!(has(&references, "Injectable", CallType::Decorator));
+        let read = references
+            .iter()
+            .find(|reference| reference.callee_name == "MAX_USERS")
+            .unwrap();
+        assert_eq!(read.enclosing.name, "load");
+        let decorator = references
+            .iter()
+            .find(|reference| reference.callee_name == "Injectable")
+            .unwrap();
+        assert_eq!(decorator.enclosing.name, call_extractor::MODULE_SYMBOL_NAME);
+        // Parameters and locals shadow module-level names.
+        assert!(!has(&references, "user", CallType::Read));
+        assert!(!has(&references, "limit", CallType::Read));
+        assert!(!has(&references, "count", CallType::Read));
+        // Method calls stay with the call extractor.
+        assert!(!references
+            .iter()
+            .any(|reference| reference.callee_name == "set"));
+    }
+
+    #[test]
+    fn test_block_scoped_bindings_shadow_module_names() {
+        let content = r#"
+const user = loadDefault();
+for (const entry of entries) {
+  audit(entry);
+}
+{
+  const config = localConfig();
+  apply(config);
+}
+function run() {
+  if (ready) {
+    let user = next();
+    send(user);
+  }
+  return user;
+}
+const snapshot = config;
+"#;
+        let references = extract_references(content, "typescript").unwrap();
+        let reads = |name: &str| {
+            references
+                .iter()
+                .filter(|reference| {
+                    reference.callee_name == name && reference.call_type == CallType::Read
+                })
+                .map(|reference| reference.line)
+                .collect::<Vec<_>>()
+        };
+
+        assert!(reads("entry").is_empty());
+        assert_eq!(reads("config"), vec![17]);
+        // Only the read outside the `if` block reaches the module-level `user`.
+        assert_eq!(reads("user"), vec![15]);
+    }
+
+    #[test]
+    fn test_python_references() {
+        let content = r#"
+from dataclasses import dataclass
+from .dto import UserDto
+
+RETRIES = 3
+
+@dataclass
+class Loader:
+    def load(self, user: UserDto) -> list[UserDto]:
+        attempts = RETRIES
+        self.last = user.name
+        return [user] * attempts
+"#;
+        let references = extract_references(content, "python").unwrap();
+
+        assert!(has(&references, "UserDto", CallType::TypeReference));
+        assert!(has(&references, "RETRIES", CallType::Read));
+        assert!(has(&references, "last", CallType::Write));
+        assert!(has(&references, "name", CallType::FieldAccess));
+        assert!(has(&references, "dataclass", CallType::Decorator));
+        assert!(!has(&references, "attempts", CallType::Read));
+        assert!(!has(&references, "user", CallType::Read));
+    }
+
+    #[test]
+    fn test_rust_go_and_java_type_references() {
+        let rust = "struct Repo { users: Vec<UserDto> }\nfn find(repo: &Repo) -> Option<UserDto> { repo.users.first().cloned() }\n";
+        let references = extract_references(rust, "rust").unwrap();
+        assert!(has(&references, "UserDto", CallType::TypeReference));
+        assert!(has(&references, "Repo", CallType::TypeReference));
+        assert!(has(&references, "users", CallType::FieldAccess));
+
+        let go = "package users\n\ntype Repo struct{ cache map[string]UserDto }\n\nfunc (r *Repo) Get(id string) UserDto { return r.cache[id] }\n";
+        let references = extract_references(go, "go").unwrap();
+        assert!(has(&references, "UserDto", CallType::TypeReference));
+        assert!(has(&references, "cache", CallType::FieldAccess));
+        assert!(!has(&references, "id", CallType::Read));
+
+        let java = "@Entity\nclass Repo {\n  private UserDto last;\n  @Override\n  public UserDto get() { return this.last; }\n}\n";
+        let references = extract_references(java, "java").unwrap();
+        assert!(has(&references, "UserDto", CallType::TypeReference));
+        assert!(has(&references, "Entity", CallType::Decorator));
+        assert!(has(&references, "Override", CallType::Decorator));
+        assert!(has(&references, "last", CallType::FieldAccess));
+    }
+
+    #[test]
+    fn test_validate_reference_query_names_the_failing_capture() {
+        let grammar: tree_sitter::Language = tree_sitter_python::LANGUAGE.into();
+        let error = validate_reference_query(&grammar, "(identifier) @reference.usage", "refs.scm")
+            .unwrap_err();
+        assert!(error
+            .to_string()
+            .starts_with("invalid reference query refs.scm: unknown capture `@reference.usage`"));
+    }
+}
@@ -0,0 +1,1239 @@
+use crate::types::Language;
+use anyhow::{anyhow, bail, Context, Result};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tree_sitter::{Parser, Query};
+
+/// Everything the parser and call extractor need to know about a language:
+/// how to recognize its files, which grammar parses it, which node kinds
+/// become chunks and symbols, and how call sites are queried.
+pub struct LanguageDefinition {
+    pub language: Language,
+    pub name: &'static str,
+    pub aliases: Vec<&'static str>,
+    pub extensions: Vec<&'static str>,
+    pub grammar: Option<tree_sitter::Language>,
+    pub semantic_nodes: HashSet<&'static str>,
+    pub comment_nodes: HashSet<&'static str>,
+    pub call_query: Option<&'static str>,
+    /// Query for non-call references: type uses, field accesses, reads,
+    /// writes and decorators.
+    pub reference_query: Option<&'static str>,
+    /// Identifiers compare case-insensitively (PHP functions, Apex).
+    pub case_insensitive: bool,
+}
+
+impl LanguageDefinition {
+    pub fn is_semantic_node(&self, kind: &str) -> bool {
+        self.semantic_nodes.contains(kind)
+    }
+
+    pub fn is_comment_node(&self, kind: &str) -> bool {
+        self.comment_nodes.contains(kind)
+    }
+}
+
+struct BuiltinSpec {
+    language: Language,
+    name: &'static str,
+    aliases: &'static [&'static str],
+    extensions: &'static [&'static str],
+    grammar: Option<fn() -> tree_sitter::Language>,
+    semantic_nodes: &'static [&'static str],
+    comment_nodes: &'static [&'static str],
+    call_query: Option<&'static str>,
+    reference_query: Option<&'static str>,
+    case_insensitive: bool,
+}
+
+const TS_SEMANTIC_NODES: &[&str] = &[
+    "function_declaration",
+    "function",
+    "arrow_function",
+    "method_definition",
+    "class_declaration",
+    "interface_declaration",
+    "type_alias_declaration",
+    "enum_declaration",
+    "export_statement",
+    "lexical_declaration",
+    "abstract_class_declaration",
+    "expression_statement",
+    "if_statement",
+    "for_statement",
+    "return_statement",
+    "try_statement",
+    "while_statement",
+    "statement_block",
+    "for_in_statement",
+];
+
+const JAVA_COMMENT_NODES: &[&str] = &["line_comment", "block_comment"];
+
+const BUILTINS: &[BuiltinSpec] = &[
+    BuiltinSpec {
+        language: Language::TypeScript,
+        name: "typescript",
+        aliases: &["ts"],
+        extensions: &["ts", "mts", "cts"],
+        grammar: Some(|| tree_sitter_typescript::LANGUAGE_TSX.into()),
+        semantic_nodes: TS_SEMANTIC_NODES,
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/typescript-calls.scm")),
+        reference_query: Some(include_str!("../queries/typescript-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::TypeScriptTsx,
+        name: "tsx",
+        aliases: &[],
+        extensions: &["tsx"],
+        grammar: Some(|| tree_sitter_typescript::LANGUAGE_TSX.into()),
+        semantic_nodes: TS_SEMANTIC_NODES,
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/typescript-calls.scm")),
+        reference_query: Some(include_str!("../queries/typescript-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::JavaScript,
+        name: "javascript",
+        aliases: &["js"],
+        extensions: &["js", "mjs", "cjs"],
+        grammar: Some(|| tree_sitter_javascript::LANGUAGE.into()),
+        semantic_nodes: TS_SEMANTIC_NODES,
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/javascript-calls.scm")),
+        reference_query: Some(include_str!("../queries/javascript-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::JavaScriptJsx,
+        name: "jsx",
+        aliases: &[],
+        extensions: &["jsx"],
+        grammar: Some(|| tree_sitter_javascript::LANGUAGE.into()),
+        semantic_nodes: TS_SEMANTIC_NODES,
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/javascript-calls.scm")),
+        reference_query: Some(include_str!("../queries/javascript-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Python,
+        name: "python",
+        aliases: &["py"],
+        extensions: &["py", "pyi"],
+        grammar: Some(|| tree_sitter_python::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "class_definition",
+            "decorated_definition",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/python-calls.scm")),
+        reference_query: Some(include_str!("../queries/python-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Rust,
+        name: "rust",
+        aliases: &["rs"],
+        extensions: &["rs"],
+        grammar: Some(|| tree_sitter_rust::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_item",
+            "impl_item",
+            "struct_item",
+            "enum_item",
+            "trait_item",
+            "mod_item",
+            "macro_definition",
+        ],
+        comment_nodes: &["line_comment", "block_comment"],
+        call_query: Some(include_str!("../queries/rust-calls.scm")),
+        reference_query: Some(include_str!("../queries/rust-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Swift,
+        name: "swift",
+        aliases: &[],
+        extensions: &["swift"],
+        grammar: Some(|| tree_sitter_swift::LANGUAGE.into()),
+        semantic_nodes: &[
+            "class_declaration",
+            "protocol_declaration",
+            "function_declaration",
+            "protocol_function_declaration",
+            "init_declaration",
+            "deinit_declaration",
+            "subscript_declaration",
+        ],
+        comment_nodes: &["comment", "multiline_comment"],
+        call_query: Some(include_str!("../queries/swift-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Go,
+        name: "go",
+        aliases: &[],
+        extensions: &["go"],
+        grammar: Some(|| tree_sitter_go::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_declaration",
+            "method_declaration",
+            "type_declaration",
+            "type_spec",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/go-calls.scm")),
+        reference_query: Some(include_str!("../queries/go-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Java,
+        name: "java",
+        aliases: &[],
+        extensions: &["java"],
+        grammar: Some(|| tree_sitter_java::LANGUAGE.into()),
+        semantic_nodes: &[
+            "class_declaration",
+            "method_declaration",
+            "constructor_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "annotation_type_declaration",
+        ],
+        comment_nodes: JAVA_COMMENT_NODES,
+        call_query: Some(include_str!("../queries/java-calls.scm")),
+        reference_query: Some(include_str!("../queries/java-references.scm")),
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::CSharp,
+        name: "csharp",
+        aliases: &["cs", "c#"],
+        extensions: &["cs"],
+        grammar: Some(|| tree_sitter_c_sharp::LANGUAGE.into()),
+        semantic_nodes: &[
+            "class_declaration",
+            "method_declaration",
+            "constructor_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "struct_declaration",
+            "record_declaration",
+            "property_declaration",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/csharp-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Ruby,
+        name: "ruby",
+        aliases: &["rb"],
+        extensions: &["rb"],
+        grammar: Some(|| tree_sitter_ruby::LANGUAGE.into()),
+        semantic_nodes: &["method", "singleton_method", "class", "module"],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/ruby-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::C,
+        name: "c",
+        aliases: &[],
+        extensions: &["c", "h"],
+        grammar: Some(|| tree_sitter_c::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "struct_specifier",
+            "enum_specifier",
+            "type_definition",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/c-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Cpp,
+        name: "cpp",
+        aliases: &["c++"],
+        extensions: &["cpp", "cc", "cxx", "hpp", "hxx"],
+        grammar: Some(|| tree_sitter_cpp::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "class_specifier",
+            "struct_specifier",
+            "enum_specifier",
+            "namespace_definition",
+            "template_declaration",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/cpp-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Metal,
+        name: "metal",
+        aliases: &[],
+        extensions: &["metal"],
+        grammar: Some(|| tree_sitter_cpp::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "class_specifier",
+            "struct_specifier",
+            "union_specifier",
+            "enum_specifier",
+            "type_definition",
+            "alias_declaration",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/metal-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Json,
+        name: "json",
+        aliases: &[],
+        extensions: &["json"],
+        grammar: Some(|| tree_sitter_json::LANGUAGE.into()),
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Toml,
+        name: "toml",
+        aliases: &[],
+        extensions: &["toml"],
+        grammar: Some(|| tree_sitter_toml_ng::LANGUAGE.into()),
+        semantic_nodes: &["table", "table_array_element"],
+        comment_nodes: &["comment"],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Yaml,
+        name: "yaml",
+        aliases: &["yml"],
+        extensions: &["yaml", "yml"],
+        grammar: Some(|| tree_sitter_yaml::LANGUAGE.into()),
+        semantic_nodes: &["block_mapping_pair", "block_sequence"],
+        comment_nodes: &["comment"],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Bash,
+        name: "bash",
+        aliases: &["sh", "zsh"],
+        extensions: &["sh", "bash", "zsh"],
+        grammar: Some(|| tree_sitter_bash::LANGUAGE.into()),
+        semantic_nodes: &["function_definition"],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/bash-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Markdown,
+        name: "markdown",
+        aliases: &["md"],
+        extensions: &["md", "mdx"],
+        grammar: Some(|| tree_sitter_md::LANGUAGE.into()),
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Html,
+        name: "html",
+        aliases: &["htm"],
+        extensions: &["html", "htm"],
+        grammar: Some(|| tree_sitter_html::LANGUAGE.into()),
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Php,
+        name: "php",
+        aliases: &[],
+        extensions: &["php", "inc"],
+        grammar: Some(|| tree_sitter_php::LANGUAGE_PHP.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "method_declaration",
+            "class_declaration",
+            "interface_declaration",
+            "trait_declaration",
+            "enum_declaration",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/php-calls.scm")),
+        reference_query: None,
+        case_insensitive: true,
+    },
+    // Apex grammar (tree-sitter-sfapex) is Java-derived: the declaration node
+    // kinds match Java exactly, plus `trigger_declaration` which is unique to
+    // Apex (Salesforce database triggers). Verified against tree-sitter-sfapex
+    // 3.0 by parsing representative classes/triggers/interfaces.
+    BuiltinSpec {
+        language: Language::Apex,
+        name: "apex",
+        aliases: &[],
+        extensions: &["cls", "trigger"],
+        grammar: Some(|| tree_sitter_sfapex::apex::LANGUAGE.into()),
+        semantic_nodes: &[
+            "class_declaration",
+            "method_declaration",
+            "constructor_declaration",
+            "interface_declaration",
+            "enum_declaration",
+            "trigger_declaration",
+        ],
+        comment_nodes: JAVA_COMMENT_NODES,
+        call_query: Some(include_str!("../queries/apex-calls.scm")),
+        reference_query: None,
+        case_insensitive: true,
+    },
+    BuiltinSpec {
+        language: Language::Zig,
+        name: "zig",
+        aliases: &[],
+        extensions: &["zig"],
+        grammar: Some(|| tree_sitter_zig::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_declaration",
+            "test_declaration",
+            "struct_declaration",
+            "enum_declaration",
+            "union_declaration",
+            "opaque_declaration",
+            "error_set_declaration",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/zig-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    // GDScript grammar (PrestonKnopp/tree-sitter-gdscript). Declaration-like
+    // nodes only — variable_statement is intentionally excluded because
+    // module-level `var foo = ...` lines would generate one chunk per
+    // variable and drown out real declarations.
+    BuiltinSpec {
+        language: Language::Gdscript,
+        name: "gdscript",
+        aliases: &["gd"],
+        extensions: &["gd"],
+        grammar: Some(|| tree_sitter_gdscript::LANGUAGE.into()),
+        semantic_nodes: &[
+            "function_definition",
+            "constructor_definition",
+            "class_definition",
+            "enum_definition",
+            "signal_statement",
+            "const_statement",
+            "class_name_statement",
+        ],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/gdscript-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Matlab,
+        name: "matlab",
+        aliases: &["m"],
+        extensions: &["m"],
+        grammar: Some(|| tree_sitter_matlab::LANGUAGE.into()),
+        semantic_nodes: &["function_definition", "class_definition"],
+        comment_nodes: &["comment"],
+        call_query: Some(include_str!("../queries/matlab-calls.scm")),
+        reference_query: None,
+        case_insensitive: false,
+    },
+    // Recognized so their files are labeled correctly, but chunked by lines:
+    // no grammar for them is bundled. `.m` is shared with MATLAB and resolved
+    // from content by `detection`.
+    BuiltinSpec {
+        language: Language::ObjectiveC,
+        name: "objc",
+        aliases: &["objective-c", "objectivec"],
+        extensions: &["mm"],
+        grammar: None,
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Dockerfile,
+        name: "dockerfile",
+        aliases: &["docker", "containerfile"],
+        extensions: &["dockerfile", "containerfile"],
+        grammar: None,
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Make,
+        name: "make",
+        aliases: &["makefile"],
+        extensions: &["mk", "mak"],
+        grammar: None,
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+    BuiltinSpec {
+        language: Language::Text,
+        name: "text",
+        aliases: &["txt"],
+        extensions: &["txt"],
+        grammar: None,
+        semantic_nodes: &[],
+        comment_nodes: &[],
+        call_query: None,
+        reference_query: None,
+        case_insensitive: false,
+    },
+];
+
+impl From<&BuiltinSpec> for LanguageDefinition {
+    fn from(spec: &BuiltinSpec) -> Self {
+        Self {
+            language: spec.language,
+            name: spec.name,
+            aliases: spec.aliases.to_vec(),
+            extensions: spec.extensions.to_vec(),
+            grammar: spec.grammar.map(|grammar| grammar()),
+            semantic_nodes: spec.semantic_nodes.iter().copied().collect(),
+            comment_nodes: spec.comment_nodes.iter().copied().collect(),
+            call_query: spec.call_query,
+            reference_query: spec.reference_query,
+            case_insensitive: spec.case_insensitive,
+        }
+    }
+}
+
+/// Name and extension lookup over built-in and runtime-registered languages.
+/// Registered definitions are leaked so lookups can hand out `'static`
+/// references; registrations are expected once per process, at startup.
+pub struct LanguageRegistry {
+    custom: Vec<&'static LanguageDefinition>,
+    by_name: HashMap<String, Language>,
+    by_extension: HashMap<String, Language>,
+}
+
+impl LanguageRegistry {
+    fn with_builtins() -> Self {
+        let mut registry = Self {
+            custom: Vec::new(),
+            by_name: HashMap::new(),
+            by_extension: HashMap::new(),
+        };
+        for definition in BUILTIN_DEFINITIONS.values() {
+            registry.index(definition);
+        }
+        registry
+    }
+
+    fn index(&mut self, definition: &LanguageDefinition) {
+        for name in std::iter::once(&definition.name).chain(&definition.aliases) {
+            self.by_name.insert(name.to_string(), definition.language);
+        }
+        for extension in &definition.extensions {
+            self.by_extension
+                .insert(extension.to_string(), definition.language);
+        }
+    }
+
+    /// Drops the names and extensions `definition` still owns, handing any a
+    /// built-in also claims back to that built-in.
+    fn unindex(&mut self, definition: &LanguageDefinition) {
+        let owned = |map: &mut HashMap<String, Language>, key: &str| {
+            if map.get(key) == Some(&definition.language) {
+                map.remove(key);
+            }
+        };
+        for name in std::iter::once(&definition.name).chain(&definition.aliases) {
+            owned(&mut self.by_name, name);
+        }
+        for extension in &definition.extensions {
+            owned(&mut self.by_extension, extension);
+        }
+        for builtin in BUILTIN_DEFINITIONS.values() {
+            for name in std::iter::once(&builtin.name).chain(&builtin.aliases) {
+                self.by_name
+                    .entry(name.to_string())
+                    .or_insert(builtin.language);
+            }
+            for extension in &builtin.extensions {
+                self.by_extension
+                    .entry(extension.to_string())
+                    .or_insert(builtin.language);
+            }
+        }
+    }
+}
+
+lazy_static! {
+    static ref BUILTIN_DEFINITIONS: HashMap<Language, LanguageDefinition> = BUILTINS
+        .iter()
+        .map(|spec| (spec.language, LanguageDefinition::from(spec)))
+        .collect();
+    static ref REGISTRY: RwLock<LanguageRegistry> = RwLock::new(LanguageRegistry::with_builtins());
+    static ref CALL_QUERY_OVERRIDES: RwLock<CallQueryOverrides> =
+        RwLock::new(CallQueryOverrides::default());
+}
+
+/// Definition backing `language`. Built-ins are served without locking since
+/// this sits on the per-node hot path of chunk extraction.
+pub fn definition(language: &Language) -> &'static LanguageDefinition {
+    match language {
+        Language::Custom(id) => REGISTRY.read().unwrap().custom[*id as usize],
+        builtin => &BUILTIN_DEFINITIONS[builtin],
+    }
+}
+
+pub(crate) fn language_for_extension(extension: &str) -> Option<Language> {
+    let registry = REGISTRY.read().unwrap();
+    registry
+        .by_extension
+        .get(&extension.to_lowercase())
+        .copied()
+}
+
+pub(crate) fn language_for_name(name: &str) -> Option<Language> {
+    let registry = REGISTRY.read().unwrap();
+    registry.by_name.get(&name.to_lowercase()).copied()
+}
+
+/// Names of every case-insensitive language, built-in or registered.
+pub(crate) fn case_insensitive_languages() -> Vec<&'static str> {
+    let registry = REGISTRY.read().unwrap();
+    let mut names: Vec<&'static str> = BUILTIN_DEFINITIONS
+        .values()
+        .chain(registry.custom.iter().copied())
+        .filter(|definition| definition.case_insensitive)
+        .map(|definition| definition.name)
+        .collect();
+    names.sort_unstable();
+    names
+}
+
+/// Every known language, built-ins first, in a stable order.
+pub fn all_definitions() -> Vec<&'static LanguageDefinition> {
+    let registry = REGISTRY.read().unwrap();
+    BUILTINS
+        .iter()
+        .map(|spec| &BUILTIN_DEFINITIONS[&spec.language])
+        .chain(registry.custom.iter().copied())
+        .collect()
+}
+
+/// Position of `language` in `all_definitions()`: built-ins in declaration
+/// order, then registrations in the order they were added.
+#[cfg(feature = "parser-metrics")]
+pub(crate) fn language_index(language: &Language) -> usize {
+    match language {
+        Language::Custom(id) => BUILTINS.len() + *id as usize,
+        builtin => BUILTINS
+            .iter()
+            .position(|spec| spec.language == *builtin)
+            .expect("every built-in language has a spec"),
+    }
+}
+
+/// Whether `language` has a call query, loaded or its definition's own.
+pub fn has_call_query(language: &Language) -> bool {
+    CALL_QUERY_OVERRIDES.read().unwrap().get(language).is_some()
+        || definition(language).call_query.is_some()
+}
+
+/// The compiled call query `extract_calls` runs for `language`: the one
+/// loaded with [`load_call_queries`] if any, otherwise the definition's own.
+pub(crate) fn call_query(language: &Language) -> Result<Option<Arc<Query>>> {
+    if let Some(loaded) = CALL_QUERY_OVERRIDES.read().unwrap().get(language) {
+        return Ok(Some(Arc::clone(&loaded.query)));
+    }
+    let definition = definition(language);
+    match (&definition.grammar, definition.call_query) {
+        (Some(grammar), Some(source)) => crate::query_cache::query(
+            *language,
+            crate::query_cache::QueryKind::Calls,
+            grammar,
+            source,
+        )
+        .map(Some),
+        _ => Ok(None),
+    }
+}
+
+/// How a user-supplied call query combines with the language's own.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum CallQueryMode {
+    /// Runs alongside the built-in patterns.
+    #[default]
+    Extend,
+    /// Runs instead of the built-in patterns.
+    Replace,
+}
+
+impl CallQueryMode {
+    pub fn from_name(name: &str) -> Result<Self> {
+        match name {
+            "extend" => Ok(Self::Extend),
+            "replace" => Ok(Self::Replace),
+            other => bail!(
+                "unknown call query mode `{}`; expected `extend` or `replace`",
+                other
+            ),
+        }
+    }
+}
+
+/// A `.scm` file of extra call patterns for one language, written with the
+/// same capture names as the built-in queries.
+#[derive(Debug, Clone)]
+pub struct CallQueryConfig {
+    pub language: String,
+    pub path: PathBuf,
+    pub mode: CallQueryMode,
+}
+
+/// A user-supplied call query merged with the patterns it extends, compiled
+/// when it is loaded.
+pub(crate) struct LoadedCallQuery {
+    pub source: String,
+    pub query: Arc<Query>,
+}
+
+/// The user-supplied call queries in effect, by language.
+#[derive(Clone, Default)]
+pub struct CallQueryOverrides {
+    queries: HashMap<Language, Arc<LoadedCallQuery>>,
+}
+
+impl CallQueryOverrides {
+    pub(crate) fn get(&self, language: &Language) -> Option<&LoadedCallQuery> {
+        self.queries.get(language).map(Arc::as_ref)
+    }
+
+    /// The overrides `configs` describe, validating every file first.
+    /// Several files for one language are concatenated in order; any
+    /// `replace` entry drops that language's built-in patterns. Queries whose
+    /// merged source matches one in `self` are reused rather than recompiled.
+    /// Also returns the languages whose call query is overridden.
+    pub fn load(&self, configs: &[CallQueryConfig]) -> Result<(Self, Vec<String>)> {
+        let mut merged: Vec<(Language, CallQueryMode, String)> = Vec::new();
+        for config in configs {
+            let language = language_for_name(&config.language)
+                .ok_or_else(|| anyhow!("unknown language `{}` for call query", config.language))?;
+            let grammar = definition(&language).grammar.as_ref().ok_or_else(|| {
+                anyhow!(
+                    "language `{}` has no grammar to run a call query against",
+                    config.language
+                )
+            })?;
+            let source = std::fs::read_to_string(&config.path)
+                .with_context(|| format!("Failed to read call query {}", config.path.display()))?;
+            crate::call_extractor::validate_call_query(
+                grammar,
+                &source,
+                &config.path.display().to_string(),
+            )?;
+
+            match merged
+                .iter_mut()
+                .find(|(existing, _, _)| *existing == language)
+            {
+                Some((_, mode, combined)) => {
+                    if config.mode == CallQueryMode::Replace {
+                        *mode = CallQueryMode::Replace;
+                    }
+                    combined.push('\n');
+                    combined.push_str(&source);
+                }
+                None => merged.push((language, config.mode, source)),
+            }
+        }
+
+        let mut queries = HashMap::new();
+        let mut names = Vec::new();
+        for (language, mode, source) in merged {
+            let definition = definition(&language);
+            let source = match (mode, definition.call_query) {
+                (CallQueryMode::Extend, Some(builtin)) => format!("{}\n{}", builtin, source),
+                _ => source,
+            };
+            let loaded = match self.queries.get(&language) {
+                Some(previous) if previous.source == source => Arc::clone(previous),
+                _ => {
+                    let grammar = definition
+                        .grammar
+                        .as_ref()
+                        .ok_or_else(|| anyhow!("language `{}` has no grammar", definition.name))?;
+                    let query = Query::new(grammar, &source).map_err(|error| {
+                        anyhow!(
+                            "merged call query for `{}` is invalid: {}",
+                            definition.name,
+                            error
+                        )
+                    })?;
+                    Arc::new(LoadedCallQuery {
+                        source,
+                        query: Arc::new(query),
+                    })
+                }
+            };
+            queries.insert(language, loaded);
+            names.push(definition.name.to_string());
+        }
+        Ok((Self { queries }, names))
+    }
+}
+
+/// Replaces the process's user-supplied call queries with `configs`; see
+/// [`CallQueryOverrides::load`]. A bad file leaves the previous queries in
+/// place, and unchanged queries are not recompiled. Returns the languages
+/// whose call query is now overridden.
+pub fn load_call_queries(configs: &[CallQueryConfig]) -> Result<Vec<String>> {
+    let current = CALL_QUERY_OVERRIDES.read().unwrap().clone();
+    let (overrides, names) = current.load(configs)?;
+    *CALL_QUERY_OVERRIDES.write().unwrap() = overrides;
+    Ok(names)
+}
+
+/// One `languages[]` entry of a registry config file. The grammar is either a
+/// built-in one reused under a new name (`grammar`) or a compiled tree-sitter
+/// grammar loaded from a shared library (`library` + `symbol`).
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase", deny_unknown_fields)]
+pub struct LanguageConfig {
+    pub name: String,
+    #[serde(default)]
+    pub aliases: Vec<String>,
+    pub extensions: Vec<String>,
+    pub grammar: Option<String>,
+    pub library: Option<PathBuf>,
+    /// Exported grammar function; defaults to `tree_sitter_<name>`.
+    pub symbol: Option<String>,
+    pub semantic_nodes: Vec<String>,
+    #[serde(default)]
+    pub comment_nodes: Vec<String>,
+    /// Path to a `.scm` call query using the built-in capture names.
+    pub call_query: Option<PathBuf>,
+    /// Path to a `.scm` reference query using the `@reference.*` captures.
+    pub reference_query: Option<PathBuf>,
+    #[serde(default)]
+    pub case_insensitive: bool,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RegistryFile {
+    languages: Vec<LanguageConfig>,
+}
+
+/// Registers every language described by the JSON config at `path`. Relative
+/// `library`, `callQuery` and `referenceQuery` paths resolve against the
+/// config's directory. Returns the registered language names.
+pub fn register_languages_from_file(path: &Path) -> Result<Vec<String>> {
+    let text = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read language config {}", path.display()))?;
+    let file: RegistryFile = serde_json::from_str(&text)
+        .with_context(|| format!("Invalid language config {}", path.display()))?;
+    let base_dir = path.parent().unwrap_or(Path::new("."));
+
+    file.languages
+        .into_iter()
+        .map(|config| {
+            let name = config.name.clone();
+            register_language(config, base_dir)
+                .with_context(|| format!("Failed to register language `{}`", name))?;
+            Ok(name)
+        })
+        .collect()
+}
+
+/// Validates `config` against its grammar and makes it available to the
+/// parser and call extractor. Re-registering a name replaces the definition.
+pub fn register_language(config: LanguageConfig, base_dir: &Path) -> Result<Language> {
+    let name = config.name.to_lowercase();
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-')
+    {
+        bail!("language names may only contain a-z, 0-9, `_` and `-`");
+    }
+    if BUILTIN_DEFINITIONS
+        .values()
+        .any(|builtin| builtin.name == name || builtin.aliases.contains(&name.as_str()))
+    {
+        bail!("`{}` is a built-in language and cannot be redefined", name);
+    }
+    if config.extensions.is_empty() {
+        bail!("at least one extension is required");
+    }
+
+    let grammar = match (&config.grammar, &config.library) {
+        (Some(builtin), None) => {
+            let language = language_for_name(builtin)
+                .filter(|language| !matches!(language, Language::Custom(_)))
+                .ok_or_else(|| anyhow!("unknown built-in grammar `{}`", builtin))?;
+            definition(&language)
+                .grammar
+                .clone()
+                .ok_or_else(|| anyhow!("built-in language `{}` has no grammar", builtin))?
+        }
+        (None, Some(library)) => {
+            let symbol = config
+                .symbol
+                .clone()
+                .unwrap_or_else(|| format!("tree_sitter_{}", name.replace('-', "_")));
+            load_grammar(&base_dir.join(library), &symbol)?
+        }
+        _ => bail!("exactly one of `grammar` or `library` must be set"),
+    };
+    Parser::new()
+        .set_language(&grammar)
+        .map_err(|error| anyhow!("incompatible grammar: {}", error))?;
+
+    for kind in config.semantic_nodes.iter().chain(&config.comment_nodes) {
+        if grammar.id_for_node_kind(kind, true) == 0 {
+            bail!("grammar has no node kind `{}`", kind);
+        }
+    }
+
+    let call_query = match &config.call_query {
+        Some(path) => {
+            let path = base_dir.join(path);
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read call query {}", path.display()))?;
+            crate::call_extractor::validate_call_query(
+                &grammar,
+                &source,
+                &path.display().to_string(),
+            )?;
+            Some(leak(source))
+        }
+        None => None,
+    };
+    let reference_query = match &config.reference_query {
+        Some(path) => {
+            let path = base_dir.join(path);
+            let source = std::fs::read_to_string(&path)
+                .with_context(|| format!("Failed to read reference query {}", path.display()))?;
+            crate::reference_extractor::validate_reference_query(
+                &grammar,
+                &source,
+                &path.display().to_string(),
+            )?;
+            Some(leak(source))
+        }
+        None => None,
+    };
+
+    let mut registry = REGISTRY.write().unwrap();
+    let existing = registry
+        .by_name
+        .get(&name)
+        .copied()
+        .filter(|language| matches!(language, Language::Custom(_)));
+    let language = existing.unwrap_or(Language::Custom(registry.custom.len() as u16));
+    let definition: &'static LanguageDefinition = Box::leak(Box::new(LanguageDefinition {
+        language,
+        name: leak(name),
+        aliases: config
+            .aliases
+            .into_iter()
+            .map(|alias| leak(alias.to_lowercase()))
+            .collect(),
+        extensions: config
+            .extensions
+            .into_iter()
+            .map(|extension| leak(extension.trim_start_matches('.').to_lowercase()))
+            .collect(),
+        grammar: Some(grammar),
+        semantic_nodes: config.semantic_nodes.into_iter().map(leak).collect(),
+        comment_nodes: config.comment_nodes.into_iter().map(leak).collect(),
+        call_query,
+        reference_query,
+        case_insensitive: config.case_insensitive,
+    }));
+
+    match language {
+        Language::Custom(id) if (id as usize) < registry.custom.len() => {
+            let previous = registry.custom[id as usize];
+            registry.unindex(previous);
+            registry.custom[id as usize] = definition;
+        }
+        _ => registry.custom.push(definition),
+    }
+    registry.index(definition);
+    Ok(language)
+}
+
+fn load_grammar(path: &Path, symbol: &str) -> Result<tree_sitter::Language> {
+    // SAFETY: the library is trusted project configuration. It is never
+    // unloaded because parsed trees keep pointers into its grammar tables.
+    unsafe {
+        let library = libloading::Library::new(path)
+            .with_context(|| format!("Failed to load grammar library {}", path.display()))?;
+        let function = library
+            .get::<unsafe extern "C" fn() -> *const ()>(symbol.as_bytes())
+            .with_context(|| format!("{} does not export `{}`", path.display(), symbol))?;
+        let language_fn = tree_sitter_language::LanguageFn::from_raw(*function);
+        std::mem::forget(library);
+        Ok(tree_sitter::Language::new(language_fn))
+    }
+}
+
+fn leak(text: String) -> &'static str {
+    Box::leak(text.into_boxed_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunker::ChunkingProfile;
+    use crate::parser::parse_file_internal;
+    use streaming_iterator::StreamingIterator;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_builtin_definitions_cover_extensions_and_aliases() {
+        assert_eq!(language_for_extension("MTS"), Some(Language::TypeScript));
+        assert_eq!(language_for_name("c#"), Some(Language::CSharp));
+        assert!(definition(&Language::Rust).is_semantic_node("impl_item"));
+        assert!(definition(&Language::Java).is_comment_node("block_comment"));
+        assert_eq!(case_insensitive_languages(), vec!["apex", "php"]);
+        assert!(definition(&Language::Text).grammar.is_none());
+    }
+
+    #[test]
+    fn test_register_language_from_config_file() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("tasks-calls.scm"),
+            "(call function: (identifier) @callee.name) @call\n",
+        )
+        .unwrap();
+        let config_path = dir.path().join("languages.json");
+        std::fs::write(
+            &config_path,
+            r#"{
+                "languages": [{
+                    "name": "pytasks",
+                    "extensions": [".pytask"],
+                    "grammar": "python",
+                    "semanticNodes": ["function_definition"],
+                    "commentNodes": ["comment"],
+                    "callQuery": "tasks-calls.scm"
+                }]
+            }"#,
+        )
+        .unwrap();
+
+        let names = register_languages_from_file(&config_path).unwrap();
+        assert_eq!(names, vec!["pytasks"]);
+        let language = Language::from_extension("pytask");
+        assert!(matches!(language, Language::Custom(_)));
+        assert_eq!(language.as_str(), "pytasks");
+        assert_eq!(Language::from_string("PyTasks"), language);
+
+        let content = "# Builds the artifacts for the release.\ndef build(target):\n    compile(target)\n    return package(target)\n";
+        let chunks =
+            parse_file_internal("release.pytask", content, &ChunkingProfile::default()).unwrap();
+        assert_eq!(chunks.len(), 1);
+        assert_eq!(chunks[0].chunk_type, "function_definition");
+        assert_eq!(chunks[0].name.as_deref(), Some("build"));
+        assert_eq!(chunks[0].language, "pytasks");
+        assert_eq!(
+            chunks[0].doc_comment.as_deref(),
+            Some("Builds the artifacts for the release.")
+        );
+
+        let calls = crate::call_extractor::extract_calls(content, "pytasks").unwrap();
+        let names: Vec<_> = calls.iter().map(|call| call.callee_name.as_str()).collect();
+        assert_eq!(names, vec!["compile", "package"]);
+    }
+
+    #[test]
+    fn test_register_language_rejects_invalid_configs() {
+        let dir = TempDir::new().unwrap();
+        let config = |json: &str| serde_json::from_str::<LanguageConfig>(json).unwrap();
+
+        let error = register_language(
+            config(r#"{"name": "rust", "extensions": ["rsx"], "grammar": "rust", "semanticNodes": []}"#),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("built-in language"));
+
+        let error = register_language(
+            config(r#"{"name": "badkinds", "extensions": ["bk"], "grammar": "python", "semanticNodes": ["function_item"]}"#),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "grammar has no node kind `function_item`"
+        );
+
+        let error = register_language(
+            config(r#"{"name": "nolib", "extensions": ["nl"], "library": "missing.so", "semanticNodes": []}"#),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().contains("Failed to load grammar library"));
+
+        std::fs::write(
+            dir.path().join("broken.scm"),
+            "(call function: @callee.name",
+        )
+        .unwrap();
+        let error = register_language(
+            config(r#"{"name": "badquery", "extensions": ["bq"], "grammar": "python", "semanticNodes": [], "callQuery": "broken.scm"}"#),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("invalid call query"));
+        assert_eq!(Language::from_extension("bq"), Language::Text);
+
+        std::fs::write(dir.path().join("refs.scm"), "(identifier) @reference.usage").unwrap();
+        let error = register_language(
+            config(r#"{"name": "badrefs", "extensions": ["br"], "grammar": "python", "semanticNodes": [], "referenceQuery": "refs.scm"}"#),
+            dir.path(),
+        )
+        .unwrap_err();
+        assert!(error.to_string().starts_with("invalid reference query"));
+    }
+
+    #[test]
+    fn test_reregistering_language_drops_previous_aliases_and_extensions() {
+        let dir = TempDir::new().unwrap();
+        let config = |json: &str| serde_json::from_str::<LanguageConfig>(json).unwrap();
+
+        let first = register_language(
+            config(r#"{"name": "recipes", "aliases": ["cook"], "extensions": ["recipe", "rcp"], "grammar": "python", "semanticNodes": []}"#),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(Language::from_extension("rcp"), first);
+        assert_eq!(Language::from_string("cook"), first);
+
+        let second = register_language(
+            config(r#"{"name": "recipes", "aliases": ["chef"], "extensions": ["recipe"], "grammar": "python", "semanticNodes": []}"#),
+            dir.path(),
+        )
+        .unwrap();
+        assert_eq!(second, first);
+        assert_eq!(Language::from_extension("recipe"), second);
+        assert_eq!(Language::from_string("chef"), second);
+        assert_eq!(Language::from_extension("rcp"), Language::Text);
+        assert_eq!(Language::from_string("cook"), Language::Text);
+    }
+
+    #[test]
+    fn test_load_call_queries_extends_builtin_patterns() {
+        let dir = TempDir::new().unwrap();
+        let events = dir.path().join("events.scm");
+        std::fs::write(
+            &events,
+            r#"; dispatch("event") calls the handler registered for "event".
+(call_expression
+  function: (identifier) @_dispatch
+  arguments: (arguments . (string (string_fragment) @callee.name))
+  (#eq? @_dispatch "dispatch")) @call
+
+; container.resolve<T>() instantiates T.
+(call_expression
+  function: (member_expression
+    property: (property_identifier) @_resolve)
+  type_arguments: (type_arguments (type_identifier) @callee.name)
+  (#eq? @_resolve "resolve")) @constructor
+"#,
+        )
+        .unwrap();
+        let broken = dir.path().join("broken.scm");
+        std::fs::write(
+            &broken,
+            "(call_expression function: (identifier) @callee.nme) @call\n",
+        )
+        .unwrap();
+
+        let content = "function boot(container) {\n  dispatch(\"userCreated\");\n  const users = container.resolve<UserService>();\n}\n";
+        let extend = [CallQueryConfig {
+            language: "ts".to_string(),
+            path: events.clone(),
+            mode: CallQueryMode::Extend,
+        }];
+        let (overrides, names) = CallQueryOverrides::default().load(&extend).unwrap();
+        assert_eq!(names, vec!["typescript"]);
+
+        let loaded = overrides.get(&Language::TypeScript).unwrap();
+        let grammar = definition(&Language::TypeScript).grammar.clone().unwrap();
+        let tree = crate::call_extractor::parse_source(&grammar, content).unwrap();
+        let mut cursor = tree_sitter::QueryCursor::new();
+        let mut captures = cursor.captures(&loaded.query, tree.root_node(), content.as_bytes());
+        let mut found = Vec::new();
+        while let Some((match_, index)) = captures.next() {
+            let capture = match_.captures[*index];
+            if loaded.query.capture_names()[capture.index as usize] == "callee.name" {
+                found.push(capture.node.utf8_text(content.as_bytes()).unwrap());
+            }
+        }
+        for name in ["dispatch", "userCreated", "resolve", "UserService"] {
+            assert!(found.contains(&name), "{name} not captured");
+        }
+
+        let (reloaded, _) = overrides.load(&extend).unwrap();
+        assert!(Arc::ptr_eq(
+            &reloaded.get(&Language::TypeScript).unwrap().query,
+            &loaded.query
+        ));
+
+        let Err(error) = overrides.load(&[CallQueryConfig {
+            language: "typescript".to_string(),
+            path: broken,
+            mode: CallQueryMode::Replace,
+        }]) else {
+            panic!("a broken call query should not load");
+        };
+        assert!(error.to_string().contains("unknown capture `@callee.nme`"));
+
+        let (cleared, names) = overrides.load(&[]).unwrap();
+        assert!(names.is_empty());
+        assert!(cleared.get(&Language::TypeScript).is_none());
+    }
+}
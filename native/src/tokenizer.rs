@@ -0,0 +1,616 @@
+use anyhow::{anyhow, Result};
+use lazy_static::lazy_static;
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Counts tokens the way an embedding model would, so chunks can be sized
+/// against the model's input limit instead of a byte heuristic.
+pub trait Tokenizer: Send + Sync {
+    fn count_tokens(&self, text: &str) -> usize;
+
+    /// Tokens the model adds around every input, such as `[CLS]` and `[SEP]`.
+    fn special_tokens(&self) -> usize {
+        0
+    }
+}
+
+/// WordPiece models wrap input in `[CLS]` ... `[SEP]`, BPE models in
+/// `<s>` ... `</s>`, unless a `tokenizer.json` says otherwise.
+const DEFAULT_SPECIAL_TOKENS: usize = 2;
+
+/// The historical `len / 4` estimate, used until a vocabulary is configured.
+pub struct CharRatioTokenizer {
+    chars_per_token: usize,
+}
+
+impl Default for CharRatioTokenizer {
+    fn default() -> Self {
+        Self { chars_per_token: 4 }
+    }
+}
+
+impl Tokenizer for CharRatioTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        text.len() / self.chars_per_token
+    }
+}
+
+/// BERT-style WordPiece: whitespace and punctuation pre-splitting followed by
+/// greedy longest-match-first lookup against the vocabulary.
+pub struct WordPieceTokenizer {
+    vocab: HashSet<String>,
+    continuing_prefix: String,
+    lowercase: bool,
+    max_word_chars: usize,
+    special_tokens: usize,
+}
+
+impl WordPieceTokenizer {
+    pub fn new(vocab: HashSet<String>, continuing_prefix: &str, lowercase: bool) -> Self {
+        Self {
+            vocab,
+            continuing_prefix: continuing_prefix.to_string(),
+            lowercase,
+            max_word_chars: 100,
+            special_tokens: DEFAULT_SPECIAL_TOKENS,
+        }
+    }
+
+    /// Loads a `vocab.txt` with one token per line.
+    pub fn from_vocab_file(path: &Path, lowercase: bool) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let vocab: HashSet<String> = content
+            .lines()
+            .map(|line| line.trim_end_matches('\r').to_string())
+            .filter(|line| !line.is_empty())
+            .collect();
+        if vocab.is_empty() {
+            return Err(anyhow!("WordPiece vocab {} is empty", path.display()));
+        }
+        Ok(Self::new(vocab, "##", lowercase))
+    }
+
+    fn count_word(&self, word: &str) -> usize {
+        let chars: Vec<char> = word.chars().collect();
+        if chars.len() > self.max_word_chars {
+            return 1;
+        }
+
+        let mut count = 0;
+        let mut start = 0;
+        let mut piece = String::new();
+        while start < chars.len() {
+            let mut end = chars.len();
+            let mut matched = false;
+            while start < end {
+                piece.clear();
+                if start > 0 {
+                    piece.push_str(&self.continuing_prefix);
+                }
+                piece.extend(&chars[start..end]);
+                if self.vocab.contains(&piece) {
+                    matched = true;
+                    break;
+                }
+                end -= 1;
+            }
+            if !matched {
+                // The whole word maps to the unknown token.
+                return 1;
+            }
+            count += 1;
+            start = end;
+        }
+        count
+    }
+}
+
+impl Tokenizer for WordPieceTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        let text = if self.lowercase {
+            text.to_lowercase()
+        } else {
+            text.to_string()
+        };
+
+        let mut count = 0;
+        for word in text.split_whitespace() {
+            let mut run_start = None;
+            for (idx, ch) in word.char_indices() {
+                if ch.is_alphanumeric() {
+                    run_start.get_or_insert(idx);
+                    continue;
+                }
+                if let Some(start) = run_start.take() {
+                    count += self.count_word(&word[start..idx]);
+                }
+                count += self.count_word(&word[idx..idx + ch.len_utf8()]);
+            }
+            if let Some(start) = run_start {
+                count += self.count_word(&word[start..]);
+            }
+        }
+        count
+    }
+
+    fn special_tokens(&self) -> usize {
+        self.special_tokens
+    }
+}
+
+/// GPT-2 style byte-level BPE driven by a merges table.
+pub struct BpeTokenizer {
+    ranks: HashMap<(String, String), usize>,
+    byte_encoder: [char; 256],
+    special_tokens: usize,
+}
+
+impl BpeTokenizer {
+    pub fn new(merges: Vec<(String, String)>) -> Self {
+        let ranks = merges
+            .into_iter()
+            .enumerate()
+            .map(|(rank, pair)| (pair, rank))
+            .collect();
+        Self {
+            ranks,
+            byte_encoder: byte_level_alphabet(),
+            special_tokens: DEFAULT_SPECIAL_TOKENS,
+        }
+    }
+
+    /// Loads a `merges.txt` (one space-separated pair per line, optional
+    /// `#version` header). The vocabulary itself is not needed to count tokens.
+    pub fn from_merges_file(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)?;
+        let merges = content
+            .lines()
+            .filter(|line| !line.starts_with("#version") && !line.trim().is_empty())
+            .map(parse_merge_line)
+            .collect::<Result<Vec<_>>>()?;
+        if merges.is_empty() {
+            return Err(anyhow!("BPE merges file {} is empty", path.display()));
+        }
+        Ok(Self::new(merges))
+    }
+
+    fn count_piece(&self, piece: &str) -> usize {
+        let mut symbols: Vec<String> = piece
+            .bytes()
+            .map(|byte| self.byte_encoder[byte as usize].to_string())
+            .collect();
+
+        while symbols.len() > 1 {
+            let best = symbols
+                .windows(2)
+                .enumerate()
+                .filter_map(|(idx, pair)| {
+                    self.ranks
+                        .get(&(pair[0].clone(), pair[1].clone()))
+                        .map(|rank| (*rank, idx))
+                })
+                .min();
+            let Some((_, idx)) = best else {
+                break;
+            };
+            let right = symbols.remove(idx + 1);
+            symbols[idx].push_str(&right);
+        }
+        symbols.len()
+    }
+}
+
+impl Tokenizer for BpeTokenizer {
+    fn count_tokens(&self, text: &str) -> usize {
+        byte_level_pieces(text)
+            .into_iter()
+            .map(|piece| self.count_piece(piece))
+            .sum()
+    }
+
+    fn special_tokens(&self) -> usize {
+        self.special_tokens
+    }
+}
+
+fn parse_merge_line(line: &str) -> Result<(String, String)> {
+    let mut parts = line.split(' ');
+    match (parts.next(), parts.next(), parts.next()) {
+        (Some(left), Some(right), None) if !left.is_empty() && !right.is_empty() => {
+            Ok((left.to_string(), right.to_string()))
+        }
+        _ => Err(anyhow!("Invalid BPE merge line: {:?}", line)),
+    }
+}
+
+/// GPT-2's reversible byte → printable-char table.
+fn byte_level_alphabet() -> [char; 256] {
+    let mut table = ['\0'; 256];
+    let mut next = 256u32;
+    for byte in 0..=255u8 {
+        let printable = matches!(byte, b'!'..=b'~' | 0xA1..=0xAC | 0xAE..=0xFF);
+        table[byte as usize] = if printable {
+            byte as char
+        } else {
+            let ch = char::from_u32(next).unwrap_or('\u{FFFD}');
+            next += 1;
+            ch
+        };
+    }
+    table
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum CharClass {
+    Letter,
+    Number,
+    Space,
+    Other,
+}
+
+fn char_class(ch: char) -> CharClass {
+    if ch.is_whitespace() {
+        CharClass::Space
+    } else if ch.is_alphabetic() {
+        CharClass::Letter
+    } else if ch.is_numeric() {
+        CharClass::Number
+    } else {
+        CharClass::Other
+    }
+}
+
+/// Approximates the GPT-2 pre-tokenizer: runs of letters, digits or symbols,
+/// each optionally led by one space, with remaining whitespace kept as runs.
+fn byte_level_pieces(text: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_at = |idx: usize| chars.get(idx).map(|(byte, _)| *byte).unwrap_or(text.len());
+    let run_end = |from: usize, class: CharClass| {
+        let mut end = from;
+        while end < chars.len() && char_class(chars[end].1) == class {
+            end += 1;
+        }
+        end
+    };
+
+    let mut pieces = Vec::new();
+    let mut idx = 0;
+    while idx < chars.len() {
+        let class = char_class(chars[idx].1);
+        if class != CharClass::Space {
+            let end = run_end(idx, class);
+            pieces.push(&text[byte_at(idx)..byte_at(end)]);
+            idx = end;
+            continue;
+        }
+
+        let end = run_end(idx, CharClass::Space);
+        if end < chars.len() && chars[end - 1].1 == ' ' {
+            if end - 1 > idx {
+                pieces.push(&text[byte_at(idx)..byte_at(end - 1)]);
+            }
+            let word_end = run_end(end, char_class(chars[end].1));
+            pieces.push(&text[byte_at(end - 1)..byte_at(word_end)]);
+            idx = word_end;
+        } else {
+            pieces.push(&text[byte_at(idx)..byte_at(end)]);
+            idx = end;
+        }
+    }
+    pieces
+}
+
+/// Loads a Hugging Face `tokenizer.json` with a `WordPiece` or `BPE` model.
+fn from_tokenizer_json(path: &Path, lowercase: Option<bool>) -> Result<Arc<dyn Tokenizer>> {
+    let json: serde_json::Value = serde_json::from_str(&std::fs::read_to_string(path)?)?;
+    let model = &json["model"];
+    let special_tokens = post_processor_special_tokens(&json["post_processor"]);
+    match model["type"].as_str() {
+        Some("WordPiece") => {
+            let vocab = model["vocab"]
+                .as_object()
+                .ok_or_else(|| anyhow!("{} has no WordPiece vocab", path.display()))?
+                .keys()
+                .cloned()
+                .collect();
+            let prefix = model["continuing_subword_prefix"].as_str().unwrap_or("##");
+            let lowercase = lowercase
+                .or_else(|| json["normalizer"]["lowercase"].as_bool())
+                .unwrap_or(false);
+            let mut tokenizer = WordPieceTokenizer::new(vocab, prefix, lowercase);
+            tokenizer.special_tokens = special_tokens;
+            Ok(Arc::new(tokenizer))
+        }
+        Some("BPE") => {
+            let merges = model["merges"]
+                .as_array()
+                .ok_or_else(|| anyhow!("{} has no BPE merges", path.display()))?
+                .iter()
+                .map(|merge| match merge {
+                    serde_json::Value::String(line) => parse_merge_line(line),
+                    serde_json::Value::Array(pair) => match (pair.first(), pair.get(1)) {
+                        (
+                            Some(serde_json::Value::String(left)),
+                            Some(serde_json::Value::String(right)),
+                        ) => Ok((left.clone(), right.clone())),
+                        _ => Err(anyhow!("Invalid BPE merge: {}", merge)),
+                    },
+                    _ => Err(anyhow!("Invalid BPE merge: {}", merge)),
+                })
+                .collect::<Result<Vec<_>>>()?;
+            let mut tokenizer = BpeTokenizer::new(merges);
+            tokenizer.special_tokens = special_tokens;
+            Ok(Arc::new(tokenizer))
+        }
+        other => Err(anyhow!(
+            "Unsupported tokenizer model {:?} in {}",
+            other.unwrap_or("<missing>"),
+            path.display()
+        )),
+    }
+}
+
+/// Special tokens a `tokenizer.json` post-processor adds to a single input.
+fn post_processor_special_tokens(processor: &serde_json::Value) -> usize {
+    match processor["type"].as_str() {
+        None => 0,
+        Some("TemplateProcessing") => processor["single"].as_array().map_or(0, |single| {
+            single
+                .iter()
+                .filter(|piece| piece.get("SpecialToken").is_some())
+                .count()
+        }),
+        Some("Sequence") => processor["processors"].as_array().map_or(0, |processors| {
+            processors.iter().map(post_processor_special_tokens).sum()
+        }),
+        Some("ByteLevel") => 0,
+        Some(_) => DEFAULT_SPECIAL_TOKENS,
+    }
+}
+
+/// Builds a tokenizer from disk. `kind` is `"wordpiece"`, `"bpe"` or
+/// `"chars"`; when omitted it is inferred from the file name
+/// (`tokenizer.json`, `vocab.txt`, or a BPE `merges.txt`).
+pub fn load_tokenizer(
+    kind: Option<&str>,
+    vocab_path: Option<&str>,
+    merges_path: Option<&str>,
+    lowercase: Option<bool>,
+) -> Result<Arc<dyn Tokenizer>> {
+    let vocab_path = vocab_path.map(Path::new);
+    let merges_path = merges_path.map(Path::new);
+    let is_tokenizer_json = vocab_path
+        .and_then(|path| path.file_name())
+        .is_some_and(|name| name.to_string_lossy().ends_with("tokenizer.json"));
+
+    let kind = match kind {
+        Some(kind) => kind.to_lowercase(),
+        None if is_tokenizer_json => "json".to_string(),
+        None if merges_path.is_some() => "bpe".to_string(),
+        None if vocab_path.is_some() => "wordpiece".to_string(),
+        None => "chars".to_string(),
+    };
+
+    match kind.as_str() {
+        "chars" | "heuristic" => Ok(Arc::new(CharRatioTokenizer::default())),
+        _ if is_tokenizer_json => from_tokenizer_json(vocab_path.unwrap(), lowercase),
+        "wordpiece" => {
+            let path =
+                vocab_path.ok_or_else(|| anyhow!("WordPiece tokenizer needs a vocab path"))?;
+            Ok(Arc::new(WordPieceTokenizer::from_vocab_file(
+                path,
+                lowercase.unwrap_or(false),
+            )?))
+        }
+        "bpe" => {
+            let path = merges_path.ok_or_else(|| anyhow!("BPE tokenizer needs a merges path"))?;
+            Ok(Arc::new(BpeTokenizer::from_merges_file(path)?))
+        }
+        other => Err(anyhow!("Unknown tokenizer kind: {}", other)),
+    }
+}
+
+/// A tokenizer shared by the chunk configs that use it. Configs compare
+/// equal when they share the same instance.
+#[derive(Clone)]
+pub struct SharedTokenizer(Arc<dyn Tokenizer>);
+
+lazy_static! {
+    static ref CHAR_RATIO: SharedTokenizer =
+        SharedTokenizer(Arc::new(CharRatioTokenizer::default()));
+    /// Tokenizers loaded from disk, keyed by what was asked for and checked
+    /// against the files' size and mtime, so each parse call does not reload
+    /// a vocabulary.
+    static ref LOADED: Mutex<HashMap<String, (Vec<FileStamp>, SharedTokenizer)>> =
+        Mutex::new(HashMap::new());
+}
+
+type FileStamp = Option<(u64, SystemTime)>;
+
+impl SharedTokenizer {
+    pub fn new(tokenizer: Arc<dyn Tokenizer>) -> Self {
+        Self(tokenizer)
+    }
+
+    /// The tokenizer for `kind` and the given files, loaded once and reused
+    /// until one of the files changes.
+    pub fn load(
+        kind: Option<&str>,
+        vocab_path: Option<&str>,
+        merges_path: Option<&str>,
+        lowercase: Option<bool>,
+    ) -> Result<Self> {
+        if kind.is_none() && vocab_path.is_none() && merges_path.is_none() {
+            return Ok(Self::default());
+        }
+        let key = format!("{kind:?}|{vocab_path:?}|{merges_path:?}|{lowercase:?}");
+        let stamps: Vec<FileStamp> = [vocab_path, merges_path]
+            .into_iter()
+            .flatten()
+            .map(|path| {
+                let metadata = std::fs::metadata(path).ok()?;
+                Some((metadata.len(), metadata.modified().ok()?))
+            })
+            .collect();
+        if let Some((loaded_stamps, tokenizer)) = LOADED.lock().unwrap().get(&key) {
+            if *loaded_stamps == stamps {
+                return Ok(tokenizer.clone());
+            }
+        }
+        let tokenizer = Self(load_tokenizer(kind, vocab_path, merges_path, lowercase)?);
+        LOADED
+            .lock()
+            .unwrap()
+            .insert(key, (stamps, tokenizer.clone()));
+        Ok(tokenizer)
+    }
+
+    /// Tokens `text` takes as one model input, special tokens included.
+    pub fn count_input_tokens(&self, text: &str) -> usize {
+        self.0.count_tokens(text) + self.0.special_tokens()
+    }
+}
+
+impl Default for SharedTokenizer {
+    /// The `len / 4` estimate.
+    fn default() -> Self {
+        CHAR_RATIO.clone()
+    }
+}
+
+impl std::ops::Deref for SharedTokenizer {
+    type Target = dyn Tokenizer;
+
+    fn deref(&self) -> &Self::Target {
+        self.0.as_ref()
+    }
+}
+
+impl std::fmt::Debug for SharedTokenizer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SharedTokenizer")
+            .field("special_tokens", &self.0.special_tokens())
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for SharedTokenizer {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn wordpiece(tokens: &[&str]) -> WordPieceTokenizer {
+        WordPieceTokenizer::new(
+            tokens.iter().map(|token| token.to_string()).collect(),
+            "##",
+            true,
+        )
+    }
+
+    #[test]
+    fn test_wordpiece_greedy_longest_match() {
+        let tokenizer = wordpiece(&["un", "##aff", "##able", "hello", "(", ")", "."]);
+
+        assert_eq!(tokenizer.count_tokens("unaffable"), 3);
+        assert_eq!(tokenizer.count_tokens("Hello hello"), 2);
+        assert_eq!(tokenizer.count_tokens("hello()."), 4);
+        // Unknown words collapse to a single [UNK].
+        assert_eq!(tokenizer.count_tokens("zzz hello"), 2);
+    }
+
+    #[test]
+    fn test_bpe_applies_merges_by_rank() {
+        let tokenizer = BpeTokenizer::new(vec![
+            ("l".into(), "o".into()),
+            ("lo".into(), "w".into()),
+            ("Ġ".into(), "low".into()),
+        ]);
+
+        assert_eq!(tokenizer.count_tokens("low"), 1);
+        assert_eq!(tokenizer.count_tokens("low low"), 2);
+        assert_eq!(tokenizer.count_tokens("lower"), 3);
+    }
+
+    #[test]
+    fn test_byte_level_pieces_attach_single_leading_space() {
+        assert_eq!(
+            byte_level_pieces("let  x = 42;\n"),
+            vec!["let", " ", " x", " =", " 42", ";", "\n"]
+        );
+    }
+
+    #[test]
+    fn test_load_tokenizer_from_files() {
+        let dir = TempDir::new().unwrap();
+        let vocab = dir.path().join("vocab.txt");
+        fs::write(&vocab, "[UNK]\nfn\nmain\n##s\n").unwrap();
+        let merges = dir.path().join("merges.txt");
+        fs::write(&merges, "#version: 0.2\nf n\n").unwrap();
+        let json = dir.path().join("tokenizer.json");
+        fs::write(
+            &json,
+            r###"{"model": {"type": "BPE", "merges": [["f", "n"], "m a"]}}"###,
+        )
+        .unwrap();
+
+        let wordpiece = load_tokenizer(None, vocab.to_str(), None, None).unwrap();
+        assert_eq!(wordpiece.count_tokens("fn mains"), 3);
+
+        let bpe = load_tokenizer(None, None, merges.to_str(), None).unwrap();
+        assert_eq!(bpe.count_tokens("fn"), 1);
+
+        let from_json = load_tokenizer(None, json.to_str(), None, None).unwrap();
+        assert_eq!(from_json.count_tokens("fnma"), 2);
+
+        // Special tokens come from the post-processor when there is one.
+        assert_eq!(wordpiece.special_tokens(), 2);
+        assert_eq!(from_json.special_tokens(), 0);
+        let templated = dir.path().join("templated-tokenizer.json");
+        fs::write(
+            &templated,
+            r###"{"model": {"type": "WordPiece", "vocab": {"fn": 0}},
+                "post_processor": {"type": "TemplateProcessing", "single": [
+                    {"SpecialToken": {"id": "[CLS]"}}, {"Sequence": {"id": "A"}},
+                    {"SpecialToken": {"id": "[SEP]"}}]}}"###,
+        )
+        .unwrap();
+        let templated = load_tokenizer(None, templated.to_str(), None, None).unwrap();
+        assert_eq!(templated.special_tokens(), 2);
+
+        assert!(load_tokenizer(Some("bpe"), None, None, None).is_err());
+        assert!(load_tokenizer(Some("sentencepiece"), None, None, None).is_err());
+    }
+
+    #[test]
+    fn test_shared_tokenizers_are_reused_per_source() {
+        let dir = TempDir::new().unwrap();
+        let vocab = dir.path().join("vocab.txt");
+        fs::write(&vocab, "[UNK]\nfn\nmain\n").unwrap();
+        let merges = dir.path().join("merges.txt");
+        fs::write(&merges, "f n\n").unwrap();
+
+        let wordpiece = SharedTokenizer::load(None, vocab.to_str(), None, None).unwrap();
+        let bpe = SharedTokenizer::load(None, None, merges.to_str(), None).unwrap();
+        assert_eq!(
+            wordpiece,
+            SharedTokenizer::load(None, vocab.to_str(), None, None).unwrap()
+        );
+        // Loading one source leaves tokenizers loaded from another alone.
+        assert_ne!(wordpiece, bpe);
+        assert_eq!(wordpiece.count_tokens("fn main"), 2);
+        assert_eq!(bpe.count_input_tokens("fn"), 3);
+        assert_eq!(
+            SharedTokenizer::load(None, None, None, None).unwrap(),
+            SharedTokenizer::default()
+        );
+    }
+}
@@ -0,0 +1,333 @@
+use anyhow::{anyhow, Result};
+use ignore::gitignore::{Gitignore, GitignoreBuilder};
+use ignore::{WalkBuilder, WalkState};
+use std::path::Path;
+use std::sync::{Arc, Mutex};
+use std::time::UNIX_EPOCH;
+
+#[derive(Debug, Clone)]
+pub struct WalkedFile {
+    pub path: String,
+    pub size: u64,
+    pub mtime_ms: i64,
+    pub hash: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct SkippedWalkEntry {
+    pub path: String,
+    /// `too_large`, or `walk_error`, `metadata_error` or `hash_error` when
+    /// the entry could not be listed, stat'ed or read.
+    pub reason: &'static str,
+    /// The underlying error, for every reason but `too_large`.
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Default)]
+pub struct WalkOutput {
+    pub files: Vec<WalkedFile>,
+    pub skipped: Vec<SkippedWalkEntry>,
+}
+
+/// Builds a gitignore-style matcher from glob patterns anchored at `root`.
+/// Returns `None` when no patterns are given so callers can distinguish
+/// "match nothing" from "no filter configured".
+fn build_glob_matcher(root: &Path, globs: &[String]) -> Result<Option<Gitignore>> {
+    if globs.is_empty() {
+        return Ok(None);
+    }
+
+    let mut builder = GitignoreBuilder::new(root);
+    builder.allow_unclosed_class(false);
+    for glob in globs {
+        builder
+            .add_line(None, glob)
+            .map_err(|e| anyhow!("Invalid glob {:?}: {}", glob, e))?;
+    }
+    let matcher = builder
+        .build()
+        .map_err(|e| anyhow!("Failed to build glob matcher: {}", e))?;
+    Ok(Some(matcher))
+}
+
+/// The path a walk error is about. Errors that name none, such as a failed
+/// read of the root itself, are reported against `root`.
+fn error_path(error: &ignore::Error, root: &Path) -> String {
+    fn named(error: &ignore::Error) -> Option<&Path> {
+        match error {
+            ignore::Error::WithPath { path, .. } => Some(path),
+            ignore::Error::Loop { child, .. } => Some(child),
+            ignore::Error::WithDepth { err, .. } | ignore::Error::WithLineNumber { err, .. } => {
+                named(err)
+            }
+            ignore::Error::Partial(errors) => errors.iter().find_map(named),
+            _ => None,
+        }
+    }
+    named(error).unwrap_or(root).to_string_lossy().to_string()
+}
+
+fn modified_ms(metadata: &std::fs::Metadata) -> i64 {
+    metadata
+        .modified()
+        .ok()
+        .and_then(|modified| modified.duration_since(UNIX_EPOCH).ok())
+        .map(|duration| duration.as_millis() as i64)
+        .unwrap_or(0)
+}
+
+/// Walks `root` in parallel, honoring .gitignore, .ignore, git excludes and any
+/// `custom_ignore_filenames`, and hashes every file that passes the filters.
+///
+/// Exclude globs prune whole directories; include globs are matched against
+/// files only. Hidden entries are skipped, matching the TypeScript walker.
+/// Files that are too large, and entries that could not be listed, stat'ed
+/// or hashed, are reported in `skipped` with their reason.
+pub fn collect_files_parallel(
+    root: &str,
+    include_globs: &[String],
+    exclude_globs: &[String],
+    max_file_size: u64,
+    custom_ignore_filenames: &[String],
+) -> Result<WalkOutput> {
+    let root_path = Path::new(root);
+    if !root_path.is_dir() {
+        return Err(anyhow!("Not a directory: {}", root));
+    }
+
+    let include = build_glob_matcher(root_path, include_globs)?;
+    let exclude = build_glob_matcher(root_path, exclude_globs)?.map(Arc::new);
+
+    let mut builder = WalkBuilder::new(root_path);
+    builder
+        .hidden(true)
+        .git_ignore(true)
+        .git_exclude(true)
+        .ignore(true)
+        .parents(true)
+        .require_git(false);
+    for filename in custom_ignore_filenames {
+        builder.add_custom_ignore_filename(filename);
+    }
+    if let Some(exclude) = exclude.clone() {
+        builder.filter_entry(move |entry| {
+            let is_dir = entry.file_type().is_some_and(|ft| ft.is_dir());
+            !exclude
+                .matched_path_or_any_parents(entry.path(), is_dir)
+                .is_ignore()
+        });
+    }
+
+    let output = Mutex::new(WalkOutput::default());
+
+    builder.build_parallel().run(|| {
+        let include = include.as_ref();
+        let output = &output;
+        Box::new(move |entry| {
+            let skip = |path: String, reason: &'static str, error: Option<String>| {
+                output.lock().unwrap().skipped.push(SkippedWalkEntry {
+                    path,
+                    reason,
+                    error,
+                });
+                WalkState::Continue
+            };
+            let entry = match entry {
+                Ok(entry) => entry,
+                Err(error) => {
+                    return skip(
+                        error_path(&error, root_path),
+                        "walk_error",
+                        Some(error.to_string()),
+                    )
+                }
+            };
+            if !entry.file_type().is_some_and(|ft| ft.is_file()) {
+                return WalkState::Continue;
+            }
+
+            let path = entry.path();
+            if let Some(include) = include {
+                if !include.matched(path, false).is_ignore() {
+                    return WalkState::Continue;
+                }
+            }
+
+            let path_str = path.to_string_lossy().to_string();
+            let metadata = match entry.metadata() {
+                Ok(metadata) => metadata,
+                Err(error) => return skip(path_str, "metadata_error", Some(error.to_string())),
+            };
+
+            if metadata.len() > max_file_size {
+                return skip(path_str, "too_large", None);
+            }
+
+            let hash = match crate::hasher::xxhash_file(&path_str) {
+                Ok(hash) => hash,
+                Err(error) => return skip(path_str, "hash_error", Some(error.to_string())),
+            };
+
+            output.lock().unwrap().files.push(WalkedFile {
+                path: path_str,
+                size: metadata.len(),
+                mtime_ms: modified_ms(&metadata),
+                hash,
+            });
+            WalkState::Continue
+        })
+    });
+
+    let mut output = output.into_inner().unwrap();
+    output.files.sort_by(|a, b| a.path.cmp(&b.path));
+    output.skipped.sort_by(|a, b| a.path.cmp(&b.path));
+    Ok(output)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn write(root: &Path, relative: &str, content: &str) {
+        let path = root.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn relative_paths(root: &Path, output: &WalkOutput) -> Vec<String> {
+        output
+            .files
+            .iter()
+            .map(|file| {
+                Path::new(&file.path)
+                    .strip_prefix(root)
+                    .unwrap()
+                    .to_string_lossy()
+                    .replace('\\', "/")
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_collect_files_honors_gitignore_and_ignore_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, ".gitignore", "generated/\n*.log\n");
+        write(root, "src/.ignore", "skip.ts\n");
+        write(root, "src/main.ts", "export const a = 1;");
+        write(root, "src/skip.ts", "export const b = 2;");
+        write(root, "generated/out.ts", "export const c = 3;");
+        write(root, "debug.log", "noise");
+        write(root, ".hidden/secret.ts", "export const d = 4;");
+
+        let output =
+            collect_files_parallel(root.to_str().unwrap(), &[], &[], 1024 * 1024, &[]).unwrap();
+
+        assert_eq!(relative_paths(root, &output), vec!["src/main.ts"]);
+    }
+
+    #[test]
+    fn test_collect_files_applies_include_exclude_and_custom_ignore_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, ".codebaseignore", "legacy/\n");
+        write(root, "src/a.ts", "export const a = 1;");
+        write(root, "src/b.py", "b = 2");
+        write(root, "src/nested/c.ts", "export const c = 3;");
+        write(root, "vendor/d.ts", "export const d = 4;");
+        write(root, "legacy/e.ts", "export const e = 5;");
+
+        let output = collect_files_parallel(
+            root.to_str().unwrap(),
+            &["**/*.ts".to_string()],
+            &["vendor/**".to_string()],
+            1024 * 1024,
+            &[".codebaseignore".to_string()],
+        )
+        .unwrap();
+
+        assert_eq!(
+            relative_paths(root, &output),
+            vec!["src/a.ts", "src/nested/c.ts"]
+        );
+    }
+
+    #[test]
+    fn test_collect_files_reports_size_mtime_hash_and_oversized_files() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "small.ts", "const x = 1;");
+        write(root, "large.ts", &"x".repeat(2048));
+
+        let output = collect_files_parallel(root.to_str().unwrap(), &[], &[], 1024, &[]).unwrap();
+
+        assert_eq!(output.files.len(), 1);
+        let file = &output.files[0];
+        assert_eq!(file.size, 12);
+        assert!(file.mtime_ms > 0);
+        assert_eq!(file.hash, crate::hasher::xxhash_content("const x = 1;"));
+        assert_eq!(output.skipped.len(), 1);
+        assert!(output.skipped[0].path.ends_with("large.ts"));
+        assert_eq!(output.skipped[0].reason, "too_large");
+    }
+
+    #[test]
+    fn test_walk_errors_are_reported_against_their_path() {
+        let root = Path::new("/project");
+        let io = || ignore::Error::Io(std::io::Error::from(std::io::ErrorKind::PermissionDenied));
+        let nested = ignore::Error::WithDepth {
+            depth: 2,
+            err: Box::new(ignore::Error::WithPath {
+                path: root.join("src/locked"),
+                err: Box::new(io()),
+            }),
+        };
+
+        assert_eq!(error_path(&nested, root), "/project/src/locked");
+        assert_eq!(error_path(&io(), root), "/project");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_files_reports_unreadable_directories() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        write(root, "src/main.ts", "export const a = 1;");
+        write(root, "locked/secret.ts", "export const b = 2;");
+        let locked = root.join("locked");
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o000)).unwrap();
+        let readable = fs::read_dir(&locked).is_ok();
+
+        let output =
+            collect_files_parallel(root.to_str().unwrap(), &[], &[], 1024 * 1024, &[]).unwrap();
+        fs::set_permissions(&locked, fs::Permissions::from_mode(0o755)).unwrap();
+
+        // Permissions do not bind root; the walk then just lists both files.
+        if !readable {
+            assert_eq!(relative_paths(root, &output), vec!["src/main.ts"]);
+            assert_eq!(output.skipped.len(), 1);
+            assert_eq!(output.skipped[0].reason, "walk_error");
+            assert!(output.skipped[0].path.ends_with("locked"));
+            assert!(output.skipped[0].error.is_some());
+        }
+    }
+
+    #[test]
+    fn test_collect_files_rejects_missing_root_and_invalid_globs() {
+        assert!(collect_files_parallel("/definitely/not/here", &[], &[], 1024, &[]).is_err());
+
+        let dir = TempDir::new().unwrap();
+        let result = collect_files_parallel(
+            dir.path().to_str().unwrap(),
+            &["src/[".to_string()],
+            &[],
+            1024,
+            &[],
+        );
+        assert!(result.is_err());
+    }
+}
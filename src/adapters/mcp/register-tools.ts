@@ -287,7 +287,8 @@ export function registerMcpTools(server: McpServer, runtime: McpServerRuntime):
   server.tool(
     TOOL_NAME.CALL_GRAPH,
     "Find direct callers or callees by function or method name. Unique names resolve automatically; when duplicate names are reported, retry with filePath."
-      + " Supports relationship types: Call, MethodCall, Constructor, Import, Inherits, Implements.",
+      + " Supports relationship types: Call, MethodCall, Constructor, Import, Inherits, Implements,"
+      + " TypeReference, FieldAccess, Read, Write, Decorator; the last five are only returned when relationshipType names one.",
     {
       name: z.string().describe("Function or method name to query"),
       direction: allowNullAsUndefined(
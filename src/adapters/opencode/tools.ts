@@ -322,7 +322,8 @@ export const implementation_lookup: ToolDefinition = tool({
 export const call_graph: ToolDefinition = tool({
   description:
     "Query the call graph by function or method name to find direct callers or callees. Unique names resolve automatically; use filePath only when duplicate names are reported."
-    + " Supports relationship types: Call, MethodCall, Constructor, Import, Inherits, Implements.",
+    + " Supports relationship types: Call, MethodCall, Constructor, Import, Inherits, Implements,"
+    + " TypeReference, FieldAccess, Read, Write, Decorator; the last five are only returned when relationshipType names one.",
   args: {
     name: z.string().describe("Function or method name to query"),
     direction: z.enum(CALL_GRAPH_DIRECTIONS).default("callers").describe("Direction: 'callers' finds who calls this function, 'callees' finds what this function calls"),
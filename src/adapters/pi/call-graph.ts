@@ -14,6 +14,11 @@ const RelationshipType = Type.Union([
   Type.Literal("Import"),
   Type.Literal("Inherits"),
   Type.Literal("Implements"),
+  Type.Literal("TypeReference"),
+  Type.Literal("FieldAccess"),
+  Type.Literal("Read"),
+  Type.Literal("Write"),
+  Type.Literal("Decorator"),
 ]);
 
 function text(text: string, details?: unknown) {
@@ -26,6 +26,8 @@ export function getDefaultIndexingConfig(): IndexingConfig {
     maxFilesPerDirectory: 100,
     fallbackToTextOnMaxChunks: true,
     gitBlame: { enabled: false },
+    callQueries: [],
+    tokenizer: null,
   };
 }
 
@@ -68,6 +68,33 @@ export interface IndexingConfig {
   gitBlame: {
     enabled: boolean;
   };
+  /**
+   * Extra tree-sitter call query files, relative to the project root, run by
+   * call graph extraction alongside (`extend`, default) or instead of
+   * (`replace`) the built-in patterns for their language.
+   */
+  callQueries: CallQueryConfig[];
+  /**
+   * Native tokenizer used to size chunks against the embedding model's token
+   * limit. Paths are relative to the project root. `null` sizes chunks by
+   * bytes only.
+   */
+  tokenizer: TokenizerConfig | null;
+}
+
+export interface TokenizerConfig {
+  kind?: "wordpiece" | "bpe" | "chars";
+  vocabPath?: string;
+  mergesPath?: string;
+  lowercase?: boolean;
+  /** Defaults to the embedding model's safe chunk token limit. */
+  maxChunkTokens?: number;
+}
+
+export interface CallQueryConfig {
+  language: string;
+  path: string;
+  mode: "extend" | "replace";
 }
 
 export interface SearchConfig {
@@ -179,6 +206,18 @@ export type ParsedCodebaseIndexConfig = CodebaseIndexConfig & {
   additionalInclude: string[];
 };
 
+function parseTokenizerConfig(raw: unknown): TokenizerConfig | null {
+  if (!raw || typeof raw !== "object") return null;
+  const { kind, vocabPath, mergesPath, lowercase, maxChunkTokens } = raw as Record<string, unknown>;
+  const config: TokenizerConfig = {};
+  if (kind === "wordpiece" || kind === "bpe" || kind === "chars") config.kind = kind;
+  if (typeof vocabPath === "string") config.vocabPath = vocabPath;
+  if (typeof mergesPath === "string") config.mergesPath = mergesPath;
+  if (typeof lowercase === "boolean") config.lowercase = lowercase;
+  if (typeof maxChunkTokens === "number" && maxChunkTokens >= 1) config.maxChunkTokens = Math.floor(maxChunkTokens);
+  return config;
+}
+
 export function parseConfig(raw: unknown): ParsedCodebaseIndexConfig {
   const input = (raw && typeof raw === "object" ? raw : {}) as Record<string, unknown>;
   const embeddingProviderValue = getResolvedString(input.embeddingProvider, "$root.embeddingProvider");
@@ -223,6 +262,15 @@ export function parseConfig(raw: unknown): ParsedCodebaseIndexConfig {
         ? (rawIndexing.gitBlame as { enabled: boolean }).enabled
         : defaultIndexing.gitBlame.enabled,
     },
+    callQueries: Array.isArray(rawIndexing.callQueries)
+      ? rawIndexing.callQueries.flatMap((entry): CallQueryConfig[] => {
+        if (!entry || typeof entry !== "object") return [];
+        const { language, path, mode } = entry as Record<string, unknown>;
+        if (typeof language !== "string" || typeof path !== "string") return [];
+        return [{ language, path, mode: mode === "replace" ? "replace" : "extend" }];
+      })
+      : defaultIndexing.callQueries,
+    tokenizer: parseTokenizerConfig(rawIndexing.tokenizer) ?? defaultIndexing.tokenizer,
   };
 
   const rawSearch = (input.search && typeof input.search === "object" ? input.search : {}) as Record<string, unknown>;
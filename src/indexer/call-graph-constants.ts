@@ -45,3 +45,9 @@ export const CALL_GRAPH_SYMBOL_CHUNK_TYPES = new Set([
   "const_statement",
   "class_name_statement",
 ]);
+
+// Owner of call sites outside every declaration, such as top-level imports.
+// Created per file only when such a site exists. Its kind is distinct from real
+// `module` declarations so graph and lookup queries can leave it out.
+export const MODULE_SYMBOL_NAME = "<module>";
+export const MODULE_SYMBOL_KIND = "file_scope";
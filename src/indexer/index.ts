@@ -21,7 +21,7 @@ import {
   VectorStore,
   InvertedIndex,
   Database,
-  parseFiles,
+  parseFilesFromPaths,
   createEmbeddingTexts,
   generateChunkId,
   generateChunkHash,
@@ -30,10 +30,12 @@ import {
   hashFile,
   hashContent,
   extractCalls,
+  extractReferences,
+  loadCallQueries,
   parseFileAsText,
   estimateTokens,
 } from "../native/index.js";
-import type { SymbolData, CallEdgeData, PathHopData, ReachabilityData, CommunityData, CommunityCouplingData, CentralityData } from "../native/index.js";
+import type { ParsedFile, SymbolData, CallEdgeData, PathHopData, ReachabilityData, CommunityData, CommunityCouplingData, CentralityData, CallGraphExportFormat, CallGraphFilter, CallPathsData, SymbolCandidateData, EdgeWeights, WeightedReachabilityData } from "../native/index.js";
 import { getBranchOrDefault, getBaseBranch, isGitRepo } from "../git/index.js";
 import { isFullGitCommit, resolveLocalGitCommit, withMaterializedBranch } from "../git/branch-materialization.js";
 import type { HostMode } from "../config/host.js";
@@ -59,8 +61,12 @@ export {
   rerankResults,
 } from "./search-ranking.js";
 import { inferExactSymbolFromQuery } from "../tools/symbol-inference.js";
-import { CALL_GRAPH_SYMBOL_CHUNK_TYPES } from "./call-graph-constants.js";
-export { CALL_GRAPH_SYMBOL_CHUNK_TYPES } from "./call-graph-constants.js";
+import {
+  CALL_GRAPH_SYMBOL_CHUNK_TYPES,
+  MODULE_SYMBOL_KIND,
+  MODULE_SYMBOL_NAME,
+} from "./call-graph-constants.js";
+export { CALL_GRAPH_SYMBOL_CHUNK_TYPES, MODULE_SYMBOL_NAME } from "./call-graph-constants.js";
 import {
   buildDeterministicIdentifierPass,
   buildIdentifierDefinitionLane,
@@ -109,7 +115,7 @@ import {
 import { iterateOrderedFileBatches, type FileBatchLimits } from "./file-batches.js";
 import { canonicalizePathForComparison } from "../utils/canonical-path.js";
 
-export const CALL_GRAPH_LANGUAGES = new Set(["typescript", "tsx", "javascript", "jsx", "python", "go", "rust", "swift", "php", "apex", "zig", "gdscript", "matlab", "bash", "c", "cpp", "metal"]);
+export const CALL_GRAPH_LANGUAGES = new Set(["typescript", "tsx", "javascript", "jsx", "python", "go", "rust", "swift", "php", "apex", "zig", "gdscript", "matlab", "bash", "c", "cpp", "metal", "java", "csharp", "ruby"]);
 // Languages whose identifiers are case-insensitive at the language level.
 // The Rust call_extractor lowercases callee names for these languages (except
 // constructors and imports), so same-file resolution in this file must use
@@ -177,7 +183,7 @@ function resolveSameCommunityCandidateIds(
     .map((candidate) => candidate.id));
 }
 // Existing indexes without this metadata are the implicit version 1.
-const CALL_GRAPH_RESOLUTION_VERSION = "4";
+const CALL_GRAPH_RESOLUTION_VERSION = "8";
 const PHP_FUNCTION_SYMBOL_CHUNK_TYPES = new Set([
   "function_declaration",
   "function",
@@ -220,6 +226,10 @@ const EXECUTABLE_SYMBOL_CHUNK_TYPES = new Set([
   "test_declaration",
 ]);
 
+function symbolPositionKey(symbol: Pick<SymbolData, "name" | "kind" | "startLine" | "startCol">): string {
+  return `${symbol.name}:${symbol.kind}:${symbol.startLine}:${symbol.startCol}`;
+}
+
 // A type and its methods often cover the same lines. The shortest range is the
 // most precise symbol to own an edge. For equal ranges, executable symbols are
 // more specific than container types.
@@ -322,6 +332,14 @@ function isSqliteCorruptionError(error: unknown): boolean {
     || message.includes("sqlite_corrupt");
 }
 
+export interface DegradedFile {
+  path: string;
+  syntaxErrors: number;
+  fellBackToLines: boolean;
+  fallbackReason?: string;
+  error?: string;
+}
+
 export interface IndexStats {
   totalFiles: number;
   totalChunks: number;
@@ -333,6 +351,8 @@ export interface IndexStats {
   removedChunks: number;
   skippedFiles: SkippedFile[];
   parseFailures: string[];
+  /** Files that parsed with syntax errors, fell back to line chunks, or failed. */
+  degradedFiles: DegradedFile[];
   failedBatchesPath?: string;
   warning?: string;
   resetCorruptedIndex?: boolean;
@@ -366,6 +386,7 @@ export interface SearchResult {
   score: number;
   chunkType: string;
   name?: string;
+  qualifiedName?: string;
   blame?: GitBlameMetadata;
 }
 
@@ -425,6 +446,8 @@ export interface StatusResult {
   compatibility: IndexCompatibility | null;
   failedBatchesCount: number;
   failedBatchesPath?: string;
+  /** Indexed files whose last parse had syntax errors, fell back to line chunks, or failed. */
+  degradedFiles: DegradedFile[];
   warning?: string;
 }
 
@@ -459,6 +482,11 @@ interface ReaderArtifactFingerprint {
   databaseIdentity: string;
 }
 
+interface CallQueryFingerprint {
+  hash: string;
+  languages: string[];
+}
+
 const STARTUP_WARNING_METADATA_KEY = "index.startupWarning";
 const READER_ARTIFACT_RETRY_INTERVAL_MS = 1_000;
 
@@ -681,6 +709,7 @@ function promoteIdentifierMatches(
             endLine: chunk.endLine,
             chunkType,
             name: chunk.name ?? undefined,
+            qualifiedName: chunk.qualifiedName ?? undefined,
             language: chunk.language,
             hash: chunk.contentHash,
             ...metadataFromBlame(blameFromChunkData(chunk)),
@@ -799,6 +828,7 @@ export function buildSymbolDefinitionLane(
           endLine: chunk.endLine,
           chunkType,
           name: chunk.name ?? undefined,
+          qualifiedName: chunk.qualifiedName ?? undefined,
           language: chunk.language,
           hash: chunk.contentHash,
           ...metadataFromBlame(blameFromChunkData(chunk)),
@@ -1132,10 +1162,15 @@ export class Indexer {
   private database: Database | null = null;
   private provider: EmbeddingProviderInterface | null = null;
   private configuredProviderInfo: ConfiguredProviderInfo | null = null;
+  /** Hash of the loaded call query files ("" for none) and the languages they cover. */
+  private callQueryFingerprint: CallQueryFingerprint = { hash: "", languages: [] };
   private reranker: RerankerInterface | null = null;
   private fileHashCache: Map<string, string> = new Map();
   private fileHashCachePath: string = "";
   private failedBatchesPath: string = "";
+  private parseDiagnosticsPath: string = "";
+  /** Parse outcomes since the last save, by stored path; `null` clears a file's entry. */
+  private pendingParseDiagnostics = new Map<string, DegradedFile | null>();
   private currentBranch: string = "default";
   private baseBranch: string = "main";
   private logger: Logger;
@@ -1276,6 +1311,7 @@ export class Indexer {
   private refreshRuntimeArtifactPaths(): void {
     this.fileHashCachePath = this.getRuntimeArtifactPath("file-hashes.json");
     this.failedBatchesPath = this.getRuntimeArtifactPath("failed-batches.json");
+    this.parseDiagnosticsPath = this.getRuntimeArtifactPath("parse-diagnostics.json");
   }
 
   private getPreparedChunkId(chunkId: string): string {
@@ -1432,6 +1468,49 @@ export class Indexer {
       obj[k] = v;
     }
     this.atomicWriteSync(this.fileHashCachePath, JSON.stringify(obj));
+    this.saveParseDiagnostics();
+  }
+
+  private loadParseDiagnostics(): Map<string, DegradedFile> {
+    if (!existsSync(this.parseDiagnosticsPath)) {
+      return new Map();
+    }
+
+    try {
+      const parsed = JSON.parse(readFileSync(this.parseDiagnosticsPath, "utf-8")) as Record<string, DegradedFile>;
+      return new Map(Object.entries(parsed));
+    } catch (error: unknown) {
+      const message = error instanceof Error ? error.message : String(error);
+      this.logger.warn("Failed to load parse diagnostics, resetting them", {
+        parseDiagnosticsPath: this.parseDiagnosticsPath,
+        error: message,
+      });
+      return new Map();
+    }
+  }
+
+  /**
+   * Folds pending parse outcomes into the saved diagnostics and drops files
+   * no longer in the hash cache, so they follow the same lifecycle.
+   */
+  private saveParseDiagnostics(): void {
+    const diagnostics = this.loadParseDiagnostics();
+    for (const [filePath, degraded] of this.pendingParseDiagnostics) {
+      if (degraded) {
+        diagnostics.set(filePath, degraded);
+      } else {
+        diagnostics.delete(filePath);
+      }
+    }
+    this.pendingParseDiagnostics.clear();
+
+    const obj: Record<string, DegradedFile> = {};
+    for (const [filePath, degraded] of diagnostics) {
+      if (this.fileHashCache.has(filePath)) {
+        obj[filePath] = degraded;
+      }
+    }
+    this.atomicWriteSync(this.parseDiagnosticsPath, JSON.stringify(obj));
   }
 
   private atomicWriteSync(targetPath: string, data: string): void {
@@ -1548,6 +1627,10 @@ export class Indexer {
     const referencedSymbolIds = new Set(database.getReferencedSymbolIds(removedSymbolCandidates));
     const removableSymbolIds = removedSymbolCandidates.filter((symbolId) => !referencedSymbolIds.has(symbolId));
     database.clearCallEdgeTargetsForSymbols(removableSymbolIds);
+    // Removed symbols still have their names until they are collected.
+    const previousSymbolIdSet = new Set(previousSymbolIds);
+    const addedSymbolIds = currentSymbolIds.filter((symbolId) => !previousSymbolIdSet.has(symbolId));
+    database.resolveCallEdges(branchCatalogKey, [...addedSymbolIds, ...removedSymbolCandidates]);
     database.gcOrphanSymbols();
     database.gcOrphanCallEdges();
     database.gcOrphanEmbeddings();
@@ -1585,6 +1668,31 @@ export class Indexer {
     return this.getBranchMigrationMetadataKey("index.callGraphResolutionVersion", catalogIdentity);
   }
 
+  private getCallQueryMetadataKey(
+    catalogIdentity = this.getBranchCatalogIdentity(),
+  ): string {
+    return this.getBranchMigrationMetadataKey("index.callQueries", catalogIdentity);
+  }
+
+  private getStoredCallQueryFingerprint(
+    database: Database,
+    catalogIdentity = this.getBranchCatalogIdentity(),
+  ): CallQueryFingerprint {
+    const stored = database.getMetadata(this.getCallQueryMetadataKey(catalogIdentity));
+    if (!stored) {
+      return { hash: "", languages: [] };
+    }
+    try {
+      const parsed = JSON.parse(stored) as Partial<CallQueryFingerprint>;
+      return {
+        hash: typeof parsed.hash === "string" ? parsed.hash : "",
+        languages: Array.isArray(parsed.languages) ? parsed.languages.filter((language) => typeof language === "string") : [],
+      };
+    } catch {
+      return { hash: stored, languages: [] };
+    }
+  }
+
   private getSwiftParserVersionMetadataKey(
     catalogIdentity = this.getBranchCatalogIdentity(),
   ): string {
@@ -1614,7 +1722,8 @@ export class Indexer {
       && database.getMetadata(this.getMetalParserVersionMetadataKey(catalogIdentity))
       === METAL_PARSER_VERSION
       && database.getMetadata(this.getSymbolExtractorVersionMetadataKey(catalogIdentity))
-      === SYMBOL_EXTRACTOR_VERSION;
+      === SYMBOL_EXTRACTOR_VERSION
+      && this.getStoredCallQueryFingerprint(database, catalogIdentity).hash === this.callQueryFingerprint.hash;
   }
 
   private hasProjectForceReembedPending(): boolean {
@@ -1989,6 +2098,9 @@ export class Indexer {
       if (existsSync(this.fileHashCachePath)) {
         unlinkSync(this.fileHashCachePath);
       }
+      if (existsSync(this.parseDiagnosticsPath)) {
+        unlinkSync(this.parseDiagnosticsPath);
+      }
 
       await this.healthCheckUnlocked();
     }
@@ -2961,6 +3073,21 @@ export class Indexer {
     this.readIssues = [];
     this.readerArtifactRetryAfter.clear();
 
+    // Call query overrides are process-wide, so an empty list resets any a
+    // previous indexer loaded.
+    const callQueries = this.config.indexing.callQueries.map((query) => ({
+      language: query.language,
+      path: path.resolve(this.projectRoot, query.path),
+      mode: query.mode,
+    }));
+    const callQueryLanguages = loadCallQueries(callQueries);
+    this.callQueryFingerprint = {
+      hash: callQueries.length > 0
+        ? hashContent(JSON.stringify(callQueries.map((query) => [query.language, query.mode, readFileSync(query.path, "utf-8")])))
+        : "",
+      languages: callQueryLanguages,
+    };
+
     if (this.config.embeddingProvider === 'custom') {
       if (!this.config.customProvider) {
         throw new Error("embeddingProvider is 'custom' but customProvider config is missing.");
@@ -3322,7 +3449,7 @@ export class Indexer {
     if (!existsSync(this.indexPath)) return;
 
     const names = await fsPromises.readdir(this.indexPath);
-    const runtimeStatePattern = /^(?:file-hashes|failed-batches)(?:\.[a-f0-9]{16})?\.json$/;
+    const runtimeStatePattern = /^(?:file-hashes|failed-batches|parse-diagnostics)(?:\.[a-f0-9]{16})?\.json$/;
     await Promise.all(
       names
         .filter((name) => runtimeStatePattern.test(name))
@@ -3403,6 +3530,7 @@ export class Indexer {
         nodeType: metadata.chunkType,
         name: metadata.name,
         language: metadata.language,
+        qualifiedName: metadata.qualifiedName,
       };
       chunkDataBatch.push(chunkData);
       chunkIds.push(key);
@@ -3767,6 +3895,7 @@ export class Indexer {
       removedChunks: 0,
       skippedFiles: [],
       parseFailures: [],
+      degradedFiles: [],
     };
 
     onProgress?.({
@@ -3778,6 +3907,7 @@ export class Indexer {
     });
 
     this.loadFileHashCache();
+    this.pendingParseDiagnostics.clear();
 
     const swiftParserMetadataKey = this.getSwiftParserVersionMetadataKey();
     const reparseCachedSwiftFiles = database.getMetadata(swiftParserMetadataKey) !== SWIFT_PARSER_VERSION;
@@ -3785,6 +3915,13 @@ export class Indexer {
     const reparseCachedMetalFiles = database.getMetadata(metalParserMetadataKey) !== METAL_PARSER_VERSION;
     const symbolExtractorMetadataKey = this.getSymbolExtractorVersionMetadataKey();
     const refreshCachedSymbols = database.getMetadata(symbolExtractorMetadataKey) !== SYMBOL_EXTRACTOR_VERSION;
+    const callQueryMetadataKey = this.getCallQueryMetadataKey();
+    const storedCallQueries = this.getStoredCallQueryFingerprint(database);
+    // Files in a language whose call query was added, edited or removed keep
+    // edges from the old query until they are extracted again.
+    const callQueryLanguagesToRefresh = storedCallQueries.hash === this.callQueryFingerprint.hash
+      ? new Set<string>()
+      : new Set([...storedCallQueries.languages, ...this.callQueryFingerprint.languages]);
     if (
       reparseCachedSwiftFiles &&
       Array.from(this.fileHashCache.keys()).some((filePath) => path.extname(filePath).toLowerCase() === ".swift")
@@ -3833,9 +3970,10 @@ export class Indexer {
 
       const cachedHashMatches = this.fileHashCache.get(storedPath) === currentHash;
       const needsCallGraphRefresh = cachedHashMatches &&
-        needsCallGraphResolutionMigration &&
+        (needsCallGraphResolutionMigration || callQueryLanguagesToRefresh.size > 0) &&
         database.getChunksByFile(storedPath).some((chunk) =>
-          chunk.language === "php" || chunk.language === "c" || chunk.language === "cpp"
+          (needsCallGraphResolutionMigration && (chunk.language === "php" || chunk.language === "c" || chunk.language === "cpp"))
+          || callQueryLanguagesToRefresh.has(chunk.language)
         );
       const requiresSwiftParserUpgrade =
         reparseCachedSwiftFiles && path.extname(storedPath).toLowerCase() === ".swift";
@@ -3997,15 +4135,31 @@ export class Indexer {
         (descriptor) => descriptor.sourceBytes,
         this.fileBatchLimits,
       )) {
-        const loadedFiles = await Promise.all(descriptorBatch.map(async (descriptor) => ({
-          path: descriptor.storedPath,
-          content: await fsPromises.readFile(descriptor.materializedPath, "utf-8"),
-          hash: descriptor.hash,
-        })));
-        const loadedByPath = new Map(loadedFiles.map((file) => [file.path, file]));
-        const descriptorByPath = new Map(descriptorBatch.map((descriptor) => [descriptor.storedPath, descriptor]));
+        const descriptorByPath = new Map(descriptorBatch.map((descriptor) => [descriptor.materializedPath, descriptor]));
         const parseStartTime = performance.now();
-        const parsedFiles = parseFiles(loadedFiles);
+        const tokenizer = this.config.indexing.tokenizer;
+        // Files are read, hashed and parsed natively, so their contents never
+        // cross into JavaScript unless a fallback below needs them.
+        const parsedFiles: ParsedFile[] = [];
+        await parseFilesFromPaths(
+          descriptorBatch.map((descriptor) => descriptor.materializedPath),
+          (batch) => {
+            parsedFiles.push(...batch);
+          },
+          undefined,
+          tokenizer
+            ? {
+              maxChunkTokens: tokenizer.maxChunkTokens ?? maxChunkTokens,
+              tokenizer: {
+                kind: tokenizer.kind,
+                vocabPath: tokenizer.vocabPath && path.resolve(this.projectRoot, tokenizer.vocabPath),
+                mergesPath: tokenizer.mergesPath && path.resolve(this.projectRoot, tokenizer.mergesPath),
+                lowercase: tokenizer.lowercase,
+              },
+            }
+            : undefined,
+          { includeCalls: true },
+        );
         const parseMs = performance.now() - parseStartTime;
         this.logger.recordFilesParsed(parsedFiles.length);
         this.logger.recordParseDuration(parseMs);
@@ -4019,17 +4173,42 @@ export class Indexer {
         const symbolBatch: SymbolData[] = [];
         const edgeBatch: CallEdgeData[] = [];
 
-        for (const parsed of parsedFiles) {
-          const loadedFile = loadedByPath.get(parsed.path);
-          const descriptor = descriptorByPath.get(parsed.path);
-          if (!loadedFile || !descriptor) {
-            throw new Error(`Parsed file was not present in its source batch: ${parsed.path}`);
+        for (const parsedFromDisk of parsedFiles) {
+          const descriptor = descriptorByPath.get(parsedFromDisk.path);
+          if (!descriptor) {
+            throw new Error(`Parsed file was not present in its source batch: ${parsedFromDisk.path}`);
           }
-
+          // An unreadable file has no hash; fail the run as reading it here would.
+          if (!parsedFromDisk.hash && parsedFromDisk.diagnostics?.error) {
+            throw new Error(parsedFromDisk.diagnostics.error);
+          }
+          const parsed = { ...parsedFromDisk, path: descriptor.storedPath };
+          let content: string | undefined;
+          const readContent = async (): Promise<string> =>
+            content ??= await fsPromises.readFile(descriptor.materializedPath, "utf-8");
+
+          const relativeParsedPath = path.isAbsolute(parsed.path)
+            ? path.relative(this.projectRoot, parsed.path)
+            : parsed.path;
           if (parsed.chunks.length === 0) {
-            stats.parseFailures.push(path.isAbsolute(parsed.path)
-              ? path.relative(this.projectRoot, parsed.path)
-              : parsed.path);
+            stats.parseFailures.push(relativeParsedPath);
+          }
+          const diagnostics = parsed.diagnostics;
+          if (diagnostics) {
+            const syntaxErrors = diagnostics.errorNodeCount + diagnostics.missingNodeCount;
+            const degraded = syntaxErrors > 0 || diagnostics.fellBackToLines || diagnostics.error
+              ? {
+                path: relativeParsedPath,
+                syntaxErrors,
+                fellBackToLines: diagnostics.fellBackToLines,
+                fallbackReason: diagnostics.fallbackReason,
+                error: diagnostics.error,
+              }
+              : null;
+            if (degraded) {
+              stats.degradedFiles.push(degraded);
+            }
+            this.pendingParseDiagnostics.set(parsed.path, degraded);
           }
 
           let chunksToProcess = parsed.chunks;
@@ -4037,7 +4216,7 @@ export class Indexer {
             this.config.indexing.fallbackToTextOnMaxChunks &&
             chunksToProcess.length > this.config.indexing.maxChunksPerFile
           ) {
-            chunksToProcess = parseFileAsText(parsed.path, loadedFile.content);
+            chunksToProcess = parseFileAsText(parsed.path, await readContent());
           }
           chunksToProcess = selectIndexableChunks(
             chunksToProcess,
@@ -4070,6 +4249,12 @@ export class Indexer {
               nodeType: chunk.chunkType,
               name: chunk.name,
               language: chunk.language,
+              qualifiedName: chunk.qualifiedName,
+              parentSymbol: chunk.parentSymbol,
+              depth: chunk.depth,
+              signature: chunk.signature,
+              docComment: chunk.docComment,
+              visibility: chunk.visibility,
               blameSha: blameMetadata.blameSha,
               blameAuthor: blameMetadata.blameAuthor,
               blameAuthorEmail: blameMetadata.blameAuthorEmail,
@@ -4097,6 +4282,7 @@ export class Indexer {
                 endLine: chunk.endLine,
                 chunkType: chunk.chunkType,
                 name: chunk.name,
+                qualifiedName: chunk.qualifiedName,
                 language: chunk.language,
                 hash: contentHash,
                 ...blameMetadata,
@@ -4125,13 +4311,21 @@ export class Indexer {
               endLine: parsedSymbol.endLine,
               endCol: parsedSymbol.endCol,
               language: parsedSymbol.language,
+              qualifiedName: parsedSymbol.qualifiedName,
+              parentSymbol: parsedSymbol.parentSymbol,
+              depth: parsedSymbol.depth,
+              signature: parsedSymbol.signature,
+              docComment: parsedSymbol.docComment,
+              visibility: parsedSymbol.visibility,
             };
             fileSymbols.push(symbol);
             symbolBatch.push(symbol);
             allSymbolIds.add(symbolId);
           }
 
-          const fileLanguage = parsed.symbols[0]?.language ?? parsed.chunks[0]?.language;
+          const fileLanguage = parsed.language
+            ?? parsed.symbols[0]?.language
+            ?? parsed.chunks[0]?.language;
           if (!fileLanguage || !CALL_GRAPH_LANGUAGES.has(fileLanguage)) {
             continue;
           }
@@ -4146,11 +4340,47 @@ export class Indexer {
             symbolsByName.set(key, symbols);
           }
 
-          for (const site of extractCalls(loadedFile.content, fileLanguage)) {
-            const enclosingSymbol = findEnclosingSymbol(fileSymbols, site.line, site.column);
-            if (!enclosingSymbol) {
-              continue;
+          const symbolsByPosition = new Map(
+            fileSymbols.map((symbol) => [symbolPositionKey(symbol), symbol] as const),
+          );
+          let moduleSymbol: SymbolData | undefined;
+          const getModuleSymbol = (): SymbolData => {
+            if (!moduleSymbol) {
+              const preparedNamespace = this.getPreparedBranchNamespace();
+              moduleSymbol = {
+                id: `sym_${hashContent(
+                  (preparedNamespace ? `${preparedNamespace}:` : "") +
+                  parsed.path + ":" + MODULE_SYMBOL_NAME + ":" + descriptor.hash,
+                ).slice(0, 16)}`,
+                filePath: parsed.path,
+                name: MODULE_SYMBOL_NAME,
+                kind: MODULE_SYMBOL_KIND,
+                startLine: 1,
+                startCol: 0,
+                endLine: parsed.lineCount,
+                endCol: parsed.lastLineLength,
+                language: fileLanguage,
+                qualifiedName: MODULE_SYMBOL_NAME,
+                depth: 0,
+              };
+              symbolBatch.push(moduleSymbol);
+              allSymbolIds.add(moduleSymbol.id);
             }
+            return moduleSymbol;
+          };
+
+          const sites = parsed.calls ?? [
+            ...extractCalls(await readContent(), fileLanguage),
+            ...extractReferences(await readContent(), fileLanguage),
+          ];
+          for (const site of sites) {
+            // Symbols of kinds the call graph skips hand their sites to the
+            // nearest symbol it keeps.
+            const enclosingSymbol = site.enclosingSymbol.name === MODULE_SYMBOL_NAME
+              ? getModuleSymbol()
+              : symbolsByPosition.get(symbolPositionKey(site.enclosingSymbol))
+                ?? findEnclosingSymbol(fileSymbols, site.line, site.column)
+                ?? getModuleSymbol();
 
             let candidates = symbolsByName.get(normalizeSymbolKey(site.calleeName));
             if (fileLanguage === "php" && candidates) {
@@ -4176,6 +4406,7 @@ export class Indexer {
               line: site.line,
               col: site.column,
               isResolved: resolvedTarget !== undefined,
+              qualifier: site.qualifier,
             });
           }
         }
@@ -4351,6 +4582,7 @@ export class Indexer {
         database.setMetadata(swiftParserMetadataKey, SWIFT_PARSER_VERSION);
         database.setMetadata(metalParserMetadataKey, METAL_PARSER_VERSION);
         database.setMetadata(symbolExtractorMetadataKey, SYMBOL_EXTRACTOR_VERSION);
+        database.setMetadata(callQueryMetadataKey, JSON.stringify(this.callQueryFingerprint));
         this.saveBranchCommit(database, indexedCommit);
         this.saveIndexMetadata(configuredProviderInfo);
         this.indexCompatibility = { compatible: true };
@@ -4390,6 +4622,7 @@ export class Indexer {
         database.setMetadata(swiftParserMetadataKey, SWIFT_PARSER_VERSION);
         database.setMetadata(metalParserMetadataKey, METAL_PARSER_VERSION);
         database.setMetadata(symbolExtractorMetadataKey, SYMBOL_EXTRACTOR_VERSION);
+        database.setMetadata(callQueryMetadataKey, JSON.stringify(this.callQueryFingerprint));
         this.saveBranchCommit(database, indexedCommit);
         this.saveIndexMetadata(configuredProviderInfo);
         this.indexCompatibility = { compatible: true };
@@ -4470,6 +4703,7 @@ export class Indexer {
       database.setMetadata(swiftParserMetadataKey, SWIFT_PARSER_VERSION);
       database.setMetadata(metalParserMetadataKey, METAL_PARSER_VERSION);
       database.setMetadata(symbolExtractorMetadataKey, SYMBOL_EXTRACTOR_VERSION);
+      database.setMetadata(callQueryMetadataKey, JSON.stringify(this.callQueryFingerprint));
       this.saveBranchCommit(database, indexedCommit);
       this.saveIndexMetadata(configuredProviderInfo);
       this.indexCompatibility = { compatible: true };
@@ -4956,6 +5190,7 @@ export class Indexer {
           score: r.score,
           chunkType: r.metadata.chunkType,
           name: r.metadata.name,
+          qualifiedName: r.metadata.qualifiedName,
           blame: blameFromMetadata(r.metadata),
         };
       })
@@ -5036,6 +5271,8 @@ export class Indexer {
       compatibility,
       failedBatchesCount,
       failedBatchesPath: failedBatchesCount > 0 ? this.failedBatchesPath : undefined,
+      degradedFiles: Array.from(this.loadParseDiagnostics().values())
+        .sort((a, b) => a.path.localeCompare(b.path)),
       warning: warning || undefined,
     };
   }
@@ -5622,6 +5859,7 @@ export class Indexer {
           score: r.score,
           chunkType: r.metadata.chunkType,
           name: r.metadata.name,
+          qualifiedName: r.metadata.qualifiedName,
           blame: blameFromMetadata(r.metadata),
         };
       })
@@ -5707,6 +5945,26 @@ export class Indexer {
     return shortest.map((hop) => this.resolveFilePathRecord(hop));
   }
 
+  async findCallPaths(
+    from: string,
+    to: string,
+    options: { k?: number; maxDepth?: number; weights?: EdgeWeights; branch?: string } = {},
+  ): Promise<CallPathsData> {
+    const { database, readIssues } = await this.ensureInitialized();
+    this.requireReadableComponents(readIssues, "database");
+    const resolvedBranch = this.resolveBranchCatalogKey(options.branch);
+    const found = database.findCallPaths(from, to, resolvedBranch, options.k, options.maxDepth, options.weights);
+    const resolveCandidate = (candidate: SymbolCandidateData) => this.resolveFilePathRecord(candidate);
+    return {
+      paths: found.paths.map((path) => ({
+        ...path,
+        hops: path.hops.map((hop) => this.resolveFilePathRecord(hop)),
+      })),
+      fromCandidates: found.fromCandidates.map(resolveCandidate),
+      toCandidates: found.toCandidates.map(resolveCandidate),
+    };
+  }
+
   async findCallPathBySymbolIds(
     fromSymbolId: string,
     toSymbolId: string,
@@ -5835,6 +6093,24 @@ export class Indexer {
       .map((entry) => this.resolveFilePathRecord(entry));
   }
 
+  async getWeightedReachability(
+    rootSymbolIds: string[],
+    direction: "callers" | "callees" | "both",
+    options: { weights?: EdgeWeights; maxDepth?: number; maxCost?: number; branch?: string } = {},
+  ): Promise<WeightedReachabilityData[]> {
+    const { database, readIssues } = await this.ensureInitialized();
+    this.requireReadableComponents(readIssues, "database");
+    const resolvedBranch = this.resolveBranchCatalogKey(options.branch);
+    return database.getWeightedReachability(
+      rootSymbolIds,
+      resolvedBranch,
+      direction,
+      options.weights,
+      options.maxDepth,
+      options.maxCost,
+    ).map((entry) => this.resolveFilePathRecord(entry));
+  }
+
   async detectCommunities(branch?: string, symbolIds?: string[]): Promise<CommunityData[]> {
     const { database, readIssues } = await this.ensureInitialized();
     this.requireReadableComponents(readIssues, "database");
@@ -5865,6 +6141,17 @@ export class Indexer {
       .map((entry) => this.resolveFilePathRecord(entry));
   }
 
+  async exportCallGraph(
+    format: CallGraphExportFormat,
+    filter?: CallGraphFilter,
+    branch?: string
+  ): Promise<string> {
+    const { database, readIssues } = await this.ensureInitialized();
+    this.requireReadableComponents(readIssues, "database");
+    const resolvedBranch = this.resolveBranchCatalogKey(branch);
+    return database.exportCallGraph(resolvedBranch, format, filter);
+  }
+
   async getPrImpact(opts: {
     pr?: number;
     branch?: string;
@@ -82,15 +82,32 @@ function createMockNativeBinding() {
     parseFiles: () => {
       throw error;
     },
+    parseFilesFromPaths: () => {
+      throw error;
+    },
     hashContent: () => {
       throw error;
     },
     hashFile: () => {
       throw error;
     },
+    walkFiles: () => {
+      throw error;
+    },
+    countTokens: () => {
+      throw error;
+    },
+    estimateChunksTokens: () => {
+      throw error;
+    },
     extractCalls: () => {
       throw error;
     },
+    ParseSession: class {
+      constructor() {
+        throw error;
+      }
+    },
     VectorStore: class {
       constructor() {
         throw error;
@@ -123,6 +140,9 @@ function createMockNativeBinding() {
       getTransitiveReachability() {
         throw error;
       }
+      getWeightedReachability() {
+        throw error;
+      }
       detectCommunities() {
         throw error;
       }
@@ -132,6 +152,9 @@ function createMockNativeBinding() {
       computeCentrality() {
         throw error;
       }
+      exportCallGraph() {
+        throw error;
+      }
     },
   };
 }
@@ -1,14 +1,20 @@
 import type {
   BranchDelta,
   CallEdgeData,
+  CallGraphExportFormat,
+  CallGraphFilter,
+  CallPathsData,
+  CallResolutionStats,
   CentralityData,
   ChunkData,
   CommunityCouplingData,
   CommunityData,
   DatabaseStats,
+  EdgeWeights,
   PathHopData,
   ReachabilityData,
   SymbolData,
+  WeightedReachabilityData,
 } from "./types.js";
 import { native } from "./binding.js";
 
@@ -273,14 +279,14 @@ export class Database {
     this.throwIfClosed();
     return this.inner.getSymbolsByNameCi(name);
   }
-  getSymbolsForBranch(branch: string): SymbolData[] {
+  getSymbolsForBranch(branch: string, includeFileScopes?: boolean): SymbolData[] {
     this.throwIfClosed();
-    return this.inner.getSymbolsForBranch(branch);
+    return this.inner.getSymbolsForBranch(branch, includeFileScopes);
   }
 
-  getSymbolsForFiles(filePaths: string[], branch: string): SymbolData[] {
+  getSymbolsForFiles(filePaths: string[], branch: string, includeFileScopes?: boolean): SymbolData[] {
     this.throwIfClosed();
-    return this.inner.getSymbolsForFiles(filePaths, branch);
+    return this.inner.getSymbolsForFiles(filePaths, branch, includeFileScopes);
   }
 
   deleteSymbolsByFile(filePath: string): number {
@@ -328,6 +334,16 @@ export class Database {
     this.inner.resolveCallEdge(edgeId, toSymbolId);
   }
 
+  /**
+   * Resolves the branch's call edges for that branch. Pass the symbols added
+   * to or removed from it, before orphans are collected, to revisit only
+   * the edges they can affect.
+   */
+  resolveCallEdges(branch: string, changedSymbolIds?: string[]): CallResolutionStats {
+    this.throwIfClosed();
+    return this.inner.resolveCallEdges(branch, changedSymbolIds ?? null);
+  }
+
   findShortestPath(
     fromName: string,
     toName: string,
@@ -338,6 +354,30 @@ export class Database {
     return this.inner.findShortestPath(fromName, toName, branch, maxDepth ?? null);
   }
 
+  /**
+   * Up to `k` (default 3, at most 50) distinct call paths, fewest hops first, or cheapest
+   * first under `weights`. `from` and `to` are symbol ids or names; a name
+   * shared by several symbols returns their candidates instead of paths.
+   */
+  findCallPaths(
+    from: string,
+    to: string,
+    branch: string,
+    k?: number,
+    maxDepth?: number,
+    weights?: EdgeWeights
+  ): CallPathsData {
+    this.throwIfClosed();
+    return this.inner.findCallPaths(
+      from,
+      to,
+      branch,
+      k ?? null,
+      maxDepth ?? null,
+      weights ?? null
+    );
+  }
+
   addSymbolsToBranch(branch: string, symbolIds: string[]): void {
     this.throwIfClosed();
     this.inner.addSymbolsToBranch(branch, symbolIds);
@@ -402,6 +442,29 @@ export class Database {
     );
   }
 
+  /**
+   * Like `getTransitiveReachability`, with the cost of each symbol's cheapest
+   * chain under `weights` (the defaults when omitted). Sorted by cost.
+   */
+  getWeightedReachability(
+    rootSymbolIds: string[],
+    branch: string,
+    direction: string,
+    weights?: EdgeWeights,
+    maxDepth?: number,
+    maxCost?: number
+  ): WeightedReachabilityData[] {
+    this.throwIfClosed();
+    return this.inner.getWeightedReachability(
+      rootSymbolIds,
+      branch,
+      direction,
+      weights ?? null,
+      maxDepth ?? null,
+      maxCost ?? null
+    );
+  }
+
   detectCommunities(
     branch: string,
     symbolIds?: string[]
@@ -415,6 +478,20 @@ export class Database {
     return this.inner.computeCentrality(branch);
   }
 
+  /**
+   * The branch's call graph as Graphviz DOT, GraphML or JSON Graph Format.
+   * Call sites between the same two symbols with the same call type become
+   * one edge carrying their count.
+   */
+  exportCallGraph(
+    branch: string,
+    format: CallGraphExportFormat,
+    filter?: CallGraphFilter
+  ): string {
+    this.throwIfClosed();
+    return this.inner.exportCallGraph(branch, format, filter ?? null);
+  }
+
   detectCommunityCouplings(branch: string): CommunityCouplingData[] {
     this.throwIfClosed();
     return this.inner.detectCommunityCouplings(branch).map((entry: CommunityCouplingData) => ({
@@ -1,6 +1,14 @@
 export type {
   BranchDelta,
   CallEdgeData,
+  CallGraphExportFormat,
+  CallGraphFilter,
+  CallPathData,
+  CallPathsData,
+  CallQueryFile,
+  CallResolutionStats,
+  ParseDiagnostics,
+  SyntaxErrorRange,
   CallSiteData,
   CentralityData,
   ChunkData,
@@ -11,14 +19,29 @@ export type {
   CommunityRelationshipData,
   DatabaseStats,
   DynamicBatchOptions,
+  EdgeWeights,
+  EnclosingSymbolData,
   FileInput,
+  IncrementalParseResult,
   KeywordSearchResult,
   ParsedFile,
   ParsedSymbol,
   PathHopData,
   ReachabilityData,
   SearchResult,
+  SkippedWalkEntry,
+  SymbolCandidateData,
   SymbolData,
+  TextEdit,
+  WalkedFile,
+  WalkFilesResult,
+  WeightedReachabilityData,
+  ChunkingLimits,
+  ChunkingOptions,
+  ParseOptions,
+  LanguageInfo,
+  ParserLanguageMetrics,
+  TokenizerOptions,
   CallType,
   Confidence,
   ChunkType,
@@ -35,13 +58,24 @@ export {
   parseFile,
   parseFileAsText,
   parseFiles,
+  parseFilesFromPaths,
   hashContent,
   hashFile,
+  walkFiles,
+  countTokens,
+  estimateChunksTokens,
+  registerLanguages,
+  listLanguages,
+  getParserMetrics,
+  resetParserMetrics,
+  loadCallQueries,
   extractCalls,
+  extractReferences,
   generateChunkId,
   generateChunkHash,
 } from "./parsing.js";
 
+export { ParseSession } from "./parse-session.js";
 export { VectorStore } from "./vector-store.js";
 export { InvertedIndex } from "./inverted-index.js";
 export { Database } from "./database.js";
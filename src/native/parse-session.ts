@@ -0,0 +1,45 @@
+import type { ChunkingOptions, IncrementalParseResult, ParseOptions, TextEdit } from "./types.js";
+import { native } from "./binding.js";
+import { mapChunk, mapParsedSymbol } from "./parsing.js";
+
+/**
+ * Keeps the last parse tree per path so edited files reparse incrementally.
+ * Results are a delta against the previous parse: changed, shifted and removed
+ * chunks and symbols. A non-incremental result replaces everything stored for
+ * the file and lists what the previous parse reported as removed.
+ */
+export class ParseSession {
+  private inner: any;
+
+  constructor(options?: ChunkingOptions, parseOptions?: ParseOptions) {
+    this.inner = new native.ParseSession(options, parseOptions);
+  }
+
+  update(filePath: string, content: string, edits?: TextEdit[]): IncrementalParseResult {
+    const result = this.inner.update(filePath, content, edits);
+    return {
+      path: result.path,
+      hash: result.hash,
+      incremental: result.incremental,
+      changedChunks: result.changedChunks.map(mapChunk),
+      changedSymbols: result.changedSymbols.map(mapParsedSymbol),
+      shiftedChunks: result.shiftedChunks.map(mapChunk),
+      shiftedSymbols: result.shiftedSymbols.map(mapParsedSymbol),
+      removedChunks: result.removedChunks.map(mapChunk),
+      removedSymbols: result.removedSymbols.map(mapParsedSymbol),
+      changedRanges: result.changedRanges,
+    };
+  }
+
+  remove(filePath: string): boolean {
+    return this.inner.remove(filePath);
+  }
+
+  clear(): void {
+    this.inner.clear();
+  }
+
+  size(): number {
+    return this.inner.size();
+  }
+}
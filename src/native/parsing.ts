@@ -1,27 +1,92 @@
-import type { CallSiteData, CodeChunk, FileInput, ParsedFile, ParsedSymbol, ChunkType } from "./types.js";
+import type {
+  CallQueryFile,
+  CallSiteData,
+  CodeChunk,
+  FileInput,
+  ParsedFile,
+  ParsedSymbol,
+  ChunkType,
+  ChunkingOptions,
+  LanguageInfo,
+  ParseOptions,
+  ParserLanguageMetrics,
+  TokenizerOptions,
+  WalkFilesResult,
+} from "./types.js";
 import { native } from "./binding.js";
 
-export function parseFile(filePath: string, content: string): CodeChunk[] {
-  const result = native.parseFile(filePath, content);
+export function parseFile(
+  filePath: string,
+  content: string,
+  options?: ChunkingOptions,
+  parseOptions?: ParseOptions,
+): CodeChunk[] {
+  const result = native.parseFile(filePath, content, options, parseOptions);
   return result.map(mapChunk);
 }
 
-export function parseFileAsText(filePath: string, content: string): CodeChunk[] {
-  const result = native.parseFileAsText(filePath, content);
+export function parseFileAsText(
+  filePath: string,
+  content: string,
+  options?: ChunkingOptions,
+): CodeChunk[] {
+  const result = native.parseFileAsText(filePath, content, options);
   return result.map(mapChunk);
 }
 
-export function parseFiles(files: FileInput[]): ParsedFile[] {
-  const result = native.parseFiles(files);
-  return result.map((f: any) => ({
+export function parseFiles(
+  files: FileInput[],
+  options?: ChunkingOptions,
+  parseOptions?: ParseOptions,
+): ParsedFile[] {
+  const result = native.parseFiles(files, options, parseOptions);
+  return result.map(mapParsedFile);
+}
+
+/**
+ * Reads and parses files natively, delivering results in bounded batches so
+ * file contents never cross the JS/Rust boundary twice. Resolves to the number
+ * of files parsed; unreadable or unparseable files are skipped. If `onBatch`
+ * throws, parsing stops and the promise rejects with the thrown error.
+ */
+export async function parseFilesFromPaths(
+  paths: string[],
+  onBatch: (batch: ParsedFile[]) => void,
+  batchSize?: number,
+  options?: ChunkingOptions,
+  parseOptions?: ParseOptions,
+): Promise<number> {
+  return native.parseFilesFromPaths(
+    paths,
+    (batch: any[]) => onBatch(batch.map(mapParsedFile)),
+    batchSize,
+    options,
+    parseOptions,
+  );
+}
+
+function mapParsedFile(f: any): ParsedFile {
+  return {
     path: f.path,
     chunks: f.chunks.map(mapChunk),
     symbols: (f.symbols ?? []).map(mapParsedSymbol),
     hash: f.hash,
-  }));
+    language: f.language,
+    languageReason: f.languageReason,
+    diagnostics: f.diagnostics
+      ? {
+        ...f.diagnostics,
+        fallbackReason: f.diagnostics.fallbackReason ?? undefined,
+        error: f.diagnostics.error ?? undefined,
+      }
+      : undefined,
+    calls: f.calls ?? undefined,
+    lineCount: f.lineCount,
+    lastLineLength: f.lastLineLength,
+  };
 }
 
-function mapParsedSymbol(symbol: any): ParsedSymbol {
+export function mapParsedSymbol(symbol: any): ParsedSymbol {
   return {
     name: symbol.name,
     kind: symbol.kind,
@@ -30,10 +95,16 @@ function mapParsedSymbol(symbol: any): ParsedSymbol {
     endLine: symbol.endLine ?? symbol.end_line,
     endCol: symbol.endCol ?? symbol.end_col,
     language: symbol.language,
+    qualifiedName: symbol.qualifiedName ?? symbol.qualified_name ?? symbol.name,
+    parentSymbol: symbol.parentSymbol ?? symbol.parent_symbol ?? undefined,
+    depth: symbol.depth ?? 0,
+    signature: symbol.signature ?? undefined,
+    docComment: symbol.docComment ?? symbol.doc_comment ?? undefined,
+    visibility: symbol.visibility ?? undefined,
   };
 }
 
-function mapChunk(c: any): CodeChunk {
+export function mapChunk(c: any): CodeChunk {
   return {
     content: c.content,
     startLine: c.startLine ?? c.start_line,
@@ -43,6 +114,15 @@ function mapChunk(c: any): CodeChunk {
     chunkType: (c.chunkType ?? c.chunk_type) as ChunkType,
     name: c.name ?? undefined,
     language: c.language,
+    fenceLanguage: c.fenceLanguage ?? c.fence_language ?? undefined,
+    qualifiedName: c.qualifiedName ?? c.qualified_name ?? undefined,
+    parentSymbol: c.parentSymbol ?? c.parent_symbol ?? undefined,
+    depth: c.depth ?? undefined,
+    signature: c.signature ?? undefined,
+    docComment: c.docComment ?? c.doc_comment ?? undefined,
+    visibility: c.visibility ?? undefined,
+    partIndex: c.partIndex ?? c.part_index ?? undefined,
+    partCount: c.partCount ?? c.part_count ?? undefined,
   };
 }
 
@@ -54,10 +134,84 @@ export function hashFile(filePath: string): string {
   return native.hashFile(filePath);
 }
 
+/** Token count under `tokenizer`, or the `len / 4` estimate without one. */
+export function countTokens(text: string, tokenizer?: TokenizerOptions): number {
+  return native.countTokens(text, tokenizer);
+}
+
+/** Tokens the chunks' embedding texts take, the model's special tokens included. */
+export function estimateChunksTokens(chunks: CodeChunk[], tokenizer?: TokenizerOptions): number {
+  return native.estimateChunksTokens(chunks, tokenizer);
+}
+
+/**
+ * Registers extra languages from a JSON config (`{ "languages": [...] }`),
+ * each naming its extensions, a built-in `grammar` or a compiled grammar
+ * `library`, semantic and comment node kinds, and optional `callQuery` and
+ * `referenceQuery` files.
+ * Returns the registered language names.
+ */
+export function registerLanguages(configPath: string): string[] {
+  return native.registerLanguages(configPath);
+}
+
+export function listLanguages(): LanguageInfo[] {
+  return native.listLanguages();
+}
+
+/**
+ * Per-language counters for `parseFiles` and `parseFilesFromPaths`: files,
+ * bytes, chunks, symbols, line-chunk fallbacks and p50/p95 parse time. Empty
+ * when the addon was built without the `parser-metrics` feature.
+ */
+export function getParserMetrics(): ParserLanguageMetrics[] {
+  return native.getParserMetrics();
+}
+
+export function resetParserMetrics(): void {
+  native.resetParserMetrics();
+}
+
+/**
+ * Walks `root` in parallel off the main thread, honoring `.gitignore`,
+ * `.ignore` and `customIgnoreFilenames`, and hashes every file that passes
+ * the globs. Entries that could not be read are listed in `skipped` with the
+ * error. Unlike `collectFiles` in `utils/files`, it has no depth or
+ * per-directory limits.
+ */
+export function walkFiles(
+  root: string,
+  includeGlobs: string[],
+  excludeGlobs: string[],
+  maxFileSize: number,
+  customIgnoreFilenames?: string[],
+): Promise<WalkFilesResult> {
+  return native.walkFiles(root, includeGlobs, excludeGlobs, maxFileSize, customIgnoreFilenames);
+}
+
+/**
+ * Loads call query `.scm` files that extend or replace a language's built-in
+ * patterns, using the same capture names (`@callee.name`, `@call`,
+ * `@method.call`, `@import.name`, ...). Throws on the first invalid file,
+ * naming the offending capture, and leaves earlier queries in place.
+ */
+export function loadCallQueries(files: CallQueryFile[]): string[] {
+  return native.loadCallQueries(files);
+}
+
 export function extractCalls(content: string, language: string): CallSiteData[] {
   return native.extractCalls(content, language);
 }
 
+/**
+ * Type uses, field accesses, reads and writes of non-local names, and
+ * decorators, as `TypeReference`, `FieldAccess`, `Read`, `Write` and
+ * `Decorator` sites. Never repeats a site reported by `extractCalls`.
+ */
+export function extractReferences(content: string, language: string): CallSiteData[] {
+  return native.extractReferences(content, language);
+}
+
 export function generateChunkId(filePath: string, chunk: CodeChunk): string {
   const hash = hashContent(`${filePath}:${chunk.startLine}:${chunk.endLine}:${chunk.content}`);
   return `chunk_${hash.slice(0, 16)}`;
@@ -3,6 +3,89 @@ export interface FileInput {
   content: string;
 }
 
+export interface WalkedFile {
+  path: string;
+  size: number;
+  mtimeMs: number;
+  hash: string;
+}
+
+export interface SkippedWalkEntry {
+  path: string;
+  /** `too_large`, `walk_error`, `metadata_error` or `hash_error`. */
+  reason: string;
+  /** The underlying error, for every reason but `too_large`. */
+  error?: string;
+}
+
+export interface WalkFilesResult {
+  files: WalkedFile[];
+  skipped: SkippedWalkEntry[];
+}
+
+/** Chunk size limits in bytes of chunk content; unset fields keep defaults. */
+export interface ChunkingLimits {
+  minChunkSize?: number;
+  maxChunkSize?: number;
+  targetChunkSize?: number;
+  overlapLines?: number;
+  mergeSmallChunks?: boolean;
+  /**
+   * Split chunks so none's embedding text exceeds this many tokens under the
+   * tokenizer in `ChunkingOptions`. Unset sizes chunks by bytes only; `0` in a
+   * language override drops an inherited budget.
+   */
+  maxChunkTokens?: number;
+}
+
+export interface ChunkingOptions extends ChunkingLimits {
+  /** Per-language limits keyed by language name; unset fields inherit. */
+  languageOverrides?: Record<string, ChunkingLimits>;
+  /**
+   * Tokens of `maxChunkTokens` kept free for the type, name and path prefix of
+   * the embedding text; the tokenizer's special tokens are reserved as well.
+   * Default 64.
+   */
+  reservedTokens?: number;
+  /** Counts tokens for `maxChunkTokens`; the `len / 4` estimate when unset. */
+  tokenizer?: TokenizerOptions;
+}
+
+/**
+ * How files are parsed. The budget limits apply per file: a file over any
+ * limit is chunked by lines and its diagnostics name the limit; `0` disables
+ * a limit.
+ */
+export interface ParseOptions {
+  timeoutMs?: number;
+  maxBytes?: number;
+  maxAstNodes?: number;
+  /** Average line length above which a file is treated as minified. */
+  minifiedLineLength?: number;
+  /** Also extract call sites and references from each parsed tree. */
+  includeCalls?: boolean;
+}
+
+export interface TokenizerOptions {
+  /** "wordpiece", "bpe" or "chars"; inferred from the paths when omitted. */
+  kind?: "wordpiece" | "bpe" | "chars";
+  /** WordPiece `vocab.txt` or a Hugging Face `tokenizer.json`. */
+  vocabPath?: string;
+  /** BPE `merges.txt`. */
+  mergesPath?: string;
+  lowercase?: boolean;
+}
+
+export interface LanguageInfo {
+  name: string;
+  extensions: string[];
+  hasGrammar: boolean;
+  hasCallQuery: boolean;
+  hasReferenceQuery: boolean;
+  caseInsensitive: boolean;
+  builtin: boolean;
+}
+
 export interface CodeChunk {
   content: string;
   startLine: number;
@@ -12,6 +95,24 @@ export interface CodeChunk {
   chunkType: ChunkType;
   name?: string;
   language: string;
+  /** Info-string language of a Markdown fenced code block; `language` stays the file's. */
+  fenceLanguage?: string;
+  /** Name including enclosing declarations, e.g. `UserService.save`. */
+  qualifiedName?: string;
+  /** Qualified name of the nearest enclosing declaration. */
+  parentSymbol?: string;
+  /** Number of enclosing declarations. */
+  depth?: number;
+  /** Parameter list and return type as written. */
+  signature?: string;
+  /** Leading doc comment or Python docstring, comment markers stripped. */
+  docComment?: string;
+  /** `public`, `private`, `protected`, `internal`, or a language keyword. */
+  visibility?: string;
+  /** 0-based position of this piece when an oversized node was split. */
+  partIndex?: number;
+  /** Number of pieces the node was split into. */
+  partCount?: number;
 }
 
 export type ChunkType =
@@ -35,6 +136,74 @@ export interface ParsedFile {
   chunks: CodeChunk[];
   symbols: ParsedSymbol[];
   hash: string;
+  /** Detected language name. */
+  language?: string;
+  /**
+   * What decided `language`: `modeline`, `filename`, `extension`, `shebang`,
+   * `content`, `parse-errors` or `fallback`.
+   */
+  languageReason?: string;
+  diagnostics?: ParseDiagnostics;
+  /**
+   * Call sites followed by references, as `extractCalls` and
+   * `extractReferences` report them; set only with `includeCalls`.
+   */
+  calls?: CallSiteData[];
+  /** Lines in the file, as `content.split("\n").length` counts them. */
+  lineCount: number;
+  /** Length of the last line, as `lines[lines.length - 1].length`. */
+  lastLineLength: number;
+}
+
+/** How well a file parsed, so poorly indexed files can be listed. */
+export interface ParseDiagnostics {
+  parseDurationMs: number;
+  errorNodeCount: number;
+  missingNodeCount: number;
+  /** The first ERROR/MISSING nodes in document order. */
+  syntaxErrors: SyntaxErrorRange[];
+  /**
+   * Syntax errors or a parse budget forced line chunks. A clean file with no
+   * declarations is chunked by lines without counting as a fallback.
+   */
+  fellBackToLines: boolean;
+  /**
+   * Why: `syntax-errors`, or the parse budget limit that was hit
+   * (`too-large`, `minified`, `timeout`, `too-many-nodes`).
+   */
+  fallbackReason?: string;
+  /** Why the file could not be read or parsed; it then has no chunks. */
+  error?: string;
+}
+
+export interface SyntaxErrorRange {
+  kind: "error" | "missing";
+  startByte: number;
+  endByte: number;
+  startLine: number;
+  endLine: number;
+}
+
+export interface TextEdit {
+  startByte: number;
+  oldEndByte: number;
+  newText: string;
+}
+
+export interface IncrementalParseResult {
+  path: string;
+  hash: string;
+  incremental: boolean;
+  /** New chunks and chunks whose text changed. */
+  changedChunks: CodeChunk[];
+  changedSymbols: ParsedSymbol[];
+  /** Unchanged chunks at the lines an edit above them moved them to. */
+  shiftedChunks: CodeChunk[];
+  shiftedSymbols: ParsedSymbol[];
+  /** Chunks of the previous parse that were edited or deleted, at their previous lines. */
+  removedChunks: CodeChunk[];
+  removedSymbols: ParsedSymbol[];
+  changedRanges: Array<{ startLine: number; endLine: number }>;
 }
 
 export interface ParsedSymbol {
@@ -45,11 +214,49 @@ export interface ParsedSymbol {
   endLine: number;
   endCol: number;
   language: string;
+  qualifiedName: string;
+  parentSymbol?: string;
+  depth: number;
+  signature?: string;
+  docComment?: string;
+  visibility?: string;
 }
 
 export type Confidence = "Direct" | "Inferred";
 
-export type CallType = "Call" | "MethodCall" | "Constructor" | "Import" | "Inherits" | "Implements";
+export type CallType =
+  | "Call"
+  | "MethodCall"
+  | "Constructor"
+  | "Import"
+  | "Inherits"
+  | "Implements"
+  | "TypeReference"
+  | "FieldAccess"
+  | "Read"
+  | "Write"
+  | "Decorator";
+
+export interface CallQueryFile {
+  language: string;
+  path: string;
+  /** `extend` (default) adds to the built-in patterns; `replace` drops them. */
+  mode?: "extend" | "replace";
+}
+
+/** Parse counters for one language since start-up or `resetParserMetrics()`. */
+export interface ParserLanguageMetrics {
+  language: string;
+  filesParsed: number;
+  bytes: number;
+  chunks: number;
+  symbols: number;
+  /** Files chunked by lines instead of by syntax, for any reason. */
+  fallbacks: number;
+  /** Parse time percentiles, accurate to about 20%. */
+  p50ParseMs: number;
+  p95ParseMs: number;
+}
 
 export interface CallSiteData {
   calleeName: string;
@@ -57,6 +264,20 @@ export interface CallSiteData {
   column: number;
   callType: CallType;
   confidence: Confidence;
+  qualifier?: string;
+  enclosingSymbol: EnclosingSymbolData;
+}
+
+/** The symbol around a call site; `<module>` outside every declaration. */
+export interface EnclosingSymbolData {
+  name: string;
+  kind: string;
+  startLine: number;
+  startCol: number;
+  endLine: number;
+  endCol: number;
+  startByte: number;
+  endByte: number;
 }
 
 export interface SymbolData {
@@ -69,6 +290,12 @@ export interface SymbolData {
   endLine: number;
   endCol: number;
   language: string;
+  qualifiedName?: string;
+  parentSymbol?: string;
+  depth?: number;
+  signature?: string;
+  docComment?: string;
+  visibility?: string;
 }
 
 export interface CallEdgeData {
@@ -83,6 +310,19 @@ export interface CallEdgeData {
   line: number;
   col: number;
   isResolved: boolean;
+  qualifier?: string;
+  resolutionStrategy?: string;
+  resolutionConfidence?: number;
+}
+
+export interface CallResolutionStats {
+  edges: number;
+  receiverType: number;
+  sameFile: number;
+  import: number;
+  uniqueName: number;
+  unresolved: number;
+  updated: number;
 }
 
 export interface PathHopData {
@@ -93,6 +333,30 @@ export interface PathHopData {
   callType: string;
 }
 
+/** A symbol an ambiguous path endpoint could refer to. */
+export interface SymbolCandidateData {
+  symbolId: string;
+  symbolName: string;
+  kind: string;
+  filePath: string;
+  line: number;
+}
+
+export interface CallPathData {
+  hops: PathHopData[];
+  /** The number of edges, or their summed cost when weights were given. */
+  cost: number;
+}
+
+export interface CallPathsData {
+  /** Loopless paths, cheapest first. */
+  paths: CallPathData[];
+  /** Set, with no paths, when `from` names several symbols. */
+  fromCandidates: SymbolCandidateData[];
+  /** Set, with no paths, when `to` names several symbols. */
+  toCandidates: SymbolCandidateData[];
+}
+
 export interface ReachabilityData {
   symbolId: string;
   symbolName: string;
@@ -100,6 +364,55 @@ export interface ReachabilityData {
   depth: number;
 }
 
+export interface WeightedReachabilityData extends ReachabilityData {
+  /** Cost of the cheapest chain from the roots. */
+  cost: number;
+}
+
+/**
+ * Edge costs for weighted path and reachability searches, layered over the
+ * defaults: calls cost 1, plus 2 for an inferred call site and up to 3 for an
+ * unresolved target. Imports, inheritance and reference edges are only
+ * followed when given a cost in `callTypes`.
+ */
+export interface EdgeWeights {
+  /** Cost per call type; must be positive. */
+  callTypes?: Record<string, number>;
+  /** Call types whose edges are never followed. */
+  excludeCallTypes?: string[];
+  /** Added to inferred edges. */
+  inferred?: number;
+  excludeInferred?: boolean;
+  /**
+   * Added to edges without a stored target, and in part to targets resolved
+   * with less than full confidence.
+   */
+  unresolved?: number;
+  excludeUnresolved?: boolean;
+}
+
+/** Output formats of `exportCallGraph`. */
+export type CallGraphExportFormat = "dot" | "graphml" | "json";
+
+/** Restricts `exportCallGraph` to part of a branch; restrictions combine. */
+export interface CallGraphFilter {
+  /** Symbols in files under this stored path prefix. */
+  filePrefix?: string;
+  /** One community as numbered by `detectCommunities` for the branch. */
+  communityId?: number;
+  /** These symbols and what they reach, as `getTransitiveReachability` finds it. */
+  rootSymbolIds?: string[];
+  /** Defaults to `both`. */
+  direction?: "callers" | "callees" | "both";
+  /** Defaults to 10. */
+  maxDepth?: number;
+  /**
+   * Edge types to export. Defaults to every type except the reference types
+   * (`TypeReference`, `FieldAccess`, `Read`, `Write`, `Decorator`).
+   */
+  callTypes?: string[];
+}
+
 export interface CommunityData {
   symbolId: string;
   symbolName: string;
@@ -147,6 +460,7 @@ export interface ChunkMetadata {
   endLine: number;
   chunkType: ChunkType;
   name?: string;
+  qualifiedName?: string;
   language: string;
   hash: string;
   blameSha?: string;
@@ -175,6 +489,12 @@ export interface ChunkData {
   nodeType?: string;
   name?: string;
   language: string;
+  qualifiedName?: string;
+  parentSymbol?: string;
+  depth?: number;
+  signature?: string;
+  docComment?: string;
+  visibility?: string;
   blameSha?: string;
   blameAuthor?: string;
   blameAuthorEmail?: string;
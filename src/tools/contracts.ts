@@ -23,6 +23,11 @@ export const RELATIONSHIP_TYPES = [
   "Import",
   "Inherits",
   "Implements",
+  "TypeReference",
+  "FieldAccess",
+  "Read",
+  "Write",
+  "Decorator",
 ] as const;
 export type RelationshipType = (typeof RELATIONSHIP_TYPES)[number];
 
@@ -16,7 +16,7 @@ import {
   CODE_COMMUNITIES_MIN_COUPLING,
   CODE_COMMUNITIES_MIN_SIZE,
 } from "./contracts.js";
-import type { SharedCodeCommunitiesArgs } from "./contracts.js";
+import type { RelationshipType, SharedCodeCommunitiesArgs } from "./contracts.js";
 import { calculatePercentage, formatProgressTitle, formatStatus } from "./utils.js";
 import type { LogLevel } from "../config/schema.js";
 import type { LogEntry } from "../utils/logger.js";
@@ -345,7 +345,7 @@ export async function getCallGraphData(
     direction?: "callers" | "callees";
     symbolId?: string;
     filePath?: string;
-    relationshipType?: "Call" | "MethodCall" | "Constructor" | "Import" | "Inherits" | "Implements";
+    relationshipType?: RelationshipType;
   },
 ): Promise<CallGraphDataResult> {
   await ensureAutoIndexReadyForRetrieval(projectRoot, host);
@@ -362,7 +362,7 @@ export async function getCallGraphDataForIndexer(
     direction?: "callers" | "callees";
     symbolId?: string;
     filePath?: string;
-    relationshipType?: "Call" | "MethodCall" | "Constructor" | "Import" | "Inherits" | "Implements";
+    relationshipType?: RelationshipType;
   },
 ): Promise<CallGraphDataResult> {
   const symbols = await indexer.getCallGraphSymbols();
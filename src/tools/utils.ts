@@ -1,4 +1,4 @@
-import type { IndexStats, IndexProgress, SearchResult, HealthCheckResult, StatusResult } from "../indexer/index.js";
+import type { DegradedFile, IndexStats, IndexProgress, SearchResult, HealthCheckResult, StatusResult } from "../indexer/index.js";
 import type { CallGraphDataResult, CallGraphPathResult, CallGraphSymbolResolution, IndexStatusResult } from "./operations.js";
 import type { LogEntry } from "../utils/logger.js";
 import { formatExactSearchHandoff } from "./context-pack.js";
@@ -89,11 +89,28 @@ export function formatIndexStats(stats: IndexStats, verbose: boolean = false): s
       lines.push("");
       lines.push(`Files with no extractable chunks (${stats.parseFailures.length}): ${stats.parseFailures.slice(0, 10).join(", ")}${stats.parseFailures.length > 10 ? "..." : ""}`);
     }
+
+    if (stats.degradedFiles.length > 0) {
+      lines.push("");
+      lines.push(formatDegradedFiles(stats.degradedFiles));
+    }
   }
 
   return lines.join("\n");
 }
 
+function formatDegradedFiles(files: DegradedFile[]): string {
+  const describe = (file: DegradedFile): string => {
+    if (file.error) return `${file.path} (${file.error})`;
+    const issues = [
+      file.syntaxErrors > 0 ? `${file.syntaxErrors} syntax error${file.syntaxErrors === 1 ? "" : "s"}` : "",
+      file.fellBackToLines ? `line chunks${file.fallbackReason ? `: ${file.fallbackReason}` : ""}` : "",
+    ].filter(Boolean);
+    return `${file.path} (${issues.join(", ")})`;
+  };
+  return `Files with degraded parses (${files.length}): ${files.slice(0, 10).map(describe).join(", ")}${files.length > 10 ? "..." : ""}`;
+}
+
 export function formatStatus(status: StatusResult | IndexStatusResult): string {
   const autoIndex = "autoIndex" in status ? status.autoIndex : undefined;
   const autoIndexLines = autoIndex ? formatAutoIndexStatus(autoIndex) : [];
@@ -139,6 +156,11 @@ export function formatStatus(status: StatusResult | IndexStatusResult): string {
     }
   }
 
+  if (status.degradedFiles.length > 0) {
+    lines.push("");
+    lines.push(formatDegradedFiles(status.degradedFiles));
+  }
+
   if (status.warning) {
     lines.push("");
     lines.push(`INDEX WARNING: ${status.warning}`);
@@ -233,7 +255,8 @@ export function formatCodebasePeek(results: SearchResult[]): string {
 
   const formatted = results.map((r, idx) => {
     const location = `${r.filePath}:${r.startLine}-${r.endLine}`;
-    const name = r.name ? `"${r.name}"` : "(anonymous)";
+    const displayName = r.qualifiedName ?? r.name;
+    const name = displayName ? `"${displayName}"` : "(anonymous)";
     return `[${idx + 1}] ${r.chunkType} ${name} at ${location} (score: ${r.score.toFixed(2)})${formatBlame(r)}`;
   });
 
@@ -370,8 +393,9 @@ export function formatCallGraphPathResult(result: CallGraphPathResult): string {
 }
 
 function formatResultHeader(result: SearchResult, index: number): string {
-  return result.name
-    ? `[${index + 1}] ${result.chunkType} "${result.name}" in ${result.filePath}:${result.startLine}-${result.endLine}`
+  const name = result.qualifiedName ?? result.name;
+  return name
+    ? `[${index + 1}] ${result.chunkType} "${name}" in ${result.filePath}:${result.startLine}-${result.endLine}`
     : `[${index + 1}] ${result.chunkType} in ${result.filePath}:${result.startLine}-${result.endLine}`;
 }
 
@@ -68,6 +68,7 @@ function status(indexed: boolean): StatusResult {
     baseBranch: "main",
     compatibility: { compatible: true },
     failedBatchesCount: 0,
+    degradedFiles: [],
   };
 }
 
@@ -3,8 +3,8 @@ import * as fs from "fs";
 import * as path from "path";
 import * as os from "os";
 import { parseConfig } from "../src/config/schema.js";
-import { extractCalls, Database, hashContent, parseFiles } from "../src/native/index.js";
-import type { SymbolData, CallEdgeData } from "../src/native/index.js";
+import { extractCalls, extractReferences, Database, hashContent, parseFiles } from "../src/native/index.js";
+import type { SymbolData, CallEdgeData, CallPathsData } from "../src/native/index.js";
 import {
   Indexer,
   CALL_GRAPH_LANGUAGES,
@@ -185,6 +185,23 @@ function migrationMetadataKey(prefix: string, catalogIdentity = "default"): stri
       expect(callNames).toContain("getData");
     });
 
+    it("should extract references that are not calls", () => {
+      const content = [
+        'import { UserDto } from "./dto";',
+        "const MAX_USERS = 10;",
+        "export function load(user: UserDto): boolean {",
+        "  return user.active && MAX_USERS > 0 && validate(user);",
+        "}",
+      ].join("\n");
+      const references = extractReferences(content, "typescript");
+
+      expect(references).toContainEqual(expect.objectContaining({ calleeName: "UserDto", callType: "TypeReference", line: 3 }));
+      expect(references).toContainEqual(expect.objectContaining({ calleeName: "active", callType: "FieldAccess" }));
+      expect(references).toContainEqual(expect.objectContaining({ calleeName: "MAX_USERS", callType: "Read" }));
+      expect(references.some((reference) => reference.calleeName === "user")).toBe(false);
+      expect(references.some((reference) => reference.calleeName === "validate")).toBe(false);
+    });
+
     it("should handle edge cases", () => {
       const content = fs.readFileSync(path.join(fixturesDir, "edge-cases.ts"), "utf-8");
       const calls = extractCalls(content, "typescript");
@@ -199,6 +216,43 @@ function migrationMetadataKey(prefix: string, catalogIdentity = "default"): stri
       expect(callNames).toContain("fetchData");
     });
 
+    it("should report the symbol enclosing each call site", () => {
+      const content = `import { setup } from "./setup";
+setup();
+
+function run() {
+  helper();
+}
+`;
+      const calls = extractCalls(content, "typescript");
+
+      const helperCall = calls.find((c) => c.calleeName === "helper");
+      expect(helperCall?.enclosingSymbol).toMatchObject({
+        name: "run",
+        kind: "function_declaration",
+        startLine: 4,
+        startCol: 0,
+      });
+      const topLevel = calls.filter((c) => c.calleeName === "setup");
+      expect(topLevel.map((c) => c.callType).sort()).toEqual(["Call", "Import"]);
+      for (const call of topLevel) {
+        expect(call.enclosingSymbol).toMatchObject({ name: "<module>", kind: "file_scope", startByte: 0 });
+      }
+    });
+
+    it("should mark computed method calls as inferred", () => {
+      const content = fs.readFileSync(path.join(fixturesDir, "edge-cases.ts"), "utf-8");
+      const calls = extractCalls(content, "typescript");
+
+      expect(calls.find((c) => c.calleeName === "staticName")).toMatchObject({
+        callType: "MethodCall",
+        confidence: "Inferred",
+      });
+      // A parameter holds no known string, so its site names no symbol.
+      expect(calls.find((c) => c.calleeName === "[methodName]")?.confidence).toBe("Inferred");
+      expect(calls.find((c) => c.calleeName === "trueCase")?.confidence).toBe("Direct");
+    });
+
     describe("php call extraction", () => {
       it("should extract direct function calls", () => {
         const content = fs.readFileSync(path.join(fixturesDir, "php-simple-calls.php"), "utf-8");
@@ -438,23 +492,19 @@ function buildReport(): string {
           indexing: { watchFiles: false },
         });
 
-        CASE_INSENSITIVE_LANGUAGES.delete("php");
         let indexer = new Indexer(projectDir, config, "opencode");
+        let targetId: string;
         try {
           await indexer.index();
-          const caller = (await indexer.getSymbolsForBranch()).find(
-            (symbol) => symbol.name === "callBuildReport",
-          );
-          const edge = (await indexer.getCallees(caller!.id)).find(
-            (candidate) => candidate.targetName === "buildreport",
-          );
-          expect(edge?.isResolved).toBe(false);
+          const symbols = await indexer.getSymbolsForBranch();
+          targetId = symbols.find((symbol) => symbol.name === "buildReport")!.id;
         } finally {
           await indexer.close();
-          CASE_INSENSITIVE_LANGUAGES.add("php");
         }
 
+        // Simulate edges stored by an older resolver that missed the target.
         const database = new Database(path.join(projectDir, ".opencode", "index", "codebase.db"));
+        database.clearCallEdgeTargetsForSymbols([targetId]);
         database.deleteMetadata(migrationMetadataKey("index.callGraphResolutionVersion"));
         database.close();
         const embeddingCallsBeforeUpgrade = fetchSpy.mock.calls.length;
@@ -1022,6 +1072,81 @@ const math = @import("math.zig");
     });
   });
 
+  describe("java, csharp and ruby call extraction", () => {
+    it("should enable call graphs for all three languages", () => {
+      expect(CALL_GRAPH_LANGUAGES.has("java")).toBe(true);
+      expect(CALL_GRAPH_LANGUAGES.has("csharp")).toBe(true);
+      expect(CALL_GRAPH_LANGUAGES.has("ruby")).toBe(true);
+    });
+
+    it("should extract Java calls, constructors, imports and supertypes", () => {
+      const content = `
+import java.util.List;
+
+class OrderService extends BaseService implements Runnable {
+    public void run() {
+        validate(order);
+        repository.save(order);
+        Order created = new Order(1);
+    }
+}
+`;
+      const calls = extractCalls(content, "java");
+      expect(calls.find((c) => c.calleeName === "validate")?.callType).toBe("Call");
+      expect(calls.find((c) => c.calleeName === "save")?.callType).toBe("MethodCall");
+      expect(calls.find((c) => c.calleeName === "Order")?.callType).toBe("Constructor");
+      expect(calls.find((c) => c.calleeName === "List")?.callType).toBe("Import");
+      expect(calls.find((c) => c.calleeName === "BaseService")?.callType).toBe("Inherits");
+      expect(calls.find((c) => c.calleeName === "Runnable")?.callType).toBe("Implements");
+    });
+
+    it("should extract C# calls, constructors, usings and base types", () => {
+      const content = `
+using System.Collections.Generic;
+
+public class OrderService : BaseService, IDisposable
+{
+    public void Run()
+    {
+        Validate(order);
+        repository.Save(order);
+        var names = new List<string>();
+    }
+}
+`;
+      const calls = extractCalls(content, "csharp");
+      expect(calls.find((c) => c.calleeName === "Validate")?.callType).toBe("Call");
+      expect(calls.find((c) => c.calleeName === "Save")?.callType).toBe("MethodCall");
+      expect(calls.find((c) => c.calleeName === "List")?.callType).toBe("Constructor");
+      expect(calls.find((c) => c.calleeName === "Generic")?.callType).toBe("Import");
+      expect(calls.find((c) => c.calleeName === "BaseService")?.callType).toBe("Inherits");
+      expect(calls.find((c) => c.calleeName === "IDisposable")?.callType).toBe("Implements");
+    });
+
+    it("should extract Ruby calls, constructors, requires and mixins", () => {
+      const content = `
+require_relative "lib/helper"
+
+class Admin < User
+  include Comparable
+
+  def promote(level)
+    validate!(level)
+    self.save
+    Role.new(level)
+  end
+end
+`;
+      const calls = extractCalls(content, "ruby");
+      expect(calls.find((c) => c.calleeName === "validate!")?.callType).toBe("Call");
+      expect(calls.find((c) => c.calleeName === "save")?.callType).toBe("MethodCall");
+      expect(calls.find((c) => c.calleeName === "Role")?.callType).toBe("Constructor");
+      expect(calls.find((c) => c.calleeName === "lib/helper")?.callType).toBe("Import");
+      expect(calls.find((c) => c.calleeName === "User")?.callType).toBe("Inherits");
+      expect(calls.find((c) => c.calleeName === "Comparable")?.callType).toBe("Implements");
+    });
+  });
+
   describe("C call graph", () => {
     const filePath = path.join(fixturesDir, "c-calls.c");
     const content = fs.readFileSync(filePath, "utf-8");
@@ -2450,6 +2575,63 @@ main() {
       expect(callees[0].isResolved).toBe(false);
       expect(callees[0].toSymbolId).toBeUndefined();
     });
+
+    it("should pick the method of the receiver's declared type", () => {
+      const db = openDb();
+
+      const method = (id: string, filePath: string, parentSymbol: string): SymbolData => ({
+        id,
+        filePath,
+        name: "save",
+        kind: "method",
+        startLine: 2,
+        startCol: 2,
+        endLine: 4,
+        endCol: 2,
+        language: "typescript",
+        parentSymbol,
+      });
+      db.upsertSymbolsBatch([
+        {
+          id: "sym_service_run",
+          filePath: "/src/service.ts",
+          name: "run",
+          kind: "function",
+          startLine: 1,
+          startCol: 0,
+          endLine: 5,
+          endCol: 0,
+          language: "typescript",
+        },
+        method("sym_user_save", "/src/users.ts", "UserRepo"),
+        method("sym_order_save", "/src/orders.ts", "OrderRepo"),
+      ]);
+      db.upsertCallEdgesBatch([
+        {
+          id: "edge_receiver",
+          fromSymbolId: "sym_service_run",
+          targetName: "save",
+          callType: "MethodCall",
+          confidence: "Direct",
+          line: 3,
+          col: 9,
+          isResolved: false,
+          qualifier: "OrderRepo",
+        },
+      ]);
+      db.addSymbolsToBranchBatch("test", ["sym_service_run", "sym_user_save", "sym_order_save"]);
+
+      const stats = db.resolveCallEdges("test");
+      expect(stats).toMatchObject({ edges: 1, receiverType: 1, updated: 1 });
+
+      const callees = db.getCallees("sym_service_run", "test");
+      expect(callees[0]).toMatchObject({
+        toSymbolId: "sym_order_save",
+        isResolved: true,
+        resolutionStrategy: "receiver-type",
+        resolutionConfidence: 0.9,
+      });
+    });
   });
 
   describe("branch awareness", () => {
@@ -2915,6 +3097,115 @@ main() {
       expect(result.length).toBe(0);
     });
 
+    it("should return k paths by id and candidates for ambiguous names", () => {
+      const db = openDb();
+      const symbol = (id: string, name: string, filePath: string) => db.upsertSymbol({
+        id,
+        filePath,
+        name,
+        kind: "function",
+        startLine: 1,
+        startCol: 0,
+        endLine: 10,
+        endCol: 0,
+        language: "typescript",
+      });
+      symbol("sym_root", "root", "src/root.ts");
+      symbol("sym_left", "left", "src/left.ts");
+      symbol("sym_right", "right", "src/right.ts");
+      symbol("sym_save_a", "save", "src/a/save.ts");
+      symbol("sym_save_b", "save", "src/b/save.ts");
+      db.addSymbolsToBranch("main", ["sym_root", "sym_left", "sym_right", "sym_save_a", "sym_save_b"]);
+      const edge = (id: string, from: string, to: string, targetName: string) => ({
+        id,
+        fromSymbolId: from,
+        targetName,
+        toSymbolId: to,
+        callType: "Call",
+        confidence: "Direct",
+        line: 2,
+        col: 0,
+        isResolved: true,
+      });
+      db.upsertCallEdgesBatch([
+        edge("edge_rl", "sym_root", "sym_left", "left"),
+        edge("edge_rr", "sym_root", "sym_right", "right"),
+        edge("edge_ls", "sym_left", "sym_save_a", "save"),
+        edge("edge_rs", "sym_right", "sym_save_a", "save"),
+      ]);
+
+      const byId = db.findCallPaths("root", "sym_save_a", "main", 5);
+      expect(byId.paths.map((path) => path.hops.map((hop) => hop.symbolName))).toEqual([
+        ["root", "left", "save"],
+        ["root", "right", "save"],
+      ]);
+      expect(db.findCallPaths("root", "sym_save_a", "main", 1).paths).toHaveLength(1);
+
+      const ambiguous = db.findCallPaths("root", "save", "main");
+      expect(ambiguous.paths).toEqual([]);
+      expect(ambiguous.fromCandidates).toEqual([]);
+      expect(ambiguous.toCandidates.map((candidate) => candidate.filePath)).toEqual([
+        "src/a/save.ts",
+        "src/b/save.ts",
+      ]);
+    });
+
+    it("should rank weighted paths by edge credibility", () => {
+      const db = openDb();
+      for (const [id, name] of [["sym_handler", "handle"], ["sym_service", "service"], ["sym_db", "query"]]) {
+        db.upsertSymbol({
+          id,
+          filePath: `src/${name}.ts`,
+          name,
+          kind: "function",
+          startLine: 1,
+          startCol: 0,
+          endLine: 10,
+          endCol: 0,
+          language: "typescript",
+        });
+      }
+      db.addSymbolsToBranch("main", ["sym_handler", "sym_service", "sym_db"]);
+      const edge = (id: string, from: string, to: string | undefined, targetName: string) => ({
+        id,
+        fromSymbolId: from,
+        targetName,
+        toSymbolId: to,
+        callType: "Call",
+        confidence: "Direct",
+        line: 2,
+        col: 0,
+        isResolved: to !== undefined,
+      });
+      db.upsertCallEdgesBatch([
+        edge("edge_hd", "sym_handler", undefined, "query"),
+        edge("edge_hs", "sym_handler", "sym_service", "service"),
+        edge("edge_sd", "sym_service", "sym_db", "query"),
+      ]);
+      const names = (found: CallPathsData) => found.paths.map((path) => path.hops.map((hop) => hop.symbolName));
+
+      expect(names(db.findCallPaths("sym_handler", "sym_db", "main", 1))).toEqual([["handle", "query"]]);
+      const weighted = db.findCallPaths("sym_handler", "sym_db", "main", 2, 10, {});
+      expect(names(weighted)).toEqual([
+        ["handle", "service", "query"],
+        ["handle", "query"],
+      ]);
+      expect(weighted.paths.map((path) => path.cost)).toEqual([2, 4]);
+      expect(names(db.findCallPaths("sym_handler", "sym_db", "main", 2, 10, { excludeUnresolved: true }))).toEqual([
+        ["handle", "service", "query"],
+      ]);
+      expect(() => db.findCallPaths("sym_handler", "sym_db", "main", 2, 10, { callTypes: { Call: 0 } })).toThrow(
+        /must be a positive number/,
+      );
+
+      const reached = db.getWeightedReachability(["sym_handler"], "main", "callees", { callTypes: { Call: 0.5 } });
+      expect(reached.map((entry) => [entry.symbolId, entry.depth, entry.cost])).toEqual([
+        ["sym_service", 1, 0.5],
+        ["sym_db", 2, 1],
+      ]);
+      expect(db.getWeightedReachability(["sym_handler"], "main", "callees", {}, 10, 1)).toHaveLength(1);
+    });
+
     it("should respect maxDepth limit", () => {
       const db = openDb();
 
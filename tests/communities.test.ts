@@ -473,4 +473,23 @@ describe("code_communities tool", () => {
     expect(centrality[0]).toHaveProperty("callerCount");
     expect(centrality[0]).toHaveProperty("calleeCount");
   });
+
+  it("exports the call graph restricted to a file prefix", async () => {
+    const indexer = await createIndexer();
+    await setupTwoCommunityGraph(indexer);
+
+    const graph = JSON.parse(await indexer.exportCallGraph("json", { filePrefix: "src/auth/" }, "main"));
+    expect(Object.keys(graph.graph.nodes).sort()).toEqual([
+      "sym_auth_jwt",
+      "sym_auth_service",
+      "sym_auth_validate",
+    ]);
+    expect(graph.graph.edges).toHaveLength(2);
+
+    const dot = await indexer.exportCallGraph("dot", { rootSymbolIds: ["sym_logger"], direction: "callers", maxDepth: 1 }, "main");
+    expect(dot).toContain("\"sym_db_query\" -> \"sym_logger\"");
+    expect(dot).not.toContain("sym_auth_jwt");
+
+    await expect(indexer.exportCallGraph("svg" as "dot", undefined, "main")).rejects.toThrow("unknown call graph format");
+  });
 });
@@ -98,6 +98,36 @@ describe("config schema", () => {
       expect(parseConfig({ search: { communityBoost: "0.25" } }).search.communityBoost).toBe(0);
     });
 
+    it("parses call query files and drops malformed entries", () => {
+      expect(parseConfig(undefined).indexing.callQueries).toEqual([]);
+      const config = parseConfig({
+        indexing: {
+          callQueries: [
+            { language: "typescript", path: ".queries/events.scm" },
+            { language: "python", path: "calls.scm", mode: "replace" },
+            { language: "go" },
+            "calls.scm",
+          ],
+        },
+      });
+      expect(config.indexing.callQueries).toEqual([
+        { language: "typescript", path: ".queries/events.scm", mode: "extend" },
+        { language: "python", path: "calls.scm", mode: "replace" },
+      ]);
+    });
+
+    it("parses the chunk tokenizer and drops malformed fields", () => {
+      expect(parseConfig(undefined).indexing.tokenizer).toBeNull();
+      const config = parseConfig({
+        indexing: {
+          tokenizer: { vocabPath: "models/vocab.txt", kind: "sentencepiece", lowercase: true, maxChunkTokens: 0 },
+        },
+      });
+      expect(config.indexing.tokenizer).toEqual({ vocabPath: "models/vocab.txt", lowercase: true });
+      expect(parseConfig({ indexing: { tokenizer: { maxChunkTokens: 512.7 } } }).indexing.tokenizer)
+        .toEqual({ maxChunkTokens: 512 });
+    });
+
     it("should return defaults for null input", () => {
       const config = parseConfig(null);
 
@@ -265,6 +265,23 @@ describe("indexer clearIndex force rebuild", () => {
     await expect(restartedIndexer.index()).rejects.toThrow("Run index_codebase with force=true to rebuild the index");
   });
 
+  it("persists degraded parse diagnostics for status until the file parses cleanly", async () => {
+    const brokenFile = path.join(tempDir, "src", "broken.ts");
+    fs.writeFileSync(brokenFile, "export function broken( {\n  return 1;\n}\n", "utf-8");
+    const stats = await createIndexer(tempDir, 8).index();
+    expect(stats.degradedFiles.map((file) => file.path)).toEqual([path.join("src", "broken.ts")]);
+
+    const restarted = createIndexer(tempDir, 8);
+    const status = await restarted.getStatus();
+    expect(status.degradedFiles).toHaveLength(1);
+    expect(status.degradedFiles[0].path).toBe(path.join("src", "broken.ts"));
+    expect(status.degradedFiles[0].syntaxErrors).toBeGreaterThan(0);
+
+    fs.writeFileSync(brokenFile, "export function broken() {\n  return 1;\n}\n", "utf-8");
+    await restarted.index();
+    expect((await restarted.getStatus()).degradedFiles).toEqual([]);
+  });
+
   it("marks legacy absolute path storage as incompatible until force rebuild", async () => {
     const indexer = createIndexer(tempDir, 8);
     await indexer.index();
@@ -107,6 +107,7 @@ let mockIndexResult = {
   removedChunks: 0,
   skippedFiles: [],
   parseFailures: [],
+  degradedFiles: [],
 };
 
 let mockStatusResult = {
@@ -119,6 +120,7 @@ let mockStatusResult = {
   baseBranch: "main",
   compatibility: { compatible: true },
   failedBatchesCount: 0,
+  degradedFiles: [],
   failedBatchesPath: undefined as string | undefined,
 };
 
@@ -421,6 +423,7 @@ describe("MCP server tools and prompts", () => {
       removedChunks: 0,
       skippedFiles: [],
       parseFailures: [],
+      degradedFiles: [],
     };
     mockStatusResult = {
       indexed: true,
@@ -432,6 +435,7 @@ describe("MCP server tools and prompts", () => {
       baseBranch: "main",
       compatibility: { compatible: true },
       failedBatchesCount: 0,
+      degradedFiles: [],
       failedBatchesPath: undefined,
     };
     mockHealthCheckResult = {
@@ -1140,6 +1144,7 @@ describe("MCP server tools and prompts", () => {
       baseBranch: "default",
       compatibility: null,
       failedBatchesCount: 2,
+      degradedFiles: [],
       failedBatchesPath: "/tmp/index/failed-batches.json",
     };
 
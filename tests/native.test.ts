@@ -5,6 +5,10 @@ import * as os from "os";
 import {
   parseFile,
   parseFiles,
+  parseFilesFromPaths,
+  extractCalls,
+  extractReferences,
+  loadCallQueries,
   hashContent,
   hashFile,
   VectorStore,
@@ -14,6 +18,7 @@ import {
   generateChunkId,
   estimateTokens,
   type CodeChunk,
+  type ParsedFile,
 } from "../src/native/index.js";
 
 describe("native module", () => {
@@ -687,6 +692,18 @@ end
       expect(results[1].path).toBe("b.ts");
     });
 
+    it("returns call sites from the same parse only when asked", () => {
+      const content = "function run() { helper(); }\nconst limit: Config = load();";
+      const [plain] = parseFiles([{ path: "a.ts", content }]);
+      const [withCalls] = parseFiles([{ path: "a.ts", content }], undefined, { includeCalls: true });
+
+      expect(plain.calls).toBeUndefined();
+      expect(withCalls.calls).toEqual([
+        ...extractCalls(content, "typescript"),
+        ...extractReferences(content, "typescript"),
+      ]);
+    });
+
     it("extracts nested class methods as symbols without changing semantic chunks", () => {
       const [result] = parseFiles([{
         path: "service.ts",
@@ -763,6 +780,87 @@ const values = [1, 2].map(item => item * 2);
     });
   });
 
+  describe("parseFilesFromPaths", () => {
+    let tempDir: string;
+    let paths: string[];
+
+    beforeEach(() => {
+      tempDir = fs.mkdtempSync(path.join(os.tmpdir(), "parse-stream-test-"));
+      paths = [1, 2, 3, 4, 5, 6].map((i) => {
+        const filePath = path.join(tempDir, `f${i}.ts`);
+        fs.writeFileSync(filePath, `export function f${i}() { return ${i}; }\n`);
+        return filePath;
+      });
+    });
+
+    afterEach(() => {
+      fs.rmSync(tempDir, { recursive: true, force: true });
+    });
+
+    it("should deliver parsed files in batches", async () => {
+      const batches: number[] = [];
+      const parsed = await parseFilesFromPaths(paths, (batch) => batches.push(batch.length), 2);
+
+      expect(parsed).toBe(6);
+      expect(batches).toEqual([2, 2, 2]);
+    });
+
+    it("should report each file's extent without its content", async () => {
+      const content = "export const start = 1;\nexport const face = \"\u{1F600}\";";
+      fs.writeFileSync(paths[0], content);
+      const files: ParsedFile[] = [];
+      await parseFilesFromPaths(paths.slice(0, 1), (batch) => files.push(...batch));
+
+      const lines = content.split("\n");
+      expect(files[0].lineCount).toBe(lines.length);
+      expect(files[0].lastLineLength).toBe(lines[lines.length - 1].length);
+      expect(files[0].hash).toBe(hashContent(content));
+    });
+
+    it("should stop and reject with the error when the callback throws", async () => {
+      const error = new Error("batch rejected");
+      let calls = 0;
+      const streaming = parseFilesFromPaths(paths, () => {
+        calls++;
+        throw error;
+      }, 2);
+
+      await expect(streaming).rejects.toBe(error);
+      await new Promise((resolve) => setTimeout(resolve, 50));
+      expect(calls).toBe(1);
+    });
+  });
+
+  describe("loadCallQueries", () => {
+    let tempDir: string;
+
+    beforeEach(() => {
+      tempDir = fs.mkdtempSync(path.join(os.tmpdir(), "call-query-test-"));
+    });
+
+    afterEach(() => {
+      loadCallQueries([]);
+      fs.rmSync(tempDir, { recursive: true, force: true });
+    });
+
+    it("should reset earlier overrides when given no queries", () => {
+      const queryPath = path.join(tempDir, "events.scm");
+      fs.writeFileSync(queryPath, `(call_expression
+  function: (identifier) @_dispatch
+  arguments: (arguments . (string (string_fragment) @callee.name))
+  (#eq? @_dispatch "dispatch")) @call
+`);
+      const content = `dispatch("userCreated");\n`;
+      const callees = () => extractCalls(content, "typescript").map((call) => call.calleeName);
+
+      expect(loadCallQueries([{ language: "typescript", path: queryPath }])).toEqual(["typescript"]);
+      expect(callees()).toContain("userCreated");
+
+      expect(loadCallQueries([])).toEqual([]);
+      expect(callees()).not.toContain("userCreated");
+    });
+  });
+
   describe("hashContent", () => {
     it("should return consistent hash for same content", () => {
       const hash1 = hashContent("test content");
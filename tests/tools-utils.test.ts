@@ -29,6 +29,7 @@ function createBaseStats(overrides: Partial<IndexStats> = {}): IndexStats {
     removedChunks: 0,
     skippedFiles: [],
     parseFailures: [],
+    degradedFiles: [],
     failedBatchesPath: undefined,
     ...overrides,
   };
@@ -171,6 +172,27 @@ describe("tools utils", () => {
       expect(result).toContain("empty.ts");
       expect(result).toContain("broken.js");
     });
+
+    it("should include verbose degraded parses", () => {
+      const stats = createBaseStats({
+        totalFiles: 3,
+        indexedChunks: 3,
+        degradedFiles: [
+          { path: "src/new-syntax.ts", syntaxErrors: 2, fellBackToLines: false },
+          { path: "config.py", syntaxErrors: 0, fellBackToLines: true },
+          { path: "dist/app.min.js", syntaxErrors: 0, fellBackToLines: true, fallbackReason: "minified" },
+          { path: "gone.ts", syntaxErrors: 0, fellBackToLines: false, error: "Failed to read gone.ts" },
+        ],
+      });
+
+      expect(formatIndexStats(stats)).not.toContain("degraded parses");
+      const result = formatIndexStats(stats, true);
+      expect(result).toContain("Files with degraded parses (4)");
+      expect(result).toContain("src/new-syntax.ts (2 syntax errors)");
+      expect(result).toContain("config.py (line chunks)");
+      expect(result).toContain("dist/app.min.js (line chunks: minified)");
+      expect(result).toContain("gone.ts (Failed to read gone.ts)");
+    });
   });
 
   describe("formatStatus", () => {
@@ -185,6 +207,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: null,
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
       };
       const result = formatStatus(status);
@@ -204,6 +227,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: { compatible: true },
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
       };
       const result = formatStatus(status);
@@ -227,6 +251,7 @@ describe("tools utils", () => {
         baseBranch: "main",
         compatibility: { compatible: true },
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
       };
       const result = formatStatus(status);
@@ -257,6 +282,7 @@ describe("tools utils", () => {
           },
         },
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
       };
       const result = formatStatus(status);
@@ -278,6 +304,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: null,
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
       };
       const result = formatStatus(status);
@@ -296,6 +323,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: null,
         failedBatchesCount: 2,
+        degradedFiles: [],
         failedBatchesPath: "/tmp/index/failed-batches.json",
       };
       const result = formatStatus(status);
@@ -315,6 +343,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: null,
         failedBatchesCount: 0,
+        degradedFiles: [],
         failedBatchesPath: undefined,
         warning: "Detected a corrupted local SQLite index at /tmp/index/codebase.db and reset the local index. Run index_codebase to rebuild search data.",
       };
@@ -336,6 +365,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: { compatible: true },
         failedBatchesCount: 1,
+        degradedFiles: [],
         failedBatchesPath: "/tmp/index/failed-batches.json",
       };
       const result = formatStatus(status);
@@ -344,6 +374,27 @@ describe("tools utils", () => {
       expect(result).toContain("failed-batches.json");
     });
 
+    it("should list files with degraded parses", () => {
+      const status: StatusResult = {
+        indexed: true,
+        vectorCount: 100,
+        provider: "google",
+        model: "gemini-embedding-001",
+        indexPath: "/tmp/index",
+        currentBranch: "default",
+        baseBranch: "default",
+        compatibility: { compatible: true },
+        failedBatchesCount: 0,
+        degradedFiles: [
+          { path: "dist/app.min.js", syntaxErrors: 0, fellBackToLines: true, fallbackReason: "minified" },
+        ],
+      };
+      const result = formatStatus(status);
+
+      expect(result).toContain("Files with degraded parses (1)");
+      expect(result).toContain("dist/app.min.js (line chunks: minified)");
+    });
+
     it("should surface a degraded reader warning while semantic data remains usable", () => {
       const status: StatusResult = {
         indexed: true,
@@ -355,6 +406,7 @@ describe("tools utils", () => {
         baseBranch: "default",
         compatibility: { compatible: true },
         failedBatchesCount: 0,
+        degradedFiles: [],
         warning: "Keyword index could not be read; semantic search remains available. Run index_codebase to repair it under the writer lease.",
       };
       const result = formatStatus(status);